@@ -3,7 +3,9 @@
 //! A `BandAid` covers the mistake with a suggested
 //! replacement, as picked by the user.
 
-use crate::Span;
+use crate::config::CaseConfig;
+use crate::util::sub_chars;
+use crate::{Category, Span, Suggestion};
 
 /// A chosen suggestion for a certain span
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,9 +17,40 @@ pub struct BandAid {
 }
 
 impl BandAid {
-    /// Check if the bandaid covers `line` which is 1 indexed.
-    pub fn covers_line(&self, line: usize) -> bool {
-        self.span.covers_line(line)
+    /// Replace the span of `suggestion` with `replacement` as is, escaped for
+    /// the literal the suggestion was found in.
+    ///
+    /// `None` if the replacement can not be represented in a raw string
+    /// literal.
+    pub fn for_suggestion(replacement: &str, suggestion: &Suggestion<'_>) -> Option<Self> {
+        let content = suggestion.chunk.variant().escape(replacement)?;
+        Some(Self {
+            content,
+            span: suggestion.span,
+        })
+    }
+
+    /// Like `for_suggestion`, but for a checker provided `replacement`, which
+    /// was picked for another occurrence of the same word.
+    ///
+    /// If enabled by `case`, the capitalization of this occurrence is
+    /// transferred onto the replacement, so fixing `Alotted` yields
+    /// `Allotted`. Replacements of casing mistakes are kept as they are, their
+    /// case is the fix.
+    pub fn for_occurrence(
+        replacement: &str,
+        suggestion: &Suggestion<'_>,
+        case: &CaseConfig,
+    ) -> Option<Self> {
+        if suggestion.category == Category::Casing {
+            return Self::for_suggestion(replacement, suggestion);
+        }
+        let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+        let replacement = case
+            .adjust(&word, vec![replacement.to_owned()])
+            .pop()
+            .expect("Adjusting a single replacement yields one. qed");
+        Self::for_suggestion(&replacement, suggestion)
     }
 }
 
@@ -32,10 +65,70 @@ impl From<(String, &Span)> for BandAid {
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use super::*;
 
     use crate::util::load_span_from;
 
-    use crate::{LineColumn, Span};
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation, LineColumn, Span};
+
+    /// Replace `length` characters of `line`, starting at `column`.
+    pub(crate) fn bandaid(line: usize, column: usize, length: usize, replacement: &str) -> BandAid {
+        let span = Span {
+            start: LineColumn { line, column },
+            end: LineColumn {
+                line,
+                column: column + length - 1,
+            },
+        };
+        BandAid::from((replacement.to_owned(), &span))
+    }
+
+    #[test]
+    fn replacement_casing() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("Alotted This")));
+        let suggestions = dummy::check_all(&d);
+        let suggestions = suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .collect::<Vec<_>>();
+        let (misspelled, mut miscased) = (suggestions[0], suggestions[1].clone());
+        miscased.category = Category::Casing;
+
+        let preserve = CaseConfig {
+            preserve_case: true,
+            ..CaseConfig::default()
+        };
+        let content = |bandaid: Option<BandAid>| bandaid.unwrap().content;
+
+        // picked or entered replacements are applied as they are
+        assert_eq!(
+            content(BandAid::for_suggestion("allotted", misspelled)),
+            "allotted"
+        );
+        // other occurrences only adjust them if enabled
+        assert_eq!(
+            content(BandAid::for_occurrence(
+                "allotted",
+                misspelled,
+                &CaseConfig::default()
+            )),
+            "allotted"
+        );
+        assert_eq!(
+            content(BandAid::for_occurrence("allotted", misspelled, &preserve)),
+            "Allotted"
+        );
+        assert_eq!(
+            content(BandAid::for_occurrence("GitHub", misspelled, &preserve)),
+            "GitHub"
+        );
+        // the case of a casing fix is never reverted
+        assert_eq!(
+            content(BandAid::for_occurrence("this", &miscased, &preserve)),
+            "this"
+        );
+    }
 
     #[test]
     fn span_helper_integrity() {
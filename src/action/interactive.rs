@@ -3,17 +3,22 @@
 //! The result of that pick is a bandaid.
 
 use super::*;
+use crate::config::CaseConfig;
+use crate::util::sub_chars;
 
 use crossterm;
 
 use crossterm::{
     cursor,
     event::{Event, KeyCode, KeyEvent, KeyModifiers},
-    style::{Attribute, Color, ContentStyle, PrintStyledContent, StyledContent},
+    style::Print,
     terminal, QueueableCommand,
 };
 
-use std::io::stdout;
+use std::fmt;
+use std::io::{stdout, Write};
+use std::path::Path;
+use std::process::Command;
 
 const HELP: &'static str = r##"y - apply this suggestion
 n - do not apply the suggested correction
@@ -23,6 +28,9 @@ g - select a suggestion to go to
 j - leave this hunk undecided, see next undecided hunk
 J - leave this hunk undecided, see next hunk
 e - manually edit the current hunk
+o - open the file in $EDITOR at this line and check it again afterwards
+i - add the word to the ignored words of the project and skip it from now on
+a - add the word to the dictionary and skip it from now on
 ? - print help
 
 
@@ -30,6 +38,49 @@ e - manually edit the current hunk
 
 "##;
 
+const HELP_BY_WORD: &'static str = r##"y - apply this replacement to all occurrences of the word
+n - leave all occurrences of the word as they are
+q - quit; do not apply any of the picked replacements
+e - manually enter the replacement for all occurrences
+i - add the word to the ignored words of the project
+a - add the word to the dictionary
+? - print help
+
+
+
+
+"##;
+
+/// Rows used by the suggestion itself and the question, which are not
+/// available for listing the replacements.
+const RESERVED_ROWS: usize = 10;
+
+/// Position of the current suggestion within the whole session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Index of the current file.
+    pub file: usize,
+    /// Number of files with suggestions.
+    pub files: usize,
+    /// Index of the current suggestion, across all files.
+    pub suggestion: usize,
+    /// Number of suggestions across all files.
+    pub suggestions: usize,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "file {}/{} — suggestion {}/{}",
+            self.file + 1,
+            self.files,
+            self.suggestion + 1,
+            self.suggestions
+        )
+    }
+}
+
 /// Helper strict to assure we leave the terminals raw mode
 pub struct ScopedRaw;
 
@@ -59,6 +110,59 @@ impl Drop for ScopedRaw {
     }
 }
 
+/// The command opening `path` at `line` in `editor`, i.e. the value of
+/// `$EDITOR` with arguments, following the wide spread `+<line>` convention.
+fn editor_command(editor: &str, path: &Path, line: usize) -> Option<Command> {
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next()?);
+    command.args(words).arg(format!("+{}", line)).arg(path);
+    Some(command)
+}
+
+/// Open the file of `suggestion` at its line in `$VISUAL` or `$EDITOR`, and
+/// wait until the editor is closed.
+fn open_in_editor(suggestion: &Suggestion<'_>) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+    let line = match suggestion.origin {
+        ContentOrigin::RustDocTest(_, ref span) => suggestion.span.start.line + span.start.line,
+        _ => suggestion.span.start.line,
+    };
+    let path = suggestion.origin.as_path();
+    let mut command = editor_command(&editor, path, line)
+        .ok_or_else(|| anyhow::anyhow!("The editor {:?} is not a command", editor))?;
+    let status = command
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to launch the editor {:?}", editor).context(e))?;
+    if !status.success() {
+        warn!("The editor {:?} exited with {}", editor, status);
+    }
+    Ok(())
+}
+
+/// The flagged content of `suggestion`, as written.
+fn flagged_word(suggestion: &Suggestion<'_>) -> String {
+    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone())
+}
+
+/// All `suggestions` grouped by the lowercased flagged word, in order of
+/// their first occurrence, since ignoring a word is case insensitive too.
+fn group_by_word<'s>(
+    suggestions: SuggestionSet<'s>,
+) -> indexmap::IndexMap<String, Vec<Suggestion<'s>>> {
+    let mut words = indexmap::IndexMap::<String, Vec<Suggestion<'s>>>::new();
+    for (_origin, suggestions) in suggestions {
+        for suggestion in suggestions {
+            words
+                .entry(flagged_word(&suggestion).to_lowercase())
+                .or_default()
+                .push(suggestion);
+        }
+    }
+    words
+}
+
 /// In which direction we should progress.
 #[derive(Debug, Clone, Copy)]
 enum Direction {
@@ -82,6 +186,12 @@ pub(super) enum UserSelection {
     Help,
     /// Skip the remaining fixes for the current file.
     SkipFile,
+    /// Open the file in the editor and check it again.
+    Edit,
+    /// Add the word to the ignored words of the project and skip it.
+    Ignore,
+    /// Add the word to the dictionary and skip it.
+    AddToDictionary,
     /// Stop execution.
     Abort,
     /// continue as if whatever returned this was never called.
@@ -102,6 +212,10 @@ where
     pub pick_idx: usize,
     /// Total number of pickable slots.
     pub n_items: usize,
+    /// Number of slots that fit on the screen at once.
+    pub visible: usize,
+    /// First visible slot, in display order.
+    pub scroll: usize,
 }
 
 impl<'s, 't> From<&'s Suggestion<'t>> for State<'s, 't> {
@@ -113,6 +227,8 @@ impl<'s, 't> From<&'s Suggestion<'t>> for State<'s, 't> {
             pick_idx: 0usize,
             // all items provided by the checkers plus the user provided
             n_items: suggestion.replacements.len() + 1,
+            visible: suggestion.replacements.len() + 1,
+            scroll: 0,
         }
     }
 }
@@ -124,17 +240,57 @@ where
     /// Selects the next line.
     pub fn select_next(&mut self) {
         self.pick_idx = (self.pick_idx + 1).rem_euclid(self.n_items);
+        self.scroll_to_pick();
     }
 
     /// Selects the previous line.
     pub fn select_previous(&mut self) {
         self.pick_idx = (self.pick_idx + self.n_items - 1).rem_euclid(self.n_items);
+        self.scroll_to_pick();
     }
 
     /// Select the custom line, which is by definition the
     /// last selectable.
     pub fn select_custom(&mut self) {
         self.pick_idx = self.n_items - 1;
+        self.scroll_to_pick();
+    }
+
+    /// Limit the number of visible slots to what fits in a terminal with
+    /// `rows` rows, next to the suggestion itself.
+    pub fn fit_to(&mut self, rows: u16) {
+        self.visible = (rows as usize)
+            .saturating_sub(RESERVED_ROWS)
+            .max(1)
+            .min(self.n_items);
+        self.scroll_to_pick();
+    }
+
+    /// Slots are displayed bottom up, starting with the custom entry,
+    /// followed by the replacements in order.
+    fn display_position(&self, pick_idx: usize) -> usize {
+        (pick_idx + 1).rem_euclid(self.n_items)
+    }
+
+    /// The inverse of `display_position`.
+    fn pick_idx_at(&self, position: usize) -> usize {
+        (position + self.n_items - 1).rem_euclid(self.n_items)
+    }
+
+    /// Scroll just enough to keep the selected slot visible.
+    fn scroll_to_pick(&mut self) {
+        let position = self.display_position(self.pick_idx);
+        if position < self.scroll {
+            self.scroll = position;
+        } else if position >= self.scroll + self.visible {
+            self.scroll = position + 1 - self.visible;
+        }
+        self.scroll = self.scroll.min(self.n_items - self.visible);
+    }
+
+    /// The visible slots as pick indices, bottom up.
+    pub fn visible_picks(&self) -> impl Iterator<Item = usize> + '_ {
+        (self.scroll..(self.scroll + self.visible)).map(move |position| self.pick_idx_at(position))
     }
 
     /// Checks if the currently selected line is the custom entry.
@@ -142,18 +298,32 @@ where
         self.pick_idx + 1 == self.n_items
     }
 
-    /// Convert the replacement to a `BandAid`
-    pub fn to_bandaid(&self) -> BandAid {
+    /// The picked replacement, as listed or entered.
+    pub fn replacement(&self) -> &str {
         if self.is_custom_entry() {
-            BandAid::from((self.custom_replacement.clone(), &self.suggestion.span))
+            self.custom_replacement.as_str()
         } else {
-            let replacement = self
-                .suggestion
+            self.suggestion
                 .replacements
                 .get(self.pick_idx)
-                .expect("User Pick index is out of bounds");
-            BandAid::from((replacement.to_owned(), &self.suggestion.span))
+                .expect("User Pick index is out of bounds")
+        }
+    }
+
+    /// Convert the replacement to a `BandAid`, `None` if it can not be
+    /// represented in the literal of the suggestion.
+    pub fn to_bandaid(&self) -> Option<BandAid> {
+        let replacement = self.replacement();
+        let bandaid = BandAid::for_suggestion(replacement, self.suggestion);
+        if bandaid.is_none() {
+            warn!(
+                "`{}` would end the raw string literal at {}:{}",
+                replacement,
+                self.suggestion.origin.as_path().display(),
+                self.suggestion.span.start.line
+            );
         }
+        bandaid
     }
 }
 
@@ -162,6 +332,10 @@ where
 pub struct UserPicked {
     /// Associates the bandaids to a content origin, or path respectively.
     pub bandaids: indexmap::IndexMap<ContentOrigin, Vec<BandAid>>,
+    /// Words to add to the ignored words of the project.
+    pub ignored: Vec<String>,
+    /// Words to add to the dictionary.
+    pub dictionary: Vec<String>,
 }
 
 impl UserPicked {
@@ -170,6 +344,35 @@ impl UserPicked {
         self.bandaids.iter().map(|(_origin, vec)| vec.len()).sum()
     }
 
+    /// If there is nothing to write, neither changes nor added words.
+    pub fn is_empty(&self) -> bool {
+        self.bandaids.is_empty() && self.ignored.is_empty() && self.dictionary.is_empty()
+    }
+
+    /// If `word` was ignored or added to the dictionary, compared case
+    /// insensitive like the ignore file.
+    fn is_ignored(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.ignored
+            .iter()
+            .chain(self.dictionary.iter())
+            .any(|ignored| ignored.to_lowercase() == word)
+    }
+
+    /// Ignore `word` from now on, unless it already is.
+    fn add_ignored(&mut self, word: String) {
+        if !self.is_ignored(&word) {
+            self.ignored.push(word);
+        }
+    }
+
+    /// Add `word` to the dictionary, unless it is already picked.
+    fn add_to_dictionary(&mut self, word: String) {
+        if !self.is_ignored(&word) {
+            self.dictionary.push(word);
+        }
+    }
+
     /// Apply a single `BandAid`
     pub fn add_bandaid(&mut self, origin: &ContentOrigin, bandaid: BandAid) {
         self.bandaids
@@ -191,6 +394,90 @@ impl UserPicked {
             .extend(iter);
     }
 
+    /// Write the number of pending changes per file, and with `diff` also
+    /// each change with the content it replaces.
+    pub fn write_summary<W: Write>(&self, mut sink: W, diff: bool) -> Result<()> {
+        writeln!(sink, "Pending changes:")?;
+        for (origin, bandaids) in self.bandaids.iter() {
+            writeln!(sink, "  {:>4} in {}", bandaids.len(), origin)?;
+            if !diff {
+                continue;
+            }
+            for bandaid in bandaids {
+                let original = crate::util::load_span_from_file(origin.as_path(), bandaid.span)
+                    .unwrap_or_else(|e| {
+                        debug!("Failed to load the replaced content: {}", e);
+                        "?".to_owned()
+                    });
+                writeln!(
+                    sink,
+                    "       {}:{} {:?} -> {:?}",
+                    bandaid.span.start.line,
+                    bandaid.span.start.column + 1,
+                    original,
+                    bandaid.content
+                )?;
+            }
+        }
+        if !self.ignored.is_empty() {
+            writeln!(sink, "Ignored words: {}", self.ignored.join(", "))?;
+        }
+        if !self.dictionary.is_empty() {
+            writeln!(
+                sink,
+                "Words added to the dictionary: {}",
+                self.dictionary.join(", ")
+            )?;
+        }
+        writeln!(
+            sink,
+            "Total: {} changes in {} files",
+            self.total_count(),
+            self.bandaids.len()
+        )?;
+        Ok(())
+    }
+
+    /// Show the summary of all pending changes and ask for a final
+    /// confirmation before any of them is written.
+    pub fn confirm(&self) -> Result<bool> {
+        self.write_summary(stdout(), false)?;
+        loop {
+            let boring = crate::output::theme::current().question;
+            let mut question = format!(
+                "Write {} changes to {} files",
+                self.total_count(),
+                self.bandaids.len()
+            );
+            if !self.ignored.is_empty() {
+                question += &format!(" and ignore {} words", self.ignored.len());
+            }
+            if !self.dictionary.is_empty() {
+                question += &format!(" and add {} words to the dictionary", self.dictionary.len());
+            }
+            question += " [y,n,d]?";
+            stdout().queue(Print(boring.apply_to(question)))?.flush()?;
+
+            let event = {
+                let _guard = ScopedRaw::new();
+                crossterm::event::read().map_err(|e| {
+                    anyhow::anyhow!("Something unexpected happened on the CLI: {}", e)
+                })?
+            };
+            println!();
+            match event {
+                Event::Key(KeyEvent { code, modifiers }) => match code {
+                    KeyCode::Char('y') | KeyCode::Enter => return Ok(true),
+                    KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                    KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => return Ok(false),
+                    KeyCode::Char('d') => self.write_summary(stdout(), true)?,
+                    x => trace!("Unexpected input {:?}", x),
+                },
+                _ => {}
+            }
+        }
+    }
+
     /// Provide a replacement that was not provided by the backend
     fn enter_custom_replacement(
         &self,
@@ -220,8 +507,9 @@ impl UserPicked {
                 }
             }
             KeyCode::Enter => {
-                let bandaid = state.to_bandaid();
-                return Ok(UserSelection::Replacement(bandaid));
+                if let Some(bandaid) = state.to_bandaid() {
+                    return Ok(UserSelection::Replacement(bandaid));
+                }
             }
             KeyCode::Esc => return Ok(UserSelection::Abort),
             KeyCode::Char('c') if modifiers == KeyModifiers::CONTROL => {
@@ -249,27 +537,15 @@ impl UserPicked {
     fn print_replacements_list(&self, state: &mut State) -> Result<()> {
         let mut stdout = stdout();
 
-        let tick = ContentStyle::new()
-            .foreground(Color::Green)
-            .attribute(Attribute::Bold);
-
-        let highlight = ContentStyle::new()
-            .background(Color::Black)
-            .foreground(Color::Green)
-            .attribute(Attribute::Bold);
+        let theme = crate::output::theme::current();
+        let tick = &theme.selected;
+        let highlight = &theme.selected;
+        let others = &theme.choice;
+        let custom = &theme.custom;
 
-        let others = ContentStyle::new()
-            .background(Color::Black)
-            .foreground(Color::Blue);
+        // render the visible replacements in a vertical list, bottom up
 
-        let custom = ContentStyle::new()
-            .background(Color::Black)
-            .foreground(Color::Yellow);
-
-        // render all replacements in a vertical list
-
-        stdout.queue(cursor::SavePosition).unwrap();
-        let _ = stdout.flush();
+        stdout.queue(cursor::SavePosition)?;
 
         let active_idx = state.pick_idx;
 
@@ -278,130 +554,75 @@ impl UserPicked {
         } else {
             state.custom_replacement.as_str()
         };
-        if state.n_items != active_idx + 1 {
+
+        for idx in state.visible_picks() {
+            let (style, content) = if idx + 1 == state.n_items {
+                (custom, custom_content.to_owned())
+            } else if idx == active_idx {
+                (highlight, state.suggestion.replacements[idx].clone())
+            } else {
+                (others, state.suggestion.replacements[idx].clone())
+            };
             stdout
-                .queue(cursor::MoveUp(1))
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap()
-                .queue(cursor::MoveToColumn(4))
-                .unwrap()
-                .queue(PrintStyledContent(StyledContent::new(
-                    custom,
-                    custom_content,
-                )))
-                .unwrap();
-        } else {
+                .queue(cursor::MoveUp(1))?
+                .queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
+            if idx == active_idx {
+                stdout
+                    .queue(cursor::MoveToColumn(2))?
+                    .queue(Print(tick.apply_to('»')))?;
+            }
             stdout
-                .queue(cursor::MoveUp(1))
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap()
-                .queue(cursor::MoveToColumn(2))
-                .unwrap()
-                .queue(PrintStyledContent(StyledContent::new(tick.clone(), '»')))
-                .unwrap()
-                .queue(cursor::MoveToColumn(4))
-                .unwrap()
-                .queue(PrintStyledContent(StyledContent::new(
-                    custom,
-                    custom_content,
-                )))
-                .unwrap();
+                .queue(cursor::MoveToColumn(4))?
+                .queue(Print(style.apply_to(content)))?;
         }
-        let _ = stdout.flush();
 
-        state
-            .suggestion
-            .replacements
-            .iter()
-            .enumerate()
-            .for_each(|(idx, replacement)| {
-                let idx = idx as u16;
-                if idx != active_idx as u16 {
-                    // TODO figure out a way to deal with those errors better
-                    stdout
-                        .queue(cursor::MoveUp(1))
-                        .unwrap()
-                        .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                        .unwrap()
-                        .queue(cursor::MoveToColumn(4))
-                        .unwrap()
-                        .queue(PrintStyledContent(StyledContent::new(
-                            others.clone(),
-                            replacement.clone(),
-                        )))
-                        .unwrap();
-                } else {
-                    stdout
-                        .queue(cursor::MoveUp(1))
-                        .unwrap()
-                        .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                        .unwrap()
-                        .queue(cursor::MoveToColumn(2))
-                        .unwrap()
-                        .queue(PrintStyledContent(StyledContent::new(tick.clone(), '»')))
-                        .unwrap()
-                        .queue(cursor::MoveToColumn(4))
-                        .unwrap()
-                        .queue(PrintStyledContent(StyledContent::new(
-                            highlight.clone(),
-                            replacement.clone(),
-                        )))
-                        .unwrap();
-                }
-            });
-
-        stdout.queue(cursor::RestorePosition).unwrap();
+        stdout.queue(cursor::RestorePosition)?;
 
         let _ = stdout.flush();
         Ok(())
     }
 
-    /// Wait for user input and process it into a `UserSelection` enum.
-    fn user_input(&self, state: &mut State, running_idx: (usize, usize)) -> Result<UserSelection> {
-        {
-            let _guard = ScopedRaw::new();
+    /// Print the suggestion and the question, followed by space for the
+    /// visible part of the replacements list.
+    fn print_prompt(&self, state: &mut State, question: &str) -> Result<()> {
+        if let Ok((_cols, rows)) = terminal::size() {
+            state.fit_to(rows);
+        }
 
-            let boring = ContentStyle::new()
-                .foreground(Color::Blue)
-                .attribute(Attribute::Bold);
+        println!("{}", state.suggestion);
 
-            let question = format!(
-                "({nth}/{of_n}) Apply this suggestion [y,n,q,a,d,j,e,?]?",
-                nth = running_idx.0 + 1,
-                of_n = running_idx.1
-            );
+        let _guard = ScopedRaw::new();
 
-            // a new suggestion, so prepare for the number of items that are visible
-            // and also overwrite the last lines of the regular print which would
-            // already contain the suggestions
-            // TODO deal with error conversion
-
-            // erase this many lines of the regular print
-            const ERASE: u16 = 4;
-            // lines used by the question
-            const QUESTION: u16 = 4;
-            let extra_rows_to_flush = (state.n_items - (ERASE - QUESTION) as usize) as u16;
-            stdout()
-                .queue(cursor::Hide)
-                .unwrap()
-                .queue(cursor::MoveUp(ERASE)) // erase the 5 last lines of suggestion print
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::FromCursorDown))
-                .unwrap()
-                .queue(cursor::MoveDown(1)) // add a space between the question and the error
-                .unwrap()
-                .queue(PrintStyledContent(StyledContent::new(boring, question)))
-                .unwrap()
-                .queue(terminal::ScrollUp(extra_rows_to_flush))
-                .unwrap()
-                .queue(cursor::MoveToColumn(0))
-                .unwrap()
-                .queue(cursor::MoveDown(extra_rows_to_flush))
-                .unwrap();
+        let boring = crate::output::theme::current().question;
+
+        let mut question = question.to_owned();
+        if state.visible < state.n_items {
+            question += &format!(" (showing {} of {})", state.visible, state.n_items);
         }
 
+        // overwrite the last lines of the regular print which would
+        // already contain the suggestions
+
+        // erase this many lines of the regular print
+        const ERASE: u16 = 4;
+        let extra_rows_to_flush = state.visible as u16;
+        stdout()
+            .queue(cursor::Hide)?
+            .queue(cursor::MoveUp(ERASE))?
+            .queue(terminal::Clear(terminal::ClearType::FromCursorDown))?
+            .queue(cursor::MoveDown(1))? // add a space between the question and the error
+            .queue(Print(boring.apply_to(question)))?
+            .queue(terminal::ScrollUp(extra_rows_to_flush))?
+            .queue(cursor::MoveToColumn(0))?
+            .queue(cursor::MoveDown(extra_rows_to_flush))?;
+        stdout().flush()?;
+        Ok(())
+    }
+
+    /// Wait for user input and process it into a `UserSelection` enum.
+    fn user_input(&self, state: &mut State, question: &str) -> Result<UserSelection> {
+        self.print_prompt(state, question)?;
+
         loop {
             let mut _guard = ScopedRaw::new();
 
@@ -425,8 +646,13 @@ impl UserPicked {
                 .map_err(|e| anyhow::anyhow!("Something unexpected happened on the CLI: {}", e))?
             {
                 Event::Key(event) => event,
-                Event::Resize(..) => {
+                Event::Resize(_cols, _rows) => {
+                    // start over with a clean screen, the previous layout is garbage
                     drop(_guard);
+                    stdout()
+                        .queue(terminal::Clear(terminal::ClearType::All))?
+                        .queue(cursor::MoveTo(0, 0))?;
+                    self.print_prompt(state, question)?;
                     continue;
                 }
                 sth => {
@@ -464,9 +690,10 @@ impl UserPicked {
                 KeyCode::Up => state.select_next(),
                 KeyCode::Down => state.select_previous(),
                 KeyCode::Enter | KeyCode::Char('y') => {
-                    let bandaid = state.to_bandaid();
                     // TODO handle interactive intput for those where there are no suggestions
-                    return Ok(UserSelection::Replacement(bandaid));
+                    if let Some(bandaid) = state.to_bandaid() {
+                        return Ok(UserSelection::Replacement(bandaid));
+                    }
                 }
                 KeyCode::Char('n') => return Ok(UserSelection::Skip),
                 KeyCode::Char('j') => return Ok(UserSelection::Previous),
@@ -479,6 +706,9 @@ impl UserPicked {
                     // jump to the user input entry
                     state.select_custom();
                 }
+                KeyCode::Char('o') => return Ok(UserSelection::Edit),
+                KeyCode::Char('i') => return Ok(UserSelection::Ignore),
+                KeyCode::Char('a') => return Ok(UserSelection::AddToDictionary),
                 KeyCode::Char('?') => return Ok(UserSelection::Help),
                 x => {
                     trace!("Unexpected input {:?}", x);
@@ -488,75 +718,404 @@ impl UserPicked {
         unreachable!("Unexpected return when dealing with user input")
     }
 
-    pub(super) fn select_interactive<'s>(
-        suggestions_per_path: SuggestionSet<'s>,
-        _config: &Config,
-    ) -> Result<(Self, UserSelection)> {
-        let mut picked = UserPicked::default();
+    /// Let the user pick from the `suggestions` of the single file `origin`.
+    ///
+    /// Returns `Abort` if the user quit, `Edit` once the file was edited, and
+    /// `Nop` otherwise.
+    fn select_in_file(
+        &mut self,
+        origin: &ContentOrigin,
+        suggestions: &[Suggestion<'_>],
+        offset: usize,
+        progress: &mut Progress,
+    ) -> Result<UserSelection> {
+        // TODO juck, uggly
+        let mut suggestions_it = suggestions.to_vec().into_iter().enumerate();
 
-        trace!("Select the ones to actully use");
+        let mut direction = Direction::Forward;
+        loop {
+            let opt: Option<(usize, Suggestion)> = match direction {
+                Direction::Forward => suggestions_it.next(),
+                Direction::Backward => suggestions_it.next_back(), // FIXME TODO this is just plain wrong
+            };
 
-        for (origin, suggestions) in suggestions_per_path {
-            let count = suggestions.len();
-            trace!("Path is {} and has {}", origin, count);
+            trace!("next() ---> {:?}", &opt);
 
-            // TODO juck, uggly
-            let mut suggestions_it = suggestions.clone().into_iter().enumerate();
+            if opt.is_none() {
+                match direction {
+                    Direction::Forward => {
+                        trace!("completed file, continue to next");
+                        break; // we completed this file, move on to the next
+                    }
+                    Direction::Backward => {
+                        trace!("went back, now back at the beginning");
+                        suggestions_it = suggestions.to_vec().into_iter().enumerate();
+                        continue;
+                    } // go to the start
+                }
+            }
+            let (idx, suggestion) = opt.expect("Must be Some(_)");
+            progress.suggestion = offset + idx;
+            let word = flagged_word(&suggestion);
+            if self.is_ignored(&word) {
+                continue;
+            }
+            let mut state = State::from(&suggestion);
+
+            let question = format!(
+                "({}) Apply this suggestion [y,n,q,a,d,j,e,o,i,?]?",
+                progress
+            );
+            let mut pick = self.user_input(&mut state, &question)?;
+            while pick == UserSelection::Help {
+                println!("{}", HELP);
+                pick = self.user_input(&mut state, &question)?;
+            }
+            match pick {
+                UserSelection::Abort => return Ok(UserSelection::Abort),
+                UserSelection::SkipFile => break, // break the inner loop
+                UserSelection::Previous => {
+                    unimplemented!("Requires a iterator which works bidrectionally")
+                }
+                UserSelection::Help => {
+                    unreachable!("Help must not be reachable here, it is handled before")
+                }
+                UserSelection::Edit => {
+                    open_in_editor(&suggestion)?;
+                    return Ok(UserSelection::Edit);
+                }
+                UserSelection::Replacement(bandaid) => {
+                    self.add_bandaid(origin, bandaid);
+                }
+                UserSelection::Ignore => self.add_ignored(word),
+                UserSelection::AddToDictionary => self.add_to_dictionary(word),
+                _ => continue,
+            };
 
-            let mut direction = Direction::Forward;
-            loop {
-                let opt: Option<(usize, Suggestion)> = match direction {
-                    Direction::Forward => suggestions_it.next(),
-                    Direction::Backward => suggestions_it.next_back(), // FIXME TODO this is just plain wrong
-                };
+            direction = Direction::Forward;
+        }
+        Ok(UserSelection::Nop)
+    }
 
-                trace!("next() ---> {:?}", &opt);
+    /// Let the user pick one replacement per word in `words`, applied to
+    /// all of its occurrences.
+    ///
+    /// Returns `Abort` if the user quit, and `Nop` otherwise.
+    fn select_by_word(
+        &mut self,
+        words: indexmap::IndexMap<String, Vec<Suggestion<'_>>>,
+        case: &CaseConfig,
+    ) -> Result<UserSelection> {
+        let n_words = words.len();
+        for (idx, (_word, occurrences)) in words.into_iter().enumerate() {
+            // as written at its first occurrence
+            let word = flagged_word(&occurrences[0]);
+            if self.is_ignored(&word) {
+                continue;
+            }
+            let files = occurrences
+                .iter()
+                .map(|suggestion| &suggestion.origin)
+                .collect::<indexmap::IndexSet<_>>()
+                .len();
+            println!(
+                "{} occurrences of {:?} in {} files:",
+                occurrences.len(),
+                word,
+                files
+            );
+            for suggestion in occurrences.iter() {
+                println!(
+                    "  {}:{}:{}",
+                    suggestion.origin,
+                    suggestion.span.start.line,
+                    suggestion.span.start.column + 1
+                );
+            }
 
-                if opt.is_none() {
-                    match direction {
-                        Direction::Forward => {
-                            trace!("completed file, continue to next");
-                            break; // we completed this file, move on to the next
+            let mut state = State::from(&occurrences[0]);
+            let question = format!(
+                "(word {}/{}) Apply to all occurrences [y,n,q,e,i,a,?]?",
+                idx + 1,
+                n_words
+            );
+            let mut pick = self.user_input(&mut state, &question)?;
+            while pick == UserSelection::Help {
+                println!("{}", HELP_BY_WORD);
+                pick = self.user_input(&mut state, &question)?;
+            }
+            match pick {
+                UserSelection::Abort => return Ok(UserSelection::Abort),
+                UserSelection::Replacement(_) => {
+                    // the casing and escaping of every occurrence may differ,
+                    // an entered replacement is used as is though
+                    let replacement = state.replacement().to_owned();
+                    let custom = state.is_custom_entry();
+                    for suggestion in occurrences.iter() {
+                        let bandaid = if custom {
+                            BandAid::for_suggestion(&replacement, suggestion)
+                        } else {
+                            BandAid::for_occurrence(&replacement, suggestion, case)
+                        };
+                        if let Some(bandaid) = bandaid {
+                            self.add_bandaid(&suggestion.origin, bandaid);
+                        } else {
+                            warn!(
+                                "`{}` would end the raw string literal at {}:{}",
+                                replacement,
+                                suggestion.origin.as_path().display(),
+                                suggestion.span.start.line
+                            );
                         }
-                        Direction::Backward => {
-                            trace!("went back, now back at the beginning");
-                            suggestions_it = suggestions.clone().into_iter().enumerate();
-                            continue;
-                        } // go to the start
                     }
                 }
-                let (idx, suggestion) = opt.expect("Must be Some(_)");
-                if suggestion.replacements.is_empty() {
-                    trace!("Suggestion did not contain a replacement, skip");
-                    continue;
-                }
-                println!("{}", suggestion);
+                UserSelection::Ignore => self.add_ignored(word),
+                UserSelection::AddToDictionary => self.add_to_dictionary(word),
+                // moving between files or editing them is meaningless here
+                _ => continue,
+            }
+        }
+        Ok(UserSelection::Nop)
+    }
+
+    pub(super) fn select_interactive<'s>(
+        mut suggestions_per_path: SuggestionSet<'s>,
+        config: &Config,
+    ) -> Result<(Self, UserSelection)> {
+        let mut picked = UserPicked::default();
 
-                let mut state = State::from(&suggestion);
+        trace!("Select the ones to actully use");
 
-                let mut pick = picked.user_input(&mut state, (idx, count))?;
-                while pick == UserSelection::Help {
-                    println!("{}", HELP);
-                    pick = picked.user_input(&mut state, (idx, count))?;
+        // suggestions without replacements can not be picked, so they are
+        // not part of the progress either
+        suggestions_per_path.retain(|_origin, suggestion| !suggestion.replacements.is_empty());
+
+        if config.accept_unambiguous {
+            // there is nothing to decide for suggestions with a single
+            // replacement, so only the remaining ones are presented
+            suggestions_per_path.retain(|origin, suggestion| {
+                if suggestion.replacements.len() != 1 {
+                    return true;
                 }
-                match pick {
-                    UserSelection::Abort => return Ok((picked, UserSelection::Abort)),
-                    UserSelection::SkipFile => break, // break the inner loop
-                    UserSelection::Previous => {
-                        unimplemented!("Requires a iterator which works bidrectionally")
-                    }
-                    UserSelection::Help => {
-                        unreachable!("Help must not be reachable here, it is handled before")
+                match State::from(&*suggestion).to_bandaid() {
+                    Some(bandaid) => {
+                        picked.add_bandaid(origin, bandaid);
+                        false
                     }
-                    UserSelection::Replacement(bandaid) => {
-                        picked.add_bandaid(&origin, bandaid);
-                    }
-                    _ => continue,
-                };
+                    None => true,
+                }
+            });
+            debug!("Accepted {} unambiguous suggestions", picked.total_count());
+        }
+
+        if config.fix_by_word {
+            let selection =
+                picked.select_by_word(group_by_word(suggestions_per_path), &config.case())?;
+            return Ok((picked, selection));
+        }
+
+        let mut progress = Progress {
+            file: 0,
+            files: suggestions_per_path.len(),
+            suggestion: 0,
+            suggestions: suggestions_per_path.total_count(),
+        };
+        // number of suggestions in all previous files
+        let mut offset = 0;
 
-                direction = Direction::Forward;
+        for (file, (origin, suggestions)) in suggestions_per_path.into_iter().enumerate() {
+            let mut count = suggestions.len();
+            trace!("Path is {} and has {}", origin, count);
+            progress.file = file;
+
+            let mut selection =
+                picked.select_in_file(&origin, &suggestions, offset, &mut progress)?;
+            while selection == UserSelection::Edit {
+                // picks are located in the content before editing
+                if let Some(dropped) = picked.bandaids.shift_remove(&origin) {
+                    warn!(
+                        "Discarded {} picked changes of the edited {}",
+                        dropped.len(),
+                        origin
+                    );
+                }
+                let partitions = crate::checker::partition(
+                    crate::traverse::extract_again(&origin, config)?,
+                    config,
+                );
+                let mut rechecked = SuggestionSet::new();
+                for (config, documentation) in partitions.iter() {
+                    rechecked.join(crate::checker::check(documentation, config)?);
+                }
+                let fresh = rechecked
+                    .into_iter()
+                    .filter(|(rechecked, _suggestions)| *rechecked == origin)
+                    .flat_map(|(_origin, suggestions)| suggestions)
+                    .filter(|suggestion| !suggestion.replacements.is_empty())
+                    .collect::<Vec<_>>();
+                progress.suggestions = progress.suggestions - count + fresh.len();
+                count = fresh.len();
+                selection = picked.select_in_file(&origin, &fresh, offset, &mut progress)?;
             }
+            if selection == UserSelection::Abort {
+                return Ok((picked, UserSelection::Abort));
+            }
+            offset += count;
         }
         Ok((picked, UserSelection::Nop))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("summary.rs");
+        std::fs::write(&path, "/// A literl\n/// and the secnd one.\n").unwrap();
+        let origin = ContentOrigin::RustSourceFile(path.clone());
+
+        let mut picked = UserPicked::default();
+        for (line, column, length, replacement) in
+            [(1, 6, 6, "literal"), (2, 12, 5, "second")].iter().copied()
+        {
+            let span = Span {
+                start: LineColumn { line, column },
+                end: LineColumn {
+                    line,
+                    column: column + length - 1,
+                },
+            };
+            picked.add_bandaid(&origin, BandAid::from((replacement.to_owned(), &span)));
+        }
+
+        let mut sink = Vec::new();
+        picked.write_summary(&mut sink, false).unwrap();
+        let summary = String::from_utf8(sink).unwrap();
+        assert_eq!(
+            summary,
+            format!(
+                "Pending changes:\n     2 in {}\nTotal: 2 changes in 1 files\n",
+                origin
+            )
+        );
+
+        let mut sink = Vec::new();
+        picked.write_summary(&mut sink, true).unwrap();
+        let diff = String::from_utf8(sink).unwrap();
+        let lines = diff.lines().collect::<Vec<_>>();
+        assert_eq!(lines[2], r#"       1:7 "literl" -> "literal""#);
+        assert_eq!(lines[3], r#"       2:13 "secnd" -> "second""#);
+    }
+
+    #[test]
+    fn words() {
+        use crate::checker::dummy;
+        use crate::{fluff_up, Documentation};
+
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!("Teh literals of teh literals"),
+        ));
+        let suggestions = dummy::check_all(&d);
+
+        let words = group_by_word(suggestions);
+        assert_eq!(
+            words
+                .iter()
+                .map(|(word, occurrences)| (word.as_str(), occurrences.len()))
+                .collect::<Vec<_>>(),
+            vec![("teh", 2), ("literals", 2), ("of", 1)]
+        );
+    }
+
+    #[test]
+    fn scrolling() {
+        use crate::checker::dummy;
+        use crate::{fluff_up, Documentation};
+
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("Teh")));
+        let suggestions = dummy::check_all(&d);
+        let mut suggestion = suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .next()
+            .unwrap()
+            .clone();
+        suggestion.replacements = (0..20).map(|idx| format!("the{}", idx)).collect();
+
+        let mut state = State::from(&suggestion);
+        let visible = |state: &State| state.visible_picks().collect::<Vec<_>>();
+        // everything fits, the custom entry is at the bottom
+        assert_eq!(state.n_items, 21);
+        assert_eq!(
+            visible(&state),
+            [20].iter().copied().chain(0..20).collect::<Vec<_>>()
+        );
+
+        state.fit_to(RESERVED_ROWS as u16 + 5);
+        assert_eq!(state.visible, 5);
+        assert_eq!(visible(&state), vec![20, 0, 1, 2, 3]);
+
+        // scrolls up once the pick leaves the top
+        for _ in 0..5 {
+            state.select_next();
+        }
+        assert_eq!(state.pick_idx, 5);
+        assert_eq!(visible(&state), vec![1, 2, 3, 4, 5]);
+
+        // back to the bottom
+        state.select_custom();
+        assert!(state.is_custom_entry());
+        assert_eq!(visible(&state), vec![20, 0, 1, 2, 3]);
+
+        // wraps around to the last replacement at the very top
+        state.select_previous();
+        assert_eq!(state.pick_idx, 19);
+        assert_eq!(visible(&state), vec![15, 16, 17, 18, 19]);
+        state.select_next();
+        assert!(state.is_custom_entry());
+        assert_eq!(visible(&state), vec![20, 0, 1, 2, 3]);
+
+        // at least the pick itself is visible, at most all of them
+        state.select_previous();
+        state.fit_to(0);
+        assert_eq!(visible(&state), vec![19]);
+        state.fit_to(u16::MAX);
+        assert_eq!(state.visible, 21);
+        assert_eq!(visible(&state).len(), 21);
+
+        // nothing to pick besides the custom entry
+        suggestion.replacements.clear();
+        let mut state = State::from(&suggestion);
+        state.fit_to(3);
+        assert_eq!(visible(&state), vec![0]);
+        state.select_next();
+        state.select_previous();
+        assert!(state.is_custom_entry());
+    }
+
+    #[test]
+    fn editor() {
+        let command = editor_command("code --wait", Path::new("src/lib.rs"), 7).unwrap();
+        assert_eq!(command.get_program(), "code");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec!["--wait", "+7", "src/lib.rs"]
+        );
+        assert!(editor_command(" ", Path::new("src/lib.rs"), 7).is_none());
+    }
+
+    #[test]
+    fn progress_display() {
+        let progress = Progress {
+            file: 2,
+            files: 17,
+            suggestion: 41,
+            suggestions: 318,
+        };
+        assert_eq!(progress.to_string(), "file 3/17 — suggestion 42/318");
+    }
+}
@@ -0,0 +1,188 @@
+//! Keep concurrent runs from writing to the same project at once.
+//!
+//! Before any correction is staged, a lock per project of the corrected files
+//! is taken, the project being the outermost directory with a `Cargo.toml`.
+//! A second run waits until the first one swapped its files into place, and
+//! then refuses to touch files which changed since it checked them, see
+//! [`crate::checksum`].
+//!
+//! The lock files reside in the temporary directory, so the project itself
+//! is never touched. On unix the lock is an advisory `flock`, released by the
+//! kernel even if the process is killed. Elsewhere the lock is the existence
+//! of the lock file, a leftover of a killed run is removed once it is older
+//! than [`STALE_AFTER`].
+
+use anyhow::{anyhow, Result};
+use log::{debug, info};
+
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+
+/// Age after which a lock file is assumed to be left over by a killed run,
+/// where the lock is not released by the kernel.
+#[cfg_attr(unix, allow(dead_code))]
+const STALE_AFTER: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// The project `path` belongs to, the outermost directory with a manifest,
+/// or the directory of `path` if there is none.
+fn project(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or(path);
+    dir.ancestors()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .last()
+        .unwrap_or(dir)
+        .to_owned()
+}
+
+/// The 64 bit FNV-1a hash of `bytes`, which unlike the `DefaultHasher` is
+/// the same for all builds, so different versions lock the same file.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Location of the lock file of `project`.
+fn lock_path(project: &Path) -> PathBuf {
+    let hash = fnv1a(project.to_string_lossy().as_bytes());
+    std::env::temp_dir().join(format!("cargo-spellcheck-{:016x}.lock", hash))
+}
+
+/// Held locks of all projects, released on drop.
+#[derive(Debug)]
+pub(crate) struct ProjectLock {
+    held: Vec<(PathBuf, File)>,
+}
+
+impl ProjectLock {
+    /// Lock all projects the files at `paths` belong to, waiting for other
+    /// runs holding any of them.
+    pub(crate) fn acquire<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Result<Self> {
+        let mut projects = paths.into_iter().map(project).collect::<Vec<_>>();
+        // always in the same order, so two runs never wait for each other
+        projects.sort();
+        projects.dedup();
+        let mut held = Vec::with_capacity(projects.len());
+        for project in projects {
+            let lock = lock_path(&project);
+            debug!("Locking {} with {}", project.display(), lock.display());
+            let file = lock_file(&project, &lock)
+                .map_err(|e| anyhow!("Failed to lock {}", project.display()).context(e))?;
+            held.push((lock, file));
+        }
+        Ok(Self { held })
+    }
+}
+
+#[cfg(unix)]
+fn lock_file(project: &Path, lock: &Path) -> std::io::Result<File> {
+    use std::os::unix::io::AsRawFd;
+
+    let file = OpenOptions::new().write(true).create(true).open(lock)?;
+    let fd = file.as_raw_fd();
+    if unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(file);
+    }
+    let e = std::io::Error::last_os_error();
+    if e.raw_os_error() != Some(libc::EWOULDBLOCK) {
+        return Err(e);
+    }
+    info!(
+        "Waiting for another run writing to {} to finish",
+        project.display()
+    );
+    if unsafe { libc::flock(fd, libc::LOCK_EX) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn lock_file(project: &Path, lock: &Path) -> std::io::Result<File> {
+    let mut waiting = false;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(lock) {
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                let stale = std::fs::metadata(lock)
+                    .and_then(|meta| meta.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .map_or(false, |age| age > STALE_AFTER);
+                if stale {
+                    log::warn!(
+                        "Removing the stale lock {} of {}",
+                        lock.display(),
+                        project.display()
+                    );
+                    // another waiting run may have removed it already
+                    let _ = std::fs::remove_file(lock);
+                    continue;
+                }
+                if !waiting {
+                    info!(
+                        "Waiting for another run writing to {} to finish, remove {} if there is none",
+                        project.display(),
+                        lock.display()
+                    );
+                    waiting = true;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            result => return result,
+        }
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        for (_lock, file) in self.held.drain(..) {
+            // close before removing, windows refuses to remove open files
+            drop(file);
+            // on unix, the file stays for the next run, removing it would allow
+            // a waiting run and a new one to lock different files
+            #[cfg(not(unix))]
+            {
+                if let Err(e) = std::fs::remove_file(&_lock) {
+                    log::warn!("Failed to remove lock {}: {}", _lock.display(), e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_lock_path() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            lock_path(Path::new("/home/alice/project")),
+            std::env::temp_dir().join("cargo-spellcheck-5b942efdd157cfcc.lock")
+        );
+    }
+
+    #[test]
+    fn projects() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("member/src")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\n").unwrap();
+        std::fs::write(root.join("member/Cargo.toml"), "[package]\n").unwrap();
+
+        assert_eq!(project(&root.join("member/src/lib.rs")), root);
+        assert_eq!(project(&root.join("README.md")), root);
+
+        let lock = ProjectLock::acquire(vec![
+            root.join("member/src/lib.rs").as_path(),
+            root.join("README.md").as_path(),
+        ])
+        .unwrap();
+        assert_eq!(lock.held.len(), 1);
+        drop(lock);
+        // released again
+        drop(ProjectLock::acquire(std::iter::once(root.join("README.md").as_path())).unwrap());
+    }
+}
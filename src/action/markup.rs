@@ -0,0 +1,144 @@
+//! Keep corrections of common mark files from breaking the surrounding markup.
+//!
+//! A replacement which spills into emphasis markers, link brackets or a
+//! reference definition changes how the file renders, even though only a word
+//! was meant to be replaced. All corrections are applied at once and the
+//! result is parsed again. Only if that changes anything but the text, the
+//! corrections are tried one by one and those which change the markup are not
+//! applied but reported. If the remaining ones still change the markup when
+//! combined, none of them is applied.
+
+use super::{apply_patches, Patch};
+use crate::BandAid;
+
+use anyhow::Result;
+use log::warn;
+use pulldown_cmark::{CowStr, Event, Options, Parser};
+
+use std::path::Path;
+
+/// The structure of `content`, all events with the text erased and adjacent
+/// text merged, so only changes of the markup itself are visible.
+fn structure(content: &str) -> Vec<Event<'_>> {
+    let mut structure = Vec::new();
+    for event in Parser::new_ext(content, Options::all()) {
+        let event = match event {
+            // reflowing turns line breaks into spaces and vice versa
+            Event::Text(_) | Event::SoftBreak => Event::Text(CowStr::Borrowed("")),
+            Event::Code(_) => Event::Code(CowStr::Borrowed("")),
+            Event::Html(_) => Event::Html(CowStr::Borrowed("")),
+            event => event,
+        };
+        if matches!(event, Event::Text(_)) && matches!(structure.last(), Some(Event::Text(_))) {
+            continue;
+        }
+        structure.push(event);
+    }
+    structure
+}
+
+/// If `bandaids` applied to `content` keep the markup structure as it is.
+fn preserves(content: &str, original: &[Event<'_>], bandaids: &[BandAid]) -> Result<bool> {
+    let mut patched = Vec::with_capacity(content.len());
+    apply_patches(bandaids.iter().map(Patch::from), content, &mut patched)?;
+    let patched = String::from_utf8(patched)?;
+    Ok(structure(patched.as_str()) == original)
+}
+
+/// Warn about not applying `bandaid` to the file at `path`.
+fn reject(path: &Path, bandaid: &BandAid, why: &str) {
+    warn!(
+        "Not applying `{}` at {}:{}:{}, {}",
+        bandaid.content,
+        path.display(),
+        bandaid.span.start.line,
+        bandaid.span.start.column + 1,
+        why
+    );
+}
+
+/// Retain only the bandaids which keep the markup structure of `content`, the
+/// content of the common mark file at `path`, and warn about all others.
+///
+/// Assumes `bandaids` are sorted and non overlapping, like applying them does.
+pub(crate) fn retain_preserving(
+    path: &Path,
+    content: &str,
+    bandaids: impl IntoIterator<Item = BandAid>,
+) -> Result<Vec<BandAid>> {
+    let original = structure(content);
+    let bandaids = bandaids.into_iter().collect::<Vec<_>>();
+    if preserves(content, &original, &bandaids)? {
+        return Ok(bandaids);
+    }
+
+    let mut retained = Vec::with_capacity(bandaids.len());
+    for bandaid in bandaids {
+        if preserves(content, &original, std::slice::from_ref(&bandaid))? {
+            retained.push(bandaid);
+        } else {
+            reject(path, &bandaid, "it would change the markup");
+        }
+    }
+    if preserves(content, &original, &retained)? {
+        return Ok(retained);
+    }
+    for bandaid in retained {
+        reject(
+            path,
+            &bandaid,
+            "combined with the other changes it would change the markup",
+        );
+    }
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::bandaid::tests::bandaid;
+
+    #[test]
+    fn markup_preserved() {
+        const CONTENT: &str = "A *wrod* and [a lnik][home].\n\n[home]: https://example.com\n";
+        let retained = retain_preserving(
+            Path::new("README.md"),
+            CONTENT,
+            vec![
+                // only the word
+                bandaid(1, 3, 4, "word"),
+                // swallows the closing emphasis marker
+                bandaid(1, 3, 5, "word"),
+                // the text of the link
+                bandaid(1, 16, 4, "link"),
+                // the closing bracket of the link text
+                bandaid(1, 19, 2, "k "),
+                // the label of the reference definition
+                bandaid(3, 1, 4, "Home page"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            retained,
+            vec![bandaid(1, 3, 4, "word"), bandaid(1, 16, 4, "link")]
+        );
+
+        // reflowing only moves line breaks
+        let retained = retain_preserving(
+            Path::new("README.md"),
+            "A *word*\nwrapped.\n",
+            vec![bandaid(1, 7, 2, "* ")],
+        )
+        .unwrap();
+        assert_eq!(retained.len(), 1);
+
+        // each is plain text on its own, but both together emphasize
+        let retained = retain_preserving(
+            Path::new("README.md"),
+            "A word and more.\n",
+            vec![bandaid(1, 2, 4, "*word"), bandaid(1, 11, 4, "more*")],
+        )
+        .unwrap();
+        assert!(retained.is_empty());
+    }
+}
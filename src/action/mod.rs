@@ -1,18 +1,22 @@
 //! Covers all user triggered actions (except for signals).
 
 use super::*;
-use anyhow::{anyhow, Result};
-use log::{debug, trace};
+use crate::timings::{Stage, Timer};
+use anyhow::Result;
+use log::{debug, info, trace};
 
-use std::fs::{self, OpenOptions};
-use std::io::{Read, Write};
-
-use std::path::PathBuf;
+use std::io::Write;
 
 pub mod bandaid;
 pub mod interactive;
+mod lock;
+mod markup;
+mod safe;
+mod transaction;
 
 pub(crate) use bandaid::*;
+pub use transaction::abandon as abandon_pending_writes;
+use transaction::Transaction;
 
 /// State of conclusion.
 #[derive(Debug, Clone, Copy)]
@@ -24,16 +28,6 @@ pub enum Finish {
     MistakeCount(usize),
 }
 
-impl Finish {
-    /// A helper to determine if any mistakes were found.
-    pub fn found_any(&self) -> bool {
-        match *self {
-            Self::MistakeCount(n) if n > 0 => true,
-            _ => false,
-        }
-    }
-}
-
 /// A patch to be stitched ontop of another string.
 ///
 /// Has intentionally no awareness of any rust or cmark/markdown semantics.
@@ -218,75 +212,37 @@ pub enum Action {
     Fix,
     /// Reflow doc comments so they adhere to a given maximum column width.
     Reflow,
+    /// Apply only fixes which are safe without interaction, see `safe`, and
+    /// report all others.
+    FixSafe,
 }
 
 impl Action {
-    /// Apply bandaids to the file represented by content origin.
-    pub fn write_changes_to_disk(
-        &self,
-        origin: ContentOrigin,
-        bandaids: impl IntoIterator<Item = BandAid>,
-    ) -> Result<()> {
-        match origin {
-            ContentOrigin::CommonMarkFile(path) => self.correct_file(path, bandaids),
-            ContentOrigin::RustSourceFile(path) => self.correct_file(path, bandaids),
-            ContentOrigin::RustDocTest(path, _span) => self.correct_file(path, bandaids),
-            #[cfg(test)]
-            ContentOrigin::TestEntityRust => unreachable!("Use a proper file"),
-            #[cfg(test)]
-            ContentOrigin::TestEntityCommonMark => unreachable!("Use a proper file"),
+    /// Apply bandaids to the files represented by their content origins,
+    /// either all files are corrected or none of them is touched.
+    pub fn write_all_changes_to_disk<I, B>(&self, changes: I, config: &Config) -> Result<()>
+    where
+        I: IntoIterator<Item = (ContentOrigin, B)>,
+        B: IntoIterator<Item = BandAid>,
+    {
+        let changes = changes.into_iter().collect::<Vec<_>>();
+        let _lock = lock::ProjectLock::acquire(changes.iter().map(|(origin, _)| origin.as_path()))?;
+        let mut transaction = Transaction::new(config.sync_writes);
+        for (origin, bandaids) in changes {
+            let _timer = Timer::start(Stage::Patching, origin.as_path());
+            match origin {
+                ContentOrigin::CommonMarkFile(path) => {
+                    transaction.stage_commonmark(&path, bandaids)?
+                }
+                ContentOrigin::RustSourceFile(path) => transaction.stage(&path, bandaids)?,
+                ContentOrigin::RustDocTest(path, _span) => transaction.stage(&path, bandaids)?,
+                #[cfg(test)]
+                ContentOrigin::TestEntityRust => unreachable!("Use a proper file"),
+                #[cfg(test)]
+                ContentOrigin::TestEntityCommonMark => unreachable!("Use a proper file"),
+            }
         }
-    }
-
-    /// assumes suggestions are sorted by line number and column number and must be non overlapping
-    fn correct_file(
-        &self,
-        path: PathBuf,
-        bandaids: impl IntoIterator<Item = BandAid>,
-    ) -> Result<()> {
-        let path = path
-            .as_path()
-            .canonicalize()
-            .map_err(|e| anyhow!("Failed to canonicalize {}", path.display()).context(e))?;
-        let path = path.as_path();
-        trace!("Attempting to open {} as read", path.display());
-        let ro = std::fs::OpenOptions::new()
-            .read(true)
-            .open(path)
-            .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
-
-        let mut reader = std::io::BufReader::new(ro);
-
-        const TEMPORARY: &'static str = ".spellcheck.tmp";
-
-        let tmp = std::env::current_dir()
-            .expect("Must have cwd")
-            .join(TEMPORARY);
-        // let tmp = tmp.canonicalize().map_err(|e| { anyhow!("Failed to canonicalize {}", tmp.display() ).context(e) })?;
-        //trace!("Attempting to open {} as read", tmp.display());
-        let wr = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&tmp)
-            .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
-
-        let mut writer = std::io::BufWriter::with_capacity(1024, wr);
-
-        let mut content = String::with_capacity(2e6 as usize);
-        reader.get_mut().read_to_string(&mut content)?;
-
-        apply_patches(
-            bandaids.into_iter().map(|x| Patch::from(x)),
-            content.as_str(), // FIXME for efficiency, correct_lines should integrate with `BufRead` instead of a `String` buffer
-            &mut writer,
-        )?;
-
-        writer.flush()?;
-
-        fs::rename(tmp, path)?;
-
-        Ok(())
+        transaction.commit()
     }
 
     /// Consumingly apply the user picked changes to a file.
@@ -296,13 +252,11 @@ impl Action {
     pub fn write_user_pick_changes_to_disk(
         &self,
         userpicked: interactive::UserPicked,
-        _config: &Config,
+        config: &Config,
     ) -> Result<()> {
         if userpicked.total_count() > 0 {
             debug!("Writing changes back to disk");
-            for (origin, bandaids) in userpicked.bandaids.into_iter() {
-                self.write_changes_to_disk(origin, bandaids.into_iter())?;
-            }
+            self.write_all_changes_to_disk(userpicked.bandaids, config)?;
         } else {
             debug!("No band aids to apply");
         }
@@ -310,25 +264,65 @@ impl Action {
     }
 
     /// Purpose was to check, checking complete, so print the results.
-    fn check(&self, suggestions_per_path: SuggestionSet, _config: &Config) -> Result<Finish> {
-        let mut count = 0usize;
-        for (_path, suggestions) in suggestions_per_path {
-            count += suggestions.len();
-            for suggestion in suggestions {
-                println!("{}", suggestion);
-            }
-        }
+    ///
+    /// Only suggestions at least as severe as `fail_on` are accounted for in the
+    /// mistake count.
+    fn check(
+        &self,
+        suggestions_per_path: SuggestionSet,
+        checked: &[ContentOrigin],
+        config: &Config,
+    ) -> Result<Finish> {
+        let count = suggestions_per_path
+            .iter()
+            .flat_map(|(_path, suggestions)| suggestions.iter())
+            .filter(|suggestion| suggestion.severity.fails(config.fail_on))
+            .count();
+        crate::output::write_suggestions(
+            &suggestions_per_path,
+            checked,
+            &config.output,
+            config.fail_on,
+            std::io::stdout().lock(),
+        )?;
+        crate::output::write_reports(&suggestions_per_path, &config.output)?;
         Ok(Finish::MistakeCount(count))
     }
 
-    /// Run the requested action.
-    pub fn run(self, suggestions: SuggestionSet, config: &Config) -> Result<Finish> {
+    /// Take all fixes out of `suggestions` which are safe without
+    /// interaction, apply them and return how many were applied.
+    ///
+    /// The spans of the remaining suggestions of the corrected files are stale
+    /// afterwards, so they must be checked again before being reported.
+    pub fn apply_safe_fixes(
+        &self,
+        suggestions: &mut SuggestionSet,
+        config: &Config,
+    ) -> Result<usize> {
+        let fixes = safe::take_safe_fixes(suggestions);
+        let n = fixes.values().map(Vec::len).sum::<usize>();
+        debug!("Applying {} safe fixes", n);
+        if n > 0 {
+            self.write_all_changes_to_disk(fixes, config)?;
+        }
+        Ok(n)
+    }
+
+    /// Run the requested action on the `suggestions` found in the `checked`
+    /// files.
+    pub fn run(
+        self,
+        suggestions: SuggestionSet,
+        checked: &[ContentOrigin],
+        config: &Config,
+    ) -> Result<Finish> {
         match self {
-            Self::Check => self.check(suggestions, config),
+            // the safe fixes are applied before, see `apply_safe_fixes`
+            Self::Check | Self::FixSafe => self.check(suggestions, checked, config),
             Self::Reflow => {
                 let n = suggestions.len();
 
-                for (origin, suggestions) in suggestions {
+                let changes = suggestions.into_iter().map(|(origin, suggestions)| {
                     let bandaids = suggestions
                         .into_iter()
                         .filter_map(|suggestion| {
@@ -341,8 +335,9 @@ impl Action {
                             })
                         })
                         .collect::<Vec<_>>();
-                    self.write_changes_to_disk(origin, bandaids)?;
-                }
+                    (origin, bandaids)
+                });
+                self.write_all_changes_to_disk(changes, config)?;
                 Ok(Finish::MistakeCount(n))
             }
             Self::Fix => {
@@ -350,8 +345,37 @@ impl Action {
                     interactive::UserPicked::select_interactive(suggestions, config)?;
                 if user_sel == interactive::UserSelection::Abort {
                     Ok(Finish::Abort)
+                } else if !picked.is_empty() && !picked.confirm()? {
+                    debug!("Discarding all picks as requested");
+                    Ok(Finish::Abort)
                 } else {
                     let n = picked.total_count();
+                    if !picked.ignored.is_empty() {
+                        let dir = match config.ignore_dir {
+                            Some(ref dir) => dir.clone(),
+                            None => crate::traverse::cwd()?,
+                        };
+                        let path = crate::config::IgnoreConfig::add_words(&dir, &picked.ignored)?;
+                        info!(
+                            "Added {} words to the ignore file {}",
+                            picked.ignored.len(),
+                            path.display()
+                        );
+                    }
+                    if !picked.dictionary.is_empty() {
+                        let path = config
+                            .hunspell
+                            .as_ref()
+                            .map_or_else(
+                                crate::config::HunspellConfig::personal_dictionary_path,
+                                |hunspell| hunspell.dictionary_for_picks(),
+                            )
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("There is no dictionary to add the words to")
+                            })?;
+                        let added = crate::import::add_to_dictionary(&path, &picked.dictionary)?;
+                        info!("Added {} words to the dictionary {}", added, path.display());
+                    }
                     self.write_user_pick_changes_to_disk(picked, config)?;
                     Ok(Finish::MistakeCount(n))
                 }
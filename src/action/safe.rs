@@ -0,0 +1,115 @@
+//! Fixes which are safe to apply without any interaction.
+//!
+//! Only removals of doubled words and hits in a table of well known typos,
+//! which have exactly one correction, are considered safe.
+
+use crate::util::{match_case, sub_chars};
+use crate::{BandAid, Category, ContentOrigin, Suggestion, SuggestionSet};
+
+use indexmap::IndexMap;
+
+/// Well known typos and their only correction, sorted by the typo.
+const TYPOS: &[(&str, &str)] = &[
+    ("accomodate", "accommodate"),
+    ("acheive", "achieve"),
+    ("adress", "address"),
+    ("agressive", "aggressive"),
+    ("alot", "a lot"),
+    ("apparantly", "apparently"),
+    ("arguement", "argument"),
+    ("basicly", "basically"),
+    ("begining", "beginning"),
+    ("beleive", "believe"),
+    ("commited", "committed"),
+    ("concious", "conscious"),
+    ("definately", "definitely"),
+    ("enviroment", "environment"),
+    ("existance", "existence"),
+    ("explicitely", "explicitly"),
+    ("occured", "occurred"),
+    ("occurence", "occurrence"),
+    ("paramter", "parameter"),
+    ("priviledge", "privilege"),
+    ("recieve", "receive"),
+    ("reciever", "receiver"),
+    ("refered", "referred"),
+    ("seperate", "separate"),
+    ("succesful", "successful"),
+    ("teh", "the"),
+    ("threshhold", "threshold"),
+    ("untill", "until"),
+    ("wich", "which"),
+];
+
+/// The replacement of `suggestion`, if it is safe to apply without asking.
+pub(crate) fn safe_replacement(suggestion: &Suggestion<'_>) -> Option<String> {
+    match suggestion.category {
+        Category::Repetition if suggestion.replacements.len() == 1 => {
+            suggestion.replacements.first().cloned()
+        }
+        Category::Spelling => {
+            let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+            let lowercase = word.to_lowercase();
+            TYPOS
+                .binary_search_by_key(&lowercase.as_str(), |(typo, _)| typo)
+                .ok()
+                .map(|idx| match_case(&word, TYPOS[idx].1))
+        }
+        _ => None,
+    }
+}
+
+/// Take all safe fixes out of `suggestions`, leaving only the ones
+/// which require a decision.
+///
+/// A safe fix overlapping the previous one of the same file is left in
+/// `suggestions` as well.
+pub(crate) fn take_safe_fixes(
+    suggestions: &mut SuggestionSet<'_>,
+) -> IndexMap<ContentOrigin, Vec<BandAid>> {
+    let mut fixes = IndexMap::<ContentOrigin, Vec<BandAid>>::new();
+    suggestions.retain(|origin, suggestion| {
+        let bandaid = match safe_replacement(suggestion)
+            .and_then(|replacement| BandAid::for_suggestion(&replacement, suggestion))
+        {
+            Some(bandaid) => bandaid,
+            None => return true,
+        };
+        let bandaids = fixes.entry(origin.clone()).or_default();
+        if let Some(previous) = bandaids.last() {
+            if previous.span.end >= suggestion.span.start {
+                return true;
+            }
+        }
+        bandaids.push(bandaid);
+        false
+    });
+    fixes.retain(|_origin, bandaids| !bandaids.is_empty());
+    fixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typos_are_sorted() {
+        assert!(TYPOS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn take_typos() {
+        use crate::checker::dummy;
+        use crate::{fluff_up, Documentation};
+
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("Teh literals")));
+        let mut suggestions = dummy::check_all(&d);
+        assert_eq!(suggestions.total_count(), 2);
+
+        let fixes = take_safe_fixes(&mut suggestions);
+        assert_eq!(suggestions.total_count(), 1);
+        let bandaids = fixes.get(&ContentOrigin::TestEntityRust).unwrap();
+        assert_eq!(bandaids.len(), 1);
+        assert_eq!(bandaids[0].content, "The");
+    }
+}
@@ -0,0 +1,372 @@
+//! Write corrections of multiple files all at once or not at all.
+//!
+//! Every corrected file is staged next to the original first, and only once
+//! all files are staged successfully, they are swapped into place. A failure
+//! while swapping restores the files that were already swapped.
+//!
+//! An interrupt waits for the file currently written, and removes all staged
+//! files which are not in place yet, see [`abandon`].
+
+use super::{apply_patches, markup, Patch};
+use crate::BandAid;
+
+use anyhow::{anyhow, bail, Result};
+use indexmap::IndexMap;
+use log::{trace, warn};
+
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Suffix of the file names of staged files.
+pub(crate) const TEMPORARY: &str = ".spellcheck.tmp";
+
+lazy_static::lazy_static! {
+    /// All staged files of all transactions of this process, locked while any
+    /// file is written.
+    static ref STAGED: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+/// The staged files of this process, and if writing was abandoned.
+#[derive(Debug, Default)]
+struct Registry {
+    files: HashSet<PathBuf>,
+    /// Set by [`abandon`], nothing is staged or swapped into place anymore.
+    abandoned: bool,
+}
+
+impl Registry {
+    /// Lock the registry, unless writing was abandoned.
+    fn lock() -> Result<std::sync::MutexGuard<'static, Self>> {
+        let registry = STAGED.lock().expect("Lock is never poisoned. qed");
+        if registry.abandoned {
+            bail!("Writing corrections was abandoned");
+        }
+        Ok(registry)
+    }
+}
+
+/// Wait for the file currently written to be completed, and remove all
+/// staged files which are not in place yet.
+///
+/// Only to be called right before the process exits, any further attempt
+/// to stage or commit fails.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub fn abandon() {
+    let mut registry = STAGED.lock().expect("Lock is never poisoned. qed");
+    registry.abandoned = true;
+    for tmp in registry.files.drain() {
+        trace!("Removing {}", tmp.display());
+        let _ = fs::remove_file(tmp);
+    }
+}
+
+/// A corrected file, which is not in place yet.
+#[derive(Debug)]
+struct Staged {
+    /// Location of the corrected content.
+    tmp: PathBuf,
+    /// Content of the file before any correction.
+    original: String,
+}
+
+/// All files staged so far, staged files which are not committed are
+/// removed on drop.
+#[derive(Debug, Default)]
+pub(crate) struct Transaction {
+    staged: IndexMap<PathBuf, Staged>,
+    /// Flush staged files and their directories to disk before swapping.
+    sync: bool,
+}
+
+/// Location to stage the corrected content of `path` at, unique per process
+/// so concurrent runs never write to the same staged file.
+fn tmp_path(path: &Path) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{}.{}{}",
+        file_name,
+        std::process::id(),
+        TEMPORARY
+    ))
+}
+
+/// Flush the entries of directory `dir` to disk, only possible on unix.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| anyhow!("Failed to sync directory {}", dir.display()).context(e))?;
+    Ok(())
+}
+
+impl Transaction {
+    /// With `sync`, all staged files and their directories are flushed to
+    /// disk before being swapped into place.
+    pub(crate) fn new(sync: bool) -> Self {
+        Self {
+            staged: IndexMap::new(),
+            sync,
+        }
+    }
+
+    /// Stage the corrected content of the file at `path`, applying the
+    /// changes on top of earlier staged ones for the same file.
+    ///
+    /// Assumes `bandaids` are sorted by line number and column number and are
+    /// non overlapping.
+    pub(crate) fn stage(
+        &mut self,
+        path: &Path,
+        bandaids: impl IntoIterator<Item = BandAid>,
+    ) -> Result<()> {
+        self.stage_checked(path, bandaids, false)
+    }
+
+    /// Stage the corrected content of the common mark file at `path`, but
+    /// skip all changes which would break the markup, see [`markup`].
+    pub(crate) fn stage_commonmark(
+        &mut self,
+        path: &Path,
+        bandaids: impl IntoIterator<Item = BandAid>,
+    ) -> Result<()> {
+        self.stage_checked(path, bandaids, true)
+    }
+
+    fn stage_checked(
+        &mut self,
+        path: &Path,
+        bandaids: impl IntoIterator<Item = BandAid>,
+        check_markup: bool,
+    ) -> Result<()> {
+        let path = crate::util::canonicalize(path)
+            .map_err(|e| anyhow!("Failed to canonicalize {}", path.display()).context(e))?;
+
+        let (content, tmp) = if let Some(staged) = self.staged.get(&path) {
+            let content = fs::read_to_string(&staged.tmp)
+                .map_err(|e| anyhow!("Failed to read {}", staged.tmp.display()).context(e))?;
+            (content, staged.tmp.clone())
+        } else {
+            trace!("Attempting to open {} as read", path.display());
+            let content = fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
+            crate::checksum::verify(&path, &content)?;
+            let tmp = tmp_path(&path);
+            self.staged.insert(
+                path.clone(),
+                Staged {
+                    tmp: tmp.clone(),
+                    original: content.clone(),
+                },
+            );
+            (content, tmp)
+        };
+
+        let bandaids = if check_markup {
+            markup::retain_preserving(&path, content.as_str(), bandaids)?
+        } else {
+            bandaids.into_iter().collect()
+        };
+
+        let mut registry = Registry::lock()?;
+        registry.files.insert(tmp.clone());
+
+        let wr = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&tmp)
+            .map_err(|e| anyhow!("Failed to open {}", tmp.display()).context(e))?;
+
+        let mut writer = std::io::BufWriter::with_capacity(1024, wr);
+
+        apply_patches(
+            bandaids.into_iter().map(Patch::from),
+            content.as_str(),
+            &mut writer,
+        )?;
+
+        writer.flush()?;
+        if self.sync {
+            writer
+                .get_ref()
+                .sync_all()
+                .map_err(|e| anyhow!("Failed to sync {}", tmp.display()).context(e))?;
+        }
+        drop(registry);
+        Ok(())
+    }
+
+    /// Swap all staged files into place, or none of them.
+    pub(crate) fn commit(mut self) -> Result<()> {
+        let mut registry = Registry::lock()?;
+        if self.sync {
+            for dir in self.dirs() {
+                sync_dir(dir)?;
+            }
+        }
+        let dirs = self.dirs().map(Path::to_owned).collect::<Vec<_>>();
+        let mut swapped = Vec::with_capacity(self.staged.len());
+        let mut pending = std::mem::take(&mut self.staged).into_iter();
+        while let Some((path, staged)) = pending.next() {
+            if let Err(e) = fs::rename(&staged.tmp, &path) {
+                // put back the not yet swapped files, so they are cleaned up on drop
+                self.staged.insert(path.clone(), staged);
+                self.staged.extend(pending);
+                Self::restore(swapped);
+                drop(registry);
+                return Err(anyhow!("Failed to replace {}", path.display()).context(e));
+            }
+            registry.files.remove(&staged.tmp);
+            swapped.push((path, staged.original));
+        }
+        if self.sync {
+            // make the renames themselves durable
+            for dir in dirs.iter() {
+                sync_dir(dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The distinct directories of all staged files.
+    fn dirs(&self) -> impl Iterator<Item = &Path> {
+        let mut dirs = self
+            .staged
+            .values()
+            .filter_map(|staged| staged.tmp.parent())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        dirs.dedup();
+        dirs.into_iter()
+    }
+
+    /// Write the original content back to all already swapped files.
+    fn restore(swapped: Vec<(PathBuf, String)>) {
+        for (path, original) in swapped {
+            trace!("Rolling back {}", path.display());
+            if let Err(e) = fs::write(&path, original) {
+                warn!("Failed to roll back {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if self.staged.is_empty() {
+            return;
+        }
+        let mut registry = STAGED.lock().expect("Lock is never poisoned. qed");
+        for (_path, staged) in self.staged.drain(..) {
+            registry.files.remove(&staged.tmp);
+            if let Err(e) = fs::remove_file(&staged.tmp) {
+                warn!("Failed to remove {}: {}", staged.tmp.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::bandaid::tests::bandaid;
+
+    /// A directory with two files, removed once the returned guard is dropped.
+    fn setup() -> (tempfile::TempDir, PathBuf, PathBuf, PathBuf) {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().canonicalize().unwrap();
+        let a = dir.join("a.rs");
+        let b = dir.join("b.rs");
+        fs::write(&a, "/// A literl\n").unwrap();
+        fs::write(&b, "/// The secnd\n").unwrap();
+        (tmp, dir, a, b)
+    }
+
+    #[test]
+    fn all_or_nothing() {
+        let (_tmp, dir, a, b) = setup();
+
+        let mut transaction = Transaction::new(false);
+        transaction
+            .stage(&a, vec![bandaid(1, 6, 6, "literal")])
+            .unwrap();
+        transaction
+            .stage(&b, vec![bandaid(1, 8, 5, "second")])
+            .unwrap();
+        transaction
+            .stage(&b, vec![bandaid(1, 4, 3, "One")])
+            .unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "/// A literl\n");
+        transaction.commit().unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "/// A literal\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "/// One second\n");
+
+        // staging fails for the second file, the first remains untouched
+        let mut transaction = Transaction::new(false);
+        transaction
+            .stage(&a, vec![bandaid(1, 4, 1, "The")])
+            .unwrap();
+        assert!(transaction
+            .stage(&dir.join("missing.rs"), vec![bandaid(1, 4, 1, "The")])
+            .is_err());
+        drop(transaction);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "/// A literal\n");
+
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn rollback() {
+        let (_tmp, _dir, a, b) = setup();
+
+        let mut transaction = Transaction::new(false);
+        transaction
+            .stage(&a, vec![bandaid(1, 6, 6, "literal")])
+            .unwrap();
+        transaction
+            .stage(&b, vec![bandaid(1, 8, 5, "second")])
+            .unwrap();
+        // make swapping the second file fail
+        fs::remove_file(tmp_path(&b.canonicalize().unwrap())).unwrap();
+        assert!(transaction.commit().is_err());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "/// A literl\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "/// The secnd\n");
+    }
+
+    #[test]
+    fn synced() {
+        let (_tmp, _dir, a, b) = setup();
+
+        let mut transaction = Transaction::new(true);
+        transaction
+            .stage(&a, vec![bandaid(1, 6, 6, "literal")])
+            .unwrap();
+        transaction
+            .stage(&b, vec![bandaid(1, 8, 5, "second")])
+            .unwrap();
+        transaction.commit().unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "/// A literal\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "/// The second\n");
+    }
+
+    #[test]
+    fn modified_since_checked() {
+        let (_tmp, _dir, a, _b) = setup();
+        crate::checksum::record(&a, "/// A literl\n");
+        fs::write(&a, "/// Another literl\n").unwrap();
+
+        let mut transaction = Transaction::new(false);
+        assert!(transaction
+            .stage(&a, vec![bandaid(1, 6, 6, "literal")])
+            .is_err());
+        drop(transaction);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "/// Another literl\n");
+    }
+}
@@ -0,0 +1,114 @@
+//! Disable single rules by their id, in the configuration or inline.
+//!
+//! A chunk may contain an html comment `<!-- spellcheck: allow(<ids>) -->`,
+//! which neither rustdoc nor common mark renderers display, to allow the
+//! comma separated rule ids for all findings within the chunk, i.e.
+//! `<!-- spellcheck: allow(hunspell::unknown-word) -->`.
+
+use super::unused::{self, Suppression};
+use crate::documentation::CheckableChunk;
+use crate::util::byte_range_to_char_range;
+use crate::{ContentOrigin, Suggestion};
+
+use regex::Regex;
+
+lazy_static::lazy_static! {
+    static ref DIRECTIVE: Regex = Regex::new(r"<!--\s*spellcheck:\s*allow\(([^)]*)\)\s*-->")
+        .expect("Directive regex is valid. qed");
+}
+
+/// All rule ids allowed by inline directives of `content`, each with the
+/// byte range of its directive.
+fn directives(content: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    // avoid the regex for the vast majority of chunks
+    if !content.contains("spellcheck:") {
+        return Vec::new();
+    }
+    DIRECTIVE
+        .captures_iter(content)
+        .filter_map(|captures| Some((captures.get(0)?.range(), captures.get(1)?)))
+        .flat_map(|(directive, ids)| {
+            ids.as_str()
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(move |id| (directive.clone(), id))
+        })
+        .collect()
+}
+
+/// All rule ids allowed by inline directives of `content`.
+#[cfg(test)]
+fn allowed_inline(content: &str) -> Vec<&str> {
+    directives(content)
+        .into_iter()
+        .map(|(_directive, id)| id)
+        .collect()
+}
+
+/// The inline directives of `chunk`, located at the line of the directive.
+pub(crate) fn inline(origin: &ContentOrigin, chunk: &CheckableChunk) -> Vec<Suppression> {
+    let content = chunk.as_str();
+    directives(content)
+        .into_iter()
+        .map(|(directive, rule)| {
+            let line = byte_range_to_char_range(content, directive)
+                .and_then(|range| {
+                    chunk
+                        .find_spans(range)
+                        .values()
+                        .next()
+                        .map(|span| span.start.line)
+                })
+                .unwrap_or_default();
+            Suppression::Inline {
+                path: origin.as_path().to_owned(),
+                line,
+                rule: rule.to_owned(),
+            }
+        })
+        .collect()
+}
+
+/// If the rule of `suggestion` is disabled by the configured `disabled`
+/// rule ids or by a directive of its chunk.
+pub(crate) fn is_allowed(suggestion: &Suggestion<'_>, disabled: &[String]) -> bool {
+    let id = suggestion.rule_id();
+    if disabled.iter().any(|disabled| *disabled == id) {
+        unused::used(Suppression::DisabledRule(id));
+        return true;
+    }
+    if !suggestion.chunk.as_str().contains("spellcheck:") {
+        return false;
+    }
+    let allowing = inline(&suggestion.origin, suggestion.chunk)
+        .into_iter()
+        .filter(
+            |suppression| matches!(suppression, Suppression::Inline { rule, .. } if *rule == id),
+        )
+        .collect::<Vec<_>>();
+    let allowed = !allowing.is_empty();
+    allowing.into_iter().for_each(unused::used);
+    allowed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_directives() {
+        assert!(allowed_inline("No directive, spellcheck: allow(x::y) only.").is_empty());
+        assert_eq!(
+            allowed_inline(
+                "A <!-- spellcheck: allow(hunspell::unknown-word, languagetool::EN_A_VS_AN) -->\n\
+                 and <!--spellcheck:allow(terminology::disfavored-term)-->"
+            ),
+            vec![
+                "hunspell::unknown-word",
+                "languagetool::EN_A_VS_AN",
+                "terminology::disfavored-term"
+            ]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Report terms which are spelled correctly, but disallowed by policy.
+//!
+//! Every term of the `[banned]` table is reported with its own severity, i.e.
+//! trademarks in the wrong form, deprecated product names or offensive terms,
+//! and with the replacement if one is configured. Terms are matched the same
+//! way as the ones of the terminology checker.
+
+use super::terminology::{find, pattern};
+use super::{Batch, Category, Checker, Detector, Suggestion, SuggestionSet};
+use crate::config::BannedTerm;
+use crate::util::{byte_range_to_char_range, preserve_case};
+
+use anyhow::Result;
+use regex::Regex;
+
+use std::collections::BTreeMap;
+
+pub(crate) struct BannedChecker;
+
+impl Checker for BannedChecker {
+    /// Banned terms to their replacement and severity.
+    type Config = BTreeMap<String, BannedTerm>;
+    type Engine = Vec<(Regex, BannedTerm)>;
+    const DETECTOR: Detector = Detector::Banned;
+
+    fn init(config: &Self::Config) -> Result<Self::Engine> {
+        config
+            .iter()
+            .filter(|(term, _banned)| !term.trim().is_empty())
+            .map(|(term, banned)| Ok((pattern(term)?, banned.clone())))
+            .collect()
+    }
+
+    fn check_batch<'s>(
+        engine: &Self::Engine,
+        _config: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>> {
+        let mut acc = SuggestionSet::new();
+        for &(origin, chunk) in batch {
+            let plain = chunk.erase_cmark();
+            let txt = plain.as_str();
+
+            for (bytes, banned) in find(engine, txt) {
+                let range = match byte_range_to_char_range(txt, bytes.clone()) {
+                    Some(range) => range,
+                    None => continue,
+                };
+                let term = &txt[bytes.clone()];
+                let replacements = banned
+                    .replacement
+                    .iter()
+                    .map(|replacement| preserve_case(term, replacement))
+                    .collect::<Vec<_>>();
+                let description = match banned.replacement {
+                    Some(ref replacement) => {
+                        format!("`{}` is not allowed, use `{}` instead.", term, replacement)
+                    }
+                    None => format!("`{}` is not allowed.", term),
+                };
+                for (range, span) in plain.find_spans(range) {
+                    acc.add(
+                        origin.clone(),
+                        Suggestion {
+                            detector: Detector::Banned,
+                            severity: banned.severity,
+                            category: Category::Style,
+                            rule: "banned-term".to_owned(),
+                            range,
+                            span,
+                            origin: origin.clone(),
+                            replacements: replacements.clone(),
+                            chunk,
+                            description: Some(description.clone()),
+                            confidence: None,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation, Severity};
+
+    #[test]
+    fn banned() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!(
+                "Deploy to acme cloud, the Acme Cloud Console",
+                "or the foobar-service, not to FooBar."
+            ),
+        ));
+        let batch = dummy::batch(&d);
+        let mut config = BTreeMap::new();
+        config.insert(
+            "Acme Cloud".to_owned(),
+            BannedTerm {
+                replacement: Some("Acme Platform".to_owned()),
+                severity: Severity::Warning,
+            },
+        );
+        config.insert("Acme Cloud Console".to_owned(), BannedTerm::default());
+        config.insert("foobar".to_owned(), BannedTerm::default());
+        let engine = BannedChecker::init(&config).unwrap();
+        let suggestions = BannedChecker::check_batch(&engine, &config, &batch).unwrap();
+        let found = suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .map(|suggestion| {
+                (
+                    suggestion.severity,
+                    suggestion.replacements.clone(),
+                    suggestion.description.clone().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Severity::Warning,
+                    vec!["Acme Platform".to_owned()],
+                    "`acme cloud` is not allowed, use `Acme Platform` instead.".to_owned()
+                ),
+                (
+                    Severity::Error,
+                    vec![],
+                    "`Acme Cloud Console` is not allowed.".to_owned()
+                ),
+                (
+                    Severity::Error,
+                    vec![],
+                    "`FooBar` is not allowed.".to_owned()
+                ),
+            ]
+        );
+    }
+}
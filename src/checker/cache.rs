@@ -0,0 +1,85 @@
+//! Memoization of dictionary lookups.
+//!
+//! Documentation repeats the same words over and over again, and obtaining
+//! suggestions is the most expensive part of checking a word, so each word
+//! is only looked up once per set of dictionaries and process.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Result of looking up a single word, `None` for correct words,
+/// otherwise the suggested replacements.
+pub(crate) type Lookup = Option<Vec<String>>;
+
+/// Cached lookups of a single set of dictionaries, part of the engine of the
+/// hunspell checker.
+#[derive(Debug, Default)]
+pub struct LookupCache {
+    per_word: RwLock<HashMap<String, Lookup>>,
+}
+
+lazy_static::lazy_static! {
+    static ref CACHES: Mutex<HashMap<Vec<PathBuf>, Arc<LookupCache>>> = Mutex::new(HashMap::new());
+}
+
+impl LookupCache {
+    /// The cache for the dictionaries `key`, which is shared with all
+    /// other checks using the very same dictionaries, in the same order.
+    pub(crate) fn for_dictionaries(key: Vec<PathBuf>) -> Arc<Self> {
+        let mut caches = CACHES.lock().expect("Lock is never poisoned. qed");
+        caches.entry(key).or_default().clone()
+    }
+
+    /// Return the cached result for `word` or calculate it with `lookup`.
+    pub(crate) fn get_or_insert_with<F>(&self, word: &str, lookup: F) -> Lookup
+    where
+        F: FnOnce() -> Lookup,
+    {
+        if let Some(cached) = self
+            .per_word
+            .read()
+            .expect("Lock is never poisoned. qed")
+            .get(word)
+        {
+            return cached.clone();
+        }
+        // computed without holding the lock, a concurrent lookup of the
+        // same word yields the same result
+        let result = lookup();
+        self.per_word
+            .write()
+            .expect("Lock is never poisoned. qed")
+            .insert(word.to_owned(), result.clone());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memoized() {
+        let cache = LookupCache::default();
+        let mut calls = 0;
+        for _ in 0..3 {
+            let lookup = cache.get_or_insert_with("Thisf", || {
+                calls += 1;
+                Some(vec!["This".to_owned()])
+            });
+            assert_eq!(lookup, Some(vec!["This".to_owned()]));
+        }
+        assert_eq!(cache.get_or_insert_with("This", || None), None);
+        assert_eq!(calls, 1);
+
+        let key = vec![PathBuf::from("/tmp/memoized/en_US.dic")];
+        let a = LookupCache::for_dictionaries(key.clone());
+        let b = LookupCache::for_dictionaries(key);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(
+            &a,
+            &LookupCache::for_dictionaries(vec![PathBuf::from("/tmp/memoized/de_DE.dic")])
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+//! Consistent spelling variants across all checked files.
+//!
+//! Reports every location of a word which is spelled in both the american
+//! and the british way, i.e. `behavior` and `behaviour`, so the documentation
+//! can be unified. The occurrences of the variant used less often are to be
+//! replaced by the one used more often, on a tie by the respective other.
+
+use super::variants::{spelling, Variant};
+use super::{tokenize, Category, Detector, Suggestion, SuggestionSet};
+use crate::config::ConsistencyConfig;
+use crate::documentation::CheckableChunk;
+use crate::util::{match_case, sub_chars};
+use crate::{ContentOrigin, Documentation, Range, Severity, Span};
+
+use indexmap::IndexMap;
+
+use std::cmp::Ordering;
+
+/// A single occurrence of a word spelled in one of the variants.
+struct Occurrence<'s> {
+    origin: &'s ContentOrigin,
+    chunk: &'s CheckableChunk,
+    range: Range,
+    span: Span,
+    variant: Variant,
+    /// The spelling in the other variant, matching the case.
+    other: String,
+    /// Severity configured for the part of the documentation.
+    severity: Severity,
+}
+
+/// Check the full documentation at once, since a single file may be
+/// consistent on its own.
+///
+/// The documentation may be split into parts with their own config, i.e. by
+/// per path overrides, which are still checked against each other.
+pub(crate) fn check<'s>(parts: &[(&'s Documentation, &ConsistencyConfig)]) -> SuggestionSet<'s> {
+    let mut per_word = IndexMap::<&'static str, Vec<Occurrence<'s>>>::new();
+    for (origin, chunks, config) in parts.iter().flat_map(|&(documentation, config)| {
+        documentation
+            .iter()
+            .map(move |(origin, chunks)| (origin, chunks, config))
+    }) {
+        for chunk in chunks {
+            let plain = chunk.erase_cmark();
+            let txt = plain.as_str();
+            for range in tokenize(txt) {
+                let word = sub_chars(txt, range.clone());
+                let spelling = match spelling(&word) {
+                    Some(spelling) => spelling,
+                    None => continue,
+                };
+                for (range, span) in plain.find_spans(range) {
+                    per_word.entry(spelling.word).or_default().push(Occurrence {
+                        origin,
+                        chunk,
+                        range,
+                        span,
+                        variant: spelling.variant,
+                        other: match_case(&word, &spelling.other),
+                        severity: config.severity,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut acc = SuggestionSet::new();
+    for occurrences in per_word.values() {
+        let american = occurrences
+            .iter()
+            .filter(|occurrence| occurrence.variant == Variant::American)
+            .count();
+        let british = occurrences.len() - american;
+        if american == 0 || british == 0 {
+            continue;
+        }
+        let description = format!(
+            "Spelled the american way {} and the british way {} times.",
+            american, british
+        );
+        // the share of the preferred variant, on a tie there is no way to tell
+        // which one is preferred
+        let confidence = (american.max(british) * 100 / occurrences.len()) as u8;
+        // the occurrences of the preferred variant are reported as well, but
+        // are kept as they are
+        let preferred = match american.cmp(&british) {
+            Ordering::Less => Some(Variant::British),
+            Ordering::Equal => None,
+            Ordering::Greater => Some(Variant::American),
+        };
+        for occurrence in occurrences {
+            let replacements = if Some(occurrence.variant) == preferred {
+                Vec::new()
+            } else {
+                vec![occurrence.other.clone()]
+            };
+            acc.add(
+                occurrence.origin.clone(),
+                Suggestion {
+                    detector: Detector::Consistency,
+                    severity: occurrence.severity,
+                    category: Category::Spelling,
+                    rule: "mixed-variants".to_owned(),
+                    range: occurrence.range.clone(),
+                    span: occurrence.span,
+                    origin: occurrence.origin.clone(),
+                    replacements,
+                    chunk: occurrence.chunk,
+                    description: Some(description.clone()),
+                    confidence: Some(confidence),
+                },
+            );
+        }
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluff_up;
+
+    /// The flagged words and their replacements.
+    fn found(suggestions: &SuggestionSet<'_>) -> Vec<(String, Vec<String>)> {
+        suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_location() {
+        let mut d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!("Its behaviour and colour.", "The colour is initialized."),
+        ));
+        d.join(Documentation::from((
+            ContentOrigin::TestEntityCommonMark,
+            "Describes the behaviors, colors and colours.",
+        )));
+        let suggestions = check(&[(&d, &ConsistencyConfig::default())]);
+        // behaviour is ambiguous, colour is preferred and there is no other
+        // spelling of initialized
+        let owned = |word: &str, replacements: &[&str]| {
+            (
+                word.to_owned(),
+                replacements
+                    .iter()
+                    .map(|replacement| replacement.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        assert_eq!(
+            found(&suggestions),
+            vec![
+                owned("behaviour", &["behavior"]),
+                owned("colour", &[]),
+                owned("colour", &[]),
+                owned("behaviors", &["behaviours"]),
+                owned("colors", &["colours"]),
+                owned("colours", &[]),
+            ]
+        );
+        // the share of the preferred variant
+        assert_eq!(
+            suggestions
+                .iter()
+                .flat_map(|(_origin, suggestions)| suggestions.iter())
+                .map(|suggestion| suggestion.confidence)
+                .collect::<Vec<_>>(),
+            vec![Some(50), Some(75), Some(75), Some(50), Some(75), Some(75)]
+        );
+    }
+
+    #[test]
+    fn across_parts() {
+        let a = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!("The colour and the colour."),
+        ));
+        let b = Documentation::from((ContentOrigin::TestEntityCommonMark, "The color."));
+        let warning = ConsistencyConfig {
+            severity: Severity::Warning,
+        };
+        assert!(check(&[(&a, &warning)]).total_count() == 0);
+        let suggestions = check(&[(&a, &warning), (&b, &ConsistencyConfig::default())]);
+        assert_eq!(
+            found(&suggestions)
+                .into_iter()
+                .flat_map(|(_word, replacements)| replacements)
+                .collect::<Vec<_>>(),
+            vec!["colour"]
+        );
+        assert_eq!(
+            suggestions
+                .iter()
+                .flat_map(|(_origin, suggestions)| suggestions.iter())
+                .map(|suggestion| (suggestion.origin.clone(), suggestion.severity))
+                .collect::<Vec<_>>(),
+            vec![
+                (ContentOrigin::TestEntityRust, Severity::Warning),
+                (ContentOrigin::TestEntityRust, Severity::Warning),
+                (ContentOrigin::TestEntityCommonMark, Severity::Error)
+            ]
+        );
+    }
+}
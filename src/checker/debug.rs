@@ -0,0 +1,123 @@
+//! Dump the chunks handed to a single checker and the findings it reported,
+//! including the mapping back to the source, to make false positives
+//! actionable.
+
+use super::Batch;
+use crate::util::sub_chars;
+use crate::{Detector, Span, SuggestionSet};
+
+use anyhow::Result;
+
+use std::fmt;
+use std::io::Write;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Bits of the debugged detector, `0` if none.
+static DEBUGGED: AtomicU8 = AtomicU8::new(0);
+
+/// Dump everything related to `detector` for the remainder of the process.
+pub fn enable(detector: Detector) {
+    DEBUGGED.store(detector as u8, Ordering::Relaxed);
+}
+
+/// If `detector` is the one to debug.
+pub(crate) fn is_debugged(detector: Detector) -> bool {
+    DEBUGGED.load(Ordering::Relaxed) == detector as u8
+}
+
+/// Compact `line:column-line:column` representation of a span.
+struct DisplaySpan<'a>(&'a Span);
+
+impl<'a> fmt::Display for DisplaySpan<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{}:{}-{}:{}",
+            self.0.start.line, self.0.start.column, self.0.end.line, self.0.end.column
+        )
+    }
+}
+
+/// Write the plain text of every chunk of `batch` as the checker sees it,
+/// how it maps to the source, and the findings of the checker.
+pub(crate) fn dump<W: Write>(
+    mut sink: W,
+    detector: Detector,
+    batch: &Batch<'_>,
+    suggestions: &SuggestionSet<'_>,
+) -> Result<()> {
+    for (origin, chunk) in batch {
+        let plain = chunk.erase_cmark();
+        writeln!(
+            sink,
+            "[{}] {} {:?}",
+            detector,
+            origin.as_path().display(),
+            chunk.variant()
+        )?;
+        writeln!(sink, "  plain: {:?}", plain.as_str())?;
+        for (plain_range, chunk_range) in plain.mapping() {
+            writeln!(sink, "  plain {:?} -> chunk {:?}", plain_range, chunk_range)?;
+        }
+        for (chunk_range, span) in chunk.iter() {
+            writeln!(
+                sink,
+                "  chunk {:?} -> source {}",
+                chunk_range,
+                DisplaySpan(span)
+            )?;
+        }
+        let findings = suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .filter(|suggestion| std::ptr::eq(suggestion.chunk, *chunk));
+        for suggestion in findings {
+            write!(
+                sink,
+                "  finding: chunk {:?} -> source {} {:?} replacements: {:?}",
+                suggestion.range,
+                DisplaySpan(&suggestion.span),
+                sub_chars(chunk.as_str(), suggestion.range.clone()),
+                suggestion.replacements,
+            )?;
+            if let Some(ref description) = suggestion.description {
+                write!(sink, " {}", description)?;
+            }
+            writeln!(sink)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write a raw response of a checker backend.
+#[cfg(feature = "languagetool")]
+pub(crate) fn dump_raw<W: Write, T: fmt::Debug>(
+    mut sink: W,
+    detector: Detector,
+    raw: &T,
+) -> Result<()> {
+    writeln!(sink, "[{}] raw: {:#?}", detector, raw)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation};
+
+    #[test]
+    fn dump_chunks_and_findings() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("two literals")));
+        let batch = dummy::batch(&d);
+        let suggestions = dummy::check_all(&d);
+
+        let mut sink = Vec::new();
+        dump(&mut sink, Detector::Dummy, &batch, &suggestions).unwrap();
+        let dumped = String::from_utf8(sink).unwrap();
+        assert!(dumped.starts_with("[Dummy] /tmp/test/entity.rs TripleSlash\n"));
+        assert!(dumped.contains("  plain: \"two literals\"\n  plain 0..12 -> chunk 1..13\n"));
+        assert!(dumped.contains("  chunk 0..13 -> source 1:3-1:15\n"));
+        assert!(dumped.contains("  finding: chunk 5..13 -> source 1:8-1:15 \"literals\""));
+    }
+}
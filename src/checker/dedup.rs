@@ -0,0 +1,147 @@
+//! Check chunks with identical content only once.
+//!
+//! Macro generated and templated documentation repeats the very same text
+//! for many items, the findings for one of them apply to all of them.
+
+use super::Limits;
+use crate::documentation::{CheckableChunk, CommentVariant};
+use crate::{Config, ContentOrigin, Documentation, Suggestion, SuggestionSet};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::debug;
+
+/// Chunks with the same key yield the same findings.
+type Key<'a> = (&'a str, CommentVariant);
+
+/// A check of a documentation, independent of the origins.
+pub(crate) type CheckFn =
+    for<'u> fn(&'u Documentation, &Config, &Limits<'_>) -> Result<SuggestionSet<'u>>;
+
+/// Run `check` on every distinct chunk of `documentation` once, and map the
+/// findings back onto all chunks with the very same content.
+///
+/// The `limits` only account for the distinct chunks.
+pub(crate) fn check_deduplicated<'a, 's>(
+    documentation: &'a Documentation,
+    config: &Config,
+    limits: &Limits<'_>,
+    check: CheckFn,
+) -> Result<SuggestionSet<'s>>
+where
+    'a: 's,
+{
+    let mut occurrences =
+        IndexMap::<Key<'a>, Vec<(&'a ContentOrigin, &'a CheckableChunk)>>::with_capacity(64);
+    for (origin, chunks) in documentation.iter() {
+        for chunk in chunks {
+            occurrences
+                .entry((chunk.as_str(), chunk.variant()))
+                .or_insert_with(Vec::new)
+                .push((origin, chunk));
+        }
+    }
+
+    let mut distinct = Documentation::new();
+    for (origin, chunk) in occurrences.values().filter_map(|all| all.first()) {
+        distinct.add_chunks((*origin).clone(), vec![(*chunk).clone()]);
+    }
+    debug!(
+        "Checking {} distinct chunks of {} in total",
+        occurrences.len(),
+        occurrences.values().map(Vec::len).sum::<usize>()
+    );
+
+    let found = check(&distinct, config, limits)?;
+
+    let mut suggestions = SuggestionSet::new();
+    for suggestion in found.iter().flat_map(|(_origin, found)| found.iter()) {
+        let idx = match occurrences
+            .get_index_of(&(suggestion.chunk.as_str(), suggestion.chunk.variant()))
+        {
+            Some(idx) => idx,
+            None => continue,
+        };
+        let (_key, all) = occurrences
+            .get_index(idx)
+            .expect("Index was just obtained from the map. qed");
+        for &(origin, chunk) in all {
+            for (range, span) in chunk.find_spans(suggestion.range.clone()) {
+                suggestions.add(
+                    origin.clone(),
+                    Suggestion {
+                        detector: suggestion.detector,
+                        severity: suggestion.severity,
+                        category: suggestion.category,
+                        rule: suggestion.rule.clone(),
+                        origin: origin.clone(),
+                        chunk,
+                        span,
+                        range,
+                        replacements: suggestion.replacements.clone(),
+                        description: suggestion.description.clone(),
+                        confidence: suggestion.confidence,
+                    },
+                );
+            }
+        }
+    }
+    suggestions.sort();
+    Ok(suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{dummy::DummyChecker, Checker};
+    use crate::fluff_up;
+
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CHECKED: AtomicUsize = AtomicUsize::new(0);
+
+    fn counting<'u>(
+        documentation: &'u Documentation,
+        _config: &Config,
+        limits: &Limits<'_>,
+    ) -> Result<SuggestionSet<'u>> {
+        CHECKED.fetch_add(documentation.entry_count(), Ordering::SeqCst);
+        DummyChecker::check(documentation, &(), limits)
+    }
+
+    #[test]
+    fn fan_out() {
+        const CONTENT: &str = fluff_up!("two literals");
+        let mut documentation = Documentation::new();
+        for name in &["a.rs", "b.rs", "c.rs"] {
+            documentation
+                .add_rust(ContentOrigin::RustSourceFile(PathBuf::from(name)), CONTENT)
+                .unwrap();
+        }
+        documentation
+            .add_rust(
+                ContentOrigin::RustSourceFile(PathBuf::from("d.rs")),
+                fluff_up!("other"),
+            )
+            .unwrap();
+
+        let suggestions = check_deduplicated(
+            &documentation,
+            &Config::default(),
+            &Limits::unlimited(),
+            counting,
+        )
+        .unwrap();
+        assert_eq!(CHECKED.load(Ordering::SeqCst), 2);
+        assert_eq!(suggestions.len(), 4);
+        assert_eq!(suggestions.total_count(), 7);
+        let (origin, found) = suggestions.iter().nth(1).unwrap();
+        assert_eq!(
+            origin,
+            &ContentOrigin::RustSourceFile(PathBuf::from("b.rs"))
+        );
+        assert_eq!(found[1].chunk.as_str(), " two literals");
+        assert_eq!(found[1].span.start.column, 8);
+    }
+}
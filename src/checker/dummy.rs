@@ -3,10 +3,11 @@
 //! A test checker, only available for unit tests.
 
 use super::tokenize;
-use super::Checker;
-use crate::documentation::Documentation;
-use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use super::{Batch, Checker, Limits};
+use crate::documentation::{CheckableChunk, Documentation};
+use crate::suggestion::{Category, Detector, Severity, Suggestion, SuggestionSet};
 use crate::util::sub_chars;
+use crate::ContentOrigin;
 use anyhow::Result;
 use log::trace;
 
@@ -15,50 +16,89 @@ pub struct DummyChecker;
 
 impl Checker for DummyChecker {
     type Config = ();
+    type Engine = ();
+    const DETECTOR: Detector = Detector::Dummy;
 
-    fn check<'a, 's>(docu: &'a Documentation, _: &Self::Config) -> Result<SuggestionSet<'s>>
+    fn init(_: &Self::Config) -> Result<Self::Engine> {
+        Ok(())
+    }
+
+    /// Only checks the first chunk of every origin.
+    fn check<'a, 's>(
+        docu: &'a Documentation,
+        config: &Self::Config,
+        _limits: &Limits<'_>,
+    ) -> Result<SuggestionSet<'s>>
     where
         'a: 's,
     {
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
-            SuggestionSet::new(),
-            |mut acc, (origin, chunks)| {
+        let firsts = docu
+            .iter()
+            .map(|(origin, chunks)| {
                 let chunk = chunks
                     .iter()
                     .next()
                     .expect("DummyChecker expects at least one chunk");
-                let plain = chunk.erase_cmark();
-                for (index, range) in dbg!(tokenize(plain.as_str())).into_iter().enumerate() {
+                (origin, chunk)
+            })
+            .collect::<Vec<_>>();
+        Self::check_batch(&(), config, &firsts)
+    }
+
+    fn check_batch<'s>(
+        _: &Self::Engine,
+        _: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>> {
+        let mut acc = SuggestionSet::new();
+        for &(origin, chunk) in batch {
+            let plain = chunk.erase_cmark();
+            for (index, range) in dbg!(tokenize(plain.as_str())).into_iter().enumerate() {
+                trace!(
+                    "****Token[{}]: >{}<",
+                    index,
+                    sub_chars(plain.as_str(), range.clone())
+                );
+                let detector = Detector::Dummy;
+                let range2span = plain.find_spans(range.clone());
+                for (range, span) in range2span {
                     trace!(
-                        "****Token[{}]: >{}<",
-                        index,
-                        sub_chars(plain.as_str(), range.clone())
+                        "Suggestion for {:?} -> {}",
+                        range,
+                        chunk.display(range.clone())
                     );
-                    let detector = Detector::Dummy;
-                    let range2span = plain.find_spans(range.clone());
-                    for (range, span) in range2span {
-                        trace!(
-                            "Suggestion for {:?} -> {}",
-                            range,
-                            chunk.display(range.clone())
-                        );
-                        let replacements = vec![format!("replacement_{}", index)];
-                        let suggestion = Suggestion {
-                            detector,
-                            span,
-                            range,
-                            origin: origin.clone(),
-                            replacements,
-                            chunk,
-                            description: None,
-                        };
-                        acc.add(origin.clone(), suggestion);
-                    }
+                    let replacements = vec![format!("replacement_{}", index)];
+                    let suggestion = Suggestion {
+                        detector,
+                        severity: Severity::Error,
+                        category: Category::Spelling,
+                        rule: "dummy".to_owned(),
+                        span,
+                        range,
+                        origin: origin.clone(),
+                        replacements,
+                        chunk,
+                        description: None,
+                        confidence: None,
+                    };
+                    acc.add(origin.clone(), suggestion);
                 }
-                Ok(acc)
-            },
-        )?;
-
-        Ok(suggestions)
+            }
+        }
+        Ok(acc)
     }
 }
+
+/// All chunks of `documentation` as a single batch.
+pub(crate) fn batch(documentation: &Documentation) -> Vec<(&ContentOrigin, &CheckableChunk)> {
+    documentation
+        .iter()
+        .flat_map(|(origin, chunks)| chunks.iter().map(move |chunk| (origin, chunk)))
+        .collect()
+}
+
+/// Flag every word of all chunks of `documentation`.
+pub(crate) fn check_all(documentation: &Documentation) -> SuggestionSet<'_> {
+    DummyChecker::check_batch(&(), &(), &batch(documentation))
+        .expect("Dummy checks never fail. qed")
+}
@@ -5,34 +5,62 @@
 //! the defined affixes.
 //! Can handle multiple dictionaries.
 
-use super::{tokenize, Checker, Detector, Documentation, Suggestion, SuggestionSet};
+use super::{tokenize, Batch, Category, Checker, Detector, Suggestion, SuggestionSet};
 
+use crate::config::{CaseConfig, SymbolPolicy};
 use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
 use crate::util::sub_chars;
-use crate::Range;
+use crate::{Range, Severity};
 
 use log::{debug, trace};
-use rayon::prelude::*;
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use hunspell_rs::Hunspell;
 
 use anyhow::{anyhow, bail, Result};
 
+use super::cache::LookupCache;
 use super::quirks::{
     replacements_contain_dashed, replacements_contain_dashless, transform, Transformed,
 };
+use super::skip::{is_skipped, skipped_ranges};
+use super::symbols::{is_symbol, split_at_symbols, symbol_ranges};
 
+#[derive(Clone)]
 pub struct HunspellWrapper(pub Arc<Hunspell>);
 
 unsafe impl Send for HunspellWrapper {}
 unsafe impl Sync for HunspellWrapper {}
 
+lazy_static::lazy_static! {
+    /// Initialized instances per set of dictionaries, initializing takes a significant amount of time.
+    ///
+    /// Each set has its own slot, so the map is only locked to look up the slot
+    /// and only initializations of the same set wait for each other.
+    static ref INSTANCES: Mutex<HashMap<Vec<PathBuf>, Arc<Mutex<Option<HunspellWrapper>>>>> = Mutex::new(HashMap::new());
+}
+
 pub struct HunspellChecker;
 
 impl HunspellChecker {
-    fn inner_init(config: &<Self as Checker>::Config) -> Result<HunspellWrapper> {
+    /// Start loading the dictionaries in the background, so they are ready
+    /// once the first chunk needs to be checked.
+    ///
+    /// Errors are reported when checking.
+    pub fn preload(config: &<Self as Checker>::Config) {
+        let config = config.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = Self::inner_init(&config) {
+                debug!("Preloading dictionaries failed: {:?}", e);
+            }
+        });
+    }
+
+    fn inner_init(
+        config: &<Self as Checker>::Config,
+    ) -> Result<(HunspellWrapper, Arc<LookupCache>)> {
         let search_dirs = config.search_dirs();
 
         let lang = config.lang();
@@ -82,6 +110,26 @@ impl HunspellChecker {
                 lang = lang)
             })?;
 
+        // the same dictionaries always yield the same lookup results
+        let key = vec![aff.clone(), dic.clone()]
+            .into_iter()
+            .chain(config.extra_dictionaries())
+            .collect::<Vec<_>>();
+        let cache = LookupCache::for_dictionaries(key.clone());
+
+        let slot = INSTANCES
+            .lock()
+            .expect("Lock is never poisoned. qed")
+            .entry(key)
+            .or_default()
+            .clone();
+        // held until initialization completes, so a concurrent preload is waited for
+        let mut slot = slot.lock().expect("Lock is never poisoned. qed");
+        if let Some(ref hunspell) = *slot {
+            debug!("Reusing initialized dictionaries");
+            return Ok((hunspell.clone(), cache));
+        }
+
         let dic = dic.to_str().unwrap();
         let aff = aff.to_str().unwrap();
 
@@ -97,7 +145,7 @@ impl HunspellChecker {
 
         // suggestion must contain the word itself if it is valid extra dictionary
         // be more strict about the extra dictionaries, they have to exist
-        for extra_dic in config.extra_dictionaries().iter() {
+        for extra_dic in config.extra_dictionaries() {
             debug!("Adding extra dictionary {}", extra_dic.display());
             if !extra_dic.is_file() {
                 bail!("Extra dictionary {} is not a file", extra_dic.display())
@@ -117,17 +165,31 @@ impl HunspellChecker {
             }
         }
         debug!("Dictionary setup completed successfully.");
-        Ok(HunspellWrapper(Arc::new(hunspell)))
+        let hunspell = HunspellWrapper(Arc::new(hunspell));
+        *slot = Some(hunspell.clone());
+        Ok((hunspell, cache))
     }
 }
 
 impl Checker for HunspellChecker {
     type Config = crate::config::HunspellConfig;
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
-    where
-        'a: 's,
-    {
-        let hunspell = Self::inner_init(config)?;
+    type Engine = (HunspellWrapper, Arc<LookupCache>);
+    const DETECTOR: Detector = Detector::Hunspell;
+
+    /// Dictionary lookups are cheap, so the per batch overhead is negligible.
+    const BATCH_SIZE: usize = 256;
+
+    fn init(config: &Self::Config) -> Result<Self::Engine> {
+        Self::inner_init(config)
+    }
+
+    fn check_batch<'s>(
+        engine: &Self::Engine,
+        config: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>> {
+        let (hunspell, cache) = engine;
+        let hunspell = &*hunspell.0;
 
         let (transform_regex, allow_concatenated, allow_dashed) = {
             let quirks = &config.quirks;
@@ -140,81 +202,115 @@ impl Checker for HunspellChecker {
             }
         };
 
-        let suggestions = docu
-            .par_iter()
-            .try_fold::<SuggestionSet, Result<_>, _, _>(
-                || SuggestionSet::new(),
-                move |mut acc, (origin, chunks)| {
-                    debug!("Processing {}", origin.as_path().display());
+        let severity = config.severity;
 
-                    for chunk in chunks {
-                        let plain = chunk.erase_cmark();
-                        trace!("{:?}", &plain);
-                        let txt = plain.as_str();
-                        let hunspell = &*hunspell.0;
-                        for range in tokenize(txt) {
-                            let word = sub_chars(txt, range.clone());
-                            if transform_regex.is_empty() {
+        let mut acc = SuggestionSet::new();
+        for &(origin, chunk) in batch {
+            trace!("Processing chunk of {}", origin.as_path().display());
+            let plain = chunk.erase_cmark();
+            trace!("{:?}", &plain);
+            let txt = plain.as_str();
+            let skipped =
+                skipped_ranges(txt, |word| is_skipped(word, &config.quirks, &config.skip));
+            if config.symbols == SymbolPolicy::Flag {
+                for range in symbol_ranges(txt) {
+                    let symbol = sub_chars(txt, range.clone());
+                    for (range, span) in plain.find_spans(range) {
+                        acc.add(
+                            origin.clone(),
+                            Suggestion {
+                                detector: Detector::Hunspell,
+                                severity,
+                                category: Category::Style,
+                                rule: "symbol".to_owned(),
+                                range,
+                                span,
+                                origin: origin.clone(),
+                                replacements: Vec::new(),
+                                chunk,
+                                description: Some(format!("Symbol `{}` in doc text.", symbol)),
+                                confidence: None,
+                            },
+                        );
+                    }
+                }
+            }
+            let tokens = tokenize(txt).into_iter().flat_map(|range| {
+                if !sub_chars(txt, range.clone()).chars().any(is_symbol) {
+                    vec![range]
+                } else if config.symbols == SymbolPolicy::Ignore {
+                    trace!("Skipping token {:?} with an embedded symbol", &range);
+                    Vec::new()
+                } else {
+                    split_at_symbols(txt, range)
+                }
+            });
+            for range in tokens {
+                if skipped
+                    .iter()
+                    .any(|skipped| skipped.start <= range.start && range.end <= skipped.end)
+                {
+                    trace!("Skipping token {:?}, which is no word", &range);
+                    continue;
+                }
+                let word = sub_chars(txt, range.clone());
+                if transform_regex.is_empty() {
+                    obtain_suggestions(
+                        &plain,
+                        chunk,
+                        &hunspell,
+                        cache,
+                        origin,
+                        word,
+                        range,
+                        allow_concatenated,
+                        allow_dashed,
+                        &config.case,
+                        severity,
+                        &mut acc,
+                    )
+                } else {
+                    match transform(&transform_regex[..], word.as_str(), range.clone()) {
+                        Transformed::Fragments(word_fragments) => {
+                            for (range, word_fragment) in word_fragments {
                                 obtain_suggestions(
                                     &plain,
                                     chunk,
                                     &hunspell,
+                                    cache,
                                     origin,
-                                    word,
+                                    word_fragment.to_owned(),
                                     range,
                                     allow_concatenated,
                                     allow_dashed,
+                                    &config.case,
+                                    severity,
                                     &mut acc,
-                                )
-                            } else {
-                                match transform(&transform_regex[..], word.as_str(), range.clone())
-                                {
-                                    Transformed::Fragments(word_fragments) => {
-                                        for (range, word_fragment) in word_fragments {
-                                            obtain_suggestions(
-                                                &plain,
-                                                chunk,
-                                                &hunspell,
-                                                origin,
-                                                word_fragment.to_owned(),
-                                                range,
-                                                allow_concatenated,
-                                                allow_dashed,
-                                                &mut acc,
-                                            );
-                                        }
-                                    }
-                                    Transformed::Atomic((range, word)) => {
-                                        obtain_suggestions(
-                                            &plain,
-                                            chunk,
-                                            &hunspell,
-                                            origin,
-                                            word.to_owned(),
-                                            range,
-                                            allow_concatenated,
-                                            allow_dashed,
-                                            &mut acc,
-                                        );
-                                    }
-                                    Transformed::Whitelisted(_) => {}
-                                }
+                                );
                             }
                         }
+                        Transformed::Atomic((range, word)) => {
+                            obtain_suggestions(
+                                &plain,
+                                chunk,
+                                &hunspell,
+                                cache,
+                                origin,
+                                word.to_owned(),
+                                range,
+                                allow_concatenated,
+                                allow_dashed,
+                                &config.case,
+                                severity,
+                                &mut acc,
+                            );
+                        }
+                        Transformed::Whitelisted(_) => {}
                     }
-                    Ok(acc)
-                },
-            )
-            .try_reduce(
-                || SuggestionSet::new(),
-                |mut a, b| {
-                    a.join(b);
-                    Ok(a)
-                },
-            )?;
-
-        // TODO sort spans by file and line + column
-        Ok(suggestions)
+                }
+            }
+        }
+        Ok(acc)
     }
 }
 
@@ -222,21 +318,31 @@ fn obtain_suggestions<'s>(
     plain: &PlainOverlay,
     chunk: &'s CheckableChunk,
     hunspell: &Hunspell,
+    cache: &LookupCache,
     origin: &ContentOrigin,
     word: String,
     range: Range,
     allow_concatenated: bool,
     allow_dashed: bool,
+    case: &CaseConfig,
+    severity: Severity,
     acc: &mut SuggestionSet<'s>,
 ) {
-    if !hunspell.check(&word) {
+    let lookup = cache.get_or_insert_with(&word, || {
+        if hunspell.check(&word) {
+            None
+        } else {
+            // get rid of single character suggestions
+            let replacements = hunspell
+                .suggest(&word)
+                .into_iter()
+                .filter(|x| x.len() > 1) // single char suggestions tend to be useless
+                .collect::<Vec<_>>();
+            Some(replacements)
+        }
+    });
+    if let Some(replacements) = lookup {
         trace!("No match for word (plain range: {:?}): >{}<", &range, &word);
-        // get rid of single character suggestions
-        let replacements = hunspell
-            .suggest(&word)
-            .into_iter()
-            .filter(|x| x.len() > 1) // single char suggestions tend to be useless
-            .collect::<Vec<_>>();
 
         if allow_concatenated && replacements_contain_dashless(&word, replacements.as_slice()) {
             trace!(target: "quirks", "Found dashless word in replacement suggestions, treating {} as ok", &word);
@@ -246,17 +352,48 @@ fn obtain_suggestions<'s>(
             trace!(target: "quirks", "Found dashed word in replacement suggestions, treating {} as ok", &word);
             return;
         }
+        if case.accepts(&word, replacements.as_slice()) {
+            trace!(
+                "Found word differing only in case in replacement suggestions, treating {} as ok",
+                &word
+            );
+            return;
+        }
+        // the word is known, just not with this capitalization
+        let category = if replacements
+            .iter()
+            .any(|replacement| replacement.to_lowercase() == word.to_lowercase())
+        {
+            Category::Casing
+        } else {
+            Category::Spelling
+        };
+        // a casing replacement must keep the case of the dictionary
+        let replacements = if category == Category::Spelling {
+            case.adjust(&word, replacements)
+        } else {
+            replacements
+        };
         for (range, span) in plain.find_spans(range.clone()) {
             acc.add(
                 origin.clone(),
                 Suggestion {
                     detector: Detector::Hunspell,
+                    severity,
+                    category,
+                    rule: if category == Category::Casing {
+                        "wrong-case"
+                    } else {
+                        "unknown-word"
+                    }
+                    .to_owned(),
                     range,
                     span,
                     origin: origin.clone(),
                     replacements: replacements.clone(),
                     chunk,
                     description: Some("Possible spelling mistake found.".to_owned()),
+                    confidence: None,
                 },
             )
         }
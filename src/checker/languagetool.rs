@@ -7,70 +7,141 @@
 use super::*;
 
 use crate::Range;
-use languagetool_rs::{LanguageTool, Request};
+use languagetool_rs::{LanguageTool, Request, Rule};
 use log::trace;
 
 pub struct LanguageToolChecker;
 
+/// Map a LanguageTool rule to one of our categories, where anything not more
+/// specific is considered grammar.
+fn categorize(rule: &Rule) -> Category {
+    if rule.id.contains("REPEAT") || rule.id.contains("REPETITION") {
+        return Category::Repetition;
+    }
+    match rule.category.id.as_deref() {
+        Some("TYPOS") => Category::Spelling,
+        Some("CASING") => Category::Casing,
+        Some("STYLE")
+        | Some("REDUNDANCY")
+        | Some("TYPOGRAPHY")
+        | Some("PUNCTUATION")
+        | Some("PLAIN_ENGLISH") => Category::Style,
+        _ => Category::Grammar,
+    }
+}
+
+/// Separates the chunks of a batch, so no match spans multiple chunks.
+const SEPARATOR: &str = "\n\n";
+
 impl Checker for LanguageToolChecker {
     type Config = crate::config::LanguageToolConfig;
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
-    where
-        'a: 's,
-    {
+    type Engine = ();
+    const DETECTOR: Detector = Detector::LanguageTool;
+
+    /// Bounded, since the server has a limit on the text size per request.
+    const BATCH_SIZE: usize = 16;
+
+    fn init(_config: &Self::Config) -> Result<Self::Engine> {
+        Ok(())
+    }
+
+    /// Sends all chunks of the batch with a single request.
+    fn check_batch<'s>(
+        _engine: &Self::Engine,
+        config: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>> {
         let lt = LanguageTool::new(config.url.as_str())?;
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
-            SuggestionSet::new(),
-            |mut acc, (origin, chunks)| {
-                for chunk in chunks {
-                    let plain = chunk.erase_cmark();
-                    log::trace!("markdown erasure: {:?}", &plain);
-                    let req = Request::new(plain.to_string(), "en-US".to_owned());
-                    let resp = lt.check(req)?;
-                    if let Some(software) = resp.software {
-                        log::trace!("sw: {:?}", software);
-                    }
-                    if let Some(matches) = resp.matches {
-                        for item in matches {
-                            if let Some(rule) = item.rule {
-                                if rule.id == "EN_QUOTES" {
-                                    // really annoying and pointless in code related documentation
-                                    continue;
-                                }
-                                trace!("item.rule: {:?}", rule);
-                            }
-                            trace!("item.context: {:?}", item.context);
-                            trace!("item.message: {:?}", item.message);
-                            trace!("item.short_message: {:?}", item.short_message);
-                            // TODO convert response to offsets and errors with the matching literal
-                            for (range, span) in plain.find_spans(Range {
-                                start: item.offset as usize,
-                                end: (item.offset + item.length) as usize,
-                            }) {
-                                acc.add(
-                                    origin.clone(),
-                                    Suggestion {
-                                        detector: Detector::LanguageTool,
-                                        range,
-                                        span,
-                                        origin: origin.clone(),
-                                        replacements: item
-                                            .replacements
-                                            .iter()
-                                            .filter_map(|x| x.value.clone())
-                                            .collect(),
-                                        chunk: chunk,
-                                        description: Some(item.message.clone()),
-                                    },
-                                );
-                            }
-                        }
+        let plains = batch
+            .iter()
+            .map(|(_origin, chunk)| chunk.erase_cmark())
+            .collect::<Vec<_>>();
+
+        // offsets of the individual chunks within the request text, in characters
+        let mut text = String::new();
+        let mut starts = Vec::with_capacity(plains.len());
+        let mut offset = 0usize;
+        for plain in plains.iter() {
+            log::trace!("markdown erasure: {:?}", &plain);
+            if !text.is_empty() {
+                text.push_str(SEPARATOR);
+                offset += SEPARATOR.chars().count();
+            }
+            starts.push(offset);
+            text.push_str(plain.as_str());
+            offset += plain.as_str().chars().count();
+        }
+
+        let mut acc = SuggestionSet::new();
+        if text.is_empty() {
+            return Ok(acc);
+        }
+        let req = Request::new(text, "en-US".to_owned());
+        let resp = lt.check(req)?;
+        if debug::is_debugged(Self::DETECTOR) {
+            debug::dump_raw(std::io::stderr().lock(), Self::DETECTOR, &resp)?;
+        }
+        if let Some(software) = resp.software {
+            log::trace!("sw: {:?}", software);
+        }
+        if let Some(matches) = resp.matches {
+            for item in matches {
+                let (category, rule_id) = if let Some(rule) = item.rule {
+                    if rule.id == "EN_QUOTES" {
+                        // really annoying and pointless in code related documentation
+                        continue;
                     }
-                }
-                Ok(acc)
-            },
-        )?;
+                    trace!("item.rule: {:?}", rule);
+                    (categorize(&rule), rule.id.clone())
+                } else {
+                    (Category::Grammar, "unknown".to_owned())
+                };
+                trace!("item.context: {:?}", item.context);
+                trace!("item.message: {:?}", item.message);
+                trace!("item.short_message: {:?}", item.short_message);
 
-        Ok(suggestions)
+                let start = item.offset as usize;
+                let end = (item.offset + item.length) as usize;
+                // the chunk the match is located in
+                let idx = match starts.binary_search(&start) {
+                    Ok(idx) => idx,
+                    Err(idx) => idx.saturating_sub(1),
+                };
+                let (origin, chunk) = batch[idx];
+                let plain = &plains[idx];
+                let range = Range {
+                    start: start - starts[idx],
+                    end: end - starts[idx],
+                };
+                if range.end > plain.as_str().chars().count() {
+                    trace!("Dropping match spanning multiple chunks {:?}", start..end);
+                    continue;
+                }
+                for (range, span) in plain.find_spans(range) {
+                    acc.add(
+                        origin.clone(),
+                        Suggestion {
+                            detector: Detector::LanguageTool,
+                            severity: config.severity,
+                            category,
+                            rule: rule_id.clone(),
+                            range,
+                            span,
+                            origin: origin.clone(),
+                            replacements: item
+                                .replacements
+                                .iter()
+                                .filter_map(|x| x.value.clone())
+                                .collect(),
+                            chunk,
+                            description: Some(item.message.clone()),
+                            // the server does not report how certain it is
+                            confidence: None,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(acc)
     }
 }
@@ -0,0 +1,143 @@
+//! Limits shared by all checkers of a single run.
+//!
+//! The timeouts of the config apply to each checker on its own, see
+//! `timeout`. Once the checkers found `max_suggestions_per_file` suggestions in a file,
+//! its remaining chunks are not handed to any checker anymore, since all
+//! further findings would be truncated anyways. Suggestions are counted after
+//! the same filters the final result is subject to, so the ignored ones do not
+//! count towards the limit.
+
+use super::Batch;
+use crate::config::TimeoutConfig;
+use crate::documentation::CheckableChunk;
+use crate::{Config, ContentOrigin, SuggestionSet};
+
+use indexmap::IndexMap;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// The limits of checking a documentation with a particular config.
+pub(crate) struct Limits<'c> {
+    config: Option<&'c Config>,
+    /// Suggestions found per file so far.
+    found: Mutex<HashMap<ContentOrigin, usize>>,
+    /// Chunks not checked per file, since the file reached the limit.
+    unchecked: Mutex<IndexMap<ContentOrigin, usize>>,
+}
+
+impl<'c> Limits<'c> {
+    /// The limits of `config`.
+    pub(crate) fn new(config: &'c Config) -> Self {
+        Self::with_config(Some(config))
+    }
+
+    /// Check every chunk.
+    #[cfg(test)]
+    pub(crate) fn unlimited() -> Self {
+        Self::with_config(None)
+    }
+
+    fn with_config(config: Option<&'c Config>) -> Self {
+        Self {
+            config,
+            found: Mutex::new(HashMap::new()),
+            unchecked: Mutex::new(IndexMap::new()),
+        }
+    }
+
+    fn max_per_file(&self) -> Option<usize> {
+        self.config
+            .and_then(|config| config.max_suggestions_per_file)
+    }
+
+    /// The timeouts of each checker, none if unlimited.
+    pub(crate) fn timeouts(&self) -> TimeoutConfig {
+        self.config
+            .map(|config| config.timeouts.clone())
+            .unwrap_or_default()
+    }
+
+    /// The chunks of `batch` which are still to be checked, excluding those of
+    /// files which already reached the limit.
+    pub(crate) fn retain<'s>(
+        &self,
+        batch: &Batch<'s>,
+    ) -> Vec<(&'s ContentOrigin, &'s CheckableChunk)> {
+        let max = match self.max_per_file() {
+            Some(max) => max,
+            None => return batch.to_vec(),
+        };
+        let found = self.found.lock().expect("Lock is never poisoned. qed");
+        let mut unchecked = self.unchecked.lock().expect("Lock is never poisoned. qed");
+        batch
+            .iter()
+            .copied()
+            .filter(|(origin, _chunk)| {
+                let reached = matches!(found.get(*origin), Some(found) if *found >= max);
+                if reached {
+                    *unchecked.entry((*origin).clone()).or_default() += 1;
+                }
+                !reached
+            })
+            .collect()
+    }
+
+    /// Account the suggestions of a checked batch to their files.
+    pub(crate) fn record(&self, suggestions: &SuggestionSet<'_>) {
+        let config = match self.config {
+            Some(config) if config.max_suggestions_per_file.is_some() => config,
+            _ => return,
+        };
+        let remaining = super::filter(suggestions.clone(), config);
+        let mut found = self.found.lock().expect("Lock is never poisoned. qed");
+        for (origin, suggestions) in remaining.iter() {
+            *found.entry(origin.clone()).or_default() += suggestions.len();
+        }
+    }
+
+    /// The number of chunks per file which were not checked.
+    pub(crate) fn unchecked(&self) -> IndexMap<ContentOrigin, usize> {
+        self.unchecked
+            .lock()
+            .expect("Lock is never poisoned. qed")
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, Documentation};
+
+    #[test]
+    fn reached() {
+        let mut d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("one two")));
+        d.join(Documentation::from((
+            ContentOrigin::TestEntityCommonMark,
+            "three\n\n```rust\n/// four\nstruct X;\n```",
+        )));
+        let batch = dummy::batch(&d);
+
+        let unlimited = Limits::unlimited();
+        unlimited.record(&dummy::check_all(&d));
+        assert_eq!(unlimited.retain(&batch).len(), batch.len());
+
+        let config = Config {
+            max_suggestions_per_file: Some(2),
+            ..Config::default()
+        };
+        let limits = Limits::new(&config);
+        assert_eq!(limits.retain(&batch).len(), batch.len());
+        limits.record(&dummy::check_all(&d));
+        assert!(limits
+            .retain(&batch)
+            .iter()
+            .all(|(origin, _chunk)| **origin == ContentOrigin::TestEntityCommonMark));
+        assert_eq!(
+            limits.unchecked().get(&ContentOrigin::TestEntityRust),
+            Some(&1)
+        );
+    }
+}
@@ -4,27 +4,156 @@
 //! Contains also helpers to avoid re-implementing generic
 //! algorithms again and again, i.e. tokenization.
 
-use crate::{Config, Detector, Documentation, Suggestion, SuggestionSet};
+use crate::config::CategoriesConfig;
+use crate::documentation::CheckableChunk;
+use crate::{Category, Config, ContentOrigin, Detector, Documentation, Suggestion, SuggestionSet};
 
 use anyhow::Result;
 
+use crate::sentence::follows_abbreviation;
+use crate::timings::{self, Stage};
+use crate::util::sub_chars;
 use crate::Range;
+pub(crate) use limits::Limits;
 use log::debug;
+use rayon::prelude::*;
 
+use std::time::{Duration, Instant};
+
+mod allow;
+mod banned;
+#[cfg(any(test, feature = "hunspell"))]
+mod cache;
+mod consistency;
+pub mod debug;
+mod dedup;
 #[cfg(feature = "hunspell")]
 mod hunspell;
 #[cfg(feature = "languagetool")]
 mod languagetool;
+mod limits;
+#[cfg(any(test, feature = "hunspell"))]
+mod skip;
 
 #[cfg(any(feature = "languagetool", feature = "hunspell"))]
 mod quirks;
+#[cfg(any(test, feature = "hunspell"))]
+mod symbols;
+mod terminology;
+mod timeout;
+pub mod unused;
+mod variants;
+
+/// A number of chunks to check at once, each with the origin it belongs to.
+pub type Batch<'a> = [(&'a ContentOrigin, &'a CheckableChunk)];
 
 /// Implementation for a checker
-pub(crate) trait Checker {
-    type Config;
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+///
+/// Chunks are handed to the checker in batches, so the per request and per
+/// call overhead of a backend can be amortized.
+pub trait Checker: 'static {
+    type Config: Clone + Send + Sync + 'static;
+    /// State shared by all batches of a run, i.e. a loaded dictionary.
+    type Engine: Send + Sync + 'static;
+
+    /// Identifies the checker, i.e. for timings.
+    const DETECTOR: Detector;
+
+    /// The maximum number of chunks per batch.
+    const BATCH_SIZE: usize = 64;
+
+    /// Prepare everything required for checking.
+    fn init(config: &Self::Config) -> Result<Self::Engine>;
+
+    /// Check all chunks of a single batch.
+    fn check_batch<'s>(
+        engine: &Self::Engine,
+        config: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>>;
+
+    /// Check the full documentation, batches are checked in parallel.
+    ///
+    /// Chunks of files which reached the `limits` are skipped, batches which
+    /// exceed the configured timeouts are abandoned.
+    fn check<'a, 's>(
+        docu: &'a Documentation,
+        config: &Self::Config,
+        limits: &Limits<'_>,
+    ) -> Result<SuggestionSet<'s>>
     where
-        'a: 's;
+        'a: 's,
+        Self: Sized,
+    {
+        let engine = std::sync::Arc::new(Self::init(config)?);
+        // only the workers of a run with timeouts require their own config
+        let budget = timeout::Budget::new(Self::DETECTOR, &limits.timeouts());
+        let owned = if budget.is_limited() {
+            Some(std::sync::Arc::new(config.clone()))
+        } else {
+            None
+        };
+        let chunks = docu
+            .iter()
+            .flat_map(|(origin, chunks)| chunks.iter().map(move |chunk| (origin, chunk)))
+            .collect::<Vec<_>>();
+        let suggestions = chunks
+            .par_chunks(Self::BATCH_SIZE)
+            .map(|batch| {
+                let batch = budget.retain(&limits.retain(batch));
+                if batch.is_empty() {
+                    return Ok(SuggestionSet::new());
+                }
+                let batch = batch.as_slice();
+                let start = Instant::now();
+                let suggestions = match owned {
+                    Some(ref owned) => timeout::check_batch::<Self>(&engine, owned, batch, &budget),
+                    None => Self::check_batch(&engine, config, batch),
+                };
+                let elapsed = start.elapsed();
+                budget.record(batch, elapsed);
+                if timings::is_enabled() {
+                    record_batch_timing(Stage::Check(Self::DETECTOR), batch, elapsed);
+                }
+                if let Ok(ref suggestions) = suggestions {
+                    limits.record(suggestions);
+                    if debug::is_debugged(Self::DETECTOR) {
+                        debug::dump(std::io::stderr().lock(), Self::DETECTOR, batch, suggestions)?;
+                    }
+                }
+                suggestions
+            })
+            .try_reduce(SuggestionSet::new, |mut a, b| {
+                a.join(b);
+                Ok(a)
+            });
+        budget.warn();
+        suggestions
+    }
+}
+
+/// Distribute the time spent on a batch among the origins of its chunks,
+/// proportional to the length of the chunks.
+fn batch_shares<'a, 's>(
+    batch: &'a Batch<'s>,
+    duration: Duration,
+) -> impl Iterator<Item = (&'s ContentOrigin, Duration)> + 'a {
+    let total = batch
+        .iter()
+        .map(|(_origin, chunk)| chunk.len_in_chars())
+        .sum::<usize>()
+        .max(1);
+    batch.iter().map(move |(origin, chunk)| {
+        let share = duration.as_secs_f64() * chunk.len_in_chars() as f64 / total as f64;
+        (*origin, Duration::from_secs_f64(share))
+    })
+}
+
+/// Record the time spent on a batch for the origins of its chunks.
+fn record_batch_timing(stage: Stage, batch: &Batch<'_>, duration: Duration) {
+    for (origin, share) in batch_shares(batch, duration) {
+        timings::record(stage, origin.as_path(), share);
+    }
 }
 
 /// Returns absolute offsets and the data with the token in question.
@@ -77,11 +206,137 @@ fn tokenize(s: &str) -> Vec<Range> {
 }
 
 /// Check a full document for violations using the tools we have.
+///
+/// Chunks with identical content are only checked once, except for the
+/// consistency checks.
 pub fn check<'a, 's>(documentation: &'a Documentation, config: &Config) -> Result<SuggestionSet<'s>>
 where
     'a: 's,
 {
-    let mut collective = SuggestionSet::<'s>::new();
+    check_all(&[(config, documentation)])
+}
+
+/// Check all `partitions`, each with its own config, see [`partition`].
+///
+/// The consistency checks consider all partitions at once, since each of
+/// them may be consistent on its own. Each partition keeps at most the
+/// `max_suggestions_per_file` of its config.
+pub fn check_all<'s>(partitions: &[(&Config, &'s Documentation)]) -> Result<SuggestionSet<'s>> {
+    let mut found = Vec::with_capacity(partitions.len());
+    let mut unchecked = Vec::with_capacity(partitions.len());
+    for &(config, documentation) in partitions {
+        unused::define(documentation, config);
+        let limits = Limits::new(config);
+        found.push(dedup::check_deduplicated(
+            documentation,
+            config,
+            &limits,
+            check_distinct,
+        )?);
+        unchecked.push(limits.unchecked());
+    }
+
+    // requires all occurrences, so duplicate chunks must not be collapsed
+    let parts = partitions
+        .iter()
+        .filter_map(|&(config, documentation)| {
+            config
+                .consistency
+                .as_ref()
+                .map(|consistency| (documentation, consistency))
+        })
+        .collect::<Vec<_>>();
+    if !parts.is_empty() {
+        debug!("Running consistency checks");
+        for (origin, suggestions) in consistency::check(&parts) {
+            if let Some(idx) = partitions
+                .iter()
+                .position(|(_config, documentation)| documentation.get(&origin).is_some())
+            {
+                found[idx].extend(origin, suggestions);
+            }
+        }
+    }
+
+    let mut collective = SuggestionSet::new();
+    for ((&(config, _documentation), found), unchecked) in
+        partitions.iter().zip(found).zip(unchecked)
+    {
+        let mut found = filter(found, config);
+        if let Some(max) = config.max_suggestions_per_file {
+            found.truncate(max);
+        }
+        for (origin, chunks) in unchecked {
+            found.not_checked(origin, chunks);
+        }
+        collective.merge(found);
+    }
+    Ok(collective)
+}
+
+/// Drop all findings which are disabled, ignored or not confident enough
+/// according to `config`.
+fn filter<'s>(mut collective: SuggestionSet<'s>, config: &Config) -> SuggestionSet<'s> {
+    if let Some(ref categories) = config.categories {
+        apply_categories(&mut collective, categories);
+    }
+
+    collective.retain(|_origin, suggestion| !allow::is_allowed(suggestion, &config.disabled_rules));
+
+    if config.min_confidence > 0 {
+        collective.retain(|_origin, suggestion| suggestion.is_confident(config.min_confidence));
+    }
+
+    if !config.ignore.is_empty() {
+        collective.retain(|_origin, suggestion| {
+            let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+            let ignored = config.ignore.is_ignored(&word);
+            if ignored {
+                unused::ignored(&config.ignore, &word);
+            }
+            !ignored
+        });
+    }
+
+    // a word following an abbreviation does not start a sentence
+    collective.retain(|_origin, suggestion| {
+        suggestion.detector != Detector::LanguageTool
+            || suggestion.category != Category::Casing
+            || !follows_abbreviation(
+                suggestion.chunk.as_str(),
+                suggestion.range.start,
+                &config.abbreviations,
+            )
+    });
+
+    collective
+}
+
+/// Start initializing the enabled checkers in the background, while
+/// the documentation is still being extracted.
+#[cfg_attr(not(feature = "hunspell"), allow(unused_variables))]
+pub fn preload(config: &Config) {
+    #[cfg(feature = "hunspell")]
+    {
+        if config.is_enabled(Detector::Hunspell) {
+            if let Some(ref config) = config.hunspell {
+                self::hunspell::HunspellChecker::preload(config);
+            }
+        }
+    }
+}
+
+/// Run all enabled checkers on `documentation`.
+fn check_distinct<'a>(
+    documentation: &'a Documentation,
+    config: &Config,
+    limits: &Limits<'_>,
+) -> Result<SuggestionSet<'a>> {
+    let mut collective = SuggestionSet::<'a>::new();
+    // nothing reaches the checkers, so there is no need to initialize them
+    if documentation.is_empty() {
+        return Ok(collective);
+    }
 
     #[cfg(feature = "languagetool")]
     {
@@ -92,8 +347,8 @@ where
                 .as_ref()
                 .expect("Must be Some(LanguageToolConfig) if is_enabled returns true");
 
-            let mut suggestions =
-                self::languagetool::LanguageToolChecker::check(documentation, config)?;
+            let suggestions =
+                self::languagetool::LanguageToolChecker::check(documentation, config, limits)?;
             collective.join(suggestions);
         }
     }
@@ -106,7 +361,38 @@ where
                 .hunspell
                 .as_ref()
                 .expect("Must be Some(HunspellConfig) if is_enabled returns true");
-            let suggestions = self::hunspell::HunspellChecker::check(documentation, config)?;
+            let suggestions =
+                self::hunspell::HunspellChecker::check(documentation, config, limits)?;
+            collective.join(suggestions);
+        }
+    }
+
+    if config.is_enabled(Detector::Terminology) {
+        debug!("Running terminology checks");
+        let suggestions = self::terminology::TerminologyChecker::check(
+            documentation,
+            &config.terminology,
+            limits,
+        )?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::Banned) {
+        debug!("Running banned term checks");
+        let suggestions =
+            self::banned::BannedChecker::check(documentation, &config.banned, limits)?;
+        collective.join(suggestions);
+    }
+
+    if config.is_enabled(Detector::Hunspell) {
+        let config = config
+            .hunspell
+            .as_ref()
+            .expect("Must be Some(HunspellConfig) if is_enabled returns true");
+        if config.enforce_locale {
+            debug!("Running locale checks");
+            let suggestions = self::variants::LocaleChecker::check(documentation, config, limits)?;
+            self::variants::supersede(&mut collective, &suggestions);
             collective.join(suggestions);
         }
     }
@@ -114,6 +400,46 @@ where
     Ok(collective)
 }
 
+/// Split the documentation into groups, which share the same set of
+/// applicable per path overrides, each with the resulting configuration.
+pub fn partition(documentation: Documentation, config: &Config) -> Vec<(Config, Documentation)> {
+    if config.overrides.is_empty() {
+        return vec![(config.clone(), documentation)];
+    }
+    documentation
+        .partition_by(|origin| {
+            config
+                .overrides
+                .iter()
+                .enumerate()
+                .filter(|(_idx, ovr)| ovr.matches(origin.as_path()))
+                .map(|(idx, _ovr)| idx)
+                .collect::<Vec<usize>>()
+        })
+        .into_iter()
+        .map(|(_indices, documentation)| {
+            // all files of a partition match the very same overrides
+            let config = match documentation.iter().next() {
+                Some((origin, _chunks)) => config.for_path(origin.as_path()),
+                None => config.clone(),
+            };
+            (config, documentation)
+        })
+        .collect()
+}
+
+/// Drop suggestions of disabled categories and adjust the severity of the
+/// remaining ones, based on the path of the file they originate from.
+fn apply_categories(suggestions: &mut SuggestionSet<'_>, categories: &CategoriesConfig) {
+    suggestions.retain(|origin, suggestion| {
+        let resolved = categories.resolve(suggestion.category, origin.as_path());
+        if let Some(severity) = resolved.severity {
+            suggestion.severity = severity;
+        }
+        resolved.is_enabled()
+    });
+}
+
 #[cfg(test)]
 pub mod dummy;
 
@@ -121,8 +447,8 @@ pub mod dummy;
 pub mod tests {
     use super::*;
     use crate::span::Span;
-    use crate::ContentOrigin;
     use crate::LineColumn;
+    use crate::{ContentOrigin, Severity};
 
     use crate::fluff_up;
 
@@ -151,8 +477,8 @@ pub mod tests {
             .is_test(true)
             .try_init();
         let d = Documentation::from((ContentOrigin::TestEntityRust, content));
-        let suggestion_set =
-            dummy::DummyChecker::check(&d, &()).expect("Dummy extraction must never fail");
+        let suggestion_set = dummy::DummyChecker::check(&d, &(), &Limits::unlimited())
+            .expect("Dummy extraction must never fail");
 
         // one file
         assert_eq!(suggestion_set.len(), 1);
@@ -242,4 +568,145 @@ pub mod tests {
         ];
         extraction_test_body(dbg!(SIMPLE), EXPECTED_SPANS);
     }
+
+    /// Checks in tiny batches and counts them.
+    struct Batched;
+
+    static BATCHES: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    impl Checker for Batched {
+        type Config = ();
+        type Engine = ();
+        const DETECTOR: Detector = Detector::Dummy;
+        const BATCH_SIZE: usize = 2;
+
+        fn init(_: &Self::Config) -> Result<Self::Engine> {
+            Ok(())
+        }
+
+        fn check_batch<'s>(
+            engine: &Self::Engine,
+            config: &Self::Config,
+            batch: &Batch<'s>,
+        ) -> Result<SuggestionSet<'s>> {
+            assert!(batch.len() <= Self::BATCH_SIZE);
+            BATCHES.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            dummy::DummyChecker::check_batch(engine, config, batch)
+        }
+    }
+
+    #[test]
+    fn batches() {
+        let mut d = Documentation::new();
+        for idx in 0..5 {
+            d.add_rust(
+                ContentOrigin::RustSourceFile(format!("{}.rs", idx).into()),
+                fluff_up!("two literals"),
+            )
+            .unwrap();
+        }
+        let suggestions = Batched::check(&d, &(), &Limits::unlimited()).unwrap();
+        assert_eq!(BATCHES.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(suggestions.len(), 5);
+        assert_eq!(suggestions.total_count(), 10);
+        // order is preserved
+        let (origin, _) = suggestions.iter().last().unwrap();
+        assert_eq!(origin.as_path(), std::path::Path::new("4.rs"));
+    }
+
+    #[test]
+    fn partition_by_overrides() {
+        let mut d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("two literals")));
+        d.join(Documentation::from((
+            ContentOrigin::TestEntityCommonMark,
+            "some markdown",
+        )));
+
+        let cfg = Config::parse(
+            r#"
+[Hunspell]
+
+[[overrides]]
+paths = ["/tmp/test/*.md"]
+severity = "info"
+"#,
+        )
+        .unwrap();
+
+        let partitions = partition(d, &cfg);
+        assert_eq!(partitions.len(), 2);
+        let (rust_cfg, rust) = &partitions[0];
+        assert!(rust.get(&ContentOrigin::TestEntityRust).is_some());
+        assert_eq!(
+            rust_cfg.hunspell.as_ref().unwrap().severity,
+            Severity::Error
+        );
+        let (cmark_cfg, cmark) = &partitions[1];
+        assert!(cmark.get(&ContentOrigin::TestEntityCommonMark).is_some());
+        assert_eq!(
+            cmark_cfg.hunspell.as_ref().unwrap().severity,
+            Severity::Info
+        );
+    }
+
+    #[test]
+    fn disabled_rules() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!(
+                "Our web site.",
+                "",
+                "<!-- spellcheck: allow(terminology::disfavored-term) -->"
+            ),
+        ));
+        let cfg = Config::parse("[terminology.terms]\n\"web site\" = \"website\"").unwrap();
+        assert_eq!(check(&d, &cfg).unwrap().total_count(), 0);
+
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("Our web site.")));
+        let suggestions = check(&d, &cfg).unwrap();
+        let (_origin, found) = suggestions.iter().next().unwrap();
+        assert_eq!(found[0].rule_id(), "terminology::disfavored-term");
+
+        let cfg = Config::parse(
+            "disabled_rules = [\"terminology::disfavored-term\"]\n[terminology.terms]\n\"web site\" = \"website\"",
+        )
+        .unwrap();
+        assert_eq!(check(&d, &cfg).unwrap().total_count(), 0);
+    }
+
+    #[test]
+    fn categories_filter_and_severity() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("two literals")));
+        let suggestions = dummy::DummyChecker::check(&d, &(), &Limits::unlimited())
+            .expect("Dummy extraction must never fail");
+        assert_eq!(suggestions.total_count(), 2);
+
+        let cfg = Config::parse(
+            r#"
+[categories]
+spelling = { severity = "info" }
+"#,
+        )
+        .unwrap();
+        let mut demoted = suggestions.clone();
+        apply_categories(&mut demoted, cfg.categories.as_ref().unwrap());
+        assert_eq!(demoted.total_count(), 2);
+        assert!(demoted
+            .iter()
+            .flat_map(|(_, suggestions)| suggestions.iter())
+            .all(|suggestion| suggestion.severity == Severity::Info));
+
+        let cfg = Config::parse(
+            r#"
+[[categories.overrides]]
+paths = ["/tmp/test/*.rs"]
+spelling = { enabled = false }
+"#,
+        )
+        .unwrap();
+        let mut filtered = suggestions;
+        apply_categories(&mut filtered, cfg.categories.as_ref().unwrap());
+        assert_eq!(filtered.total_count(), 0);
+        assert_eq!(filtered.len(), 0);
+    }
 }
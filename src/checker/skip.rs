@@ -0,0 +1,284 @@
+//! Tokens which are no words, and never reach the dictionary lookup.
+//!
+//! Numbers with unit suffixes and versions like `128GiB`, `3.4GHz` or
+//! `v0.4.0-alpha`, file paths, email addresses, hex hashes and UUIDs, as
+//! well as environment variable names would otherwise be split into
+//! fragments, which are looked up in the dictionary.
+
+use crate::config::{Quirks, SkipConfig};
+use crate::Range;
+
+/// Characters enclosing a word, which are not part of it.
+fn is_enclosing(c: char) -> bool {
+    matches!(
+        c,
+        '(' | ')' | '[' | ']' | '{' | '}' | '"' | '\'' | '`' | ',' | ';' | ':' | '!' | '?' | '.'
+    )
+}
+
+/// If `word` is a number, optionally followed by a unit, or a version.
+///
+/// It must start with a digit, after an optional sign or `v` prefix, and
+/// may only contain alphanumerics and the separators of numbers, units and
+/// versions.
+pub(crate) fn is_numeric(word: &str) -> bool {
+    let word = word.trim_start_matches(|c| matches!(c, '+' | '-' | '~' | '≈'));
+    let word = match word.strip_prefix(|c| c == 'v' || c == 'V') {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest,
+        _ => word,
+    };
+    word.starts_with(|c: char| c.is_ascii_digit())
+        && word.chars().all(|c| {
+            c.is_alphanumeric() || matches!(c, '.' | ',' | '_' | '-' | '+' | '/' | '%' | '°' | ':')
+        })
+}
+
+/// If `word` is a file path, i.e. `src/lib.rs`, `~/.config`, `$HOME/.cargo`
+/// or `C:\Users`.
+///
+/// Relative paths require either two separators or an extension, so
+/// `and/or` is not considered a path.
+pub(crate) fn is_path(word: &str) -> bool {
+    if word.contains("://") {
+        return false;
+    }
+    let is_separator = |c: char| c == '/' || c == '\\';
+    let mut chars = word.chars();
+    let drive = matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(letter), Some(':'), Some(separator)) if letter.is_ascii_alphabetic() && is_separator(separator)
+    );
+    // rooted in an environment variable
+    let variable = word
+        .split_once(is_separator)
+        .map(|(root, _rest)| root.starts_with(|c| c == '$' || c == '%') && is_env_var(root))
+        .unwrap_or_default();
+    if drive
+        || variable
+        || ["/", "./", "../", "~/", ".\\", "..\\"]
+            .iter()
+            .any(|prefix| word.starts_with(prefix))
+    {
+        return word.chars().count() > 1;
+    }
+    let separators = word.chars().filter(|&c| is_separator(c)).count();
+    let extension = word
+        .rsplit(is_separator)
+        .next()
+        .and_then(|file_name| file_name.rsplit_once('.'))
+        .map(|(stem, extension)| {
+            !stem.is_empty()
+                && !extension.is_empty()
+                && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+        .unwrap_or_default();
+    separators >= 2 || (separators == 1 && extension)
+}
+
+/// If `word` is an email address.
+pub(crate) fn is_email(word: &str) -> bool {
+    let word = word.strip_prefix("mailto:").unwrap_or(word);
+    let (local, domain) = match word.split_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let allowed = |c: char| c.is_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-');
+    !local.is_empty()
+        && local.chars().all(allowed)
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain.chars().all(allowed)
+}
+
+/// If `word` is a hex hash of at least 7 digits, i.e. an abbreviated commit,
+/// or a UUID.
+pub(crate) fn is_hash(word: &str) -> bool {
+    let groups = word.split('-').map(str::len).collect::<Vec<_>>();
+    let uuid = groups == [8, 4, 4, 4, 12];
+    if !uuid && (groups.len() != 1 || word.len() < 7) {
+        return false;
+    }
+    let hex = word.chars().filter(|&c| c != '-');
+    // at least one digit, otherwise it might as well be a word like `defaced`
+    hex.clone().all(|c| c.is_ascii_hexdigit()) && (uuid || hex.clone().any(|c| c.is_ascii_digit()))
+}
+
+/// If `word` is the name of an environment variable, i.e. `$HOME`, `%APPDATA%`
+/// or `CARGO_MANIFEST_DIR`.
+pub(crate) fn is_env_var(word: &str) -> bool {
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if let Some(name) = word.strip_prefix('$') {
+        let name = name.trim_start_matches('{').trim_end_matches('}');
+        return is_name(name);
+    }
+    if let Some(name) = word
+        .strip_prefix('%')
+        .and_then(|name| name.strip_suffix('%'))
+    {
+        return is_name(name);
+    }
+    // without a sigil, only screaming snake case is unambiguous
+    is_name(word)
+        && word.contains('_')
+        && word.chars().any(|c| c.is_ascii_uppercase())
+        && !word.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// If `word` is skipped with the given configuration.
+pub(crate) fn is_skipped(word: &str, quirks: &Quirks, skip: &SkipConfig) -> bool {
+    (!quirks.check_numbers() && is_numeric(word))
+        || (skip.paths && is_path(word))
+        || (skip.emails && is_email(word))
+        || (skip.hashes && is_hash(word))
+        || (skip.env_vars && is_env_var(word))
+}
+
+/// The char ranges of all whitespace separated words of `s`, for which
+/// `skipped` is true, without enclosing brackets, quotes and punctuation.
+pub(crate) fn skipped_ranges<F>(s: &str, skipped: F) -> Vec<Range>
+where
+    F: Fn(&str) -> bool,
+{
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        if chars[start].is_whitespace() {
+            start += 1;
+            continue;
+        }
+        let end = chars[start..]
+            .iter()
+            .position(|c| c.is_whitespace())
+            .map(|len| start + len)
+            .unwrap_or(chars.len());
+        let mut range = start..end;
+        while range.start < range.end && is_enclosing(chars[range.start]) {
+            range.start += 1;
+        }
+        while range.start < range.end && is_enclosing(chars[range.end - 1]) {
+            range.end -= 1;
+        }
+        let word = chars[range.clone()].iter().collect::<String>();
+        if !word.is_empty() && skipped(&word) {
+            ranges.push(range);
+        }
+        start = end;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sub_chars;
+
+    #[test]
+    fn numeric() {
+        for word in &[
+            "128GiB",
+            "10ms",
+            "3.4GHz",
+            "1.2.3",
+            "v0.4.0-alpha",
+            "2x",
+            "-5dB",
+            "100%",
+            "16:9",
+            "1st",
+            "64-bit",
+            "1,000",
+        ] {
+            assert!(is_numeric(word), "{} is numeric", word);
+        }
+        for word in &["GiB", "version", "v", "x86", "a1", "3D!", "ms."] {
+            assert!(!is_numeric(word), "{} is not numeric", word);
+        }
+    }
+
+    #[test]
+    fn path() {
+        for word in &[
+            "src/lib.rs",
+            "/usr/share/myspell",
+            "./target",
+            "~/.config",
+            "C:\\Users\\Alice",
+            "a/b/c",
+            "..\\build",
+            "%APPDATA%\\cargo",
+        ] {
+            assert!(is_path(word), "{} is a path", word);
+        }
+        for word in &[
+            "and/or",
+            "/",
+            "https://example.com/a/b",
+            "lib.rs",
+            "input/output",
+        ] {
+            assert!(!is_path(word), "{} is not a path", word);
+        }
+    }
+
+    #[test]
+    fn email() {
+        assert!(is_email("alice@example.com"));
+        assert!(is_email("mailto:first.last+tag@mail.example.org"));
+        assert!(!is_email("@example.com"));
+        assert!(!is_email("alice@localhost"));
+        assert!(!is_email("#[doc@]"));
+    }
+
+    #[test]
+    fn hash() {
+        assert!(is_hash("47e71e5"));
+        assert!(is_hash("b930cbd4a1e2f3c4d5e6f708192a3b4c5d6e7f80"));
+        assert!(is_hash("123e4567-e89b-12d3-a456-426614174000"));
+        assert!(!is_hash("defaced"));
+        assert!(!is_hash("47e71"));
+        assert!(!is_hash("47e71e5-ab"));
+    }
+
+    #[test]
+    fn env_var() {
+        for word in &[
+            "$HOME",
+            "${CARGO_HOME}",
+            "%APPDATA%",
+            "CARGO_MANIFEST_DIR",
+            "$out_dir",
+        ] {
+            assert!(is_env_var(word), "{} is an environment variable", word);
+        }
+        for word in &["HOME", "Snake_Case", "snake_case", "$", "%%", "_"] {
+            assert!(!is_env_var(word), "{} is not an environment variable", word);
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        let s = "Allocates 128GiB (v0.4.0-alpha), every 10ms.\nNot 3D! Or x86";
+        let words = skipped_ranges(s, is_numeric)
+            .into_iter()
+            .map(|range| sub_chars(s, range))
+            .collect::<Vec<_>>();
+        assert_eq!(words, vec!["128GiB", "v0.4.0-alpha", "10ms", "3D"]);
+
+        let s = "Reads `$HOME/.config` (see src/lib.rs), mail alice@example.com.";
+        let skip = SkipConfig {
+            emails: false,
+            ..SkipConfig::default()
+        };
+        let words = skipped_ranges(s, |word| is_skipped(word, &Quirks::default(), &skip))
+            .into_iter()
+            .map(|range| sub_chars(s, range))
+            .collect::<Vec<_>>();
+        assert_eq!(words, vec!["$HOME/.config", "src/lib.rs"]);
+    }
+}
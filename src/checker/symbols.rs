@@ -0,0 +1,120 @@
+//! Emoji, arrows and math symbols embedded in doc text.
+//!
+//! Depending on the `symbols` policy, tokens with embedded symbols are either
+//! split at the symbols, or not checked at all. A symbol composed of multiple
+//! chars, i.e. an emoji with a skin tone modifier or a sequence joined by zero
+//! width joiners, is kept as a whole, so neither a split nor a replacement
+//! ever covers only a part of it.
+
+use crate::Range;
+
+/// If `c` is an emoji, arrow or math symbol, or combines with one.
+pub(crate) fn is_symbol(c: char) -> bool {
+    matches!(c,
+        // math operators of latin-1
+        '±' | '×' | '÷' | '¬'
+        // zero width joiner, combining keycap, variation selectors
+        | '\u{200D}' | '\u{20E3}' | '\u{FE0E}' | '\u{FE0F}'
+        // arrows, math operators, misc technical
+        | '\u{2190}'..='\u{23FF}'
+        // misc symbols, dingbats, misc math symbols, supplemental arrows
+        | '\u{2600}'..='\u{2BFF}'
+        // mahjong and domino tiles, cards, enclosed alphanumerics with
+        // regional indicators, emoji, pictographs and their modifiers
+        | '\u{1F000}'..='\u{1FAFF}'
+        // tags of subdivision flags
+        | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+/// The char ranges of all runs of symbols in `s`.
+pub(crate) fn symbol_ranges(s: &str) -> Vec<Range> {
+    let mut ranges = Vec::<Range>::new();
+    for (idx, c) in s.chars().enumerate() {
+        if !is_symbol(c) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if last.end == idx => last.end = idx + 1,
+            _ => ranges.push(idx..idx + 1),
+        }
+    }
+    ranges
+}
+
+/// The char ranges of the parts of the token `range` of `s` between symbols.
+pub(crate) fn split_at_symbols(s: &str, range: Range) -> Vec<Range> {
+    let mut parts = Vec::new();
+    let mut start = range.start;
+    for (idx, c) in s.chars().enumerate().skip(range.start).take(range.len()) {
+        if is_symbol(c) {
+            if start < idx {
+                parts.push(start..idx);
+            }
+            start = idx + 1;
+        }
+    }
+    if start < range.end {
+        parts.push(start..range.end);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::{apply_patches, BandAid, Patch};
+    use crate::checker::tokenize;
+    use crate::util::sub_chars;
+    use crate::{fluff_up, ContentOrigin, Documentation};
+
+    #[test]
+    fn symbols() {
+        for c in &['🚀', '→', '⇒', '∀', '≤', '×', '✓', '⚠', '\u{FE0F}'] {
+            assert!(is_symbol(*c), "{} is a symbol", c);
+        }
+        for c in &['a', 'ä', 'ß', '°', '€', '-', '—', '…', '中'] {
+            assert!(!is_symbol(*c), "{} is no symbol", c);
+        }
+    }
+
+    #[test]
+    fn ranges() {
+        let s = "Fast🚀, a → b, 👍🏽 and 👨‍👩‍👧!";
+        let symbols = symbol_ranges(s)
+            .into_iter()
+            .map(|range| sub_chars(s, range))
+            .collect::<Vec<_>>();
+        assert_eq!(symbols, vec!["🚀", "→", "👍🏽", "👨‍👩‍👧"]);
+    }
+
+    #[test]
+    fn split() {
+        let s = "Fast🚀 a→b 👍🏽ok";
+        let parts = tokenize(s)
+            .into_iter()
+            .flat_map(|range| split_at_symbols(s, range))
+            .map(|range| sub_chars(s, range))
+            .collect::<Vec<_>>();
+        assert_eq!(parts, vec!["Fast", "a", "b", "ok"]);
+    }
+
+    #[test]
+    fn replace_adjacent() {
+        const SOURCE: &str = fluff_up!("Fsat👨‍👩‍👧 and 👍🏽wrk→done");
+        let d = Documentation::from((ContentOrigin::TestEntityRust, SOURCE));
+        let (_origin, chunks) = d.iter().next().unwrap();
+        let plain = chunks[0].erase_cmark();
+        let txt = plain.as_str();
+        let patches = tokenize(txt)
+            .into_iter()
+            .flat_map(|range| split_at_symbols(txt, range))
+            .flat_map(|range| plain.find_spans(range))
+            .map(|(_range, span)| Patch::from(BandAid::from(("X".to_owned(), &span))))
+            .collect::<Vec<_>>();
+        assert_eq!(patches.len(), 4);
+        let mut sink = Vec::new();
+        apply_patches(patches, SOURCE, &mut sink).unwrap();
+        assert_eq!(String::from_utf8(sink).unwrap(), fluff_up!("X👨‍👩‍👧 X 👍🏽X→X"));
+    }
+}
@@ -0,0 +1,158 @@
+//! Enforce the preferred terms of a documentation style guide.
+//!
+//! Every disfavored term of the `[terminology.terms]` table is reported with
+//! the preferred one as replacement. Terms are matched as whole words, ignoring
+//! case, and the words of a term may be separated by any number of spaces, but
+//! not by a line break, since a replacement can not span lines yet.
+
+use super::{Batch, Category, Checker, Detector, Suggestion, SuggestionSet};
+use crate::config::TerminologyConfig;
+use crate::util::{byte_range_to_char_range, match_case};
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use std::ops::Range;
+
+pub(crate) struct TerminologyChecker;
+
+/// Compile the pattern matching the disfavored `term` as whole words.
+pub(super) fn pattern(term: &str) -> Result<Regex> {
+    let words = term
+        .split_whitespace()
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(r"[ \t]+");
+    Regex::new(&format!(r"(?i)(^|[^\w-])({})($|[^\w-])", words))
+        .map_err(|e| anyhow!("Failed to compile the pattern for term {:?}", term).context(e))
+}
+
+/// Find all `terms` in `txt` as byte ranges, with whatever is associated to
+/// the term. Longer terms take precedence over overlapping shorter ones.
+pub(super) fn find<'e, T>(terms: &'e [(Regex, T)], txt: &str) -> Vec<(Range<usize>, &'e T)> {
+    let mut found = Vec::new();
+    for (regex, associated) in terms {
+        // the delimiters are part of the match, so adjacent terms
+        // are found by continuing right after the previous term
+        let mut locations = regex.capture_locations();
+        let mut offset = 0;
+        while regex
+            .captures_read_at(&mut locations, txt, offset)
+            .is_some()
+        {
+            let (start, end) = locations.get(2).expect("Term group always matches. qed");
+            offset = end;
+            found.push((start..end, associated));
+        }
+    }
+    found.sort_by_key(|(range, _associated)| (range.start, usize::MAX - range.end));
+
+    let mut end = 0;
+    found.retain(|(range, _associated)| {
+        if range.start < end {
+            return false;
+        }
+        end = range.end;
+        true
+    });
+    found
+}
+
+impl Checker for TerminologyChecker {
+    type Config = TerminologyConfig;
+    type Engine = Vec<(Regex, String)>;
+    const DETECTOR: Detector = Detector::Terminology;
+
+    fn init(config: &Self::Config) -> Result<Self::Engine> {
+        config
+            .terms
+            .iter()
+            .filter(|(term, _preferred)| !term.trim().is_empty())
+            .map(|(term, preferred)| Ok((pattern(term)?, preferred.clone())))
+            .collect()
+    }
+
+    fn check_batch<'s>(
+        engine: &Self::Engine,
+        config: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>> {
+        let mut acc = SuggestionSet::new();
+        for &(origin, chunk) in batch {
+            let plain = chunk.erase_cmark();
+            let txt = plain.as_str();
+
+            for (bytes, preferred) in find(engine, txt) {
+                let range = match byte_range_to_char_range(txt, bytes.clone()) {
+                    Some(range) => range,
+                    None => continue,
+                };
+                let replacement = match_case(&txt[bytes.clone()], preferred);
+                for (range, span) in plain.find_spans(range) {
+                    acc.add(
+                        origin.clone(),
+                        Suggestion {
+                            detector: Detector::Terminology,
+                            severity: config.severity,
+                            category: Category::Style,
+                            rule: "disfavored-term".to_owned(),
+                            range,
+                            span,
+                            origin: origin.clone(),
+                            replacements: vec![replacement.clone()],
+                            chunk,
+                            description: Some(format!(
+                                "Prefer `{}` over `{}`.",
+                                preferred,
+                                &txt[bytes.clone()]
+                            )),
+                            confidence: None,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation, Severity};
+
+    #[test]
+    fn terms() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!(
+                "Web site of the crate-level docs, not of the web",
+                "site, nor site-wide crate-levels or   Web  Site."
+            ),
+        ));
+        let batch = dummy::batch(&d);
+        let mut config = TerminologyConfig {
+            severity: Severity::Warning,
+            ..Default::default()
+        };
+        let terms = &mut config.terms;
+        terms.insert("web site".to_owned(), "website".to_owned());
+        terms.insert("site".to_owned(), "place".to_owned());
+        terms.insert("crate-level".to_owned(), "crate level".to_owned());
+        let engine = TerminologyChecker::init(&config).unwrap();
+        let suggestions = TerminologyChecker::check_batch(&engine, &config, &batch).unwrap();
+        let replacements = suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .map(|suggestion| {
+                assert_eq!(suggestion.severity, Severity::Warning);
+                suggestion.replacements[0].as_str()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            replacements,
+            vec!["Website", "crate level", "place", "Website"]
+        );
+    }
+}
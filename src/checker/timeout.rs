@@ -0,0 +1,379 @@
+//! Timeouts of checkers and of single files.
+//!
+//! With a timeout configured, every batch is checked in a worker thread, which
+//! is abandoned once it takes longer than the time left, i.e. if a remote
+//! LanguageTool server hangs. Once a checker exceeded its time, its remaining
+//! batches are skipped, and once the chunks of a file took too long, the
+//! remaining chunks of the file are. Either way, a warning tells what was
+//! skipped.
+
+use super::{Batch, Checker};
+use crate::config::TimeoutConfig;
+use crate::documentation::CheckableChunk;
+use crate::{Category, ContentOrigin, Detector, Range, Severity, Span, Suggestion, SuggestionSet};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The time left for a single run of a checker.
+pub(crate) struct Budget {
+    detector: Detector,
+    start: Instant,
+    checker: Option<Duration>,
+    file: Option<Duration>,
+    /// Time spent per file.
+    spent: Mutex<IndexMap<ContentOrigin, Duration>>,
+    /// Chunks skipped, since the checker ran out of time.
+    skipped: AtomicUsize,
+    /// Chunks skipped per file, since the file ran out of time.
+    skipped_per_file: Mutex<IndexMap<ContentOrigin, usize>>,
+    /// Chunks of the batches which did not finish in time.
+    abandoned: AtomicUsize,
+}
+
+impl Budget {
+    /// A budget with the configured timeouts.
+    pub(crate) fn new(detector: Detector, config: &TimeoutConfig) -> Self {
+        Self::with_limits(
+            detector,
+            config.checker.map(Duration::from_secs),
+            config.file.map(Duration::from_secs),
+        )
+    }
+
+    fn with_limits(detector: Detector, checker: Option<Duration>, file: Option<Duration>) -> Self {
+        Self {
+            detector,
+            start: Instant::now(),
+            checker,
+            file,
+            spent: Mutex::new(IndexMap::new()),
+            skipped: AtomicUsize::new(0),
+            skipped_per_file: Mutex::new(IndexMap::new()),
+            abandoned: AtomicUsize::new(0),
+        }
+    }
+
+    /// If any timeout is configured.
+    pub(crate) fn is_limited(&self) -> bool {
+        self.checker.is_some() || self.file.is_some()
+    }
+
+    /// The chunks of `batch` which are still to be checked, none if the
+    /// checker ran out of time.
+    pub(crate) fn retain<'s>(
+        &self,
+        batch: &Batch<'s>,
+    ) -> Vec<(&'s ContentOrigin, &'s CheckableChunk)> {
+        if matches!(self.checker, Some(checker) if self.start.elapsed() > checker) {
+            self.skipped.fetch_add(batch.len(), Ordering::Relaxed);
+            return Vec::new();
+        }
+        let file = match self.file {
+            Some(file) => file,
+            None => return batch.to_vec(),
+        };
+        let spent = self.spent.lock().expect("Lock is never poisoned. qed");
+        let mut skipped_per_file = self
+            .skipped_per_file
+            .lock()
+            .expect("Lock is never poisoned. qed");
+        batch
+            .iter()
+            .copied()
+            .filter(|(origin, _chunk)| {
+                let exceeded = matches!(spent.get(*origin), Some(spent) if *spent > file);
+                if exceeded {
+                    *skipped_per_file.entry((*origin).clone()).or_default() += 1;
+                }
+                !exceeded
+            })
+            .collect()
+    }
+
+    /// The time left for checking `batch`, the least of the checker and
+    /// of all files of the batch.
+    fn left(&self, batch: &Batch<'_>) -> Duration {
+        let checker = self
+            .checker
+            .map(|checker| checker.saturating_sub(self.start.elapsed()));
+        let spent = self.spent.lock().expect("Lock is never poisoned. qed");
+        let file = self.file.and_then(|file| {
+            batch
+                .iter()
+                .map(|(origin, _chunk)| {
+                    file.saturating_sub(spent.get(*origin).copied().unwrap_or_default())
+                })
+                .min()
+        });
+        checker
+            .into_iter()
+            .chain(file)
+            .min()
+            .unwrap_or(Duration::MAX)
+    }
+
+    /// Account `duration` spent on checking `batch` to its files,
+    /// proportional to the length of the chunks.
+    pub(crate) fn record(&self, batch: &Batch<'_>, duration: Duration) {
+        if self.file.is_none() {
+            return;
+        }
+        let mut spent = self.spent.lock().expect("Lock is never poisoned. qed");
+        for (origin, share) in super::batch_shares(batch, duration) {
+            *spent.entry(origin.clone()).or_default() += share;
+        }
+    }
+
+    /// Give up on `batch`, which took all the time left. Its files count as
+    /// timed out, since it can not be told which of them was the culprit.
+    fn abandon(&self, batch: &Batch<'_>) {
+        self.abandoned.fetch_add(batch.len(), Ordering::Relaxed);
+        if let Some(file) = self.file {
+            let mut spent = self.spent.lock().expect("Lock is never poisoned. qed");
+            for (origin, _chunk) in batch {
+                let spent = spent.entry((*origin).clone()).or_default();
+                *spent = (*spent).max(file + Duration::from_nanos(1));
+            }
+        }
+    }
+
+    /// Warn about everything that was skipped.
+    pub(crate) fn warn(&self) {
+        let abandoned = self.abandoned.load(Ordering::Relaxed);
+        if abandoned > 0 {
+            log::warn!(
+                "{} did not finish checking {} chunks in time, abandoned them",
+                self.detector,
+                abandoned
+            );
+        }
+        let skipped = self.skipped.load(Ordering::Relaxed);
+        if let (Some(checker), true) = (self.checker, skipped > 0) {
+            log::warn!(
+                "{} exceeded the timeout of {}s, skipped {} chunks",
+                self.detector,
+                checker.as_secs(),
+                skipped
+            );
+        }
+        let skipped_per_file = self
+            .skipped_per_file
+            .lock()
+            .expect("Lock is never poisoned. qed");
+        if let Some(file) = self.file {
+            for (origin, skipped) in skipped_per_file.iter() {
+                log::warn!(
+                    "{} exceeded the timeout of {}s for {}, skipped {} of its chunks",
+                    self.detector,
+                    file.as_secs(),
+                    origin.as_path().display(),
+                    skipped
+                );
+            }
+        }
+    }
+
+    /// The number of skipped chunks in total.
+    #[cfg(test)]
+    fn skipped(&self) -> usize {
+        self.skipped.load(Ordering::Relaxed)
+            + self.abandoned.load(Ordering::Relaxed)
+            + self
+                .skipped_per_file
+                .lock()
+                .expect("Lock is never poisoned. qed")
+                .values()
+                .sum::<usize>()
+    }
+}
+
+/// A suggestion without the chunk it was found in, which is only borrowed
+/// by the worker.
+struct Detached {
+    /// Position of the chunk in the batch.
+    index: usize,
+    detector: Detector,
+    severity: Severity,
+    category: Category,
+    rule: String,
+    origin: ContentOrigin,
+    span: Span,
+    range: Range,
+    replacements: Vec<String>,
+    description: Option<String>,
+    confidence: Option<u8>,
+}
+
+impl Detached {
+    fn attach<'s>(self, chunk: &'s CheckableChunk) -> Suggestion<'s> {
+        Suggestion {
+            detector: self.detector,
+            severity: self.severity,
+            category: self.category,
+            rule: self.rule,
+            origin: self.origin,
+            chunk,
+            span: self.span,
+            range: self.range,
+            replacements: self.replacements,
+            description: self.description,
+            confidence: self.confidence,
+        }
+    }
+}
+
+/// Check `batch` in a worker thread, and give up on it once it takes longer
+/// than the time left of the `budget`. The worker can not be stopped, it
+/// continues in the background and its findings are discarded.
+pub(crate) fn check_batch<'s, C>(
+    engine: &Arc<C::Engine>,
+    config: &Arc<C::Config>,
+    batch: &Batch<'s>,
+    budget: &Budget,
+) -> Result<SuggestionSet<'s>>
+where
+    C: Checker + 'static,
+    C::Engine: Send + 'static,
+    C::Config: Send + 'static,
+{
+    let owned = batch
+        .iter()
+        .map(|(origin, chunk)| ((*origin).clone(), (*chunk).clone()))
+        .collect::<Vec<_>>();
+    let engine = Arc::clone(engine);
+    let config = Arc::clone(config);
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let batch = owned
+            .iter()
+            .map(|(origin, chunk)| (origin, chunk))
+            .collect::<Vec<_>>();
+        let detached = C::check_batch(&engine, &config, &batch).map(|suggestions| {
+            suggestions
+                .into_iter()
+                .flat_map(|(_origin, suggestions)| suggestions)
+                .filter_map(|suggestion| {
+                    let index = batch
+                        .iter()
+                        .position(|(_origin, chunk)| std::ptr::eq(*chunk, suggestion.chunk))?;
+                    Some(Detached {
+                        index,
+                        detector: suggestion.detector,
+                        severity: suggestion.severity,
+                        category: suggestion.category,
+                        rule: suggestion.rule,
+                        origin: suggestion.origin,
+                        span: suggestion.span,
+                        range: suggestion.range,
+                        replacements: suggestion.replacements,
+                        description: suggestion.description,
+                        confidence: suggestion.confidence,
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+        // the receiver is gone if the batch was abandoned
+        let _ = tx.send(detached);
+    });
+
+    match rx.recv_timeout(budget.left(batch)) {
+        Ok(detached) => {
+            let mut acc = SuggestionSet::new();
+            for detached in detached? {
+                let (origin, chunk) = batch[detached.index];
+                acc.add(origin.clone(), detached.attach(chunk));
+            }
+            Ok(acc)
+        }
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            budget.abandon(batch);
+            Ok(SuggestionSet::new())
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            Err(anyhow::anyhow!("The {} worker panicked", budget.detector))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, Documentation};
+
+    #[test]
+    fn exceeded() {
+        let mut d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("one")));
+        d.join(Documentation::from((
+            ContentOrigin::TestEntityCommonMark,
+            "two\n\n```rust\n/// three\nstruct X;\n```",
+        )));
+        let batch = dummy::batch(&d);
+
+        let unlimited = Budget::with_limits(Detector::Dummy, None, None);
+        unlimited.record(&batch, Duration::from_secs(10));
+        assert_eq!(unlimited.retain(&batch).len(), batch.len());
+
+        let per_file = Budget::with_limits(Detector::Dummy, None, Some(Duration::from_secs(1)));
+        assert_eq!(per_file.retain(&batch).len(), batch.len());
+        per_file.record(&batch[..1], Duration::from_secs(2));
+        let retained = per_file.retain(&batch);
+        assert!(retained
+            .iter()
+            .all(|(origin, _chunk)| **origin == ContentOrigin::TestEntityCommonMark));
+        assert_eq!(per_file.skipped(), 1);
+
+        let checker = Budget::with_limits(Detector::Dummy, Some(Duration::ZERO), None);
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(checker.retain(&batch).is_empty());
+        assert_eq!(checker.skipped(), batch.len());
+    }
+
+    /// Never finishes a batch.
+    struct Hanging;
+
+    impl Checker for Hanging {
+        type Config = ();
+        type Engine = ();
+        const DETECTOR: Detector = Detector::Dummy;
+
+        fn init(_: &Self::Config) -> Result<Self::Engine> {
+            Ok(())
+        }
+
+        fn check_batch<'s>(
+            _: &Self::Engine,
+            _: &Self::Config,
+            _batch: &Batch<'s>,
+        ) -> Result<SuggestionSet<'s>> {
+            std::thread::sleep(Duration::from_secs(3600));
+            Ok(SuggestionSet::new())
+        }
+    }
+
+    #[test]
+    fn worker() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("one two")));
+        let batch = dummy::batch(&d);
+
+        let budget = Budget::with_limits(Detector::Dummy, Some(Duration::from_secs(60)), None);
+        let found =
+            check_batch::<dummy::DummyChecker>(&Arc::new(()), &Arc::new(()), &batch, &budget)
+                .unwrap();
+        assert_eq!(found.total_count(), 2);
+        let (_origin, suggestions) = found.iter().next().unwrap();
+        assert!(std::ptr::eq(suggestions[0].chunk, batch[0].1));
+
+        let budget = Budget::with_limits(Detector::Dummy, Some(Duration::from_millis(50)), None);
+        let start = Instant::now();
+        let found = check_batch::<Hanging>(&Arc::new(()), &Arc::new(()), &batch, &budget).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(60));
+        assert_eq!(found.total_count(), 0);
+        assert_eq!(budget.skipped(), batch.len());
+    }
+}
@@ -0,0 +1,272 @@
+//! Suppressions which did not suppress anything during a run.
+//!
+//! Only tracked if enabled with `--unused-suppressions`, and reported once
+//! the run completed, so stale entries can be removed before they hide real
+//! mistakes. Inline directives, disabled rules and ignored words are unused
+//! if they did not drop a single finding, words of the extra dictionaries if
+//! they do not occur in any checked chunk.
+
+use super::tokenize;
+use crate::config::IgnoreConfig;
+use crate::util::sub_chars;
+use crate::{Config, Documentation};
+
+use anyhow::{anyhow, Result};
+use indexmap::IndexSet;
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// A single entry which suppresses findings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Suppression {
+    /// An inline `allow` directive for `rule`.
+    Inline {
+        path: PathBuf,
+        line: usize,
+        rule: String,
+    },
+    /// An entry of `disabled_rules`.
+    DisabledRule(String),
+    /// A word of the ignore file.
+    IgnoredWord(String),
+    /// An identifier of the ignore file.
+    IgnoredIdentifier(String),
+    /// A word of an extra dictionary.
+    DictionaryWord { dictionary: PathBuf, word: String },
+}
+
+impl fmt::Display for Suppression {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inline { path, line, rule } => {
+                write!(formatter, "allow({}) at {}:{}", rule, path.display(), line)
+            }
+            Self::DisabledRule(rule) => write!(formatter, "disabled rule `{}`", rule),
+            Self::IgnoredWord(word) => write!(formatter, "ignored word `{}`", word),
+            Self::IgnoredIdentifier(identifier) => {
+                write!(formatter, "ignored identifier `{}`", identifier)
+            }
+            Self::DictionaryWord { dictionary, word } => {
+                write!(formatter, "word `{}` of {}", word, dictionary.display())
+            }
+        }
+    }
+}
+
+/// The words of a hunspell dictionary, without the leading count and the
+/// affix flags.
+fn dictionary_words(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read dictionary {}", path.display()).context(e))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.chars().all(|c| c.is_ascii_digit()))
+        .map(|line| line.split('/').next().unwrap_or(line).to_owned())
+        .collect())
+}
+
+/// All defined suppressions and the ones which suppressed something.
+#[derive(Debug, Default)]
+struct Tracker {
+    defined: Mutex<IndexSet<Suppression>>,
+    used: Mutex<HashSet<Suppression>>,
+}
+
+impl Tracker {
+    fn define(&self, suppression: Suppression) {
+        self.defined
+            .lock()
+            .expect("Lock is never poisoned. qed")
+            .insert(suppression);
+    }
+
+    fn used(&self, suppression: Suppression) {
+        self.used
+            .lock()
+            .expect("Lock is never poisoned. qed")
+            .insert(suppression);
+    }
+
+    /// Define all suppressions which apply to `documentation`, and mark the
+    /// dictionary words occurring in it as used.
+    fn define_all(&self, documentation: &Documentation, config: &Config) {
+        let mut tokens = HashSet::new();
+        for (origin, chunks) in documentation.iter() {
+            for chunk in chunks {
+                super::allow::inline(origin, chunk)
+                    .into_iter()
+                    .for_each(|suppression| self.define(suppression));
+                let plain = chunk.erase_cmark();
+                tokens.extend(
+                    tokenize(plain.as_str())
+                        .into_iter()
+                        .map(|range| sub_chars(plain.as_str(), range).to_lowercase()),
+                );
+            }
+        }
+        for rule in config.disabled_rules.iter() {
+            self.define(Suppression::DisabledRule(rule.clone()));
+        }
+        for word in config.ignore.words.iter() {
+            self.define(Suppression::IgnoredWord(word.clone()));
+        }
+        for identifier in config.ignore.identifiers.iter() {
+            self.define(Suppression::IgnoredIdentifier(identifier.clone()));
+        }
+        // the personal dictionary is shared by all projects, so it is not checked
+        let dictionaries = config
+            .hunspell
+            .iter()
+            .flat_map(|hunspell| hunspell.extra_dictionaries.iter());
+        for dictionary in dictionaries {
+            let words = match dictionary_words(dictionary) {
+                Ok(words) => words,
+                Err(e) => {
+                    log::debug!("Not tracking dictionary: {:?}", e);
+                    continue;
+                }
+            };
+            for word in words {
+                let occurs = tokens.contains(&word.to_lowercase());
+                let suppression = Suppression::DictionaryWord {
+                    dictionary: dictionary.clone(),
+                    word,
+                };
+                if occurs {
+                    self.used(suppression.clone());
+                }
+                self.define(suppression);
+            }
+        }
+    }
+
+    /// The defined suppressions which were never used, in order of definition.
+    fn unused(&self) -> Vec<Suppression> {
+        let defined = self.defined.lock().expect("Lock is never poisoned. qed");
+        let used = self.used.lock().expect("Lock is never poisoned. qed");
+        defined
+            .iter()
+            .filter(|suppression| !used.contains(*suppression))
+            .cloned()
+            .collect()
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    static ref TRACKER: Tracker = Tracker::default();
+}
+
+/// Start tracking for the remainder of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// If tracking was enabled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Define all suppressions which apply to `documentation`, if enabled.
+pub(crate) fn define(documentation: &Documentation, config: &Config) {
+    if is_enabled() {
+        TRACKER.define_all(documentation, config);
+    }
+}
+
+/// Mark `suppression` as used, if enabled.
+pub(crate) fn used(suppression: Suppression) {
+    if is_enabled() {
+        TRACKER.used(suppression);
+    }
+}
+
+/// Mark the entries of `ignore` matching `word` as used, if enabled.
+pub(crate) fn ignored(ignore: &IgnoreConfig, word: &str) {
+    if !is_enabled() {
+        return;
+    }
+    for identifier in ignore
+        .identifiers
+        .iter()
+        .filter(|identifier| *identifier == word)
+    {
+        TRACKER.used(Suppression::IgnoredIdentifier(identifier.clone()));
+    }
+    for ignored in ignore
+        .words
+        .iter()
+        .filter(|ignored| ignored.to_lowercase() == word.to_lowercase())
+    {
+        TRACKER.used(Suppression::IgnoredWord(ignored.clone()));
+    }
+}
+
+/// Report all suppressions which were never used.
+pub fn report<W: Write>(mut sink: W) -> Result<()> {
+    let unused = TRACKER.unused();
+    if unused.is_empty() {
+        return Ok(());
+    }
+    writeln!(sink, "Unused suppressions:")?;
+    for suppression in unused {
+        writeln!(sink, "  {}", suppression)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fluff_up, ContentOrigin};
+
+    #[test]
+    fn tracked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dictionary = tmp.path().join("unused.dic");
+        std::fs::write(&dictionary, "2\ntokio\nunheardof/S\n").unwrap();
+
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!("Use tokio.", "<!-- spellcheck: allow(hunspell::symbol) -->"),
+        ));
+        let mut config = Config::parse(
+            r#"
+disabled_rules = ["terminology::disfavored-term"]
+[Hunspell]
+"#,
+        )
+        .unwrap();
+        config.hunspell.as_mut().unwrap().extra_dictionaries = vec![dictionary.clone()];
+        config.ignore = IgnoreConfig::parse(r#"words = ["Serde"]"#).unwrap();
+
+        let tracker = Tracker::default();
+        tracker.define_all(&d, &config);
+        tracker.used(Suppression::DisabledRule(
+            "terminology::disfavored-term".to_owned(),
+        ));
+
+        assert_eq!(
+            tracker.unused(),
+            vec![
+                Suppression::Inline {
+                    path: PathBuf::from("/tmp/test/entity.rs"),
+                    line: 2,
+                    rule: "hunspell::symbol".to_owned(),
+                },
+                Suppression::IgnoredWord("Serde".to_owned()),
+                Suppression::DictionaryWord {
+                    dictionary,
+                    word: "unheardof".to_owned(),
+                },
+            ]
+        );
+    }
+}
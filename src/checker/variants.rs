@@ -0,0 +1,385 @@
+//! Spelling variants of American and British English.
+//!
+//! Dictionaries commonly accept both variants of a word, so with
+//! `enforce_locale` every word only valid in the other variant than the one
+//! of the configured `lang` is reported, with the locale correct replacement.
+
+use super::{tokenize, Batch, Category, Checker, Detector, Suggestion, SuggestionSet};
+use crate::config::HunspellConfig;
+use crate::util::{match_case, sub_chars};
+
+use anyhow::{bail, Result};
+use log::warn;
+
+use std::collections::HashMap;
+
+/// The spelling variant of a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Variant {
+    American,
+    British,
+}
+
+impl Variant {
+    /// The variant used by `lang`, i.e. `en_US`, `None` if the locale is not
+    /// English or, like `en_CA`, mixes both variants.
+    pub(crate) fn from_lang(lang: &str) -> Option<Self> {
+        let lang = lang.replace('-', "_").to_lowercase();
+        match lang.as_str() {
+            "en_us" | "en_ph" => Some(Self::American),
+            // `colour` and `centre`, but `organize` and `analyze`
+            "en_ca" => None,
+            // the commonwealth follows the british spelling
+            lang if lang.starts_with("en_") => Some(Self::British),
+            _ => None,
+        }
+    }
+
+    /// If `lang` is English, but uses both variants, so there is nothing to
+    /// enforce.
+    fn is_mixed(lang: &str) -> bool {
+        lang.replace('-', "_").eq_ignore_ascii_case("en_ca")
+    }
+}
+
+/// Words with differing spelling, `(american, british)`, which are inflected
+/// with the regular suffixes.
+const INFLECTED: &[(&str, &str)] = &[
+    ("analyze", "analyse"),
+    ("apologize", "apologise"),
+    ("armor", "armour"),
+    ("authorize", "authorise"),
+    ("behavior", "behaviour"),
+    ("categorize", "categorise"),
+    ("center", "centre"),
+    ("color", "colour"),
+    ("customize", "customise"),
+    ("favor", "favour"),
+    ("fiber", "fibre"),
+    ("finalize", "finalise"),
+    ("flavor", "flavour"),
+    ("honor", "honour"),
+    ("initialize", "initialise"),
+    ("labor", "labour"),
+    ("liter", "litre"),
+    ("materialize", "materialise"),
+    ("maximize", "maximise"),
+    ("minimize", "minimise"),
+    ("neighbor", "neighbour"),
+    ("normalize", "normalise"),
+    ("optimize", "optimise"),
+    ("organize", "organise"),
+    ("parallelize", "parallelise"),
+    ("prioritize", "prioritise"),
+    ("realize", "realise"),
+    ("recognize", "recognise"),
+    ("rumor", "rumour"),
+    ("serialize", "serialise"),
+    ("specialize", "specialise"),
+    ("standardize", "standardise"),
+    ("summarize", "summarise"),
+    ("synchronize", "synchronise"),
+    ("theater", "theatre"),
+    ("utilize", "utilise"),
+    ("visualize", "visualise"),
+];
+
+/// Words with differing spelling, `(american, british)`, which are only
+/// valid as is.
+const EXACT: &[(&str, &str)] = &[
+    ("aluminum", "aluminium"),
+    ("canceled", "cancelled"),
+    ("canceling", "cancelling"),
+    ("catalog", "catalogue"),
+    ("catalogs", "catalogues"),
+    ("defense", "defence"),
+    ("gray", "grey"),
+    ("judgment", "judgement"),
+    ("labeled", "labelled"),
+    ("labeling", "labelling"),
+    ("modeled", "modelled"),
+    ("modeling", "modelling"),
+    ("offense", "offence"),
+    ("signaled", "signalled"),
+    ("signaling", "signalling"),
+    ("traveled", "travelled"),
+    ("traveler", "traveller"),
+    ("travelers", "travellers"),
+    ("traveling", "travelling"),
+];
+
+/// The regular inflections of `word`, in a fixed order, so the nth
+/// inflection of either variant of a word corresponds to the other.
+fn inflect(word: &str) -> Vec<String> {
+    let (stem, silent_e) = match word.strip_suffix('e') {
+        Some(stem) => (stem, true),
+        None => (word, false),
+    };
+    let mut inflections = vec![word.to_owned(), format!("{}s", word)];
+    if silent_e {
+        inflections.push(format!("{}d", word));
+    } else {
+        inflections.push(format!("{}ed", word));
+    }
+    inflections.push(format!("{}ing", stem));
+    if let Some(stem) = word
+        .strip_suffix("ize")
+        .or_else(|| word.strip_suffix("ise"))
+    {
+        let suffix = &word[stem.len()..stem.len() + 2];
+        inflections.push(format!("{}{}ation", stem, suffix));
+        inflections.push(format!("{}{}ations", stem, suffix));
+        inflections.push(format!("{}r", word));
+        inflections.push(format!("{}rs", word));
+    }
+    inflections
+}
+
+/// A word spelled in one of the variants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Spelling {
+    /// Identifies the word regardless of variant and inflection, by its
+    /// uninflected american spelling.
+    pub(crate) word: &'static str,
+    /// The variant the word is spelled in.
+    pub(crate) variant: Variant,
+    /// The same inflection of the word in the other variant, lowercase.
+    pub(crate) other: String,
+}
+
+lazy_static::lazy_static! {
+    /// All inflected forms of either variant, lowercase.
+    static ref VARIANTS: HashMap<String, Spelling> = {
+        let mut variants = HashMap::new();
+        let exact = EXACT.iter().map(|&(american, british)| {
+            (american, american.to_owned(), british.to_owned())
+        });
+        let inflected = INFLECTED.iter().flat_map(|&(american, british)| {
+            inflect(american)
+                .into_iter()
+                .zip(inflect(british))
+                .map(move |(inflected_american, inflected_british)| {
+                    (american, inflected_american, inflected_british)
+                })
+        });
+        for (word, american, british) in exact.chain(inflected) {
+            variants.insert(
+                american.clone(),
+                Spelling {
+                    word,
+                    variant: Variant::American,
+                    other: british.clone(),
+                },
+            );
+            variants.insert(
+                british,
+                Spelling {
+                    word,
+                    variant: Variant::British,
+                    other: american,
+                },
+            );
+        }
+        variants
+    };
+}
+
+/// The word and variant `word` is spelled in, if it is spelled differently
+/// in the other variant.
+pub(crate) fn spelling(word: &str) -> Option<&'static Spelling> {
+    VARIANTS.get(&word.to_lowercase())
+}
+
+/// The spelling of `word` in `variant`, if `word` is spelled the way of the
+/// other variant.
+pub(crate) fn replacement(word: &str, variant: Variant) -> Option<String> {
+    spelling(word)
+        .filter(|spelling| spelling.variant != variant)
+        .map(|spelling| match_case(word, &spelling.other))
+}
+
+/// Drop the dictionary findings at all spans `locale` reports as well.
+///
+/// A dictionary of one variant usually rejects the words of the other one, so
+/// both flag the very same word, but only `locale` has the one locale correct
+/// replacement.
+pub(crate) fn supersede(collective: &mut SuggestionSet<'_>, locale: &SuggestionSet<'_>) {
+    let spans = locale
+        .iter()
+        .map(|(origin, found)| {
+            let spans = found
+                .iter()
+                .map(|suggestion| suggestion.span)
+                .collect::<Vec<_>>();
+            (origin.clone(), spans)
+        })
+        .collect::<HashMap<_, _>>();
+    collective.retain(|origin, suggestion| {
+        suggestion.detector != Detector::Hunspell
+            || !spans
+                .get(origin)
+                .map_or(false, |spans| spans.contains(&suggestion.span))
+    });
+}
+
+/// Reports words spelled the way of the other locale than the configured one.
+pub(crate) struct LocaleChecker;
+
+impl Checker for LocaleChecker {
+    type Config = HunspellConfig;
+    /// `None` if the locale uses both variants.
+    type Engine = Option<Variant>;
+    const DETECTOR: Detector = Detector::Hunspell;
+
+    /// Table lookups are cheap, so the per batch overhead is negligible.
+    const BATCH_SIZE: usize = 256;
+
+    fn init(config: &Self::Config) -> Result<Self::Engine> {
+        match Variant::from_lang(config.lang()) {
+            Some(variant) => Ok(Some(variant)),
+            None if Variant::is_mixed(config.lang()) => {
+                warn!(
+                    "`{}` uses both the american and the british spelling, not enforcing the locale",
+                    config.lang()
+                );
+                Ok(None)
+            }
+            None => bail!(
+                "Enforcing the locale requires an english `lang`, i.e. `en_US` or `en_GB`, not `{}`",
+                config.lang()
+            ),
+        }
+    }
+
+    fn check_batch<'s>(
+        engine: &Self::Engine,
+        config: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>> {
+        let mut acc = SuggestionSet::new();
+        let variant = match *engine {
+            Some(variant) => variant,
+            None => return Ok(acc),
+        };
+        for &(origin, chunk) in batch {
+            let plain = chunk.erase_cmark();
+            let txt = plain.as_str();
+            for range in tokenize(txt) {
+                let word = sub_chars(txt, range.clone());
+                let replacement = match replacement(&word, variant) {
+                    Some(replacement) => replacement,
+                    None => continue,
+                };
+                for (range, span) in plain.find_spans(range) {
+                    acc.add(
+                        origin.clone(),
+                        Suggestion {
+                            detector: Detector::Hunspell,
+                            severity: config.severity,
+                            category: Category::Spelling,
+                            rule: "other-locale".to_owned(),
+                            range,
+                            span,
+                            origin: origin.clone(),
+                            replacements: vec![replacement.clone()],
+                            chunk,
+                            description: Some(format!(
+                                "Spelling of the other locale than `{}`.",
+                                config.lang()
+                            )),
+                            confidence: None,
+                        },
+                    );
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+
+    #[test]
+    fn lang() {
+        assert_eq!(Variant::from_lang("en_US"), Some(Variant::American));
+        assert_eq!(Variant::from_lang("en-GB"), Some(Variant::British));
+        assert_eq!(Variant::from_lang("en_AU"), Some(Variant::British));
+        assert_eq!(Variant::from_lang("en_CA"), None);
+        assert_eq!(Variant::from_lang("de_DE"), None);
+
+        let mut config = crate::Config::default().hunspell.unwrap();
+        config.lang = Some("en-CA".to_owned());
+        assert_eq!(LocaleChecker::init(&config).unwrap(), None);
+        config.lang = Some("de_DE".to_owned());
+        assert!(LocaleChecker::init(&config).is_err());
+    }
+
+    #[test]
+    fn replacements() {
+        assert_eq!(
+            replacement("Colours", Variant::American),
+            Some("Colors".to_owned())
+        );
+        assert_eq!(replacement("colors", Variant::American), None);
+        assert_eq!(
+            replacement("initialized", Variant::British),
+            Some("initialised".to_owned())
+        );
+        assert_eq!(
+            replacement("initialising", Variant::American),
+            Some("initializing".to_owned())
+        );
+        assert_eq!(
+            replacement("serialization", Variant::British),
+            Some("serialisation".to_owned())
+        );
+        assert_eq!(
+            replacement("centred", Variant::American),
+            Some("centered".to_owned())
+        );
+        assert_eq!(
+            replacement("gray", Variant::British),
+            Some("grey".to_owned())
+        );
+        assert_eq!(replacement("license", Variant::British), None);
+    }
+
+    #[test]
+    fn check() {
+        use crate::{fluff_up, ContentOrigin, Documentation};
+
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!("Normalises the colour of the `colour` value."),
+        ));
+        let batch = dummy::batch(&d);
+        let config = crate::Config::default().hunspell.unwrap();
+        let variant = LocaleChecker::init(&config).unwrap();
+        let suggestions = LocaleChecker::check_batch(&variant, &config, &batch).unwrap();
+        let replacements = suggestions
+            .iter()
+            .flat_map(|(_origin, suggestions)| suggestions.iter())
+            .map(|suggestion| suggestion.replacements.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            replacements,
+            vec![vec!["Normalizes".to_owned()], vec!["color".to_owned()]]
+        );
+
+        // the dictionary lookup flags the same word
+        let mut collective = SuggestionSet::new();
+        for (origin, found) in suggestions.iter() {
+            for suggestion in found {
+                let mut rejected = suggestion.clone();
+                rejected.rule = "misspelled".to_owned();
+                rejected.replacements = vec!["cooler".to_owned()];
+                collective.add(origin.clone(), rejected);
+            }
+        }
+        supersede(&mut collective, &suggestions);
+        assert_eq!(collective.total_count(), 0);
+    }
+}
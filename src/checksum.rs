@@ -0,0 +1,62 @@
+//! Content hashes of all checked files, to detect modifications between
+//! checking a file and applying fixes to it.
+//!
+//! Fixes are located by line and column, applying them to a file that changed
+//! in the meantime would patch the wrong locations.
+
+use anyhow::{bail, Result};
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static::lazy_static! {
+    static ref CHECKSUMS: Mutex<HashMap<PathBuf, u64>> = Mutex::new(HashMap::new());
+}
+
+/// Calculate the hash of a file's content.
+fn checksum(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Paths are compared canonicalized, if possible.
+fn key(path: &Path) -> PathBuf {
+    crate::util::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Record the `content` of the file at `path`, as it was checked.
+pub fn record(path: &Path, content: &str) {
+    let mut checksums = CHECKSUMS.lock().expect("Lock is never poisoned. qed");
+    checksums.insert(key(path), checksum(content));
+}
+
+/// Verify the current `content` of the file at `path` is the checked one,
+/// files which were never checked pass.
+pub fn verify(path: &Path, content: &str) -> Result<()> {
+    let checksums = CHECKSUMS.lock().expect("Lock is never poisoned. qed");
+    match checksums.get(&key(path)) {
+        Some(recorded) if *recorded != checksum(content) => bail!(
+            "{} was modified since it was checked, refusing to apply fixes to it",
+            path.display()
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modified() {
+        let path = Path::new("/tmp/checksum/modified.rs");
+        record(path, "/// A literl\n");
+        assert!(verify(path, "/// A literl\n").is_ok());
+        assert!(verify(path, "/// A literl\n/// and more\n").is_err());
+        assert!(verify(Path::new("/tmp/checksum/unchecked.rs"), "").is_ok());
+    }
+}
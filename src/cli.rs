@@ -0,0 +1,851 @@
+//! The command line interface of the `cargo spellcheck` binary.
+
+use crate::{
+    absolute, action, canonicalize, checker, commit_msg, completions, config, explain, import, man,
+    output, reflow, strip_path_prefix, timings, traverse, Action, Config, Documentation, Finish,
+    SuggestionSet,
+};
+
+use docopt::Docopt;
+
+use log::{debug, info, trace, warn};
+use serde::Deserialize;
+
+#[cfg(not(target_os = "windows"))]
+use signal_hook::{iterator, SIGINT, SIGQUIT, SIGTERM};
+
+use checker::Checker;
+use std::path::PathBuf;
+
+/// Docopt usage string.
+pub(crate) const USAGE: &str = r#"
+Spellcheck all your doc comments
+
+Usage:
+    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>] [--code=<code>] [--fix-safe] [--format=<format>] [--column-encoding=<encoding>] [--sort-by=<key>] [--group-by=<key>] [--report=<report>...] [--skip-readme] [--checkers=<checkers>] [--min-confidence=<percent>] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [--follow-symlinks] [--only-public] [--targets=<targets>] [--fast] [--workspace | --package=<name>...] [--staged] [--extra-src-dir=<dir>...] [--timings] [--unused-suppressions] [--debug-checker=<name>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [--code=<code>] [--accept-unambiguous] [--by-word] [--skip-readme] [--checkers=<checkers>] [--min-confidence=<percent>] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [--follow-symlinks] [--only-public] [--targets=<targets>] [--fast] [--workspace | --package=<name>...] [--staged] [--extra-src-dir=<dir>...] [--timings] [--unused-suppressions] [--debug-checker=<name>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] reflow [--cfg=<cfg>] [--code=<code>] [--skip-readme] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [--follow-symlinks] [--only-public] [--targets=<targets>] [--fast] [--workspace | --package=<name>...] [--staged] [--extra-src-dir=<dir>...] [--timings] [--debug-checker=<name>] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] list-files [--cfg=<cfg>] [--skip-readme] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [--follow-symlinks] [--only-public] [--targets=<targets>] [--workspace | --package=<name>...] [--staged] [--extra-src-dir=<dir>...] [[--recursive] <paths>... ]
+    cargo-spellcheck [(-v...|-q)] explain [--cfg=<cfg>] [--checkers=<checkers>] <subject>
+    cargo-spellcheck [(-v...|-q)] commit-msg [--cfg=<cfg>] [--code=<code>] [--format=<format>] [--checkers=<checkers>] <file>
+    cargo-spellcheck [(-v...|-q)] config (--user|--stdout|--cfg=<cfg>) [--force]
+    cargo-spellcheck [(-v...|-q)] import [--output=<dic>] [--force] <paths>...
+    cargo-spellcheck [(-v...|-q)] completions <shell>
+    cargo-spellcheck [(-v...|-q)] [--cfg=<cfg>] [--fix | --fix-safe] [--accept-unambiguous] [--by-word] [--code=<code>] [--format=<format>] [--column-encoding=<encoding>] [--sort-by=<key>] [--group-by=<key>] [--report=<report>...] [--skip-readme] [--checkers=<checkers>] [--min-confidence=<percent>] [--include=<glob>...] [--exclude=<glob>...] [--no-ignore] [--follow-symlinks] [--only-public] [--targets=<targets>] [--fast] [--workspace | --package=<name>...] [--staged] [--extra-src-dir=<dir>...] [--timings] [--unused-suppressions] [--debug-checker=<name>] [[--recursive] <paths>... ]
+    cargo-spellcheck --help
+    cargo-spellcheck --version
+
+Options:
+  -h --help                 Show this screen.
+  --version                 Print the version and exit.
+
+  --fix                     Interactively apply spelling and grammer fixes, synonym to `fix` sub-command.
+  --fix-safe                Apply doubled word removals and corrections of well known typos without asking, report all other mistakes.
+  --accept-unambiguous      Apply suggestions with a single replacement without asking, only prompt for the others.
+  --by-word                 Pick a replacement once per misspelled word for all of its occurrences, instead of per occurrence.
+  -r --recursive            If a path is provided, if recursion into subdirectories is desired.
+  --checkers=<checkers>     Calculate the intersection between
+                            configured by config file and the ones provided on commandline.
+  --min-confidence=<percent>  Only report findings the checkers are at least this many percent certain of.
+  -f --force                Overwrite any existing configuration file. [default=false]
+  -c --cfg=<cfg>            Use a non default configuration file.
+                            Passing a directory will attempt to open `cargo_spellcheck.toml` in that directory.
+  --user                    Write the configuration file to the default user configuration directory.
+  --stdout                  Print the configuration file to stdout and exit.
+  --output=<dic>            Write the words imported from cspell, codespell or Vale files to this dictionary instead of stdout.
+  -v --verbose              Verbosity level.
+  -q --quiet                Silences all printed messages. Overrules `-v`.
+  -m --code=<code>          Overwrite the exit value for a run with content mistakes found, `1` by default. Invalid
+                            arguments or configuration exit with `64`, any other failure with `70`.
+                            For `commit-msg`, any value but `0` is used, otherwise `1`.
+  --format=<format>         Output format of the found mistakes, one of `human`, `grouped`, `json`, `csv`, `tap` or `rustc`.
+  --column-encoding=<encoding>  Count the columns of the `json` and `csv` output in `chars`, `utf-8` bytes or `utf-16` code units.
+  --sort-by=<key>           Order the found mistakes by `file`, `word` or `severity`, within each group.
+  --group-by=<key>          Cluster the found mistakes by `file`, `word` or `severity`, each under a heading.
+  --report=<report>         Also write a report of the found mistakes as `<kind>=<path>`, i.e. `html=spellcheck.html`.
+  --skip-readme             Do not attempt to process README.md files listed in Cargo.toml manifests.
+  --include=<glob>          Only check files matching the glob pattern, in addition to the `include` config entries.
+  --exclude=<glob>          Skip files matching the glob pattern, in addition to the `exclude` config entries.
+  --no-ignore               Also check files ignored by `.gitignore` and `.ignore` files.
+  --follow-symlinks         Follow symlinked files and directories found during traversal.
+  --only-public             Only check the documentation of `pub` items reachable from the crate root.
+  --targets=<targets>       Also check these targets of the manifests, comma separated `tests`, `examples`, `benches` and `build`, or `all` or `none`.
+  --fast                    Extract `///` and `//!` comments and `mod` declarations by scanning lines instead of parsing, faster but less accurate, not combinable with `--only-public`.
+  --workspace               Check all packages of the workspace, including their readme files.
+  -p --package=<name>       Only check the given packages of the workspace.
+  --extra-src-dir=<dir>     Also check all `.rs` files in the directory, i.e. generated code.
+  --timings                 Report the time spent per stage, checker and file.
+  --unused-suppressions     Report inline allow directives, disabled rules, ignored and dictionary words which suppressed nothing.
+  --debug-checker=<name>    Dump the chunks passed to the checker and its findings, with their mapping to the source.
+  --staged                  Only check files staged in git, the given paths or all staged files if none are given. `check` reads their staged content.
+"#;
+
+/// A simple exit code representation.
+///
+/// `Custom` can be specified by the user, others map to thei unix equivalents
+/// where available.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ExitCode {
+    /// Regular termination and does not imply anything in regards to spelling
+    /// mistakes found or not.
+    Success,
+    /// Terminate requested by a *nix signal.
+    Signal,
+    /// Mistakes were found, `1` unless specified with `--code=<code>`.
+    Custom(u8),
+    /// Invalid commandline arguments or configuration, `EX_USAGE`.
+    BadUsage,
+    /// Any failure other than the mistakes found, `EX_SOFTWARE`.
+    InternalError,
+}
+
+impl ExitCode {
+    fn as_u8(&self) -> u8 {
+        match *self {
+            Self::Success => 0u8,
+            Self::Signal => 130u8,
+            Self::Custom(code) => code,
+            Self::BadUsage => 64u8,
+            Self::InternalError => 70u8,
+        }
+    }
+
+    /// The exit code of a failed run, see `UsageError`.
+    fn of(e: &anyhow::Error) -> Self {
+        if e.downcast_ref::<UsageError>().is_some() {
+            Self::BadUsage
+        } else {
+            Self::InternalError
+        }
+    }
+}
+
+/// Marks an error as caused by invalid arguments or configuration, which exits
+/// with `EX_USAGE` rather than `EX_SOFTWARE`.
+#[derive(Debug)]
+struct UsageError;
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid arguments or configuration")
+    }
+}
+
+/// Attach `UsageError` to the failure of parsing or validating an argument.
+fn usage<T>(result: anyhow::Result<T>) -> anyhow::Result<T> {
+    result.map_err(|e| e.context(UsageError))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct Args {
+    arg_paths: Vec<PathBuf>,
+    flag_fix: bool,
+    flag_accept_unambiguous: bool,
+    flag_by_word: bool,
+    flag_fix_safe: bool,
+    flag_recursive: bool,
+    flag_verbose: usize,
+    flag_quiet: bool,
+    flag_version: bool,
+    flag_help: bool,
+    flag_checkers: Option<String>,
+    flag_min_confidence: Option<u8>,
+    flag_cfg: Option<PathBuf>,
+    flag_force: bool,
+    flag_user: bool,
+    flag_skip_readme: bool,
+    flag_include: Vec<String>,
+    flag_exclude: Vec<String>,
+    flag_no_ignore: bool,
+    flag_follow_symlinks: bool,
+    flag_only_public: bool,
+    flag_targets: Option<String>,
+    flag_fast: bool,
+    flag_workspace: bool,
+    flag_package: Vec<String>,
+    flag_staged: bool,
+    flag_extra_src_dir: Vec<PathBuf>,
+    flag_timings: bool,
+    flag_unused_suppressions: bool,
+    flag_debug_checker: Option<String>,
+    flag_code: Option<u8>,
+    flag_format: Option<String>,
+    flag_column_encoding: Option<String>,
+    flag_sort_by: Option<String>,
+    flag_group_by: Option<String>,
+    flag_report: Vec<String>,
+    flag_stdout: bool,
+    flag_output: Option<PathBuf>,
+    cmd_fix: bool,
+    cmd_reflow: bool,
+    cmd_config: bool,
+    cmd_import: bool,
+    cmd_completions: bool,
+    cmd_list_files: bool,
+    cmd_explain: bool,
+    cmd_commit_msg: bool,
+    arg_shell: String,
+    arg_file: PathBuf,
+    arg_subject: String,
+}
+
+/// Handle incoming signals.
+///
+/// Only relevant for *-nix platforms.
+#[cfg(not(target_os = "windows"))]
+fn signal_handler() {
+    let signals =
+        iterator::Signals::new(vec![SIGTERM, SIGINT, SIGQUIT]).expect("Failed to create Signals");
+    for s in signals.forever() {
+        match s {
+            SIGTERM | SIGINT | SIGQUIT => {
+                // complete the file in flight, but do not touch any other
+                action::abandon_pending_writes();
+                if let Err(e) = action::interactive::ScopedRaw::restore_terminal() {
+                    warn!("Failed to restore terminal: {}", e);
+                }
+                std::process::exit(130);
+            }
+            sig => warn!("Received unhandled signal {}, ignoring", sig),
+        }
+    }
+}
+
+/// Agjust the raw arguments for call variants.
+///
+/// The program could be called like `cargo-spellcheck`, `cargo spellcheck` or
+/// `cargo spellcheck check` and even ``cargo-spellcheck check`.
+fn normalize_args(mut argv_iter: impl Iterator<Item = String>) -> Vec<String> {
+    // if ends with file name `cargo-spellcheck`, split
+    if let Some(arg0) = argv_iter.next() {
+        match PathBuf::from(&arg0)
+            .file_name()
+            .map(|x| x.to_str())
+            .flatten()
+        {
+            Some(file_name) => {
+                // allow all variants
+                // cargo spellcheck ...
+                // cargo-spellcheck ...
+                // cargo-spellcheck spellcheck ...
+                let mut next = vec!["cargo-spellcheck".to_owned()];
+
+                match argv_iter.next() {
+                    Some(arg)
+                        if file_name.starts_with("cargo-spellcheck") && arg == "spellcheck" => {}
+                    Some(arg) => next.push(arg.to_owned()),
+                    _ => {}
+                };
+                next.into_iter().chain(argv_iter).collect()
+            }
+            _ => std::iter::once(arg0).chain(argv_iter).collect(),
+        }
+    } else {
+        Vec::new()
+    }
+}
+
+/// Parse the arguments, after adjusting them with `normalize_args`.
+fn parse_args(argv_iter: impl Iterator<Item = String>) -> Result<Args, docopt::Error> {
+    let argv = normalize_args(argv_iter);
+    Docopt::new(USAGE).and_then(|d| {
+        if argv.is_empty() {
+            d
+        } else {
+            d.argv(argv.into_iter())
+        }
+        .deserialize()
+    })
+}
+
+/// The inner main.
+fn run() -> anyhow::Result<ExitCode> {
+    #[cfg(debug_assertions)]
+    let _ = ::rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build_global();
+
+    // hidden from the usage, renders the man page at release time
+    if normalize_args(std::env::args()).get(1).map(String::as_str) == Some("gen-man") {
+        man::generate(USAGE, &mut std::io::stdout().lock())?;
+        return Ok(ExitCode::Success);
+    }
+
+    let args = match parse_args(std::env::args()) {
+        Ok(args) => args,
+        Err(e) if e.fatal() => {
+            eprintln!("{}", e);
+            return Ok(ExitCode::BadUsage);
+        }
+        // help and version
+        Err(e) => e.exit(),
+    };
+
+    let verbosity = match args.flag_verbose {
+        _ if args.flag_quiet => log::LevelFilter::Off,
+        n if n > 4 => log::LevelFilter::Trace,
+        4 => log::LevelFilter::Debug,
+        3 => log::LevelFilter::Info,
+        2 => log::LevelFilter::Warn,
+        _ => log::LevelFilter::Error,
+    };
+
+    env_logger::Builder::from_env(env_logger::Env::new().filter_or("CARGO_SPELLCHECK", "warn"))
+        .filter_level(verbosity)
+        .init();
+
+    if args.flag_version {
+        println!("cargo-spellcheck {}", env!("CARGO_PKG_VERSION"));
+        return Ok(ExitCode::Success);
+    }
+
+    if args.flag_help {
+        println!("{}", USAGE);
+        return Ok(ExitCode::Success);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    let _signalthread = std::thread::spawn(move || signal_handler());
+
+    let checkers = |config: &mut Config| {
+        // overwrite checkers
+        if let Some(checkers) = args.flag_checkers.clone() {
+            let checkers = checkers
+                .split(',')
+                .map(|checker| checker.to_lowercase())
+                .collect::<Vec<_>>();
+            if !checkers.contains(&"hunspell".to_owned()) {
+                if !config.hunspell.take().is_some() {
+                    warn!("Hunspell was never configured.")
+                }
+            }
+            if !checkers.contains(&"languagetool".to_owned()) {
+                if !config.languagetool.take().is_some() {
+                    warn!("Languagetool was never configured.")
+                }
+            }
+            if !checkers.contains(&"consistency".to_owned()) {
+                if !config.consistency.take().is_some() {
+                    warn!("Consistency was never configured.")
+                }
+            }
+            if !checkers.contains(&"terminology".to_owned()) {
+                if config.terminology.is_empty() {
+                    warn!("Terminology was never configured.")
+                }
+                config.terminology.clear();
+            }
+            if !checkers.contains(&"banned".to_owned()) {
+                if config.banned.is_empty() {
+                    warn!("Banned terms were never configured.")
+                }
+                config.banned.clear();
+            }
+            if !checkers.contains(&"reflow".to_owned()) {
+                warn!("Reflow is a separate sub command.")
+            }
+        }
+    };
+
+    // handle `config` sub command
+    if args.cmd_config {
+        trace!("Configuration chore");
+        let mut config = Config::full();
+        checkers(&mut config);
+
+        let config_path = match args.flag_cfg.as_ref() {
+            Some(path) => Some(path.to_owned()),
+            None if args.flag_user => Some(Config::default_path()?),
+            None => None,
+        };
+
+        if args.flag_stdout {
+            println!("{}", config.to_toml()?);
+            return Ok(ExitCode::Success);
+        }
+
+        if let Some(path) = config_path {
+            if path.is_file() && !args.flag_force {
+                return usage(Err(anyhow::anyhow!(
+                    "Attempting to overwrite {} requires `--force`.",
+                    path.display()
+                )));
+            }
+            info!("Writing configuration file to {}", path.display());
+            config.write_values_to_path(path)?;
+        }
+        return Ok(ExitCode::Success);
+    } else {
+        trace!("Not configuration sub command");
+    }
+
+    // handle `completions` sub command
+    if args.cmd_completions {
+        let shell: completions::Shell = usage(args.arg_shell.parse())?;
+        completions::generate(shell, USAGE, &mut std::io::stdout().lock())?;
+        return Ok(ExitCode::Success);
+    }
+
+    // handle `import` sub command
+    if args.cmd_import {
+        let mut words = Vec::new();
+        for path in args.arg_paths.iter() {
+            words.extend(import::read_words(path)?);
+        }
+        let dictionary = import::to_dictionary(words);
+        if let Some(path) = args.flag_output {
+            if path.is_file() && !args.flag_force {
+                return usage(Err(anyhow::anyhow!(
+                    "Attempting to overwrite {} requires `--force`.",
+                    path.display()
+                )));
+            }
+            std::fs::write(&path, dictionary)
+                .map_err(|e| anyhow::anyhow!("Failed to write {}", path.display()).context(e))?;
+            info!(
+                "Wrote dictionary {}, add it to the `extra_dictionaries`",
+                path.display()
+            );
+        } else {
+            print!("{}", dictionary);
+        }
+        return Ok(ExitCode::Success);
+    }
+
+    // TODO refactor needed
+
+    // the current work dir as fallback
+    let cwd = traverse::cwd()?;
+    let mut manifest_path: PathBuf = cwd.as_path().join("Cargo.toml");
+
+    // TODO Currently uses the first manifest dir as search dir for a spellcheck.toml
+    // TODO with a fallback to the cwd as project dir.
+    // TODO But it would be preferable to use the config specific to each dir if available.
+    for path in args.arg_paths.iter() {
+        let path = if let Some(path) = canonicalize(&absolute(path, &traverse::cwd()?)).ok() {
+            path
+        } else {
+            warn!(
+                "Provided path could not be canonicalized {}",
+                path.display()
+            );
+            // does not exist or access issues
+            continue;
+        };
+
+        if path.is_dir() {
+            let path = path.join("Cargo.toml");
+            if path.is_file() {
+                debug!("Using {} manifest as anchor file", path.display());
+                manifest_path = path;
+                break;
+            }
+        } else if let Some(file_name) = path.file_name() {
+            if file_name == "Cargo.toml" && path.is_file() {
+                debug!("Using {} manifest as anchor file", path.display());
+                manifest_path = path.to_owned();
+                break;
+            }
+        }
+        // otherwise it's a file and we do not care about it
+    }
+
+    let manifest_dir = manifest_path.with_file_name(""); //.expect("Found file ends in Cargo.toml and is abs. qed");
+
+    let (explicit_cfg, config_path) = match args.flag_cfg.as_ref() {
+        Some(config_path) => {
+            let config_path = absolute(config_path, &traverse::cwd()?);
+            (true, config_path)
+        }
+        None => {
+            let resolved_config_path = config::Config::project_config(&manifest_dir)
+                .or_else(|e| {
+                    debug!("Manifest dir found {}: {}", manifest_dir.display(), e);
+                    // in case there is none, attempt the cwd first before falling back to the user config
+                    // this is a common case for workspace setups where we want to sanitize a sub project
+                    config::Config::project_config(cwd.as_path())
+                })
+                .or_else(|e| {
+                    debug!("Fallback to user default lookup, failed to load project specific config {}: {}", manifest_dir.display(), e);
+                    Config::default_path()
+                })?;
+            (false, resolved_config_path)
+        }
+    };
+    info!("Using configuration file {}", config_path.display());
+    let mut config = match Config::load_from(&config_path) {
+        Ok(config) => config,
+        Err(e) => {
+            if explicit_cfg {
+                return usage(Err(e));
+            } else {
+                debug!(
+                    "Loading configuration from {} failed due to: {}",
+                    config_path.display(),
+                    e
+                );
+                warn!(
+                    "Loading configuration from {} failed, falling back to default values",
+                    config_path.display(),
+                );
+                Config::default()
+            }
+        }
+    };
+
+    checkers(&mut config);
+
+    let ignore_dir = config::IgnoreConfig::dir_of(&manifest_dir);
+    if let Some(ignore) = usage(config::IgnoreConfig::load_from(&ignore_dir))? {
+        config.exclude.extend(ignore.paths.iter().cloned());
+        config.ignore = ignore;
+    }
+    config.ignore_dir = Some(ignore_dir);
+
+    for glob in args.flag_include.iter() {
+        config.include.push(usage(glob.parse())?);
+    }
+    for glob in args.flag_exclude.iter() {
+        config.exclude.push(usage(glob.parse())?);
+    }
+    if args.flag_no_ignore {
+        config.respect_ignore_files = false;
+    }
+    if args.flag_follow_symlinks {
+        config.follow_symlinks = true;
+    }
+    config.only_public = args.flag_only_public;
+    if let Some(ref targets) = args.flag_targets {
+        config.targets = usage(targets.parse())?;
+    }
+    if let Some(min_confidence) = args.flag_min_confidence {
+        config.min_confidence = min_confidence;
+    }
+    if args.flag_fast {
+        config.fast_extraction = true;
+    }
+    usage(config.validate())?;
+    config
+        .extra_src_dirs
+        .extend(args.flag_extra_src_dir.iter().cloned());
+    if let Some(ref format) = args.flag_format {
+        config.output.format = usage(format.parse())?;
+    }
+    if let Some(ref encoding) = args.flag_column_encoding {
+        config.output.column_encoding = usage(encoding.parse())?;
+    }
+    if let Some(ref key) = args.flag_sort_by {
+        config.output.sort_by = usage(key.parse())?;
+    }
+    if let Some(ref key) = args.flag_group_by {
+        config.output.group_by = Some(usage(key.parse())?);
+    }
+    usage(config.output.validate())?;
+    for report in args.flag_report.iter() {
+        config.output.reports.push(usage(report.parse())?);
+    }
+    config.accept_unambiguous = args.flag_accept_unambiguous;
+    config.fix_by_word = args.flag_by_word;
+    usage(output::theme::set(&config.theme))?;
+
+    // handle `explain` sub command
+    if args.cmd_explain {
+        explain::explain(&args.arg_subject, &config, &mut std::io::stdout().lock())?;
+        return Ok(ExitCode::Success);
+    }
+
+    // handle `commit-msg` sub command, mistakes always fail the hook
+    if args.cmd_commit_msg {
+        checker::preload(&config);
+        let documentation = commit_msg::extract(&args.arg_file)?;
+        let suggestions = checker::check(&documentation, &config)?;
+        let checked = documentation
+            .iter()
+            .map(|(origin, _chunks)| origin.clone())
+            .collect::<Vec<_>>();
+        let code = args.flag_code.filter(|&code| code != 0).unwrap_or(1);
+        return match Action::Check.run(suggestions, &checked, &config)? {
+            Finish::MistakeCount(0) => Ok(ExitCode::Success),
+            Finish::MistakeCount(_n) => Ok(ExitCode::Custom(code)),
+            Finish::Abort => Ok(ExitCode::Signal),
+        };
+    }
+
+    // extract operation mode
+    let action = if args.flag_fix_safe {
+        Action::FixSafe
+    } else if args.cmd_fix || args.flag_fix {
+        Action::Fix
+    } else if args.cmd_reflow {
+        Action::Reflow
+    } else {
+        Action::Check
+    };
+
+    debug!("Executing: {:?} with {:?}", action, &config);
+
+    let selection = if args.flag_workspace {
+        traverse::PackageSelection::Workspace
+    } else if !args.flag_package.is_empty() {
+        traverse::PackageSelection::Packages(args.flag_package)
+    } else {
+        traverse::PackageSelection::Default
+    };
+
+    let mut paths = args.arg_paths;
+    let mut recursive = args.flag_recursive;
+    if args.flag_staged {
+        let staged = traverse::staged_files(&traverse::cwd()?)?;
+        if staged.is_empty() {
+            info!("No staged files, nothing to check");
+            return Ok(ExitCode::Success);
+        }
+        if paths.is_empty() {
+            paths = staged
+                .iter()
+                .filter(|path| {
+                    path.extension()
+                        .filter(|ext| *ext == "rs" || *ext == "md")
+                        .is_some()
+                })
+                .cloned()
+                .collect();
+            recursive = false;
+        }
+        config.restrict_to = Some(staged);
+        // fixes are applied to the working tree, so they must be found there
+        config.from_index = action == Action::Check;
+    }
+
+    // handle `list-files` sub command, only traverses
+    if args.cmd_list_files {
+        let cwd = traverse::cwd()?;
+        for discovered in
+            traverse::discover(paths, recursive, args.flag_skip_readme, &selection, &config)?
+        {
+            let path = discovered.path.as_path();
+            let path = strip_path_prefix(path, &cwd).unwrap_or(path);
+            println!(
+                "{}: {}, {}",
+                path.display(),
+                discovered.kind,
+                discovered.reason
+            );
+        }
+        return Ok(ExitCode::Success);
+    }
+
+    if args.flag_timings {
+        timings::enable();
+    }
+    if args.flag_unused_suppressions {
+        checker::unused::enable();
+    }
+    if let Some(ref checker) = args.flag_debug_checker {
+        checker::debug::enable(usage(checker.parse())?);
+    }
+
+    if action != Action::Reflow {
+        checker::preload(&config);
+    }
+
+    let combined = traverse::extract(
+        paths.clone(),
+        recursive,
+        args.flag_skip_readme,
+        &selection,
+        &config,
+    )?;
+
+    // per path overrides require checking each group with its own config
+    let partitions = checker::partition(combined, &config);
+
+    let rechecked;
+    let mut suggestion_set = check_partitions(&partitions, action)?;
+    if action == Action::FixSafe && action.apply_safe_fixes(&mut suggestion_set, &config)? > 0 {
+        // the spans of all other suggestions are stale now
+        let combined =
+            traverse::extract(paths, recursive, args.flag_skip_readme, &selection, &config)?;
+        rechecked = checker::partition(combined, &config);
+        suggestion_set = check_partitions(&rechecked, action)?;
+    }
+
+    let checked = partitions
+        .iter()
+        .flat_map(|(_config, documentation)| documentation.iter())
+        .map(|(origin, _chunks)| origin.clone())
+        .collect::<Vec<_>>();
+    let finish = action.run(suggestion_set, &checked, &config)?;
+
+    if timings::is_enabled() {
+        timings::report(std::io::stderr().lock())?;
+    }
+    if checker::unused::is_enabled() {
+        checker::unused::report(std::io::stderr().lock())?;
+    }
+
+    match finish {
+        Finish::MistakeCount(0) => Ok(ExitCode::Success),
+        Finish::MistakeCount(_n) => Ok(ExitCode::Custom(args.flag_code.unwrap_or(1))),
+        Finish::Abort => Ok(ExitCode::Signal),
+    }
+}
+
+/// Check all `partitions`, each with its own config, see `checker::partition`.
+fn check_partitions<'a>(
+    partitions: &'a [(Config, Documentation)],
+    action: Action,
+) -> anyhow::Result<SuggestionSet<'a>> {
+    let mut suggestion_set = SuggestionSet::new();
+    match action {
+        Action::Reflow => {
+            for (config, documentation) in partitions.iter() {
+                let limits = checker::Limits::new(config);
+                let mut found =
+                    reflow::Reflow::check(documentation, &config.reflow_config(), &limits)?;
+                if let Some(max) = config.max_suggestions_per_file {
+                    found.truncate(max);
+                }
+                for (origin, chunks) in limits.unchecked() {
+                    found.not_checked(origin, chunks);
+                }
+                suggestion_set.merge(found);
+            }
+        }
+        Action::Check | Action::Fix | Action::FixSafe => {
+            let partitions = partitions
+                .iter()
+                .map(|(config, documentation)| (config, documentation))
+                .collect::<Vec<_>>();
+            suggestion_set.merge(checker::check_all(&partitions)?);
+        }
+    }
+    Ok(suggestion_set)
+}
+
+/// Run `cargo spellcheck` with the arguments of the process and exit with
+/// the resulting exit code.
+pub fn main() {
+    let exit_code = match run() {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::of(&e)
+        }
+    };
+    let val = exit_code.as_u8();
+    if val != 0 {
+        std::process::exit(val as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commandline_to_iter(s: &'static str) -> impl Iterator<Item = String> {
+        s.split(' ').map(|s| s.to_owned()).into_iter()
+    }
+
+    #[test]
+    fn normalized() {
+        assert_eq!(
+            normalize_args(commandline_to_iter("cargo-spellcheck spellcheck gen-man")),
+            vec!["cargo-spellcheck", "gen-man"]
+        );
+        assert_eq!(
+            normalize_args(commandline_to_iter("/usr/bin/cargo-spellcheck gen-man")),
+            vec!["cargo-spellcheck", "gen-man"]
+        );
+    }
+
+    #[test]
+    fn docopt() {
+        let commands = vec![
+            "cargo spellcheck",
+            "cargo spellcheck -vvvv",
+            "cargo spellcheck --fix",
+            "cargo spellcheck fix",
+            "cargo-spellcheck",
+            "cargo-spellcheck -vvvv",
+            "cargo-spellcheck --fix",
+            "cargo-spellcheck fix",
+            "cargo-spellcheck fix -r file.rs",
+            "cargo-spellcheck -q fix Cargo.toml",
+            "cargo spellcheck -v fix Cargo.toml",
+            "cargo spellcheck -m 11 check",
+            "cargo-spellcheck reflow",
+            "cargo spellcheck check --exclude=target/** --exclude=vendor/**",
+            "cargo-spellcheck fix --include=src/** -r src",
+            "cargo-spellcheck check --no-ignore",
+            "cargo-spellcheck check --follow-symlinks -r src",
+            "cargo spellcheck check --only-public",
+            "cargo spellcheck check --targets=tests,examples",
+            "cargo spellcheck list-files --targets=all",
+            "cargo spellcheck check --min-confidence=60",
+            "cargo spellcheck --fix --min-confidence=80",
+            "cargo spellcheck commit-msg .git/COMMIT_EDITMSG",
+            "cargo spellcheck commit-msg --code=2 --checkers=hunspell .git/COMMIT_EDITMSG",
+            "cargo spellcheck check --fast --staged",
+            "cargo spellcheck check --workspace",
+            "cargo spellcheck check -p foo -p bar",
+            "cargo spellcheck fix --package=foo",
+            "cargo spellcheck check --staged",
+            "cargo spellcheck check --extra-src-dir=target/debug/build/foo-1234/out",
+            "cargo spellcheck check --format=json",
+            "cargo spellcheck check --format=grouped",
+            "cargo spellcheck check --format=csv",
+            "cargo spellcheck check --format=tap",
+            "cargo spellcheck check --report=html=target/spellcheck.html",
+            "cargo spellcheck --report html=a.html --report html=b.html",
+            "cargo-spellcheck check --format=rustc",
+            "cargo spellcheck fix --timings",
+            "cargo spellcheck check --debug-checker=hunspell src/lib.rs",
+            "cargo spellcheck check --format=json --column-encoding=utf-16",
+            "cargo spellcheck check --sort-by=word",
+            "cargo-spellcheck check --group-by=file --sort-by=severity",
+            "cargo spellcheck --group-by word --format=rustc",
+            "cargo spellcheck fix --accept-unambiguous",
+            "cargo-spellcheck check --fix-safe",
+            "cargo-spellcheck --fix-safe --format=json src/lib.rs",
+            "cargo-spellcheck import cspell.json .codespellrc",
+            "cargo-spellcheck import --output=imported.dic --force styles/Vocab/Project/accept.txt",
+            "cargo spellcheck --fix --accept-unambiguous src/lib.rs",
+            "cargo-spellcheck fix --by-word",
+            "cargo spellcheck --fix --by-word --accept-unambiguous",
+            "cargo spellcheck completions bash",
+            "cargo spellcheck list-files",
+            "cargo-spellcheck list-files --workspace --exclude=target/** -r src",
+            "cargo spellcheck explain behaviour",
+            "cargo spellcheck check --unused-suppressions",
+            "cargo-spellcheck explain --checkers=hunspell,terminology website",
+            "cargo spellcheck check --checkers=terminology,banned",
+            "cargo-spellcheck completions powershell",
+        ];
+        for command in commands {
+            assert!(parse_args(commandline_to_iter(command)).is_ok());
+        }
+    }
+
+    #[test]
+    fn exit_codes() {
+        let code = |command| parse_args(commandline_to_iter(command)).unwrap().flag_code;
+        assert_eq!(code("cargo spellcheck check"), None);
+        assert_eq!(code("cargo spellcheck check --code=0"), Some(0));
+        assert_eq!(code("cargo spellcheck -m 11 check"), Some(11));
+
+        assert_eq!(ExitCode::Success.as_u8(), 0);
+        assert_eq!(ExitCode::Custom(1).as_u8(), 1);
+        assert_eq!(ExitCode::BadUsage.as_u8(), 64);
+        assert_eq!(ExitCode::InternalError.as_u8(), 70);
+        assert_eq!(ExitCode::Signal.as_u8(), 130);
+
+        let bad_format = usage("markdown".parse::<output::OutputFormat>()).unwrap_err();
+        assert_eq!(ExitCode::of(&bad_format), ExitCode::BadUsage);
+        let bad_config =
+            usage(Config::load_from(&PathBuf::from("missing/spellcheck.toml"))).unwrap_err();
+        assert_eq!(ExitCode::of(&bad_config), ExitCode::BadUsage);
+        let failure = anyhow::anyhow!("Failed to read the file");
+        assert_eq!(ExitCode::of(&failure), ExitCode::InternalError);
+    }
+}
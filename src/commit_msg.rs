@@ -0,0 +1,110 @@
+//! Check commit messages, i.e. from the `commit-msg` hook of git.
+//!
+//! The message is checked as common mark, so code spans and blocks are
+//! skipped, with the same dictionaries and ignore rules as the documentation.
+//! Comment lines, everything below the scissors line of `git commit --verbose`
+//! and the trailers, i.e. `Signed-off-by:`, are blanked out beforehand, so the
+//! lines of the findings still match the file.
+
+use crate::{ContentOrigin, Documentation};
+
+use anyhow::{anyhow, Result};
+
+use std::path::Path;
+
+/// Git removes everything below this comment line.
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// If `line` is a trailer, i.e. `Signed-off-by: A U Thor <author@example.com>`.
+fn is_trailer(line: &str) -> bool {
+    match line.find(':') {
+        Some(colon) => {
+            let token = &line[..colon];
+            !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        }
+        None => false,
+    }
+}
+
+/// The message with comments, the verbose diff and the trailers replaced by
+/// empty lines.
+fn strip(message: &str) -> String {
+    let mut cut = false;
+    let mut lines = message
+        .lines()
+        .map(|line| {
+            cut |= line.starts_with('#') && line.contains(SCISSORS);
+            if cut || line.starts_with('#') {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // the trailers are the last paragraph, which is never the subject
+    if let Some(end) = lines.iter().rposition(|line| !line.trim().is_empty()) {
+        let start = lines[..end]
+            .iter()
+            .rposition(|line| line.trim().is_empty())
+            .map_or(0, |blank| blank + 1);
+        let trailers = &lines[start..=end];
+        // values may be continued on lines starting with whitespace
+        let is_trailers = is_trailer(trailers[0])
+            && trailers
+                .iter()
+                .all(|line| is_trailer(line) || line.starts_with(char::is_whitespace));
+        if start > 0 && is_trailers {
+            lines[start..=end].iter_mut().for_each(|line| *line = "");
+        }
+    }
+
+    let mut stripped = lines.join("\n");
+    stripped.push('\n');
+    stripped
+}
+
+/// Load the commit message file at `path`, empty if there is nothing but
+/// comments and trailers.
+pub fn extract(path: &Path) -> Result<Documentation> {
+    let message = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read commit message {}", path.display()).context(e))?;
+    let stripped = strip(message.as_str());
+    let mut documentation = Documentation::new();
+    if !stripped.trim().is_empty() {
+        documentation.add_commonmark(ContentOrigin::CommonMarkFile(path.to_owned()), &stripped)?;
+    }
+    Ok(documentation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stripped() {
+        const MESSAGE: &str = r#"Fix the frobnicator
+
+Speeds up `frob_all` by a lot.
+
+Signed-off-by: A U Thor <author@example.com>
+Co-authored-by: Other Person
+  <other@example.com>
+# Please enter the commit message for your changes.
+# ------------------------ >8 ------------------------
+diff --git a/src/lib.rs b/src/lib.rs
+"#;
+        assert_eq!(
+            strip(MESSAGE),
+            "Fix the frobnicator\n\nSpeeds up `frob_all` by a lot.\n\n\n\n\n\n\n\n"
+        );
+
+        // a last paragraph of prose is kept
+        assert_eq!(
+            strip("Subject\n\nNote: the body.\nMore prose.\n"),
+            "Subject\n\nNote: the body.\nMore prose.\n"
+        );
+        // as is a subject which looks like a trailer
+        assert_eq!(strip("docs: fix typo\n"), "docs: fix typo\n");
+    }
+}
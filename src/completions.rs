@@ -0,0 +1,574 @@
+//! Shell completion scripts, generated from the docopt usage string.
+//!
+//! Subcommands and options are taken from the usage string, so the scripts
+//! never diverge from what the commandline accepts. The values of options with
+//! a fixed set of choices, i.e. `--format`, and the ones taking file or
+//! directory paths are completed as well.
+
+use anyhow::{bail, Result};
+
+use std::io::Write;
+use std::str::FromStr;
+
+/// The name of the binary the completions are registered for.
+const BIN: &str = "cargo-spellcheck";
+
+/// Values of `--format`.
+const FORMATS: &[&str] = &["human", "grouped", "json", "csv", "tap", "rustc"];
+/// Values of `--column-encoding`.
+const ENCODINGS: &[&str] = &["chars", "utf-8", "utf-16"];
+/// Values of `--sort-by` and `--group-by`.
+const SORT_KEYS: &[&str] = &["file", "word", "severity"];
+/// Values of `--checkers`, comma separated, and of `--debug-checker`.
+const CHECKERS: &[&str] = crate::config::CHECKER_NAMES;
+/// Values of `--targets`, comma separated.
+const TARGETS: &[&str] = &["tests", "examples", "benches", "build", "all", "none"];
+/// Values of the `completions` subcommand.
+const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell"];
+
+/// A shell to generate the completion script for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    /// Bash, sourced from `~/.bashrc` or placed in the `bash-completion` dir.
+    Bash,
+    /// Zsh, placed as `_cargo-spellcheck` in a directory of the `fpath`.
+    Zsh,
+    /// Fish, placed in `~/.config/fish/completions/`.
+    Fish,
+    /// PowerShell, sourced from the `$PROFILE`.
+    PowerShell,
+}
+
+impl FromStr for Shell {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "bash" => Self::Bash,
+            "zsh" => Self::Zsh,
+            "fish" => Self::Fish,
+            "powershell" | "pwsh" => Self::PowerShell,
+            other => bail!(
+                "Unknown shell `{}`, expected one of {}",
+                other,
+                SHELLS.join(", ")
+            ),
+        })
+    }
+}
+
+/// What the value of an option is completed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Values {
+    /// A flag without a value.
+    None,
+    /// Anything, nothing to complete.
+    Any,
+    /// One of a fixed set.
+    OneOf(&'static [&'static str]),
+    /// A file path.
+    File,
+    /// A directory path.
+    Dir,
+}
+
+/// An option of the usage string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Opt {
+    pub(crate) short: Option<char>,
+    pub(crate) long: String,
+    /// The placeholder of the value, i.e. `<cfg>`, if it takes one.
+    pub(crate) value: Option<String>,
+    values: Values,
+    pub(crate) help: String,
+}
+
+/// The subcommands, in order of their first appearance in `usage`.
+pub(crate) fn subcommands(usage: &str) -> Vec<&str> {
+    let mut subcommands = Vec::new();
+    let lines = usage
+        .lines()
+        .skip_while(|line| !line.starts_with("Usage:"))
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty());
+    for line in lines {
+        let subcommand = line
+            .split_whitespace()
+            .skip(1)
+            .find(|word| word.starts_with(|c: char| c.is_ascii_lowercase()));
+        if let Some(subcommand) = subcommand {
+            if !subcommands.contains(&subcommand) {
+                subcommands.push(subcommand);
+            }
+        }
+    }
+    subcommands
+}
+
+/// What the values of the option `long` are completed with, if it takes one.
+fn values_of(long: &str) -> Values {
+    match long {
+        "format" => Values::OneOf(FORMATS),
+        "column-encoding" => Values::OneOf(ENCODINGS),
+        "sort-by" | "group-by" => Values::OneOf(SORT_KEYS),
+        "checkers" | "debug-checker" => Values::OneOf(CHECKERS),
+        "targets" => Values::OneOf(TARGETS),
+        "cfg" | "output" => Values::File,
+        "extra-src-dir" => Values::Dir,
+        _ => Values::Any,
+    }
+}
+
+/// The options of the `Options:` section of `usage`, with the continued lines
+/// of their descriptions joined.
+pub(crate) fn options(usage: &str) -> Vec<Opt> {
+    let mut options: Vec<Opt> = Vec::new();
+    for line in usage
+        .lines()
+        .skip_while(|line| !line.starts_with("Options:"))
+        .skip(1)
+    {
+        let line = line.trim();
+        if !line.starts_with('-') {
+            if let (Some(opt), false) = (options.last_mut(), line.is_empty()) {
+                opt.help.push(' ');
+                opt.help.push_str(line);
+            }
+            continue;
+        }
+        let (spec, help) = line.split_at(line.find("  ").unwrap_or(line.len()));
+        let mut opt = Opt {
+            short: None,
+            long: String::new(),
+            value: None,
+            values: Values::None,
+            help: help.trim().to_owned(),
+        };
+        for word in spec.split_whitespace() {
+            if let Some(long) = word.strip_prefix("--") {
+                let mut parts = long.splitn(2, '=');
+                opt.long = parts.next().unwrap_or_default().to_owned();
+                if let Some(value) = parts.next() {
+                    opt.value = Some(value.to_owned());
+                    opt.values = values_of(&opt.long);
+                }
+            } else {
+                opt.short = word.chars().nth(1);
+            }
+        }
+        options.push(opt);
+    }
+    options
+}
+
+/// Write the completion script for `shell` derived from `usage`.
+pub fn generate<W: Write>(shell: Shell, usage: &str, sink: &mut W) -> Result<()> {
+    let subcommands = subcommands(usage);
+    let options = options(usage);
+    match shell {
+        Shell::Bash => bash(sink, &subcommands, &options),
+        Shell::Zsh => zsh(sink, &subcommands, &options),
+        Shell::Fish => fish(sink, &subcommands, &options),
+        Shell::PowerShell => powershell(sink, &subcommands, &options),
+    }
+}
+
+fn bash<W: Write>(sink: &mut W, subcommands: &[&str], options: &[Opt]) -> Result<()> {
+    let flags = options
+        .iter()
+        .flat_map(|opt| {
+            let short = opt.short.map(|short| format!("-{}", short));
+            let long = match opt.values {
+                Values::None => format!("--{}", opt.long),
+                _ => format!("--{}=", opt.long),
+            };
+            short.into_iter().chain(Some(long))
+        })
+        .collect::<Vec<_>>();
+    writeln!(sink, "_cargo_spellcheck() {{")?;
+    writeln!(sink, r#"    local cur="${{COMP_WORDS[COMP_CWORD]}}""#)?;
+    writeln!(sink, r#"    local prev="${{COMP_WORDS[COMP_CWORD-1]}}""#)?;
+    // `=` is a word of its own, `--format=js` is `--format`, `=`, `js`
+    writeln!(sink, r#"    if [[ "$cur" == "=" ]]; then"#)?;
+    writeln!(sink, r#"        cur="""#)?;
+    writeln!(sink, r#"    elif [[ "$prev" == "=" ]]; then"#)?;
+    writeln!(sink, r#"        prev="${{COMP_WORDS[COMP_CWORD-2]}}""#)?;
+    writeln!(sink, "    fi")?;
+    writeln!(sink, r#"    case "$prev" in"#)?;
+    for opt in options {
+        let mut patterns = vec![format!("--{}", opt.long)];
+        patterns.extend(opt.short.map(|short| format!("-{}", short)));
+        let reply = match opt.values {
+            Values::OneOf(values) => format!(r#"compgen -W "{}" -- "$cur""#, values.join(" ")),
+            Values::File => r#"compgen -f -- "$cur""#.to_owned(),
+            Values::Dir => r#"compgen -d -- "$cur""#.to_owned(),
+            Values::Any | Values::None => continue,
+        };
+        writeln!(
+            sink,
+            "        {})\n            COMPREPLY=($({}))\n            return\n            ;;",
+            patterns.join("|"),
+            reply
+        )?;
+    }
+    writeln!(
+        sink,
+        "        completions)\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            return\n            ;;",
+        SHELLS.join(" ")
+    )?;
+    writeln!(sink, "    esac")?;
+    writeln!(sink, r#"    if [[ "$cur" == -* ]]; then"#)?;
+    writeln!(
+        sink,
+        r#"        COMPREPLY=($(compgen -W "{}" -- "$cur"))"#,
+        flags.join(" ")
+    )?;
+    writeln!(
+        sink,
+        r#"        [[ "${{COMPREPLY[0]}}" == *= ]] && compopt -o nospace"#
+    )?;
+    writeln!(sink, "    else")?;
+    writeln!(
+        sink,
+        r#"        COMPREPLY=($(compgen -W "{}" -- "$cur") $(compgen -f -- "$cur"))"#,
+        subcommands.join(" ")
+    )?;
+    writeln!(sink, "    fi")?;
+    writeln!(sink, "}}")?;
+    writeln!(sink, "complete -F _cargo_spellcheck {}", BIN)?;
+    Ok(())
+}
+
+/// Escape a description within a single quoted `_arguments` spec.
+fn zsh_escape(s: &str) -> String {
+    s.replace('\'', r"'\''")
+        .replace('[', r"\[")
+        .replace(']', r"\]")
+        .replace(':', r"\:")
+}
+
+fn zsh<W: Write>(sink: &mut W, subcommands: &[&str], options: &[Opt]) -> Result<()> {
+    writeln!(sink, "#compdef {}", BIN)?;
+    writeln!(sink)?;
+    writeln!(sink, "_arguments -s \\")?;
+    for opt in options {
+        let action = match opt.values {
+            Values::None => String::new(),
+            Values::Any => format!(":{}: ", opt.long),
+            Values::OneOf(values) => format!(":{}:({})", opt.long, values.join(" ")),
+            Values::File => format!(":{}:_files", opt.long),
+            Values::Dir => format!(":{}:_files -/", opt.long),
+        };
+        let eq = if opt.values == Values::None { "" } else { "=" };
+        let help = zsh_escape(&opt.help);
+        match opt.short {
+            Some(short) => writeln!(
+                sink,
+                "    '(-{short} --{long})'{{-{short},--{long}{eq}}}'[{help}]{action}' \\",
+                short = short,
+                long = opt.long,
+                eq = eq,
+                help = help,
+                action = action
+            )?,
+            None => writeln!(
+                sink,
+                "    '--{long}{eq}[{help}]{action}' \\",
+                long = opt.long,
+                eq = eq,
+                help = help,
+                action = action
+            )?,
+        }
+    }
+    writeln!(sink, "    '1::subcommand:({})' \\", subcommands.join(" "))?;
+    writeln!(sink, "    '*::path:_files'")?;
+    Ok(())
+}
+
+/// Quote `s` for fish.
+fn fish_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+fn fish<W: Write>(sink: &mut W, subcommands: &[&str], options: &[Opt]) -> Result<()> {
+    writeln!(
+        sink,
+        "complete -c {} -n __fish_use_subcommand -f -a {}",
+        BIN,
+        fish_quote(&subcommands.join(" "))
+    )?;
+    writeln!(
+        sink,
+        "complete -c {} -n '__fish_seen_subcommand_from completions' -x -a {}",
+        BIN,
+        fish_quote(&SHELLS.join(" "))
+    )?;
+    for opt in options {
+        let mut line = format!("complete -c {}", BIN);
+        if let Some(short) = opt.short {
+            line.push_str(&format!(" -s {}", short));
+        }
+        line.push_str(&format!(" -l {}", opt.long));
+        match opt.values {
+            Values::None => {}
+            Values::Any => line.push_str(" -x"),
+            Values::OneOf(values) => {
+                line.push_str(&format!(" -x -a {}", fish_quote(&values.join(" "))))
+            }
+            Values::File => line.push_str(" -r -F"),
+            Values::Dir => line.push_str(" -x -a '(__fish_complete_directories)'"),
+        }
+        line.push_str(&format!(" -d {}", fish_quote(&opt.help)));
+        writeln!(sink, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Quote `s` for PowerShell.
+fn powershell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Write the `[CompletionResult]`s of `candidates` as text, kind and help.
+fn powershell_candidates<W: Write>(
+    sink: &mut W,
+    candidates: &[(String, &str, String)],
+) -> Result<()> {
+    for (text, kind, help) in candidates {
+        writeln!(
+            sink,
+            "            [CompletionResult]::new({text}, {text}, [CompletionResultType]::{kind}, {help})",
+            text = powershell_quote(text),
+            kind = kind,
+            help = powershell_quote(help)
+        )?;
+    }
+    Ok(())
+}
+
+fn powershell<W: Write>(sink: &mut W, subcommands: &[&str], options: &[Opt]) -> Result<()> {
+    let shells = SHELLS
+        .iter()
+        .map(|shell| (shell.to_string(), "ParameterValue", shell.to_string()))
+        .collect::<Vec<_>>();
+    let mut candidates = subcommands
+        .iter()
+        .map(|subcommand| {
+            (
+                subcommand.to_string(),
+                "ParameterValue",
+                subcommand.to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    for opt in options {
+        if let Some(short) = opt.short {
+            candidates.push((format!("-{}", short), "ParameterName", opt.help.clone()));
+        }
+        match opt.values {
+            Values::None => {
+                candidates.push((format!("--{}", opt.long), "ParameterName", opt.help.clone()))
+            }
+            Values::OneOf(values) => {
+                for value in values {
+                    candidates.push((
+                        format!("--{}={}", opt.long, value),
+                        "ParameterName",
+                        opt.help.clone(),
+                    ));
+                }
+            }
+            Values::Any | Values::File | Values::Dir => candidates.push((
+                format!("--{}=", opt.long),
+                "ParameterName",
+                opt.help.clone(),
+            )),
+        }
+    }
+    writeln!(sink, "using namespace System.Management.Automation")?;
+    writeln!(sink)?;
+    writeln!(
+        sink,
+        "Register-ArgumentCompleter -Native -CommandName {} -ScriptBlock {{",
+        powershell_quote(BIN)
+    )?;
+    writeln!(
+        sink,
+        "    param($wordToComplete, $commandAst, $cursorPosition)"
+    )?;
+    writeln!(
+        sink,
+        "    if ($commandAst.CommandElements | Where-Object {{ $_.ToString() -eq 'completions' }}) {{"
+    )?;
+    writeln!(sink, "        $completions = @(")?;
+    powershell_candidates(sink, &shells)?;
+    writeln!(sink, "        )")?;
+    writeln!(sink, "    }} else {{")?;
+    writeln!(sink, "        $completions = @(")?;
+    powershell_candidates(sink, &candidates)?;
+    writeln!(sink, "        )")?;
+    writeln!(sink, "    }}")?;
+    writeln!(
+        sink,
+        "    $completions.Where{{ $_.CompletionText -like \"$wordToComplete*\" }}"
+    )?;
+    writeln!(sink, "}}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TargetsConfig;
+    use crate::output::{ColumnEncoding, OutputFormat, SortKey};
+    use crate::Detector;
+
+    const USAGE: &str = r#"
+Usage:
+    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>] [--format=<format>] [<paths>...]
+    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [<paths>...]
+    cargo-spellcheck [(-v...|-q)] completions <shell>
+    cargo-spellcheck [(-v...|-q)] [--cfg=<cfg>] [[--recursive] <paths>... ]
+    cargo-spellcheck --help
+
+Options:
+  -h --help                 Show this screen.
+  -c --cfg=<cfg>            Use a non default configuration file.
+                            Passing a directory will attempt to open `cargo_spellcheck.toml` in that directory.
+  --format=<format>         Output format of the found mistakes, one of `human` or `json`.
+  -m --code=<code>          Overwrite the exit value [default=0]
+"#;
+
+    #[test]
+    fn parsed() {
+        assert_eq!(subcommands(USAGE), vec!["check", "fix", "completions"]);
+        assert_eq!(
+            options(USAGE),
+            vec![
+                Opt {
+                    short: Some('h'),
+                    long: "help".to_owned(),
+                    value: None,
+                    values: Values::None,
+                    help: "Show this screen.".to_owned(),
+                },
+                Opt {
+                    short: Some('c'),
+                    long: "cfg".to_owned(),
+                    value: Some("<cfg>".to_owned()),
+                    values: Values::File,
+                    help: "Use a non default configuration file. Passing a directory will attempt to open `cargo_spellcheck.toml` in that directory.".to_owned(),
+                },
+                Opt {
+                    short: None,
+                    long: "format".to_owned(),
+                    value: Some("<format>".to_owned()),
+                    values: Values::OneOf(FORMATS),
+                    help: "Output format of the found mistakes, one of `human` or `json`."
+                        .to_owned(),
+                },
+                Opt {
+                    short: Some('m'),
+                    long: "code".to_owned(),
+                    value: Some("<code>".to_owned()),
+                    values: Values::Any,
+                    help: "Overwrite the exit value [default=0]".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn values_parse() {
+        for format in FORMATS {
+            assert!(format.parse::<OutputFormat>().is_ok());
+        }
+        for encoding in ENCODINGS {
+            assert!(encoding.parse::<ColumnEncoding>().is_ok());
+        }
+        for key in SORT_KEYS {
+            assert!(key.parse::<SortKey>().is_ok());
+        }
+        for checker in CHECKERS {
+            assert!(checker.parse::<Detector>().is_ok());
+        }
+        for target in TARGETS {
+            assert!(target.parse::<TargetsConfig>().is_ok());
+        }
+        for shell in SHELLS {
+            assert!(shell.parse::<Shell>().is_ok());
+        }
+    }
+
+    /// The values of the options with a fixed set of choices must be the ones
+    /// the help text of the real usage string lists, in either direction.
+    #[test]
+    fn values_match_usage() {
+        let accepts = |long: &str, value: &str| match long {
+            "format" => value.parse::<OutputFormat>().is_ok(),
+            "column-encoding" => value.parse::<ColumnEncoding>().is_ok(),
+            "sort-by" | "group-by" => value.parse::<SortKey>().is_ok(),
+            "targets" => value.parse::<TargetsConfig>().is_ok(),
+            _ => false,
+        };
+        let options = options(crate::cli::USAGE);
+        for long in &[
+            "format",
+            "column-encoding",
+            "sort-by",
+            "group-by",
+            "targets",
+        ] {
+            let opt = options
+                .iter()
+                .find(|opt| opt.long == *long)
+                .expect("Option is part of the usage string. qed");
+            let listed = opt
+                .help
+                .split('`')
+                .skip(1)
+                .step_by(2)
+                .filter(|value| accepts(long, value))
+                .collect::<Vec<_>>();
+            match opt.values {
+                Values::OneOf(values) => {
+                    let mut values = values.to_vec();
+                    values.sort_unstable();
+                    let mut listed = listed;
+                    listed.sort_unstable();
+                    assert_eq!(values, listed, "Values of `--{}` diverged", long);
+                }
+                other => panic!("`--{}` completes {:?}", long, other),
+            }
+        }
+    }
+
+    #[test]
+    fn scripts() {
+        let generated = |shell| {
+            let mut sink = Vec::new();
+            generate(shell, USAGE, &mut sink).unwrap();
+            String::from_utf8(sink).unwrap()
+        };
+        let bash = generated(Shell::Bash);
+        assert!(bash.contains("        --cfg|-c)\n"));
+        assert!(bash.contains("        --format)\n"));
+        assert!(bash.contains(r#"compgen -W "human grouped json csv tap rustc""#));
+        assert!(bash.contains(r#"compgen -W "check fix completions""#));
+        assert!(bash.ends_with("complete -F _cargo_spellcheck cargo-spellcheck\n"));
+
+        let zsh = generated(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef cargo-spellcheck\n"));
+        assert!(zsh.contains(
+            r"'(-m --code)'{-m,--code=}'[Overwrite the exit value \[default=0\]]:code: ' \"
+        ));
+        assert!(zsh.contains("'--format=[Output format of the found mistakes, one of `human` or `json`.]:format:(human grouped json csv tap rustc)' \\"));
+
+        let fish = generated(Shell::Fish);
+        assert!(fish.contains("complete -c cargo-spellcheck -s c -l cfg -r -F -d 'Use a non default configuration file. Passing"));
+
+        let powershell = generated(Shell::PowerShell);
+        assert!(powershell.contains("[CompletionResult]::new('--format=json', '--format=json'"));
+    }
+}
@@ -0,0 +1,81 @@
+use super::*;
+use crate::Category;
+
+/// Settings for a single category of mistakes.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CategoryConfig {
+    /// Report findings of this category at all, defaults to `true`.
+    pub enabled: Option<bool>,
+    /// Overrides the severity of the checker for findings of this category.
+    pub severity: Option<Severity>,
+}
+
+impl CategoryConfig {
+    /// Fields which are set in `other` take precedence.
+    fn merge(&mut self, other: &CategoryConfig) {
+        if other.enabled.is_some() {
+            self.enabled = other.enabled;
+        }
+        if other.severity.is_some() {
+            self.severity = other.severity;
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+}
+
+/// One optional setting per category.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct CategorySet {
+    pub spelling: Option<CategoryConfig>,
+    pub grammar: Option<CategoryConfig>,
+    pub style: Option<CategoryConfig>,
+    pub repetition: Option<CategoryConfig>,
+    pub casing: Option<CategoryConfig>,
+}
+
+impl CategorySet {
+    pub fn get(&self, category: Category) -> Option<&CategoryConfig> {
+        match category {
+            Category::Spelling => self.spelling.as_ref(),
+            Category::Grammar => self.grammar.as_ref(),
+            Category::Style => self.style.as_ref(),
+            Category::Repetition => self.repetition.as_ref(),
+            Category::Casing => self.casing.as_ref(),
+        }
+    }
+}
+
+/// Category settings which only apply to files matching one of `paths`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PathCategories {
+    pub paths: Vec<PathPattern>,
+    #[serde(flatten)]
+    pub categories: CategorySet,
+}
+
+/// Global category settings, refined by per path settings.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct CategoriesConfig {
+    #[serde(flatten)]
+    pub global: CategorySet,
+    /// Applied in order, so later entries win over earlier ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<PathCategories>,
+}
+
+impl CategoriesConfig {
+    /// Resolve the effective settings of `category` for the file at `path`.
+    pub fn resolve(&self, category: Category, path: &Path) -> CategoryConfig {
+        let mut resolved = self.global.get(category).cloned().unwrap_or_default();
+        self.overrides
+            .iter()
+            .filter(|per_path| PathPattern::any_matches(&per_path.paths, path))
+            .filter_map(|per_path| per_path.categories.get(category))
+            .for_each(|cfg| resolved.merge(cfg));
+        resolved
+    }
+}
@@ -0,0 +1,353 @@
+//! Suppressions which are managed independently of the tool settings, in a
+//! `_spellcheck.toml` or `.spellcheckignore` file.
+
+use super::*;
+
+/// File names of ignore files, in order of precedence.
+pub const IGNORE_FILE_NAMES: &[&str] = &["_spellcheck.toml", ".spellcheckignore"];
+
+/// Ignored words, identifiers and paths.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct IgnoreConfig {
+    /// Words which are never reported, compared case insensitive.
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Identifiers which are never reported, compared case sensitive.
+    #[serde(default)]
+    pub identifiers: Vec<String>,
+    /// Files matching any of these patterns are never checked.
+    #[serde(default)]
+    pub paths: Vec<PathPattern>,
+}
+
+impl IgnoreConfig {
+    pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
+        Ok(toml::from_str(s.as_ref())?)
+    }
+
+    /// The first ignore file in `dir`, if any.
+    fn path_in(dir: &Path) -> Option<PathBuf> {
+        IGNORE_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// The directory holding the ignore file of the package at `manifest_dir`.
+    ///
+    /// That is the closest of `manifest_dir` and the enclosing directories
+    /// with a manifest, i.e. the workspace root, which has an ignore file, or
+    /// `manifest_dir` itself if none of them has one.
+    pub fn dir_of(manifest_dir: &Path) -> PathBuf {
+        manifest_dir
+            .ancestors()
+            .take_while(|dir| dir.join("Cargo.toml").is_file())
+            .find(|dir| Self::path_in(dir).is_some())
+            .unwrap_or(manifest_dir)
+            .to_owned()
+    }
+
+    /// Load the first ignore file found in `dir`, if any.
+    pub fn load_from(dir: &Path) -> Result<Option<Self>> {
+        let path = match Self::path_in(dir) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        trace!("Loading ignore file {}", path.display());
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read ignore file {}", path.display()).context(e))?;
+        Self::parse(content).map(Some).map_err(|e| {
+            e.context(anyhow!(
+                "Syntax of ignore file {} is broken",
+                path.display()
+            ))
+        })
+    }
+
+    /// Add `words` to the ignore file in `dir`, which is created as
+    /// `_spellcheck.toml` if there is none, and return its path.
+    ///
+    /// The words are inserted into the existing `words` array, or appended as
+    /// a new one, so the rest of the file, including comments, stays as is.
+    pub fn add_words(dir: &Path, words: &[String]) -> Result<PathBuf> {
+        let path = Self::path_in(dir).unwrap_or_else(|| dir.join(IGNORE_FILE_NAMES[0]));
+        let ignore = Self::load_from(dir)?.unwrap_or_default();
+        let mut new = Vec::<&String>::new();
+        for word in words {
+            let word_lc = word.to_lowercase();
+            if !ignore.is_ignored(word) && !new.iter().any(|new| new.to_lowercase() == word_lc) {
+                new.push(word);
+            }
+        }
+        if new.is_empty() {
+            return Ok(path);
+        }
+        let content = if path.is_file() {
+            std::fs::read_to_string(&path)
+                .map_err(|e| anyhow!("Failed to read ignore file {}", path.display()).context(e))?
+        } else {
+            String::new()
+        };
+        let quoted = new
+            .iter()
+            .map(|word| toml::Value::String(word.to_string()).to_string())
+            .collect::<Vec<_>>();
+        let content = insert_words(&content, &quoted);
+        // never write a file which fails to load afterwards
+        Self::parse(&content).map_err(|e| {
+            e.context(anyhow!(
+                "Failed to add the words to the ignore file {}",
+                path.display()
+            ))
+        })?;
+        std::fs::write(&path, content)
+            .map_err(|e| anyhow!("Failed to write ignore file {}", path.display()).context(e))?;
+        Ok(path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.identifiers.is_empty()
+    }
+
+    /// Check if a finding for `word` must not be reported.
+    pub fn is_ignored(&self, word: &str) -> bool {
+        self.identifiers.iter().any(|identifier| identifier == word)
+            || self
+                .words
+                .iter()
+                .any(|ignored| ignored.to_lowercase() == word.to_lowercase())
+    }
+}
+
+/// Insert the `quoted` words into the `words` array of the toml `content`,
+/// or append a `words` array if there is none.
+fn insert_words(content: &str, quoted: &[String]) -> String {
+    let array = match words_array(content) {
+        Some(array) => array,
+        None => {
+            let mut content = content.to_owned();
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content += &format!("words = [{}]\n", quoted.join(", "));
+            return content;
+        }
+    };
+    let mut content = content.to_owned();
+    let line_start = content[..array.close]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    if content[line_start..array.close].trim().is_empty() && array.multiline {
+        // one word per line before the closing bracket
+        let entries = quoted
+            .iter()
+            .map(|word| format!("    {},\n", word))
+            .collect::<String>();
+        content.insert_str(line_start, &entries);
+        if !matches!(array.last, '[' | ',') {
+            content.insert(array.last_pos + 1, ',');
+        }
+    } else {
+        let separator = match array.last {
+            '[' => "",
+            ',' => " ",
+            _ => ", ",
+        };
+        content.insert_str(array.close, &format!("{}{}", separator, quoted.join(", ")));
+    }
+    content
+}
+
+/// Location of the `words` array within a toml document.
+struct WordsArray {
+    /// Byte offset of the closing bracket.
+    close: usize,
+    /// The last character before the closing bracket, which is neither
+    /// whitespace nor part of a comment, and its byte offset.
+    last: char,
+    last_pos: usize,
+    /// If the array spans multiple lines.
+    multiline: bool,
+}
+
+/// Find the top level `words` array of `content`. All keys of an ignore file
+/// are top level, there are no tables.
+fn words_array(content: &str) -> Option<WordsArray> {
+    let mut offset = 0;
+    let mut open = None;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("words") {
+            let rest = rest.trim_start();
+            if let Some(value) = rest.strip_prefix('=') {
+                if value.trim_start().starts_with('[') {
+                    let value_start = offset + line.len() - value.len();
+                    open = Some(value_start + value.find('[')?);
+                    break;
+                }
+            }
+        }
+        offset += line.len();
+    }
+    let open = open?;
+
+    let mut last = ('[', open);
+    let mut multiline = false;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut comment = false;
+    for (idx, c) in content[open + 1..].char_indices() {
+        let pos = open + 1 + idx;
+        if comment {
+            if c == '\n' {
+                comment = false;
+                multiline = true;
+            }
+            continue;
+        }
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' && q == '"' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            last = (c, pos);
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '#' => {
+                comment = true;
+                continue;
+            }
+            '\n' => multiline = true,
+            ']' => {
+                return Some(WordsArray {
+                    close: pos,
+                    last: last.0,
+                    last_pos: last.1,
+                    multiline,
+                })
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            last = (c, pos);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignored() {
+        let ignore = IgnoreConfig::parse(
+            r#"
+words = ["tokio", "Serde"]
+identifiers = ["HashMap"]
+paths = ["generated/**"]
+"#,
+        )
+        .unwrap();
+        assert!(ignore.is_ignored("Tokio"));
+        assert!(ignore.is_ignored("serde"));
+        assert!(ignore.is_ignored("HashMap"));
+        assert!(!ignore.is_ignored("hashmap"));
+        assert!(!ignore.is_ignored("tokyo"));
+        assert!(PathPattern::any_matches(
+            &ignore.paths,
+            Path::new("generated/bindings.rs")
+        ));
+    }
+
+    #[test]
+    fn load_from() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        assert!(IgnoreConfig::load_from(dir).unwrap().is_none());
+        std::fs::write(dir.join(".spellcheckignore"), "words = [\"tokio\"]\n").unwrap();
+        let ignore = IgnoreConfig::load_from(dir).unwrap().unwrap();
+        assert_eq!(ignore.words, vec!["tokio".to_owned()]);
+        std::fs::write(dir.join("_spellcheck.toml"), "unknown = 1\n").unwrap();
+        assert!(IgnoreConfig::load_from(dir).is_err());
+    }
+
+    #[test]
+    fn dir_of() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let member = root.join("member");
+        std::fs::create_dir(&member).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[workspace]\n").unwrap();
+        std::fs::write(member.join("Cargo.toml"), "[package]\n").unwrap();
+        assert_eq!(IgnoreConfig::dir_of(&member), member);
+
+        std::fs::write(root.join("_spellcheck.toml"), "").unwrap();
+        assert_eq!(IgnoreConfig::dir_of(&member), root);
+
+        std::fs::write(member.join(".spellcheckignore"), "").unwrap();
+        assert_eq!(IgnoreConfig::dir_of(&member), member);
+
+        // not beyond the outermost manifest
+        std::fs::remove_file(root.join("Cargo.toml")).unwrap();
+        std::fs::remove_file(member.join(".spellcheckignore")).unwrap();
+        assert_eq!(IgnoreConfig::dir_of(&member), member);
+    }
+
+    #[test]
+    fn add_words() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let words = vec!["tokio".to_owned()];
+        assert_eq!(
+            IgnoreConfig::add_words(dir, &words).unwrap(),
+            dir.join("_spellcheck.toml")
+        );
+
+        std::fs::remove_file(dir.join("_spellcheck.toml")).unwrap();
+        std::fs::write(dir.join(".spellcheckignore"), "words = [\"Tokio\"]\n").unwrap();
+        let words = vec!["tokio".to_owned(), "serde".to_owned()];
+        let path = IgnoreConfig::add_words(dir, &words).unwrap();
+        assert_eq!(path, dir.join(".spellcheckignore"));
+        let ignore = IgnoreConfig::load_from(dir).unwrap().unwrap();
+        assert_eq!(ignore.words, vec!["Tokio".to_owned(), "serde".to_owned()]);
+
+        // comments and the layout are kept
+        let content =
+            "# project words\nwords = [\n    \"Tokio\", # runtime\n    \"serde\"\n]\npaths = []\n";
+        std::fs::write(dir.join(".spellcheckignore"), content).unwrap();
+        let words = vec!["rustc".to_owned(), "SERDE".to_owned(), "Rustc".to_owned()];
+        IgnoreConfig::add_words(dir, &words).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join(".spellcheckignore")).unwrap(),
+            "# project words\nwords = [\n    \"Tokio\", # runtime\n    \"serde\",\n    \"rustc\",\n]\npaths = []\n"
+        );
+    }
+
+    #[test]
+    fn insert_words() {
+        let words = vec![r#""rustc""#.to_owned(), r#""cargo""#.to_owned()];
+        assert_eq!(
+            super::insert_words("# no words yet", &words),
+            "# no words yet\nwords = [\"rustc\", \"cargo\"]\n"
+        );
+        assert_eq!(
+            super::insert_words("words = []\n", &words),
+            "words = [\"rustc\", \"cargo\"]\n"
+        );
+        assert_eq!(
+            super::insert_words("words = [\"a]\", 'b'] # comment ]\n", &words),
+            "words = [\"a]\", 'b', \"rustc\", \"cargo\"] # comment ]\n"
+        );
+        assert_eq!(
+            super::insert_words("identifiers = [\"words\"]\nwords = [\"a\",]\n", &words),
+            "identifiers = [\"words\"]\nwords = [\"a\", \"rustc\", \"cargo\"]\n"
+        );
+    }
+}
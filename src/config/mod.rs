@@ -11,13 +11,24 @@
 mod search_dirs;
 pub use search_dirs::*;
 
+mod pattern;
+pub use pattern::*;
+
+mod categories;
+pub use categories::*;
+
+mod ignore;
+pub use ignore::*;
+
+use crate::output::{OutputConfig, ThemeConfig};
 use crate::reflow::ReflowConfig;
-use crate::Detector;
+use crate::{Detector, Severity};
 use anyhow::{anyhow, bail, Error, Result};
 use fancy_regex::Regex;
 use log::trace;
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::convert::AsRef;
 use std::fmt;
 use std::fs::File;
@@ -28,6 +39,90 @@ use std::path::{Path, PathBuf};
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
+    // plain values must precede the tables for serialization
+    /// Only check files matching any of these patterns, all if empty.
+    #[serde(default)]
+    pub include: Vec<PathPattern>,
+    /// Never check files matching any of these patterns.
+    #[serde(default)]
+    pub exclude: Vec<PathPattern>,
+    /// Only check these files if set, not part of the config file but
+    /// derived from i.e. `--staged`.
+    #[serde(skip)]
+    pub restrict_to: Option<HashSet<PathBuf>>,
+    /// Read the content of the files to check from the git index instead of
+    /// the working tree, not part of the config file but derived from
+    /// `--staged`.
+    #[serde(skip)]
+    pub from_index: bool,
+    /// Apply suggestions with exactly one replacement without asking, not
+    /// part of the config file but derived from `--accept-unambiguous`.
+    #[serde(skip)]
+    pub accept_unambiguous: bool,
+    /// Pick one replacement per misspelled word for all of its occurrences,
+    /// not part of the config file but derived from `--by-word`.
+    #[serde(skip)]
+    pub fix_by_word: bool,
+    /// Ignored words and identifiers, not part of the config file but loaded
+    /// from a separate ignore file.
+    #[serde(skip)]
+    pub ignore: IgnoreConfig,
+    /// Directory of the ignore file, not part of the config file but derived
+    /// from the checked project, see [`IgnoreConfig::dir_of`].
+    #[serde(skip)]
+    pub ignore_dir: Option<PathBuf>,
+    /// Skip files ignored by `.gitignore` and `.ignore` files.
+    #[serde(default = "default_true")]
+    pub respect_ignore_files: bool,
+    /// Follow symlinked files and directories during traversal.
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Do not follow modules which are only compiled if one of these cfg
+    /// predicates is true, i.e. `target_os = "windows"` or `feature = "nightly"`.
+    #[serde(default)]
+    pub exclude_cfg: Vec<String>,
+    /// Only check the documentation of `pub` items reachable from the crate
+    /// root, not part of the config file but derived from `--only-public`.
+    #[serde(skip)]
+    pub only_public: bool,
+    /// Extract doc comments and find the modules by scanning for `///`, `//!`
+    /// and `mod` lines instead of parsing the sources, i.e. for quick
+    /// pre-commit runs. Also enabled by `--fast`.
+    #[serde(default)]
+    pub fast_extraction: bool,
+    /// Check the documentation of items marked `#[doc(hidden)]`.
+    #[serde(default = "default_true")]
+    pub check_doc_hidden: bool,
+    /// Additional directories, whose `.rs` files are checked as well, i.e. for
+    /// generated code in `OUT_DIR`.
+    #[serde(default)]
+    pub extra_src_dirs: Vec<PathBuf>,
+    /// Flush corrected files and their directories to disk before they replace
+    /// the originals, for networked or crash-prone filesystems.
+    #[serde(default)]
+    pub sync_writes: bool,
+    /// The least severe findings which count as mistakes and fail the run.
+    #[serde(default)]
+    pub fail_on: Severity,
+    /// Only report findings the checkers are at least this many percent
+    /// certain of, findings without a confidence are always reported.
+    #[serde(default)]
+    pub min_confidence: u8,
+    /// Report at most this many suggestions per file, followed by a notice of
+    /// how many more were found. The remaining chunks of the file are not
+    /// checked once reached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_suggestions_per_file: Option<usize>,
+    /// Common mark files larger than this many bytes are checked in chunks,
+    /// split at paragraph boundaries.
+    #[serde(default = "default_large_file_size")]
+    pub large_file_size: usize,
+    /// Words ending with a period, which do not end a sentence, i.e. `e.g.`.
+    #[serde(default = "crate::sentence::default_abbreviations")]
+    pub abbreviations: Vec<String>,
+    /// Never report findings of these rules, i.e. `hunspell::unknown-word`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_rules: Vec<String>,
     #[serde(alias = "Hunspell")]
     pub hunspell: Option<HunspellConfig>,
     #[serde(alias = "LanguageTool")]
@@ -37,6 +132,165 @@ pub struct Config {
     #[serde(alias = "ReFlow")]
     #[serde(alias = "Reflow")]
     pub reflow: Option<ReflowConfig>,
+    #[serde(alias = "Consistency")]
+    pub consistency: Option<ConsistencyConfig>,
+    /// Disfavored terms to the preferred ones, i.e. `"web site" = "website"`.
+    #[serde(default, skip_serializing_if = "TerminologyConfig::is_empty")]
+    #[serde(alias = "Terminology")]
+    pub terminology: TerminologyConfig,
+    /// Terms disallowed by policy, i.e. deprecated product names.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(alias = "Banned")]
+    pub banned: BTreeMap<String, BannedTerm>,
+    #[serde(alias = "Categories")]
+    pub categories: Option<CategoriesConfig>,
+    /// Build targets checked besides the library and binaries.
+    #[serde(default)]
+    #[serde(alias = "Targets")]
+    pub targets: TargetsConfig,
+    /// Timeouts of the checkers.
+    #[serde(default)]
+    #[serde(alias = "Timeouts")]
+    pub timeouts: TimeoutConfig,
+    /// How the found mistakes are printed.
+    #[serde(default)]
+    #[serde(alias = "Output")]
+    pub output: OutputConfig,
+    /// Colors and text attributes of the human readable output and the
+    /// interactive picker.
+    #[serde(default)]
+    #[serde(alias = "Theme")]
+    pub theme: ThemeConfig,
+    /// Per path adjustments of the above, applied in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(alias = "Overrides")]
+    pub overrides: Vec<Override>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_large_file_size() -> usize {
+    1 << 20
+}
+
+/// Timeouts in seconds, after which the remaining chunks are skipped with a
+/// warning, unlimited if unset.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TimeoutConfig {
+    /// Time a checker may take for all files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checker: Option<u64>,
+    /// Time a checker may take for a single file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<u64>,
+}
+
+/// Build targets of a manifest which are checked besides the library and
+/// binaries, none by default.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TargetsConfig {
+    /// Integration tests, `tests/*.rs`.
+    #[serde(default)]
+    pub tests: bool,
+    /// Examples, `examples/*.rs`.
+    #[serde(default)]
+    pub examples: bool,
+    /// Benchmarks, `benches/*.rs`.
+    #[serde(default)]
+    pub benches: bool,
+    /// The build script, `build.rs`.
+    #[serde(default)]
+    pub build: bool,
+}
+
+impl std::str::FromStr for TargetsConfig {
+    type Err = Error;
+    /// Comma separated kinds of targets, all others are not checked.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut targets = Self::default();
+        for kind in s.split(',').map(str::trim).filter(|kind| !kind.is_empty()) {
+            match kind.to_lowercase().as_str() {
+                "tests" => targets.tests = true,
+                "examples" => targets.examples = true,
+                "benches" => targets.benches = true,
+                "build" => targets.build = true,
+                "all" => {
+                    targets = Self {
+                        tests: true,
+                        examples: true,
+                        benches: true,
+                        build: true,
+                    }
+                }
+                "none" => targets = Self::default(),
+                _ => bail!(
+                    "Unknown target kind `{}`, expected `tests`, `examples`, `benches`, `build`, `all` or `none`",
+                    kind
+                ),
+            }
+        }
+        Ok(targets)
+    }
+}
+
+/// The checkers which can be selected, by `--checkers` or per path.
+pub(crate) const CHECKER_NAMES: &[&str] = &[
+    "hunspell",
+    "languagetool",
+    "consistency",
+    "terminology",
+    "banned",
+];
+
+/// Deserialize checker names, failing on unknown ones instead of silently
+/// disabling all checkers which are not spelled right.
+fn checker_names<'de, D>(deserializer: D) -> std::result::Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    let checkers = Option::<Vec<String>>::deserialize(deserializer)?;
+    if let Some(unknown) = checkers
+        .iter()
+        .flatten()
+        .find(|checker| !CHECKER_NAMES.contains(&checker.to_lowercase().as_str()))
+    {
+        return Err(serde::de::Error::custom(format!(
+            "Unknown checker `{}`, expected one of {}",
+            unknown,
+            CHECKER_NAMES.join(", ")
+        )));
+    }
+    Ok(checkers)
+}
+
+/// Changes to the checker configuration for all files matching any of `paths`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Override {
+    pub paths: Vec<PathPattern>,
+    /// Restrict to these checkers, must be a subset of the configured ones.
+    #[serde(default, deserialize_with = "checker_names")]
+    pub checkers: Option<Vec<String>>,
+    /// Severity of all findings in matching files.
+    pub severity: Option<Severity>,
+    /// Hunspell dictionaries in addition to the configured ones.
+    #[serde(default)]
+    pub extra_dictionaries: Vec<PathBuf>,
+    /// Rules disabled in addition to the configured ones.
+    #[serde(default)]
+    pub disabled_rules: Vec<String>,
+    /// Report at most this many suggestions per matching file instead.
+    pub max_suggestions_per_file: Option<usize>,
+}
+
+impl Override {
+    pub fn matches(&self, path: &Path) -> bool {
+        PathPattern::any_matches(&self.paths, path)
+    }
 }
 
 #[derive(Debug)]
@@ -135,6 +389,10 @@ pub struct Quirks {
     /// that contain additional dashes.
     #[serde(default)]
     pub allow_dashes: bool,
+    /// Check numbers with unit suffixes and versions, i.e. `128GiB` or `v0.4.0`,
+    /// which are skipped otherwise.
+    #[serde(default)]
+    pub check_numbers: bool,
 }
 
 impl Default for Quirks {
@@ -145,10 +403,13 @@ impl Default for Quirks {
             transform_regex: vec![],
             allow_concatenation: false,
             allow_dashes: false,
+            check_numbers: false,
         }
     }
 }
 
+// only consulted by the hunspell checker
+#[cfg_attr(not(feature = "hunspell"), allow(dead_code))]
 impl Quirks {
     pub(crate) fn allow_concatenated(&self) -> bool {
         self.allow_concatenation
@@ -158,6 +419,10 @@ impl Quirks {
         self.allow_dashes
     }
 
+    pub(crate) fn check_numbers(&self) -> bool {
+        self.check_numbers
+    }
+
     pub(crate) fn transform_regex(&self) -> &[WrappedRegex] {
         &self.transform_regex
     }
@@ -176,9 +441,138 @@ pub struct HunspellConfig {
     /// Additional dictionaries for topic specific lingo.
     #[serde(default)]
     pub extra_dictionaries: Vec<PathBuf>,
+    /// Also use the personal dictionary of the user, if present.
+    #[serde(default = "default_true")]
+    pub use_personal_dictionary: bool,
+    /// Report words spelled the way of the other english locale than `lang`,
+    /// i.e. `colour` for `en_US`.
+    #[serde(default)]
+    pub enforce_locale: bool,
+    /// Handling of emoji, arrows and math symbols embedded in doc text.
+    #[serde(default)]
+    pub symbols: SymbolPolicy,
+    /// Severity of all findings of this checker.
+    #[serde(default)]
+    pub severity: Severity,
     /// Additional quirks besides dictionary lookups.
     #[serde(default)]
     pub quirks: Quirks,
+    /// Handling of words which only differ in case from a dictionary word.
+    #[serde(default)]
+    pub case: CaseConfig,
+    /// Tokens which are no words and are never looked up in the dictionary.
+    #[serde(default)]
+    pub skip: SkipConfig,
+}
+
+/// Case handling of dictionary lookups.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CaseConfig {
+    /// Accept words in any case, if the dictionary contains them in another
+    /// one, i.e. `github` or `GITHUB` for `GitHub`.
+    #[serde(default)]
+    pub ignore_case: bool,
+    /// Report all lowercase spellings of words, which the dictionary only
+    /// contains capitalized, i.e. proper nouns like `github` for `GitHub`.
+    #[serde(default = "default_true")]
+    pub flag_lowercase_proper_nouns: bool,
+    /// Adjust the replacements of misspelled words to the case pattern of
+    /// the word, i.e. `Receive` for `Recieve`.
+    #[serde(default)]
+    pub preserve_case: bool,
+}
+
+impl Default for CaseConfig {
+    fn default() -> Self {
+        Self {
+            ignore_case: false,
+            flag_lowercase_proper_nouns: true,
+            preserve_case: false,
+        }
+    }
+}
+
+impl CaseConfig {
+    /// If `word` is accepted, since it only differs in case from one of the
+    /// `replacements`.
+    #[cfg_attr(not(feature = "hunspell"), allow(dead_code))]
+    pub(crate) fn accepts(&self, word: &str, replacements: &[String]) -> bool {
+        let lowercase = word.to_lowercase();
+        if !replacements
+            .iter()
+            .any(|replacement| replacement.to_lowercase() == lowercase)
+        {
+            return false;
+        }
+        self.ignore_case || (!self.flag_lowercase_proper_nouns && word == lowercase)
+    }
+
+    /// The `replacements` of the misspelled `word`, adjusted to its case
+    /// pattern if enabled.
+    ///
+    /// This is the only place replacements are re-cased. Ones with capitals of
+    /// their own, i.e. proper nouns, are kept as they are.
+    pub(crate) fn adjust(&self, word: &str, replacements: Vec<String>) -> Vec<String> {
+        if !self.preserve_case {
+            return replacements;
+        }
+        let mut adjusted = Vec::with_capacity(replacements.len());
+        for replacement in replacements {
+            let replacement = crate::util::preserve_case(word, &replacement);
+            if !adjusted.contains(&replacement) {
+                adjusted.push(replacement);
+            }
+        }
+        adjusted
+    }
+}
+
+/// Tokens skipped by the dictionary lookup, all enabled by default.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SkipConfig {
+    /// File paths, i.e. `src/lib.rs` or `~/.config`.
+    #[serde(default = "default_true")]
+    pub paths: bool,
+    /// Email addresses, i.e. `alice@example.com`.
+    #[serde(default = "default_true")]
+    pub emails: bool,
+    /// Hex hashes of at least 7 digits and UUIDs.
+    #[serde(default = "default_true")]
+    pub hashes: bool,
+    /// Environment variable names, i.e. `$HOME`, `%APPDATA%` or `CARGO_HOME`.
+    #[serde(default = "default_true")]
+    pub env_vars: bool,
+}
+
+impl Default for SkipConfig {
+    fn default() -> Self {
+        Self {
+            paths: true,
+            emails: true,
+            hashes: true,
+            env_vars: true,
+        }
+    }
+}
+
+/// Handling of emoji, arrows and math symbols embedded in doc text.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolPolicy {
+    /// Symbols separate words, so `fast🚀` is checked as `fast`.
+    Boundary,
+    /// Words with an embedded symbol are not checked at all.
+    Ignore,
+    /// Symbols separate words and are reported.
+    Flag,
+}
+
+impl Default for SymbolPolicy {
+    fn default() -> Self {
+        Self::Boundary
+    }
 }
 
 impl HunspellConfig {
@@ -190,12 +584,61 @@ impl HunspellConfig {
         }
     }
 
+    #[cfg_attr(not(feature = "hunspell"), allow(dead_code))]
     pub fn search_dirs(&self) -> &[PathBuf] {
         &self.search_dirs
     }
 
-    pub fn extra_dictionaries(&self) -> &[PathBuf] {
-        &self.extra_dictionaries
+    /// The configured extra dictionaries, followed by the personal one if
+    /// enabled and present.
+    pub fn extra_dictionaries(&self) -> Vec<PathBuf> {
+        let mut extra_dictionaries = self.extra_dictionaries.clone();
+        if self.use_personal_dictionary {
+            extra_dictionaries.extend(Self::personal_dictionary());
+        }
+        extra_dictionaries
+    }
+
+    /// The personal dictionary in the user data directory, if it exists.
+    ///
+    /// It is used for every project, i.e. for names or preferred jargon.
+    /// Only looked up once per process.
+    pub fn personal_dictionary() -> Option<PathBuf> {
+        lazy_static::lazy_static! {
+            static ref PERSONAL_DICTIONARY: Option<PathBuf> =
+                HunspellConfig::find_personal_dictionary();
+        }
+        PERSONAL_DICTIONARY.clone()
+    }
+
+    /// Where the personal dictionary is located, whether it exists or not.
+    pub fn personal_dictionary_path() -> Option<PathBuf> {
+        let base = directories::ProjectDirs::from(
+            Config::QUALIFIER,
+            Config::ORGANIZATION,
+            Config::APPLICATION,
+        )?;
+        Some(base.data_dir().join("personal.dic"))
+    }
+
+    /// The dictionary words picked during `fix` are added to, the first of
+    /// the configured extra dictionaries, or else the personal dictionary.
+    pub fn dictionary_for_picks(&self) -> Option<PathBuf> {
+        self.extra_dictionaries
+            .first()
+            .cloned()
+            .or_else(Self::personal_dictionary_path)
+    }
+
+    fn find_personal_dictionary() -> Option<PathBuf> {
+        let path = Self::personal_dictionary_path()?;
+        if path.is_file() {
+            log::debug!("Using personal dictionary {}", path.display());
+            Some(path)
+        } else {
+            trace!("No personal dictionary at {}", path.display());
+            None
+        }
     }
 
     pub fn sanitize_paths(&mut self, base: &Path) -> Result<()> {
@@ -209,7 +652,7 @@ impl HunspellConfig {
                     search_dir.to_owned()
                 };
 
-                abspath.canonicalize().ok().map(|abspath| {
+                crate::util::canonicalize(&abspath).ok().map(|abspath| {
                     trace!(
                         "Sanitized ({} + {}) -> {}",
                         base.display(),
@@ -227,7 +670,7 @@ impl HunspellConfig {
         'o: for extra_dic in self.extra_dictionaries.iter_mut() {
             for search_dir in self.search_dirs.iter().filter_map(|search_dir| {
                 if !extra_dic.is_absolute() {
-                    base.join(&search_dir).canonicalize().ok()
+                    crate::util::canonicalize(&base.join(&search_dir)).ok()
                 } else {
                     Some(search_dir.to_owned())
                 }
@@ -237,7 +680,7 @@ impl HunspellConfig {
                 } else {
                     continue 'o;
                 };
-                if let Ok(abspath) = abspath.canonicalize() {
+                if let Ok(abspath) = crate::util::canonicalize(&abspath) {
                     if abspath.is_file() {
                         *extra_dic = abspath;
                         continue 'o;
@@ -260,6 +703,52 @@ impl HunspellConfig {
 #[serde(deny_unknown_fields)]
 pub struct LanguageToolConfig {
     pub url: url::Url,
+    /// Severity of all findings of this checker.
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// Reports words spelled in both the american and the british way.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConsistencyConfig {
+    /// Severity of all findings of this checker.
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+/// Disfavored terms and the severity of their findings.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TerminologyConfig {
+    /// Severity of all findings of this checker.
+    #[serde(default)]
+    pub severity: Severity,
+    /// Disfavored terms to the preferred ones.
+    #[serde(default)]
+    pub terms: BTreeMap<String, String>,
+}
+
+impl TerminologyConfig {
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.terms.clear();
+    }
+}
+
+/// A term which is disallowed by policy, even though it is spelled correctly.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct BannedTerm {
+    /// What to use instead, if anything.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    /// Severity of the findings of this term.
+    #[serde(default)]
+    pub severity: Severity,
 }
 
 impl LanguageToolConfig {
@@ -279,11 +768,145 @@ impl Config {
         if let Some(ref mut hunspell) = self.hunspell {
             hunspell.sanitize_paths(base)?;
         }
+        for dir in self.extra_src_dirs.iter_mut() {
+            if !dir.is_absolute() {
+                *dir = base.join(&dir);
+            }
+        }
+        for ovr in self.overrides.iter_mut() {
+            if ovr.extra_dictionaries.is_empty() {
+                continue;
+            }
+            // resolve the same way as the regular extra dictionaries
+            let mut hunspell = self.hunspell.clone().ok_or_else(|| {
+                anyhow!("Overrides with extra dictionaries require a hunspell config")
+            })?;
+            hunspell.extra_dictionaries = std::mem::take(&mut ovr.extra_dictionaries);
+            hunspell.sanitize_paths(base)?;
+            ovr.extra_dictionaries = hunspell.extra_dictionaries;
+        }
+        Ok(())
+    }
+
+    /// Check if the file at `path` passes the include and exclude filters.
+    pub fn is_included(&self, path: &Path) -> bool {
+        if let Some(ref restrict_to) = self.restrict_to {
+            if !restrict_to.contains(path) {
+                return false;
+            }
+        }
+        (self.include.is_empty() || PathPattern::any_matches(&self.include, path))
+            && !PathPattern::any_matches(&self.exclude, path)
+    }
+
+    /// Derive the configuration that applies with the given override in place.
+    pub fn with_override(&self, ovr: &Override) -> Self {
+        let mut config = self.clone();
+        if let Some(ref checkers) = ovr.checkers {
+            let checkers = checkers
+                .iter()
+                .map(|checker| checker.to_lowercase())
+                .collect::<Vec<_>>();
+            if !checkers.contains(&"hunspell".to_owned()) {
+                config.hunspell = None;
+            }
+            if !checkers.contains(&"languagetool".to_owned()) {
+                config.languagetool = None;
+            }
+            if !checkers.contains(&"consistency".to_owned()) {
+                config.consistency = None;
+            }
+            if !checkers.contains(&"terminology".to_owned()) {
+                config.terminology.clear();
+            }
+            if !checkers.contains(&"banned".to_owned()) {
+                config.banned.clear();
+            }
+        }
+        if let Some(ref mut hunspell) = config.hunspell {
+            if let Some(severity) = ovr.severity {
+                hunspell.severity = severity;
+            }
+            hunspell
+                .extra_dictionaries
+                .extend(ovr.extra_dictionaries.iter().cloned());
+        }
+        config
+            .disabled_rules
+            .extend(ovr.disabled_rules.iter().cloned());
+        if let Some(ref mut languagetool) = config.languagetool {
+            if let Some(severity) = ovr.severity {
+                languagetool.severity = severity;
+            }
+        }
+        if let Some(ref mut reflow) = config.reflow {
+            if let Some(severity) = ovr.severity {
+                reflow.severity = severity;
+            }
+        }
+        if let Some(ref mut consistency) = config.consistency {
+            if let Some(severity) = ovr.severity {
+                consistency.severity = severity;
+            }
+        }
+        if let Some(severity) = ovr.severity {
+            config.terminology.severity = severity;
+            for banned in config.banned.values_mut() {
+                banned.severity = severity;
+            }
+        }
+        if ovr.max_suggestions_per_file.is_some() {
+            config.max_suggestions_per_file = ovr.max_suggestions_per_file;
+        }
+        config
+    }
+
+    /// The reflow configuration, the default if reflow is not configured,
+    /// with the abbreviations which must not end a line.
+    pub fn reflow_config(&self) -> ReflowConfig {
+        let mut reflow = self.reflow.clone().unwrap_or_default();
+        reflow.abbreviations = self.abbreviations.clone();
+        reflow
+    }
+
+    /// The case handling of replacements, configured for the dictionary
+    /// lookups.
+    pub fn case(&self) -> CaseConfig {
+        self.hunspell
+            .as_ref()
+            .map(|hunspell| hunspell.case.clone())
+            .unwrap_or_default()
+    }
+
+    /// Derive the configuration that applies to the file at `path`.
+    pub fn for_path(&self, path: &Path) -> Self {
+        self.overrides
+            .iter()
+            .filter(|ovr| ovr.matches(path))
+            .fold(self.clone(), |config, ovr| config.with_override(ovr))
+    }
+
+    /// Check that the configured values are in range and can be combined.
+    pub fn validate(&self) -> Result<()> {
+        if self.min_confidence > 100 {
+            bail!(
+                "`min_confidence` is a percentage, {} is more than 100",
+                self.min_confidence
+            );
+        }
+        if self.fast_extraction && self.only_public {
+            bail!("Scanning the sources with `--fast` does not support `--only-public`");
+        }
+        if self.fast_extraction && !self.exclude_cfg.is_empty() {
+            bail!("Scanning the sources with `--fast` does not support `exclude_cfg`");
+        }
         Ok(())
     }
 
     pub fn parse<S: AsRef<str>>(s: S) -> Result<Self> {
-        Ok(toml::from_str(s.as_ref())?)
+        let config: Self = toml::from_str(s.as_ref())?;
+        config.validate()?;
+        Ok(config)
     }
 
     pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -308,18 +931,6 @@ impl Config {
             })
     }
 
-    pub fn load() -> Result<Self> {
-        if let Some(base) = directories::BaseDirs::new() {
-            Self::load_from(
-                base.config_dir()
-                    .join("cargo_spellcheck")
-                    .join("config.toml"),
-            )
-        } else {
-            bail!("No idea where your config directory is located. XDG compliance would be nice.")
-        }
-    }
-
     pub fn to_toml(&self) -> Result<String> {
         toml::to_string(self).map_err(|e| anyhow!("Failed to convert to toml").context(e))
     }
@@ -351,15 +962,6 @@ impl Config {
         Ok(self.clone())
     }
 
-    pub fn write_values_to_default_path(&self) -> Result<Self> {
-        let path = Self::default_path()?;
-        self.write_values_to_path(path)
-    }
-
-    pub fn write_default_values_to<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Self::default().write_values_to_path(path)
-    }
-
     pub fn default_path() -> Result<PathBuf> {
         if let Some(base) =
             directories::ProjectDirs::from(Self::QUALIFIER, Self::ORGANIZATION, Self::APPLICATION)
@@ -378,7 +980,7 @@ impl Config {
             .join(".config")
             .join("spellcheck.toml");
 
-        let path = path.canonicalize()?;
+        let path = crate::util::canonicalize(&path)?;
 
         if path.is_file() {
             Ok(path)
@@ -390,16 +992,14 @@ impl Config {
         }
     }
 
-    pub fn write_default_values() -> Result<Self> {
-        let d = Self::default_path()?;
-        Self::write_default_values_to(d.join("config.toml"))
-    }
-
     pub fn is_enabled(&self, detector: Detector) -> bool {
         match detector {
             Detector::Hunspell => self.hunspell.is_some(),
             Detector::LanguageTool => self.languagetool.is_some(),
             Detector::Reflow => self.reflow.is_some(),
+            Detector::Consistency => self.consistency.is_some(),
+            Detector::Terminology => !self.terminology.is_empty(),
+            Detector::Banned => !self.banned.is_empty(),
             #[cfg(test)]
             Detector::Dummy => true,
         }
@@ -408,6 +1008,7 @@ impl Config {
     pub fn full() -> Self {
         let languagetool = LanguageToolConfig {
             url: url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok"),
+            severity: Severity::default(),
         };
         Self {
             languagetool: Some(languagetool),
@@ -423,30 +1024,58 @@ impl Default for Config {
                 lang: Some("en_US".to_owned()),
                 search_dirs: SearchDirs::default(),
                 extra_dictionaries: Vec::new(),
+                use_personal_dictionary: true,
+                enforce_locale: false,
+                symbols: SymbolPolicy::default(),
                 quirks: Quirks::default(),
+                case: CaseConfig::default(),
+                skip: SkipConfig::default(),
+                severity: Severity::default(),
             }),
             // disabled by default, it's still
             // experimental and requires additional setup
             languagetool: None,
             reflow: Some(ReflowConfig::default()),
+            // disabled by default, since it considers all files at once
+            consistency: None,
+            terminology: TerminologyConfig::default(),
+            banned: BTreeMap::new(),
+            categories: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            restrict_to: None,
+            from_index: false,
+            accept_unambiguous: false,
+            fix_by_word: false,
+            ignore: IgnoreConfig::default(),
+            ignore_dir: None,
+            respect_ignore_files: true,
+            follow_symlinks: false,
+            exclude_cfg: Vec::new(),
+            only_public: false,
+            fast_extraction: false,
+            check_doc_hidden: true,
+            extra_src_dirs: Vec::new(),
+            sync_writes: false,
+            fail_on: Severity::default(),
+            min_confidence: 0,
+            max_suggestions_per_file: None,
+            large_file_size: default_large_file_size(),
+            abbreviations: crate::sentence::default_abbreviations(),
+            disabled_rules: Vec::new(),
+            targets: TargetsConfig::default(),
+            timeouts: TimeoutConfig::default(),
+            output: OutputConfig::default(),
+            theme: ThemeConfig::default(),
+            overrides: Vec::new(),
         }
     }
 }
 
-// TODO figure out which ISO spec this actually is
-pub struct CommonLang(String);
-
-impl std::str::FromStr for CommonLang {
-    type Err = Error;
-    fn from_str(_s: &str) -> std::result::Result<Self, Self::Err> {
-        //
-        unimplemented!("Common Lang needs a ref spec")
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Category;
 
     #[test]
     fn all() {
@@ -464,6 +1093,17 @@ extra_dictionaries = ["/home/bernhard/test.dic"]
         .unwrap();
     }
 
+    #[test]
+    fn roundtrip() {
+        let mut config = Config::default();
+        config.include.push("src/**".parse().unwrap());
+        config.categories = Some(CategoriesConfig::default());
+        let toml = config.to_toml().unwrap();
+        let config = Config::parse(&toml).unwrap();
+        assert_eq!(config.include.len(), 1);
+        assert!(config.categories.is_some());
+    }
+
     #[test]
     fn empty() {
         assert!(Config::parse(
@@ -592,6 +1232,462 @@ search_dirs = ["/search/1", "/search/2"]
         assert!(search_dirs.len() >= 3);
     }
 
+    #[test]
+    fn severity() {
+        let cfg = Config::parse(
+            r#"
+[LanguageTool]
+url = "http://127.0.0.1:8010/"
+severity = "warning"
+
+[Hunspell]
+severity = "info"
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.hunspell.unwrap().severity, Severity::Info);
+        assert_eq!(cfg.languagetool.unwrap().severity, Severity::Warning);
+
+        assert!(Config::parse(
+            r#"
+[Hunspell]
+severity = "fatal"
+"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn categories() {
+        let cfg = Config::parse(
+            r#"
+[categories]
+grammar = { enabled = false }
+style = { severity = "info" }
+
+[[categories.overrides]]
+paths = ["benches/**"]
+spelling = { severity = "warning" }
+style = { enabled = false }
+"#,
+        )
+        .unwrap();
+        let categories = cfg.categories.expect("Must contain categories cfg");
+
+        let src = Path::new("src/lib.rs");
+        assert!(!categories.resolve(Category::Grammar, src).is_enabled());
+        assert_eq!(
+            categories.resolve(Category::Style, src),
+            CategoryConfig {
+                enabled: None,
+                severity: Some(Severity::Info),
+            }
+        );
+        assert_eq!(
+            categories.resolve(Category::Spelling, src),
+            CategoryConfig::default()
+        );
+
+        let bench = Path::new("benches/fancy/bench.rs");
+        assert_eq!(
+            categories.resolve(Category::Spelling, bench).severity,
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            categories.resolve(Category::Style, bench),
+            CategoryConfig {
+                enabled: Some(false),
+                severity: Some(Severity::Info),
+            }
+        );
+
+        assert!(Config::parse(
+            r#"
+[categories]
+spelling = { enabled = false, color = "red" }
+"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn include_exclude() {
+        let cfg = Config::parse(
+            r#"
+include = ["src/**", "README.md"]
+exclude = ["src/generated/**"]
+"#,
+        )
+        .unwrap();
+        assert!(cfg.is_included(Path::new("src/lib.rs")));
+        assert!(cfg.is_included(Path::new("README.md")));
+        assert!(!cfg.is_included(Path::new("src/generated/bindings.rs")));
+        assert!(!cfg.is_included(Path::new("benches/bench.rs")));
+
+        let cfg = Config::parse(
+            r#"
+exclude = ["target/**"]
+"#,
+        )
+        .unwrap();
+        assert!(cfg.is_included(Path::new("src/lib.rs")));
+        assert!(!cfg.is_included(Path::new("target/debug/build/out.rs")));
+
+        assert!(Config::parse(
+            r#"
+exclude = ["src/[**"]
+"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn respect_ignore_files() {
+        assert!(Config::parse("").unwrap().respect_ignore_files);
+        assert!(
+            !Config::parse("respect_ignore_files = false")
+                .unwrap()
+                .respect_ignore_files
+        );
+    }
+
+    #[test]
+    fn use_personal_dictionary() {
+        let config = Config::parse("[hunspell]").unwrap();
+        assert!(config.hunspell.unwrap().use_personal_dictionary);
+        let config = Config::parse(
+            r#"[hunspell]
+extra_dictionaries = ["/tmp/project.dic"]
+use_personal_dictionary = false
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.hunspell.unwrap().extra_dictionaries(),
+            vec![PathBuf::from("/tmp/project.dic")]
+        );
+    }
+
+    #[test]
+    fn sync_writes() {
+        assert!(!Config::parse("").unwrap().sync_writes);
+        assert!(Config::parse("sync_writes = true").unwrap().sync_writes);
+    }
+
+    #[test]
+    fn fail_on() {
+        assert_eq!(Config::parse("").unwrap().fail_on, Severity::Error);
+        let config = Config::parse(r#"fail_on = "warning""#).unwrap();
+        assert!(Severity::Warning.fails(config.fail_on));
+        assert!(!Severity::Info.fails(config.fail_on));
+        assert!(Config::parse(r#"fail_on = "fatal""#).is_err());
+    }
+
+    #[test]
+    fn max_suggestions_per_file() {
+        assert_eq!(Config::parse("").unwrap().max_suggestions_per_file, None);
+        let config = Config::parse("max_suggestions_per_file = 100").unwrap();
+        assert_eq!(config.max_suggestions_per_file, Some(100));
+    }
+
+    #[test]
+    fn min_confidence() {
+        assert_eq!(Config::parse("").unwrap().min_confidence, 0);
+        let config = Config::parse("min_confidence = 60").unwrap();
+        assert_eq!(config.min_confidence, 60);
+        assert!(Config::parse("min_confidence = 100").is_ok());
+        assert!(Config::parse("min_confidence = 101").is_err());
+    }
+
+    #[test]
+    fn timeouts() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.timeouts.checker, None);
+        assert_eq!(config.timeouts.file, None);
+        let config = Config::parse("[timeouts]\nchecker = 300\nfile = 30").unwrap();
+        assert_eq!(config.timeouts.checker, Some(300));
+        assert_eq!(config.timeouts.file, Some(30));
+    }
+
+    #[test]
+    fn large_file_size() {
+        assert_eq!(Config::parse("").unwrap().large_file_size, 1 << 20);
+        let config = Config::parse("large_file_size = 4096").unwrap();
+        assert_eq!(config.large_file_size, 4096);
+    }
+
+    #[test]
+    fn abbreviations() {
+        let config = Config::parse("").unwrap();
+        assert!(config.abbreviations.contains(&"e.g.".to_owned()));
+        assert!(config
+            .reflow_config()
+            .abbreviations
+            .contains(&"e.g.".to_owned()));
+        let config = Config::parse(r#"abbreviations = ["approx."]"#).unwrap();
+        assert_eq!(config.abbreviations, vec!["approx.".to_owned()]);
+    }
+
+    #[test]
+    fn check_doc_hidden() {
+        assert!(Config::parse("").unwrap().check_doc_hidden);
+        assert!(Config::default().check_doc_hidden);
+        assert!(
+            !Config::parse("check_doc_hidden = false")
+                .unwrap()
+                .check_doc_hidden
+        );
+    }
+
+    #[test]
+    fn fast_extraction() {
+        let mut config = Config::parse("fast_extraction = true").unwrap();
+        assert!(config.validate().is_ok());
+        config.only_public = true;
+        assert!(config.validate().is_err());
+        assert!(Config::parse(
+            r#"
+fast_extraction = true
+exclude_cfg = ["windows"]
+"#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn terminology() {
+        let config = Config::parse(
+            r#"
+[terminology.terms]
+"web site" = "website"
+"crate-level" = "crate level"
+"#,
+        )
+        .unwrap();
+        assert!(config.is_enabled(Detector::Terminology));
+        assert_eq!(config.terminology.terms["web site"], "website");
+        assert_eq!(config.terminology.severity, Severity::Error);
+        assert!(!Config::parse("").unwrap().is_enabled(Detector::Terminology));
+
+        let config = Config::parse(
+            r#"
+[terminology]
+severity = "info"
+
+[terminology.terms]
+"web site" = "website"
+severity = "severe"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.terminology.severity, Severity::Info);
+        assert_eq!(config.terminology.terms["severity"], "severe");
+        assert_eq!(config.terminology.terms.len(), 2);
+        let serialized = Config::parse(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(serialized.terminology, config.terminology);
+        assert!(Config::parse("[terminology]\nseverty = \"warning\"").is_err());
+    }
+
+    #[test]
+    fn banned() {
+        let config = Config::parse(
+            r#"
+[banned]
+"Acme Cloud" = { replacement = "Acme Platform", severity = "warning" }
+"foobar" = {}
+"#,
+        )
+        .unwrap();
+        assert!(config.is_enabled(Detector::Banned));
+        assert_eq!(
+            config.banned["Acme Cloud"],
+            BannedTerm {
+                replacement: Some("Acme Platform".to_owned()),
+                severity: Severity::Warning,
+            }
+        );
+        assert_eq!(config.banned["foobar"], BannedTerm::default());
+        assert!(Config::parse("[banned]\nfoobar = { reason = \"none\" }").is_err());
+        assert!(!Config::parse("").unwrap().is_enabled(Detector::Banned));
+    }
+
+    #[test]
+    fn skip() {
+        let config = Config::parse(
+            r#"
+[Hunspell.skip]
+emails = false
+"#,
+        )
+        .unwrap();
+        let skip = config.hunspell.unwrap().skip;
+        assert!(skip.paths && skip.hashes && skip.env_vars);
+        assert!(!skip.emails);
+    }
+
+    #[test]
+    fn symbols() {
+        let config = Config::default();
+        assert_eq!(config.hunspell.unwrap().symbols, SymbolPolicy::Boundary);
+        let config = Config::parse(
+            r#"
+[Hunspell]
+symbols = "flag"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.hunspell.unwrap().symbols, SymbolPolicy::Flag);
+        assert!(Config::parse(
+            r#"
+[Hunspell]
+symbols = "remove"
+"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn case() {
+        let replacements = vec!["GitHub".to_owned(), "Git hub".to_owned()];
+        let case = CaseConfig::default();
+        assert!(!case.accepts("github", &replacements));
+        assert!(!case.accepts("Github", &replacements));
+        let case = CaseConfig {
+            flag_lowercase_proper_nouns: false,
+            ..CaseConfig::default()
+        };
+        assert!(case.accepts("github", &replacements));
+        assert!(!case.accepts("Github", &replacements));
+        assert!(!case.accepts("gitlab", &replacements));
+        let case = CaseConfig {
+            ignore_case: true,
+            ..CaseConfig::default()
+        };
+        assert!(case.accepts("GITHUB", &replacements));
+
+        let replacements = vec![
+            "receive".to_owned(),
+            "relieve".to_owned(),
+            "Recife".to_owned(),
+        ];
+        assert_eq!(
+            CaseConfig::default().adjust("Recieve", replacements.clone()),
+            replacements
+        );
+        let case = CaseConfig {
+            preserve_case: true,
+            ..CaseConfig::default()
+        };
+        assert_eq!(
+            case.adjust("recieve", replacements.clone()),
+            vec!["receive", "relieve", "Recife"]
+        );
+        assert_eq!(
+            case.adjust("Recieve", replacements.clone()),
+            vec!["Receive", "Relieve", "Recife"]
+        );
+        assert_eq!(
+            case.adjust("RECIEVE", replacements),
+            vec!["RECEIVE", "RELIEVE", "Recife"]
+        );
+        assert_eq!(
+            case.adjust("Recieve", vec!["receive".to_owned(), "Receive".to_owned()]),
+            vec!["Receive"]
+        );
+
+        let config = Config::parse(
+            r#"
+[Hunspell.case]
+ignore_case = true
+"#,
+        )
+        .unwrap();
+        let case = config.hunspell.unwrap().case;
+        assert!(case.ignore_case);
+        assert!(case.flag_lowercase_proper_nouns);
+    }
+
+    #[test]
+    fn follow_symlinks() {
+        assert!(!Config::parse("").unwrap().follow_symlinks);
+        assert!(
+            Config::parse("follow_symlinks = true")
+                .unwrap()
+                .follow_symlinks
+        );
+    }
+
+    #[test]
+    fn overrides() {
+        let cfg = Config::parse(
+            r#"
+[LanguageTool]
+url = "http://127.0.0.1:8010/"
+
+[Hunspell]
+lang = "en_US"
+
+[Reflow]
+max_line_length = 100
+
+[consistency]
+
+[banned]
+"Acme Cloud" = { severity = "warning" }
+
+[[overrides]]
+paths = ["benches/**", "vendor/**"]
+checkers = ["hunspell", "consistency", "banned"]
+severity = "warning"
+
+[[overrides]]
+paths = ["vendor/**"]
+severity = "info"
+max_suggestions_per_file = 10
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.overrides.len(), 2);
+
+        let src = cfg.for_path(Path::new("src/lib.rs"));
+        assert!(src.is_enabled(Detector::LanguageTool));
+        assert_eq!(src.reflow_config().severity, Severity::Error);
+        assert_eq!(src.hunspell.unwrap().severity, Severity::Error);
+        assert_eq!(src.consistency.unwrap().severity, Severity::Error);
+        assert_eq!(src.banned["Acme Cloud"].severity, Severity::Warning);
+
+        let bench = cfg.for_path(Path::new("benches/bench.rs"));
+        assert!(!bench.is_enabled(Detector::LanguageTool));
+        assert_eq!(bench.hunspell.unwrap().severity, Severity::Warning);
+        assert_eq!(bench.consistency.unwrap().severity, Severity::Warning);
+
+        let vendor = cfg.for_path(Path::new("vendor/dep/lib.rs"));
+        assert!(!vendor.is_enabled(Detector::LanguageTool));
+        assert_eq!(vendor.reflow_config().severity, Severity::Info);
+        assert_eq!(vendor.hunspell.unwrap().severity, Severity::Info);
+        assert_eq!(vendor.banned["Acme Cloud"].severity, Severity::Info);
+        assert_eq!(vendor.max_suggestions_per_file, Some(10));
+        assert_eq!(bench.max_suggestions_per_file, None);
+
+        assert!(Config::parse(
+            r#"
+[[overrides]]
+paths = ["benches/**"]
+dictionaries = []
+"#,
+        )
+        .is_err());
+        let typo = Config::parse(
+            r#"
+[[overrides]]
+paths = ["benches/**"]
+checkers = ["hunspel"]
+"#,
+        )
+        .unwrap_err();
+        assert!(typo.to_string().contains("Unknown checker `hunspel`"));
+    }
+
     #[test]
     fn partial_9() {
         let cfg = Config::parse(
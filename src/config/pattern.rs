@@ -0,0 +1,87 @@
+use super::*;
+
+/// A glob pattern to match file paths against, i.e. `benches/**`.
+#[derive(Debug, Clone)]
+pub struct PathPattern(pub glob::Pattern);
+
+impl PathPattern {
+    /// Check if the pattern matches the given path.
+    ///
+    /// Relative patterns are matched against the path relative to the
+    /// current working directory, absolute ones against the full path. On
+    /// windows, paths are matched ignoring the case.
+    pub fn matches(&self, path: &Path) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: !cfg!(windows),
+            ..glob::MatchOptions::new()
+        };
+        if self.0.matches_path_with(path, options) {
+            return true;
+        }
+        std::env::current_dir()
+            .ok()
+            .and_then(|cwd| {
+                crate::util::strip_path_prefix(path, &cwd)
+                    .map(|path| self.0.matches_path_with(path, options))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Check if any of the given `patterns` matches `path`.
+    pub fn any_matches(patterns: &[PathPattern], path: &Path) -> bool {
+        patterns.iter().any(|pattern| pattern.matches(path))
+    }
+}
+
+impl std::ops::Deref for PathPattern {
+    type Target = glob::Pattern;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Serialize for PathPattern {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PathPattern {
+    fn deserialize<D>(deserializer: D) -> Result<PathPattern, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(PathPatternVisitor)
+    }
+}
+
+impl std::str::FromStr for PathPattern {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        glob::Pattern::new(s)
+            .map(PathPattern)
+            .map_err(|e| anyhow!("Invalid glob pattern {}", s).context(e))
+    }
+}
+
+struct PathPatternVisitor;
+
+impl<'de> serde::de::Visitor<'de> for PathPatternVisitor {
+    type Value = PathPattern;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("String with a valid glob pattern")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        glob::Pattern::new(value)
+            .map(PathPattern)
+            .map_err(E::custom)
+    }
+}
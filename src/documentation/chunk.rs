@@ -78,6 +78,10 @@ pub struct CheckableChunk {
     source_mapping: IndexMap<Range, Span>,
     /// Track what kind of comment the chunk is.
     variant: CommentVariant,
+    /// Documents an item marked `#[doc(hidden)]`.
+    hidden: bool,
+    /// Documents an item which is not reachable from the crate root.
+    private: bool,
 }
 
 impl std::hash::Hash for CheckableChunk {
@@ -94,7 +98,11 @@ impl std::hash::Hash for CheckableChunk {
 impl CheckableChunk {
     /// Specific to rust source code, either as part of doc test comments or file scope.
     pub fn from_literalset(set: LiteralSet) -> Self {
-        set.into_chunk()
+        let (hidden, private) = (set.hidden, set.private);
+        let mut chunk = set.into_chunk();
+        chunk.hidden = hidden;
+        chunk.private = private;
+        chunk
     }
 
     /// Load content from string, may contain common mark content.
@@ -116,9 +124,28 @@ impl CheckableChunk {
             content,
             source_mapping,
             variant,
+            hidden: false,
+            private: false,
         }
     }
 
+    /// Mark the chunk as documenting an item marked `#[doc(hidden)]`.
+    pub(crate) fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// If the chunk documents an item marked `#[doc(hidden)]`.
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// If the chunk documents an item which is not reachable from the crate
+    /// root, i.e. which is not `pub` or within a function body.
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
+
     /// Find which part of the range maps to which span.
     /// Note that Range can very well be split into multiple fragments
     /// where each of them can be mapped to a potentially non-continuous
@@ -133,7 +160,7 @@ impl CheckableChunk {
     ///           (13,17) => (4,0)->(4,3),
     /// ]
     /// ```
-    pub(crate) fn find_spans(&self, range: Range) -> IndexMap<Range, Span> {
+    pub fn find_spans(&self, range: Range) -> IndexMap<Range, Span> {
         trace!(target: "find_spans",
             "Chunk find_span {:?}",
             &range
@@ -158,11 +185,8 @@ impl CheckableChunk {
                 (fragment_span, fragment_range, sub_fragment_range)
             })
             .inspect(|(fragment_span, fragment_range, sub_fragment_range)| {
-                let (fragment_span, fragment_range, sub_fragment_range) = (
-                    fragment_span.clone(),
-                    fragment_range.clone(),
-                    sub_fragment_range.clone(),
-                );
+                let (fragment_span, fragment_range, sub_fragment_range) =
+                    (*fragment_span, *fragment_range, sub_fragment_range.clone());
                 log::trace!(target: "find_spans",
                     ">> fragment: span: {:?} => range: {:?} | sub: {:?} -> sub_fragment: {:?}",
                     &fragment_span,
@@ -353,12 +377,12 @@ impl CheckableChunk {
     }
 
     /// Get the display wrapper type to be used with i.e. `format!(..)`.
-    pub fn display(&self, range: Range) -> ChunkDisplay {
+    pub fn display(&self, range: Range) -> ChunkDisplay<'_> {
         ChunkDisplay::from((self, range))
     }
 
     /// Iterate over all ranges and the associated span.
-    pub fn iter(&self) -> indexmap::map::Iter<Range, Span> {
+    pub fn iter(&self) -> indexmap::map::Iter<'_, Range, Span> {
         self.source_mapping.iter()
     }
 
@@ -371,7 +395,7 @@ impl CheckableChunk {
     }
 
     /// Obtain an accessor object containing mapping and string repr, removing the markdown anotations.
-    pub fn erase_cmark(&self) -> PlainOverlay {
+    pub fn erase_cmark(&self) -> PlainOverlay<'_> {
         PlainOverlay::erase_cmark(self)
     }
 
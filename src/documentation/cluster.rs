@@ -4,6 +4,7 @@ use super::{trace, LiteralSet, Spacing, TokenTree, TrimmedLiteral, TryInto};
 use crate::documentation::Range;
 use crate::Span;
 use anyhow::{anyhow, Error, Result};
+use proc_macro2::{Delimiter, Group};
 use std::convert::TryFrom;
 
 /// Cluster literals for one file
@@ -12,84 +13,289 @@ pub struct Clusters {
     pub(super) set: Vec<LiteralSet>,
 }
 
+/// The attribute starting at `idx`, either `#[..]` or `#![..]`, as whether
+/// it is an inner attribute, its bracketed content and its number of tokens.
+fn attribute_at(trees: &[TokenTree], idx: usize) -> Option<(bool, &Group, usize)> {
+    let is_punct = |idx: usize, c: char| matches!(trees.get(idx), Some(TokenTree::Punct(punct)) if punct.as_char() == c);
+    if !is_punct(idx, '#') {
+        return None;
+    }
+    let inner = is_punct(idx + 1, '!');
+    let (group_idx, len) = if inner { (idx + 2, 3) } else { (idx + 1, 2) };
+    match trees.get(group_idx) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            Some((inner, group, len))
+        }
+        _ => None,
+    }
+}
+
+/// If the content of an attribute is `doc(hidden)`.
+fn is_doc_hidden(attribute: &Group) -> bool {
+    let mut iter = attribute.stream().into_iter();
+    match (iter.next(), iter.next()) {
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(args)))
+            if ident == "doc" && args.delimiter() == Delimiter::Parenthesis =>
+        {
+            args.stream()
+                .into_iter()
+                .any(|tree| matches!(tree, TokenTree::Ident(ident) if ident == "hidden"))
+        }
+        _ => false,
+    }
+}
+
+/// If the content of an attribute is `macro_export`, which makes a macro
+/// public without `pub`.
+fn is_macro_export(attribute: &Group) -> bool {
+    let mut iter = attribute.stream().into_iter();
+    matches!(
+        (iter.next(), iter.next()),
+        (Some(TokenTree::Ident(ident)), None) if ident == "macro_export"
+    )
+}
+
+/// The index after the item starting at `idx`, which ends with a top level
+/// `;` or `,`, or its braced body.
+fn item_end(trees: &[TokenTree], mut idx: usize) -> usize {
+    let is_separator = |tree: Option<&TokenTree>| matches!(tree, Some(TokenTree::Punct(punct)) if matches!(punct.as_char(), ';' | ','));
+    // commas within generics do not end the item
+    let mut generics = 0usize;
+    while idx < trees.len() && attribute_at(trees, idx).is_none() {
+        match &trees[idx] {
+            TokenTree::Punct(punct) if punct.as_char() == ';' => return idx + 1,
+            TokenTree::Punct(punct) if punct.as_char() == '<' => generics += 1,
+            // not part of an arrow, i.e. `->` or `=>`
+            TokenTree::Punct(punct)
+                if punct.as_char() == '>'
+                    && !matches!(
+                        idx.checked_sub(1).and_then(|prev| trees.get(prev)),
+                        Some(TokenTree::Punct(prev))
+                            if matches!(prev.as_char(), '-' | '=') && prev.spacing() == Spacing::Joint
+                    ) =>
+            {
+                generics = generics.saturating_sub(1)
+            }
+            tree if generics == 0 && is_separator(Some(tree)) => return idx + 1,
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace && generics == 0 => {
+                idx += 1;
+                if is_separator(trees.get(idx)) {
+                    idx += 1;
+                }
+                return idx;
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+    idx
+}
+
+/// The context of the items of a token stream.
+#[derive(Debug, Clone, Copy, Default)]
+struct Scope {
+    /// The enclosing item is marked `#[doc(hidden)]`.
+    hidden: bool,
+    /// The enclosing item is not reachable from the crate root.
+    private: bool,
+    /// Items share the visibility of the enclosing item, i.e. enum variants,
+    /// trait items or items of trait implementations, and do not require `pub`.
+    inherited: bool,
+}
+
+impl Scope {
+    /// The scope of the item `item` with the given outer attributes, and the
+    /// scope of everything within it.
+    fn of_item(self, item: &[TokenTree], attributes: &[&Group]) -> (Self, Self) {
+        let is_ident =
+            |tree: &TokenTree, name: &str| matches!(tree, TokenTree::Ident(ident) if ident == name);
+        // the first keyword determines the kind of the item
+        let keyword = item.iter().find_map(|tree| match tree {
+            TokenTree::Ident(ident)
+                if matches!(
+                    ident.to_string().as_str(),
+                    "fn" | "struct"
+                        | "enum"
+                        | "union"
+                        | "trait"
+                        | "impl"
+                        | "mod"
+                        | "const"
+                        | "static"
+                        | "type"
+                        | "macro_rules"
+                        | "extern"
+                        | "use"
+                ) =>
+            {
+                Some(ident.to_string())
+            }
+            _ => None,
+        });
+        // `pub(crate)` and alike are not part of the public api
+        let public = matches!(item.first(), Some(tree) if is_ident(tree, "pub"))
+            && !matches!(item.get(1), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis);
+        // implementations have no visibility on their own
+        let public = public
+            || self.inherited
+            || keyword.as_deref() == Some("impl")
+            || attributes
+                .iter()
+                .any(|attribute| is_macro_export(attribute));
+        let scope = Self {
+            hidden: self.hidden || attributes.iter().any(|attribute| is_doc_hidden(attribute)),
+            private: self.private || !public,
+            inherited: false,
+        };
+        let body = match keyword.as_deref() {
+            Some("enum") | Some("trait") => Self {
+                inherited: true,
+                ..scope
+            },
+            Some("impl") => Self {
+                inherited: item.iter().any(|tree| is_ident(tree, "for")),
+                ..scope
+            },
+            // items within function bodies are never reachable
+            Some("fn") => Self {
+                private: true,
+                ..scope
+            },
+            _ => scope,
+        };
+        (scope, body)
+    }
+}
+
 impl Clusters {
     /// Only works if the file is processed line by line, otherwise
     /// requires a adjacency list.
-    fn process_literal(&mut self, source: &str, literal: proc_macro2::Literal) -> Result<()> {
+    fn process_literal(
+        &mut self,
+        source: &str,
+        literal: proc_macro2::Literal,
+        scope: Scope,
+    ) -> Result<()> {
         let literal = TrimmedLiteral::try_from((source, literal))?;
-        if let Some(cls) = self.set.last_mut() {
-            if let Err(literal) = cls.add_adjacent(literal) {
-                trace!(target: "documentation",
-                    "appending, but failed to append: {:?} to set {:?}",
-                    &literal,
-                    &cls
-                );
-                self.set.push(LiteralSet::from(literal))
-            } else {
-                trace!("successfully appended to existing: {:?} to set", &cls);
+        // docs of hidden or private items are never joined with others
+        let literal = match self.set.last_mut() {
+            Some(cls) if cls.hidden == scope.hidden && cls.private == scope.private => {
+                match cls.add_adjacent(literal) {
+                    Ok(()) => {
+                        trace!("successfully appended to existing: {:?} to set", &cls);
+                        return Ok(());
+                    }
+                    Err(literal) => {
+                        trace!(target: "documentation",
+                            "appending, but failed to append: {:?} to set {:?}",
+                            &literal,
+                            &cls
+                        );
+                        literal
+                    }
+                }
             }
-        } else {
-            self.set.push(LiteralSet::from(literal));
-        }
+            _ => literal,
+        };
+        let mut cls = LiteralSet::from(literal);
+        cls.hidden = scope.hidden;
+        cls.private = scope.private;
+        self.set.push(cls);
         Ok(())
     }
 
-    /// Helper function to parse a stream and associated the found literals
-    fn parse_token_tree(&mut self, source: &str, stream: proc_macro2::TokenStream) -> Result<()> {
-        let mut iter = stream.into_iter();
-        while let Some(tree) = iter.next() {
-            match tree {
-                TokenTree::Ident(ident) => {
-                    // if we find an identifier
-                    // which is doc
-                    if ident != "doc" {
-                        continue;
-                    }
-
+    /// Helper function to parse the content of an attribute and associate the
+    /// found doc literals with the `scope` of the item they document.
+    fn parse_attribute(
+        &mut self,
+        source: &str,
+        stream: proc_macro2::TokenStream,
+        scope: Scope,
+    ) -> Result<()> {
+        let trees = stream.into_iter().collect::<Vec<_>>();
+        let mut idx = 0;
+        while idx < trees.len() {
+            match &trees[idx] {
+                TokenTree::Ident(ident) if ident == "doc" => {
                     // this assures the sequence is as anticipated
-                    let op = iter.next();
-                    if op.is_none() {
-                        continue;
-                    }
-                    let op = op.unwrap();
-                    if let TokenTree::Punct(punct) = op {
-                        if punct.as_char() != '=' {
-                            continue;
-                        }
-                        if punct.spacing() != Spacing::Alone {
-                            continue;
-                        }
-                    } else {
-                        continue;
-                    }
-
-                    let comment = iter.next();
-                    if comment.is_none() {
-                        continue;
-                    }
-                    let comment = comment.unwrap();
-                    if let TokenTree::Literal(literal) = comment {
+                    let is_assignment = matches!(
+                        trees.get(idx + 1),
+                        Some(TokenTree::Punct(punct))
+                            if punct.as_char() == '=' && punct.spacing() == Spacing::Alone
+                    );
+                    if let (true, Some(TokenTree::Literal(literal))) =
+                        (is_assignment, trees.get(idx + 2))
+                    {
                         trace!(target: "documentation",
                             "Found doc literal at {:?}: {:?}",
                             <Span as TryInto<Range>>::try_into(Span::from(literal.span())),
                             literal
                         );
-                        if let Err(e) = self.process_literal(source, literal) {
+                        if let Err(e) = self.process_literal(source, literal.clone(), scope) {
                             log::error!(
                                 "BUG: Failed to guarantee literal content/span integrity: {}",
                                 e
                             );
-                            continue;
                         }
-                    } else {
-                        continue;
+                        idx += 2;
                     }
                 }
                 TokenTree::Group(group) => {
-                    self.parse_token_tree(source, group.stream())?;
+                    self.parse_attribute(source, group.stream(), scope)?;
                 }
                 _ => {}
             };
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    /// Helper function to parse a stream of items and associate the found
+    /// literals with the scope of the item they document.
+    fn parse_token_tree(
+        &mut self,
+        source: &str,
+        stream: proc_macro2::TokenStream,
+        scope: Scope,
+    ) -> Result<()> {
+        let trees = stream.into_iter().collect::<Vec<_>>();
+        // an inner `#![doc(hidden)]` hides the enclosing item as a whole
+        let scope = Scope {
+            hidden: scope.hidden
+                || (0..trees.len()).any(|idx| {
+                    attribute_at(&trees, idx)
+                        .map(|(inner, attribute, _len)| inner && is_doc_hidden(attribute))
+                        .unwrap_or_default()
+                }),
+            ..scope
+        };
+
+        let mut idx = 0;
+        while idx < trees.len() {
+            // the doc comments of an item may precede its `#[doc(hidden)]`,
+            // so the attributes must be looked at as a whole
+            let mut outer = Vec::new();
+            while let Some((inner, attribute, len)) = attribute_at(&trees, idx) {
+                if inner {
+                    // documents the enclosing item
+                    self.parse_attribute(source, attribute.stream(), scope)?;
+                } else {
+                    outer.push(attribute);
+                }
+                idx += len;
+            }
+            let end = item_end(&trees, idx).max(idx + 1).min(trees.len());
+            let item = &trees[idx.min(end)..end];
+            let (item_scope, body_scope) = scope.of_item(item, &outer);
+            for attribute in outer {
+                self.parse_attribute(source, attribute.stream(), item_scope)?;
+            }
+            for tree in item {
+                if let TokenTree::Group(group) = tree {
+                    self.parse_token_tree(source, group.stream(), body_scope)?;
+                }
+            }
+            idx = end;
         }
         Ok(())
     }
@@ -103,7 +309,7 @@ impl TryFrom<&str> for Clusters {
         };
         let stream = syn::parse_str::<proc_macro2::TokenStream>(source)
             .map_err(|e| anyhow!("Failed to parse content to stream").context(e))?;
-        chunk.parse_token_tree(source, stream)?;
+        chunk.parse_token_tree(source, stream, Scope::default())?;
         Ok(chunk)
     }
 }
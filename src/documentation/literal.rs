@@ -85,6 +85,28 @@ impl CommentVariant {
             "".to_string()
         }
     }
+
+    /// Escape `replacement`, so it reads the same once the literal of this
+    /// variant is rendered.
+    ///
+    /// Regular strings escape `\` and `"`, raw strings take the replacement
+    /// verbatim, `None` if it would terminate the raw string.
+    pub fn escape(&self, replacement: &str) -> Option<String> {
+        match self {
+            CommentVariant::MacroDocEq(_, 0) => {
+                Some(replacement.replace('\\', r"\\").replace('"', r#"\""#))
+            }
+            CommentVariant::MacroDocEq(_, p) => {
+                let terminator = format!("\"{}", "#".repeat(p.saturating_sub(1)));
+                if replacement.contains(terminator.as_str()) {
+                    None
+                } else {
+                    Some(replacement.to_owned())
+                }
+            }
+            _ => Some(replacement.to_owned()),
+        }
+    }
 }
 
 /// A literal with meta info where the first and list whitespace may be found.
@@ -211,7 +233,7 @@ impl TryFrom<(&str, proc_macro2::Literal)> for TrimmedLiteral {
 
             //^r(#+?)"(?:.*\s*)+(?=(?:"\1))("\1)$
             lazy_static::lazy_static! {
-                static ref BOUNDED_RAW_STR: Regex = Regex::new(r##"^(r(#+)?")(?:.*\s*)+?(?=(?:"\2))("\2)\s*\]?\s*$"##).expect("BOUNEDED_RAW_STR regex compiles");
+                static ref BOUNDED_RAW_STR: Regex = Regex::new(r##"^(r(#*)")(?:.*\s*)+?(?=(?:"\2))("\2)\s*\]?\s*$"##).expect("BOUNEDED_RAW_STR regex compiles");
                 static ref BOUNDED_STR: Regex = Regex::new(r##"^"(?:.(?!"\\"))*?"*\s*\]?\s*"$"##).expect("BOUNEDED_STR regex compiles");
             };
 
@@ -346,7 +368,7 @@ impl TrimmedLiteral {
 
     /// Display helper, mostly used for debug investigations
     #[allow(unused)]
-    pub(crate) fn display(&self, highlight: Range) -> TrimmedLiteralDisplay {
+    pub(crate) fn display(&self, highlight: Range) -> TrimmedLiteralDisplay<'_> {
         TrimmedLiteralDisplay::from((self, highlight))
     }
 }
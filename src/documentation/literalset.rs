@@ -1,4 +1,4 @@
-pub use super::{TrimmedLiteral, TrimmedLiteralDisplay};
+pub use super::TrimmedLiteral;
 use crate::{CheckableChunk, CommentVariant, Range};
 /// A set of consecutive literals.
 ///
@@ -11,6 +11,10 @@ pub struct LiteralSet {
     pub coverage: (usize, usize),
     /// Track what kind of comment the literals are
     variant: CommentVariant,
+    /// Documents an item marked `#[doc(hidden)]`.
+    pub hidden: bool,
+    /// Documents an item which is not reachable from the crate root.
+    pub private: bool,
 }
 
 impl LiteralSet {
@@ -20,6 +24,8 @@ impl LiteralSet {
             coverage: (literal.span().start.line, literal.span().end.line),
             variant: literal.variant(),
             literals: vec![literal],
+            hidden: false,
+            private: false,
         }
     }
 
@@ -12,6 +12,65 @@ use crate::documentation::{CheckableChunk, Range};
 use crate::util::sub_chars;
 use crate::Span;
 
+/// Disambiguators of intra-doc links, i.e. `fn@` in `[fn@foo]`.
+const DISAMBIGUATORS: &[&str] = &[
+    "struct",
+    "enum",
+    "trait",
+    "union",
+    "mod",
+    "module",
+    "const",
+    "constant",
+    "fn",
+    "function",
+    "method",
+    "derive",
+    "type",
+    "value",
+    "macro",
+    "prim",
+    "primitive",
+    "field",
+    "variant",
+    "tymethod",
+    "static",
+];
+
+/// If `target` is the path of an intra-doc link, i.e. `Vec::push`,
+/// `crate::module::Item`, `fn@foo`, `foo!` or `Option<T>`.
+fn is_intra_doc_path(target: &str) -> bool {
+    let target = target.trim_matches('`');
+    let target = match target.split_once('@') {
+        Some((disambiguator, path)) if DISAMBIGUATORS.contains(&disambiguator) => path,
+        Some(_) => return false,
+        None => target,
+    };
+    let target = target
+        .strip_suffix("()")
+        .or_else(|| target.strip_suffix('!'))
+        .unwrap_or(target);
+    // generic parameters do not matter for the path
+    let mut depth = 0usize;
+    let mut path = String::with_capacity(target.len());
+    for c in target.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            c if depth == 0 => path.push(c),
+            _ => {}
+        }
+    }
+    let path = path.strip_prefix("::").unwrap_or(&path);
+    !path.is_empty()
+        && depth == 0
+        && path.split("::").all(|segment| {
+            !segment.is_empty()
+                && !segment.starts_with(|c: char| c.is_ascii_digit())
+                && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+        })
+}
+
 /// A plain representation of cmark riddled chunk.
 #[derive(Clone)]
 pub struct PlainOverlay<'a> {
@@ -57,16 +116,24 @@ impl<'a> PlainOverlay<'a> {
     }
 
     /// Ranges are mapped `cmark reduced/plain -> raw`.
-    pub(crate) fn extract_plain_with_mapping(cmark: &str) -> (String, IndexMap<Range, Range>) {
+    ///
+    /// Only with `rustdoc`, the targets of intra-doc links are skipped, since
+    /// in any other common mark document `[Foo]` is a plain reference link.
+    pub(crate) fn extract_plain_with_mapping(
+        cmark: &str,
+        rustdoc: bool,
+    ) -> (String, IndexMap<Range, Range>) {
         let mut plain = String::with_capacity(cmark.len());
         let mut mapping = indexmap::IndexMap::with_capacity(128);
 
-        let broken_link_handler = &mut |_broken: pulldown_cmark::BrokenLink| -> Option<(
+        // rustdoc resolves unknown references as intra-doc links, so the
+        // reference is the target
+        let broken_link_handler = &mut |broken: pulldown_cmark::BrokenLink| -> Option<(
             pulldown_cmark::CowStr,
             pulldown_cmark::CowStr,
         )> {
             Some((
-                pulldown_cmark::CowStr::Borrowed(""),
+                pulldown_cmark::CowStr::from(broken.reference.to_owned()),
                 pulldown_cmark::CowStr::Borrowed(""),
             ))
         };
@@ -85,6 +152,7 @@ impl<'a> PlainOverlay<'a> {
         let mut code_block = false;
         let mut inception = false;
         let mut skip_link_text = false;
+        let mut intra_doc_link = false;
         let mut skip_table_text = false;
 
         for (event, byte_range) in parser.into_offset_iter() {
@@ -128,7 +196,7 @@ impl<'a> PlainOverlay<'a> {
                         code_block = true;
                         inception = fenced == rust_fence;
                     }
-                    Tag::Link(link_type, _url, _title) => {
+                    Tag::Link(link_type, url, _title) => {
                         skip_link_text = match link_type {
                             LinkType::ReferenceUnknown
                             | LinkType::Reference
@@ -139,6 +207,15 @@ impl<'a> PlainOverlay<'a> {
                             | LinkType::ShortcutUnknown => false,
                             LinkType::Autolink | LinkType::Email => true,
                         };
+                        // the text of these is the reference itself, so it is
+                        // code, unlike the human readable text of other links
+                        intra_doc_link = match link_type {
+                            LinkType::Collapsed
+                            | LinkType::CollapsedUnknown
+                            | LinkType::Shortcut
+                            | LinkType::ShortcutUnknown => rustdoc && is_intra_doc_path(&url),
+                            _ => false,
+                        };
                     }
                     Tag::List(_) => {
                         // make sure nested lists are not clumped together
@@ -154,6 +231,7 @@ impl<'a> PlainOverlay<'a> {
                         }
                         Tag::Link(_link_type, _url, _title) => {
                             // the actual rendered content is in a text section
+                            intra_doc_link = false;
                         }
                         Tag::Image(_link_type, _url, title) => {
                             Self::track(&title, char_range, &mut plain, &mut mapping);
@@ -184,6 +262,8 @@ impl<'a> PlainOverlay<'a> {
                         }
                     } else if skip_link_text {
                         skip_link_text = false
+                    } else if intra_doc_link {
+                        trace!("Skipping the target of an intra-doc link: {:?}", &s);
                     } else if !skip_table_text {
                         Self::track(&s, char_range, &mut plain, &mut mapping);
                     }
@@ -234,7 +314,8 @@ impl<'a> PlainOverlay<'a> {
     // TODO consider returning a Vec<PlainOverlay<'a>> to account for list items
     // or other non-linear information which might not pass a grammar check as a whole
     pub fn erase_cmark(chunk: &'a CheckableChunk) -> Self {
-        let (plain, mapping) = Self::extract_plain_with_mapping(chunk.as_str());
+        let rustdoc = chunk.variant() != CommentVariant::CommonMark;
+        let (plain, mapping) = Self::extract_plain_with_mapping(chunk.as_str(), rustdoc);
         Self {
             raw: chunk,
             plain,
@@ -265,7 +346,7 @@ impl<'a> PlainOverlay<'a> {
                         start: range.start + offset,
                         end: range.start + offset + len,
                     }
-                };
+                }
                 let _ = if sub.contains(&start) {
                     // calculate the offset between our `condensed_range.start` and
                     // the `sub` which is one entry in the mappings
@@ -309,6 +390,11 @@ impl<'a> PlainOverlay<'a> {
             })
     }
 
+    /// Mapping of ranges in the plain representation to ranges in the chunk.
+    pub fn mapping(&self) -> &IndexMap<Range, Range> {
+        &self.mapping
+    }
+
     /// Obtains a reference to the plain, cmark erased representation.
     pub fn as_str(&self) -> &str {
         self.plain.as_str()
@@ -14,15 +14,16 @@
 
 use super::*;
 
+use crate::timings::{Stage, Timer};
 use crate::util::load_span_from;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use indexmap::IndexMap;
 use log::trace;
 pub use proc_macro2::LineColumn;
 use proc_macro2::{Spacing, TokenTree};
 use rayon::prelude::*;
 use std::convert::{TryFrom, TryInto};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Range based on `usize`, simplification.
 pub type Range = core::ops::Range<usize>;
@@ -104,6 +105,34 @@ impl Documentation {
         });
     }
 
+    /// Extract the documentation of `content`, either rust or common mark
+    /// depending on `origin`.
+    ///
+    /// For a `RustDocTest` origin the content is treated as rust source.
+    pub fn load_from_str(origin: ContentOrigin, content: &str) -> Result<Self> {
+        let mut docs = Documentation::new();
+        match origin {
+            ContentOrigin::CommonMarkFile(_) => docs.add_commonmark(origin, content)?,
+            #[cfg(test)]
+            ContentOrigin::TestEntityCommonMark => docs.add_commonmark(origin, content)?,
+            _ => docs.add_rust(origin, content)?,
+        }
+        Ok(docs)
+    }
+
+    /// Load and extract the documentation of a single file, a `.md` file is
+    /// treated as common mark, anything else as rust source.
+    pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+        let origin = match path.extension() {
+            Some(ext) if ext == "md" => ContentOrigin::CommonMarkFile(path.to_owned()),
+            _ => ContentOrigin::RustSourceFile(path.to_owned()),
+        };
+        Self::load_from_str(origin, &content)
+    }
+
     /// Adds a set of `CheckableChunk`s to the documentation to be checked.
     fn add_inner(&mut self, origin: ContentOrigin, mut chunks: Vec<CheckableChunk>) {
         self.index
@@ -117,6 +146,10 @@ impl Documentation {
 
     /// Adds a rust content str to the documentation.
     pub fn add_rust(&mut self, origin: ContentOrigin, content: &str) -> Result<()> {
+        let _timer = Timer::start(Stage::Extraction, origin.as_path());
+        if let ContentOrigin::RustSourceFile(ref path) = origin {
+            crate::checksum::record(path, content);
+        }
         let cluster = Clusters::try_from(content)?;
 
         let chunks = Vec::<CheckableChunk>::from(cluster);
@@ -124,42 +157,114 @@ impl Documentation {
         Ok(())
     }
 
+    /// Adds a rust content str to the documentation, extracting the doc
+    /// comments by scanning the lines instead of parsing the source.
+    ///
+    /// Much faster, but only `///` and `//!` comments are found, string
+    /// literals looking like doc comments are mistaken for them, and items
+    /// are not recognized as private. Hidden items are recognized by their
+    /// `#[doc(hidden)]` attribute, as long as the braces of the source are
+    /// balanced per line outside of literals.
+    pub fn add_rust_scanned(&mut self, origin: ContentOrigin, content: &str) {
+        let _timer = Timer::start(Stage::Extraction, origin.as_path());
+        if let ContentOrigin::RustSourceFile(ref path) = origin {
+            crate::checksum::record(path, content);
+        }
+        self.add_inner(origin, scan_doc_comments(content));
+    }
+
     /// Adds a common mark content str to the documentation.
     pub fn add_commonmark(&mut self, origin: ContentOrigin, content: &str) -> Result<()> {
-        // extract the full content span and range
-        let start = LineColumn { line: 1, column: 0 };
-        let end = content
-            .lines()
-            .enumerate()
-            .last()
-            .map(|(idx, linecontent)| (idx + 1, linecontent))
-            .map(|(linenumber, linecontent)| LineColumn {
-                line: linenumber,
-                column: linecontent.chars().count().saturating_sub(1),
-            })
-            .ok_or_else(|| anyhow!("Common mark / markdown file does not contain a single line"))?;
+        self.add_commonmark_chunked(origin, content, usize::MAX)
+    }
 
-        let span = Span { start, end };
-        let source_mapping = indexmap::indexmap! {
-            0..content.chars().count() => span
-        };
-        self.add_inner(
-            origin,
-            vec![CheckableChunk::from_str(
-                content,
-                source_mapping,
-                CommentVariant::CommonMark,
-            )],
-        );
+    /// Adds a common mark content str to the documentation, split into
+    /// chunks of about `chunk_size` bytes at paragraph boundaries.
+    ///
+    /// Parsing the markup and checking a chunk then only requires memory
+    /// proportional to the chunk, i.e. a LanguageTool request is bounded by
+    /// it, while the chunks and their findings are still kept for the whole
+    /// file. References defined in another chunk are not resolved.
+    pub fn add_commonmark_chunked(
+        &mut self,
+        origin: ContentOrigin,
+        content: &str,
+        chunk_size: usize,
+    ) -> Result<()> {
+        let _timer = Timer::start(Stage::Extraction, origin.as_path());
+        if let ContentOrigin::CommonMarkFile(ref path) = origin {
+            crate::checksum::record(path, content);
+        }
+        if content.lines().next().is_none() {
+            bail!("Common mark / markdown file does not contain a single line");
+        }
+        let chunks = split_paragraphs(content, chunk_size)
+            .into_iter()
+            .map(|(first_line, part)| {
+                // extract the full content span and range of the part
+                let start = LineColumn {
+                    line: first_line,
+                    column: 0,
+                };
+                let end = part
+                    .lines()
+                    .enumerate()
+                    .last()
+                    .map(|(idx, linecontent)| LineColumn {
+                        line: first_line + idx,
+                        column: linecontent.chars().count().saturating_sub(1),
+                    })
+                    .unwrap_or(start);
+                let source_mapping = indexmap::indexmap! {
+                    0..part.chars().count() => Span { start, end }
+                };
+                CheckableChunk::from_str(part, source_mapping, CommentVariant::CommonMark)
+            })
+            .collect();
+        self.add_inner(origin, chunks);
         Ok(())
     }
 
+    /// Adds already extracted chunks to the documentation.
+    pub fn add_chunks(&mut self, origin: ContentOrigin, chunks: Vec<CheckableChunk>) {
+        self.add_inner(origin, chunks);
+    }
+
     /// Obtain the set of chunks for a particular origin.
     #[inline(always)]
     pub fn get(&self, origin: &ContentOrigin) -> Option<&[CheckableChunk]> {
         self.index.get(origin).map(AsRef::as_ref)
     }
 
+    /// Split into multiple documentations, grouped by the key `f` yields
+    /// for each origin.
+    pub fn partition_by<K, F>(self, mut f: F) -> IndexMap<K, Documentation>
+    where
+        K: std::hash::Hash + Eq,
+        F: FnMut(&ContentOrigin) -> K,
+    {
+        let mut partitions = IndexMap::<K, Documentation>::with_capacity(4);
+        for (origin, chunks) in self.index.into_iter() {
+            partitions
+                .entry(f(&origin))
+                .or_insert_with(Documentation::new)
+                .add_inner(origin, chunks);
+        }
+        partitions
+    }
+
+    /// Only keep the chunks for which `f` is true, origins without any chunks
+    /// left are removed.
+    pub fn retain_chunks<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&CheckableChunk) -> bool,
+    {
+        self.index.retain(|_origin, chunks| {
+            chunks.retain(|chunk| f(chunk));
+            !chunks.is_empty()
+        });
+    }
+
     /// Count the number of origins.
     #[inline(always)]
     pub fn entry_count(&self) -> usize {
@@ -167,6 +272,156 @@ impl Documentation {
     }
 }
 
+/// Collect consecutive lines of `///` or `//!` comments of `content` into
+/// chunks, the same way clustering the parsed literals does.
+///
+/// Items with a `#[doc(hidden)]` attribute and everything within their braces
+/// are recognized as hidden, by tracking the braces line by line.
+fn scan_doc_comments(content: &str) -> Vec<CheckableChunk> {
+    /// A chunk in the making: variant, last line, content, source mapping
+    /// and length in chars.
+    type Current = (CommentVariant, usize, String, IndexMap<Range, Span>, usize);
+
+    /// Outer doc comments are hidden along with their item, inner ones along
+    /// with the enclosing scope, which may be hidden by a following
+    /// `#![doc(hidden)]`.
+    fn finish(current: Current, hidden_item: bool, hidden_scope: bool) -> CheckableChunk {
+        let (variant, _, content, source_mapping, _) = current;
+        let hidden = hidden_scope || (variant == CommentVariant::TripleSlash && hidden_item);
+        CheckableChunk::from_string(content, source_mapping, variant).with_hidden(hidden)
+    }
+
+    let mut chunks = Vec::new();
+    let mut current: Option<Current> = None;
+    // doc comments, which are hidden once their item is known to be
+    let mut pending: Vec<Current> = Vec::new();
+    // the depth of braces at the start of the line
+    let mut depth = 0_isize;
+    // everything at or below these depths is hidden
+    let mut hidden_scopes: Vec<isize> = Vec::new();
+    // the upcoming item is `#[doc(hidden)]`
+    let mut hidden_item = false;
+    // the hidden item started, but its body or end is yet to come
+    let mut hidden_body = false;
+    for (idx, line) in content.lines().enumerate() {
+        let lineno = idx + 1;
+        let trimmed = line.trim_start();
+        let variant = if trimmed.starts_with("///") && !trimmed.starts_with("////") {
+            CommentVariant::TripleSlash
+        } else if trimmed.starts_with("//!") {
+            CommentVariant::DoubleSlashEM
+        } else {
+            pending.extend(current.take());
+            let code = trimmed.replace(char::is_whitespace, "");
+            if code.starts_with("#![") {
+                if code.contains("doc(hidden)") {
+                    hidden_scopes.push(depth);
+                }
+            } else if code.starts_with("#[") {
+                hidden_item |= code.contains("doc(hidden)");
+            } else if !code.is_empty() && !code.starts_with("//") {
+                let hidden_scope = !hidden_scopes.is_empty();
+                chunks.extend(
+                    pending
+                        .drain(..)
+                        .map(|current| finish(current, hidden_item, hidden_scope)),
+                );
+                hidden_body |= std::mem::take(&mut hidden_item);
+            }
+            let delta = crate::util::brace_delta(line);
+            if hidden_body && code.contains('{') {
+                hidden_scopes.push(depth + 1);
+                hidden_body = false;
+            } else if hidden_body && (code.ends_with(';') || code.ends_with(',')) {
+                hidden_body = false;
+            }
+            depth += delta;
+            while matches!(hidden_scopes.last(), Some(scope) if depth < *scope) {
+                hidden_scopes.pop();
+            }
+            continue;
+        };
+        let continues = matches!(current, Some((ref last_variant, last_line, ..))
+            if *last_variant == variant && last_line + 1 == lineno);
+        if !continues {
+            pending.extend(current.take());
+        }
+        let (_, last_line, text, source_mapping, cursor) =
+            current.get_or_insert_with(|| (variant, lineno, String::new(), IndexMap::new(), 0));
+        if !source_mapping.is_empty() {
+            // the newline is not covered by a span
+            text.push('\n');
+            *cursor += 1;
+        }
+        let literal = &trimmed[3..];
+        let len = literal.chars().count();
+        let column = line.chars().count() - trimmed.chars().count() + 3;
+        // an empty literal ends before it starts, as the parsed ones do
+        let span = Span {
+            start: LineColumn {
+                line: lineno,
+                column,
+            },
+            end: LineColumn {
+                line: lineno,
+                column: column + len - 1,
+            },
+        };
+        source_mapping.insert(*cursor..*cursor + len, span);
+        text.push_str(literal);
+        *cursor += len;
+        *last_line = lineno;
+    }
+    let hidden_scope = !hidden_scopes.is_empty();
+    pending.extend(current);
+    chunks.extend(
+        pending
+            .into_iter()
+            .map(|current| finish(current, hidden_item, hidden_scope)),
+    );
+    chunks
+}
+
+/// Split common mark `content` into parts of at least `chunk_size` bytes,
+/// each with its 1-indexed first line.
+///
+/// A part only ends before a line at the top level, i.e. not indented and not
+/// within a fenced code block, which follows an empty line.
+fn split_paragraphs(content: &str, chunk_size: usize) -> Vec<(usize, &str)> {
+    let mut parts = Vec::new();
+    let mut start = (1, 0);
+    let mut fence = None::<&str>;
+    let mut previous_blank = false;
+    let mut offset = 0;
+    for (idx, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        let top_level = !line.starts_with(char::is_whitespace);
+        if fence.is_none()
+            && previous_blank
+            && top_level
+            && !trimmed.is_empty()
+            && offset - start.1 >= chunk_size
+        {
+            parts.push((start.0, &content[start.1..offset]));
+            start = (idx + 1, offset);
+        }
+        match fence {
+            Some(marker) if trimmed.starts_with(marker) => fence = None,
+            Some(_) => {}
+            None => {
+                fence = ["```", "~~~"]
+                    .iter()
+                    .copied()
+                    .find(|marker| line.trim_start().starts_with(marker))
+            }
+        }
+        previous_blank = trimmed.is_empty();
+        offset += line.len();
+    }
+    parts.push((start.0, &content[start.1..]));
+    parts
+}
+
 /// only a shortcut to avoid duplicate code
 impl From<(ContentOrigin, &str)> for Documentation {
     fn from((origin, content): (ContentOrigin, &str)) -> Self {
@@ -88,8 +88,8 @@ macro_rules! end2end {
         let _plain = chunk.erase_cmark();
 
         let cfg = Default::default();
-        let suggestion_set =
-            <$checker>::check(&docs, &cfg).expect("Must not fail to extract suggestions");
+        let suggestion_set = <$checker>::check(&docs, &cfg, &crate::checker::Limits::unlimited())
+            .expect("Must not fail to extract suggestions");
         let (_, suggestions) = suggestion_set
             .iter()
             .next()
@@ -112,20 +112,6 @@ macro_rules! end2end_file_rust {
     }};
 }
 
-/// Declare an end to end test case based on an existing common mark file.
-macro_rules! end2end_file_cmark {
-    ($path: literal, $n: expr) => {{
-        let path2 = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path));
-        let origin = ContentOrigin::CommonMarkFile(path2);
-        end2end!(
-            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)),
-            origin,
-            $n,
-            DummyChecker
-        );
-    }};
-}
-
 mod e2e {
     use super::*;
 
@@ -178,7 +164,7 @@ mod e2e {
             let docs = Documentation::from((origin.clone(), $source));
 
             let suggestion_set =
-                dbg!(DummyChecker::check(&docs, &())).expect("Dummy checker never fails. qed");
+                dbg!(DummyChecker::check(&docs, &(), &crate::checker::Limits::unlimited())).expect("Dummy checker never fails. qed");
 
             let (origin2, chunks) = docs
                 .iter()
@@ -907,7 +893,7 @@ Extra ~pagaph~ paragraph.
 
 
 And a line, or a rule."##;
-    let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN);
+    let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN, true);
 
     assert_eq!(dbg!(&reduced).as_str(), PLAIN);
     assert_eq!(dbg!(&mapping).len(), 20);
@@ -924,7 +910,7 @@ fn reduction_leading_space() {
     const MARKDOWN: &str = r#"  Some __underlined__ **bold** text."#;
     const PLAIN: &str = r#"Some underlined bold text."#;
 
-    let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN);
+    let (reduced, mapping) = PlainOverlay::extract_plain_with_mapping(MARKDOWN, true);
 
     assert_eq!(dbg!(&reduced).as_str(), PLAIN);
     assert_eq!(dbg!(&mapping).len(), 5);
@@ -965,7 +951,7 @@ fn range_test() {
 }
 
 fn cmark_reduction_test(input: &'static str, expected: &'static str, expected_mapping_len: usize) {
-    let (plain, mapping) = PlainOverlay::extract_plain_with_mapping(input);
+    let (plain, mapping) = PlainOverlay::extract_plain_with_mapping(input, true);
     assert_eq!(dbg!(&plain).as_str(), expected);
     assert_eq!(dbg!(&mapping).len(), expected_mapping_len);
     for (reduced_range, markdown_range) in mapping.iter() {
@@ -1064,6 +1050,38 @@ fn link_shortcut_ref() {
         1,
     );
 }
+#[test]
+fn link_intra_doc() {
+    cmark_reduction_test(
+        r#"Use [`Vec::push`] or [crate::module::Item], see [fn@foo] and [Option<T>]."#,
+        r#"Use  or , see  and ."#,
+        5,
+    );
+    cmark_reduction_test(
+        r#"The [pushing method](Vec::push) and [Item][crate::module::Item]."#,
+        r#"The pushing method and Item."#,
+        5,
+    );
+    cmark_reduction_test(
+        r#"See [Item].
+
+[Item]: crate::module::Item"#,
+        r#"See ."#,
+        2,
+    );
+}
+
+#[test]
+fn link_shortcut_ref_commonmark() {
+    let (plain, _mapping) = PlainOverlay::extract_plain_with_mapping(
+        r#"See [Foo] and [`Vec::push`], or [the docs][Item].
+
+[Item]: crate::module::Item"#,
+        false,
+    );
+    assert_eq!(plain.as_str(), "See Foo and , or the docs.");
+}
+
 // Nested links as well as nested code blocks are
 // impossible according to the common mark spec.
 
@@ -1136,20 +1154,12 @@ pub(crate) fn annotated_literals(source: &str) -> Vec<TrimmedLiteral> {
         .collect()
 }
 
-const PREFIX_RAW_LEN: usize = 3;
-const SUFFIX_RAW_LEN: usize = 2;
-const GAENSEFUESSCHEN: usize = 1;
-
 #[derive(Clone, Debug)]
 struct Triplet {
     /// source content
     source: &'static str,
-    /// expected doc comment content without modifications
-    extracted: &'static str,
     /// expected doc comment content after applying trimming rules
     trimmed: &'static str,
-    /// expected span as extracted by proc_macro2
-    extracted_span: Span,
     /// trimmed span, so it is aligned with the proper doc comment
     trimmed_span: Span,
     /// expected variant
@@ -1164,18 +1174,7 @@ fn comment_variant_span_range_validation(index: usize) {
 /// One Doc
 struct One;
 "#,
-            extracted: r#"" One Doc""#,
             trimmed: " One Doc",
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 2_usize,
-                    column: 0,
-                },
-                end: LineColumn {
-                    line: 2_usize,
-                    column: 10_usize,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 2_usize,
@@ -1194,18 +1193,7 @@ struct One;
     ///meanie
 struct Meanie;
 "##,
-            extracted: r#""meanie""#,
             trimmed: "meanie",
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 2_usize,
-                    column: 0_usize + 7 - PREFIX_RAW_LEN,
-                },
-                end: LineColumn {
-                    line: 2_usize,
-                    column: 0_usize + 12 + SUFFIX_RAW_LEN,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 2_usize,
@@ -1224,18 +1212,7 @@ struct Meanie;
 #[doc = "Two Doc"]
 struct Two;
 "#,
-            extracted: r#""Two Doc""#,
             trimmed: "Two Doc",
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 2_usize,
-                    column: 0_usize + 10 - GAENSEFUESSCHEN,
-                },
-                end: LineColumn {
-                    line: 2__usize,
-                    column: 6__usize + 10 + GAENSEFUESSCHEN,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 2__usize,
@@ -1254,18 +1231,7 @@ struct Two;
     #[doc=r#"Three Doc"#]
 struct Three;
 "##,
-            extracted: r###"r#"Three Doc"#"###,
             trimmed: "Three Doc",
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 2_usize,
-                    column: 4__usize + 11,
-                },
-                end: LineColumn {
-                    line: 2__usize,
-                    column: 13__usize + 11,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 2__usize,
@@ -1288,26 +1254,11 @@ lines
 "##]
 struct Four;
 "###,
-            extracted: r###"r##"Four
-has
-multiple
-lines
-"##"###,
             trimmed: r#"Four
 has
 multiple
 lines
 "#,
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 2__usize,
-                    column: 12__usize - 4,
-                },
-                end: LineColumn {
-                    line: 6__usize,
-                    column: 0__usize + 3,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 2__usize,
@@ -1326,18 +1277,7 @@ lines
 #[doc        ="XYZ"]
 struct Five;
 "###,
-            extracted: r#""XYZ""#,
             trimmed: r#"XYZ"#,
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 2__usize,
-                    column: 15__usize - GAENSEFUESSCHEN,
-                },
-                end: LineColumn {
-                    line: 2__usize,
-                    column: 15__usize + 2 + GAENSEFUESSCHEN,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 2__usize,
@@ -1360,18 +1300,7 @@ struct Five;
     }
 
 "#,
-            extracted: r#"" if a layer is provided a identiacla "input" and "output", it will only be supplied an""#,
             trimmed: r#" if a layer is provided a identiacla "input" and "output", it will only be supplied an"#,
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 3_usize,
-                    column: 7_usize - GAENSEFUESSCHEN,
-                },
-                end: LineColumn {
-                    line: 2_usize,
-                    column: 92_usize + GAENSEFUESSCHEN,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 3_usize,
@@ -1394,18 +1323,7 @@ fn unicode(&self) -> bool {
 }
 
 "#,
-            extracted: r#"" 🍉 ← αA<sup>OP</sup>x + βy""#,
             trimmed: r#" 🍉 ← αA<sup>OP</sup>x + βy"#,
-            extracted_span: Span {
-                start: LineColumn {
-                    line: 3_usize,
-                    column: 3_usize - GAENSEFUESSCHEN,
-                },
-                end: LineColumn {
-                    line: 2_usize,
-                    column: 28_usize + GAENSEFUESSCHEN,
-                },
-            },
             trimmed_span: Span {
                 start: LineColumn {
                     line: 3_usize,
@@ -1429,39 +1347,61 @@ fn unicode(&self) -> bool {
         "##]
         struct Four;
         "###,
-            extracted: r###"r##"Four
-        has
-
-        multiple
-        lines
-        "##"###,
             trimmed: r#"Four
         has
 
         multiple
         lines
         "#,
-            extracted_span: Span {
+            trimmed_span: Span {
                 start: LineColumn {
                     line: 2_usize,
-                    column: 12_usize - 4,
+                    column: 12_usize,
                 },
                 end: LineColumn {
                     line: 6_usize,
-                    column: 0_usize + 3,
+                    column: 0_usize,
                 },
             },
+            variant: CommentVariant::MacroDocEq("#[ doc = ".to_string(), 3),
+        },
+        // 9
+        Triplet {
+            source: r##"
+#[doc = r"Nine"]
+struct Nine;
+"##,
+            trimmed: "Nine",
             trimmed_span: Span {
                 start: LineColumn {
                     line: 2_usize,
-                    column: 12_usize,
+                    column: 10_usize,
                 },
                 end: LineColumn {
-                    line: 6_usize,
-                    column: 0_usize,
+                    line: 2_usize,
+                    column: 13_usize,
                 },
             },
-            variant: CommentVariant::MacroDocEq("#[ doc = ".to_string(), 3),
+            variant: CommentVariant::MacroDocEq("#[doc = ".to_string(), 1),
+        },
+        // 10
+        Triplet {
+            source: r###"
+#[doc = r#"Say "ten" words"#]
+struct Ten;
+"###,
+            trimmed: r#"Say "ten" words"#,
+            trimmed_span: Span {
+                start: LineColumn {
+                    line: 2_usize,
+                    column: 11_usize,
+                },
+                end: LineColumn {
+                    line: 2_usize,
+                    column: 25_usize,
+                },
+            },
+            variant: CommentVariant::MacroDocEq("#[doc = ".to_string(), 2),
         },
     ];
 
@@ -1529,6 +1469,38 @@ fn raw_variant_7_unicode_symbols() {
     comment_variant_span_range_validation(7);
 }
 
+#[test]
+fn raw_variant_9_doc_eq_raw_without_hashes() {
+    comment_variant_span_range_validation(9);
+}
+
+#[test]
+fn raw_variant_10_doc_eq_raw_quote_chars() {
+    comment_variant_span_range_validation(10);
+}
+
+#[test]
+fn variant_escape() {
+    let variant = CommentVariant::MacroDocEq("#[doc = ".to_string(), 0);
+    assert_eq!(
+        variant.escape(r#"a "b" \c"#).as_deref(),
+        Some(r#"a \"b\" \\c"#)
+    );
+    let variant = CommentVariant::MacroDocEq("#[doc = ".to_string(), 1);
+    assert_eq!(variant.escape(r"a \c").as_deref(), Some(r"a \c"));
+    assert_eq!(variant.escape(r#"a "b""#), None);
+    let variant = CommentVariant::MacroDocEq("#[doc = ".to_string(), 2);
+    assert_eq!(
+        variant.escape(r#"a "b" \c"#).as_deref(),
+        Some(r#"a "b" \c"#)
+    );
+    assert_eq!(variant.escape(r##"a "#b"##), None);
+    assert_eq!(
+        CommentVariant::TripleSlash.escape(r#""b""#).as_deref(),
+        Some(r#""b""#)
+    );
+}
+
 #[test]
 fn variant_to_string() {
     let variant = CommentVariant::MacroDocEq("#[ doc = ".to_string(), 0);
@@ -1577,3 +1549,293 @@ fn variant_consistency() {
         );
     }
 }
+
+#[test]
+fn doc_hidden() {
+    const SOURCE: &str = r#"/// Visible.
+pub struct Visible {
+    /// Visible field.
+    pub a: u8,
+    #[doc(hidden)]
+    /// Hidden field.
+    pub b: HashMap<u8, u8>,
+    /// Another visible field.
+    pub c: u8,
+}
+
+/// Hidden function.
+#[inline]
+#[doc(hidden)]
+pub fn hidden() -> Result<(), ()> {
+    /// Hidden nested item.
+    struct Nested;
+}
+
+#[doc(hidden)]
+pub mod hidden {
+    //! Hidden module.
+
+    /// Hidden item.
+    pub struct Item;
+}
+
+mod inner {
+    #![doc(hidden)]
+    //! Hidden inner module.
+}
+
+/// Also visible.
+pub struct AlsoVisible;
+"#;
+    let mut d = Documentation::from((ContentOrigin::TestEntityRust, SOURCE));
+    let chunks = d.get(&ContentOrigin::TestEntityRust).unwrap();
+    let hidden = |hidden: bool| {
+        chunks
+            .iter()
+            .filter(|chunk| chunk.is_hidden() == hidden)
+            .map(|chunk| chunk.as_str().trim().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        hidden(false),
+        vec![
+            "Visible.",
+            "Visible field.",
+            "Another visible field.",
+            "Also visible."
+        ]
+    );
+    assert_eq!(
+        hidden(true),
+        vec![
+            "Hidden field.",
+            "Hidden function.",
+            "Hidden nested item.",
+            "Hidden module.",
+            "Hidden item.",
+            "Hidden inner module."
+        ]
+    );
+
+    let mut scanned = Documentation::new();
+    scanned.add_rust_scanned(ContentOrigin::TestEntityRust, SOURCE);
+    let scanned = scanned.get(&ContentOrigin::TestEntityRust).unwrap();
+    assert_eq!(
+        scanned
+            .iter()
+            .map(|chunk| (chunk.as_str(), chunk.is_hidden()))
+            .collect::<Vec<_>>(),
+        chunks
+            .iter()
+            .map(|chunk| (chunk.as_str(), chunk.is_hidden()))
+            .collect::<Vec<_>>()
+    );
+
+    d.retain_chunks(|chunk| !chunk.is_hidden());
+    assert_eq!(d.get(&ContentOrigin::TestEntityRust).unwrap().len(), 4);
+    d.retain_chunks(|_chunk| false);
+    assert!(d.is_empty());
+}
+
+#[test]
+fn private_items() {
+    const SOURCE: &str = r#"//! Crate.
+
+/// Public.
+pub struct Public {
+    /// Public field.
+    pub a: u8,
+    /// Private field.
+    b: u8,
+}
+
+/// Private.
+pub(crate) enum Private {
+    /// Private variant.
+    A,
+}
+
+/// Public enum.
+pub enum Enum {
+    /// Public variant.
+    A,
+}
+
+impl Public {
+    /// Public method.
+    pub fn new() -> Self {
+        /// Private nested.
+        struct Nested;
+    }
+    /// Private method.
+    fn private() {}
+}
+
+impl Default for Public {
+    /// Public trait method.
+    fn default() -> Self {}
+}
+
+/// Exported macro.
+#[macro_export]
+macro_rules! exported {
+    () => {};
+}
+
+mod private {
+    //! Private module.
+
+    /// Private item.
+    pub struct Item;
+}
+"#;
+    let d = Documentation::from((ContentOrigin::TestEntityRust, SOURCE));
+    let chunks = d.get(&ContentOrigin::TestEntityRust).unwrap();
+    let private = |private: bool| {
+        chunks
+            .iter()
+            .filter(|chunk| chunk.is_private() == private)
+            .map(|chunk| chunk.as_str().trim().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        private(false),
+        vec![
+            "Crate.",
+            "Public.",
+            "Public field.",
+            "Public enum.",
+            "Public variant.",
+            "Public method.",
+            "Public trait method.",
+            "Exported macro."
+        ]
+    );
+    assert_eq!(
+        private(true),
+        vec![
+            "Private field.",
+            "Private.",
+            "Private variant.",
+            "Private nested.",
+            "Private method.",
+            "Private module.",
+            "Private item."
+        ]
+    );
+}
+
+#[test]
+fn commonmark_chunked() {
+    const CONTENT: &str = r#"# Title
+
+First paragraph.
+
+```rust
+let x = 1;
+
+let y = 2;
+```
+
+* item
+
+  continued item
+
+Last wrod."#;
+
+    let mut d = Documentation::new();
+    d.add_commonmark_chunked(ContentOrigin::TestEntityCommonMark, CONTENT, 1)
+        .unwrap();
+    let chunks = d.get(&ContentOrigin::TestEntityCommonMark).unwrap();
+    let parts = chunks
+        .iter()
+        .map(|chunk| chunk.as_str().lines().next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        parts,
+        vec![
+            "# Title",
+            "First paragraph.",
+            "```rust",
+            "* item",
+            "Last wrod."
+        ]
+    );
+    assert_eq!(
+        chunks
+            .iter()
+            .map(CheckableChunk::as_str)
+            .collect::<String>(),
+        CONTENT
+    );
+
+    let last = chunks.last().unwrap();
+    let plain = last.erase_cmark();
+    let (_range, span) = plain.find_spans(5..9).into_iter().next().unwrap();
+    assert_eq!(span.start.line, 15);
+    assert_eq!(
+        load_span_from(&mut CONTENT.as_bytes(), span).unwrap(),
+        "wrod"
+    );
+
+    let mut whole = Documentation::new();
+    whole
+        .add_commonmark_chunked(ContentOrigin::TestEntityCommonMark, CONTENT, 1 << 20)
+        .unwrap();
+    assert_eq!(
+        whole
+            .get(&ContentOrigin::TestEntityCommonMark)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn load_by_extension() {
+    let md = Documentation::load_from_path("README.md").unwrap();
+    let (origin, chunks) = md.iter().next().unwrap();
+    assert_eq!(origin, &ContentOrigin::CommonMarkFile("README.md".into()));
+    assert_eq!(chunks[0].variant(), CommentVariant::CommonMark);
+
+    let rs = Documentation::load_from_path("src/lib.rs").unwrap();
+    let (origin, chunks) = rs.iter().next().unwrap();
+    assert_eq!(origin, &ContentOrigin::RustSourceFile("src/lib.rs".into()));
+    assert_eq!(chunks[0].variant(), CommentVariant::DoubleSlashEM);
+
+    assert!(Documentation::load_from_path("does/not/exist.rs").is_err());
+}
+
+#[test]
+fn scanned_like_parsed() {
+    const SOURCE: &str = r#"//! Crate docs.
+//!
+//! With an empty line.
+
+/// A struct 🦀 with
+///   an indented line.
+pub struct X {
+    /// A field.
+    pub y: u8,
+}
+
+//// Not a doc comment.
+/// Separated.
+"#;
+    let parsed = Documentation::from((ContentOrigin::TestEntityRust, SOURCE));
+    let mut scanned = Documentation::new();
+    scanned.add_rust_scanned(ContentOrigin::TestEntityRust, SOURCE);
+
+    let parsed = parsed.get(&ContentOrigin::TestEntityRust).unwrap();
+    let scanned = scanned.get(&ContentOrigin::TestEntityRust).unwrap();
+    assert_eq!(scanned.len(), 4);
+    assert_eq!(parsed.len(), scanned.len());
+    for (parsed, scanned) in parsed.iter().zip(scanned) {
+        assert_eq!(parsed.as_str(), scanned.as_str());
+        assert_eq!(parsed.variant(), scanned.variant());
+        assert_eq!(
+            parsed.find_spans(0..parsed.len_in_chars()),
+            scanned.find_spans(0..scanned.len_in_chars())
+        );
+    }
+}
@@ -0,0 +1,390 @@
+//! Explain why a word or phrase is flagged, and which settings influence it.
+//!
+//! The subject is checked on its own with the loaded configuration, every
+//! finding is listed with the checker that produced it, the source which
+//! rejected the subject and the configuration keys to look at. A subject
+//! naming a checker lists the configuration keys of that checker instead, a
+//! rule id as printed with each finding, i.e. `hunspell::unknown-word`, what
+//! the rule reports and whether it is disabled.
+
+use crate::checker;
+use crate::util::sub_chars;
+use crate::{Category, Config, ContentOrigin, Detector, Documentation, Severity};
+
+use anyhow::Result;
+
+use std::io::Write;
+use std::path::PathBuf;
+
+/// A single finding for the subject.
+#[derive(Debug, Clone)]
+struct Finding {
+    detector: Detector,
+    category: Category,
+    severity: Severity,
+    /// The stable id of the rule, i.e. `hunspell::unknown-word`.
+    rule: String,
+    description: Option<String>,
+    replacements: Vec<String>,
+    /// The flagged part of the subject.
+    flagged: String,
+    /// Not reported, since the ignore file lists the flagged word.
+    ignored: bool,
+}
+
+/// The configuration keys which influence the findings of all checkers.
+const COMMON_KEYS: &[&str] = &[
+    "categories",
+    "disabled_rules",
+    "ignore file `words` and `identifiers`",
+];
+
+/// The configuration keys which influence the findings of `detector`.
+fn keys(detector: Detector) -> &'static [&'static str] {
+    match detector {
+        Detector::Hunspell => &[
+            "Hunspell.lang",
+            "Hunspell.search_dirs",
+            "Hunspell.extra_dictionaries",
+            "Hunspell.use_personal_dictionary",
+            "Hunspell.enforce_locale",
+            "Hunspell.symbols",
+            "Hunspell.severity",
+            "Hunspell.quirks",
+            "Hunspell.case",
+            "Hunspell.skip",
+        ],
+        Detector::LanguageTool => &["LanguageTool.url", "LanguageTool.severity"],
+        Detector::Reflow => &["Reflow.max_line_length"],
+        Detector::Consistency => &["Consistency.severity"],
+        Detector::Terminology => &["terminology"],
+        Detector::Banned => &["banned"],
+        #[cfg(test)]
+        Detector::Dummy => &[],
+    }
+}
+
+/// What is explained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Subject<'a> {
+    /// A rule of a checker, by its id.
+    Rule(Detector, &'a str),
+    /// A checker by its name.
+    Checker(Detector),
+    /// A word or phrase, which is checked.
+    Text(&'a str),
+}
+
+impl<'a> Subject<'a> {
+    /// Rule ids take precedence, everything not naming a checker or a rule of
+    /// one is a word or phrase.
+    fn parse(subject: &'a str) -> Self {
+        let subject = subject.trim();
+        if let Some((detector, rule)) = subject.split_once("::") {
+            let is_rule = !rule.is_empty() && !rule.contains(char::is_whitespace);
+            match detector.parse::<Detector>() {
+                Ok(detector) if is_rule => return Self::Rule(detector, rule),
+                _ => {}
+            }
+        }
+        match subject.parse::<Detector>() {
+            Ok(detector) => Self::Checker(detector),
+            Err(_) => Self::Text(subject),
+        }
+    }
+}
+
+/// What the built-in `rule` of `detector` reports, the LanguageTool rules are
+/// the ones of the server.
+fn rule_description(detector: Detector, rule: &str) -> Option<&'static str> {
+    Some(match (detector, rule) {
+        (Detector::Hunspell, "unknown-word") => "Words not found in any of the dictionaries.",
+        (Detector::Hunspell, "wrong-case") => {
+            "Words only found in the dictionaries with a different case."
+        }
+        (Detector::Hunspell, "symbol") => "Symbols in doc text, which belong in code.",
+        (Detector::Hunspell, "other-locale") => {
+            "Spellings of the other English locale than the configured one."
+        }
+        (Detector::LanguageTool, _) => "A rule of the LanguageTool server, see its rule list.",
+        (Detector::Reflow, "line-length") => "Paragraphs which can be wrapped better.",
+        (Detector::Consistency, "mixed-variants") => {
+            "Words spelled in more than one variant across the documentation."
+        }
+        (Detector::Terminology, "disfavored-term") => "Terms with a preferred alternative.",
+        (Detector::Banned, "banned-term") => "Terms disallowed by policy.",
+        _ => return None,
+    })
+}
+
+/// What rejected the subject, as far as it is known without asking the
+/// checker itself.
+fn rejected_by(finding: &Finding, config: &Config) -> Option<String> {
+    match finding.detector {
+        Detector::Hunspell => config.hunspell.as_ref().map(|hunspell| {
+            let extra = hunspell
+                .extra_dictionaries()
+                .iter()
+                .map(|dic| dic.display().to_string())
+                .collect::<Vec<_>>();
+            if extra.is_empty() {
+                format!("the `{}` dictionary", hunspell.lang())
+            } else {
+                format!(
+                    "the `{}` dictionary and the extra dictionaries {}",
+                    hunspell.lang(),
+                    extra.join(", ")
+                )
+            }
+        }),
+        Detector::LanguageTool => config
+            .languagetool
+            .as_ref()
+            .map(|languagetool| format!("the LanguageTool server at {}", languagetool.url())),
+        Detector::Terminology => config
+            .terminology
+            .terms
+            .iter()
+            .find(|(term, _preferred)| term.to_lowercase() == finding.flagged.to_lowercase())
+            .map(|(term, preferred)| format!("the term `{}` = `{}`", term, preferred)),
+        Detector::Banned => config
+            .banned
+            .keys()
+            .find(|term| term.to_lowercase() == finding.flagged.to_lowercase())
+            .map(|term| format!("the banned term `{}`", term)),
+        _ => None,
+    }
+}
+
+/// Check `subject` on its own, without dropping ignored words.
+fn findings(subject: &str, config: &Config) -> Result<Vec<Finding>> {
+    let origin = ContentOrigin::CommonMarkFile(PathBuf::from("<explain>"));
+    let documentation = Documentation::load_from_str(origin, subject)?;
+    let mut unfiltered = config.clone();
+    unfiltered.ignore = Default::default();
+    unfiltered.max_suggestions_per_file = None;
+    let suggestions = checker::check(&documentation, &unfiltered)?;
+    Ok(suggestions
+        .iter()
+        .flat_map(|(_origin, suggestions)| suggestions.iter())
+        .map(|suggestion| {
+            let flagged = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+            Finding {
+                detector: suggestion.detector,
+                category: suggestion.category,
+                severity: suggestion.severity,
+                rule: suggestion.rule_id(),
+                description: suggestion.description.clone(),
+                replacements: suggestion.replacements.clone(),
+                ignored: config.ignore.is_ignored(&flagged),
+                flagged,
+            }
+        })
+        .collect())
+}
+
+/// Write whether `detector` is enabled and its configuration keys to `sink`.
+fn explain_checker<W: Write>(detector: Detector, config: &Config, sink: &mut W) -> Result<()> {
+    let state = if config.is_enabled(detector) {
+        "enabled"
+    } else {
+        "not enabled"
+    };
+    writeln!(sink, "{} is {}, configured by:", detector, state)?;
+    for key in keys(detector) {
+        writeln!(sink, "  {}", key)?;
+    }
+    Ok(())
+}
+
+/// Write what `rule` of `detector` reports and whether it is disabled to
+/// `sink`.
+fn explain_rule<W: Write>(
+    detector: Detector,
+    rule: &str,
+    config: &Config,
+    sink: &mut W,
+) -> Result<()> {
+    let id = format!("{}::{}", detector.to_string().to_lowercase(), rule);
+    let description = match rule_description(detector, rule) {
+        Some(description) => description,
+        None => {
+            writeln!(sink, "`{}` is not a rule of {}", id, detector)?;
+            return Ok(());
+        }
+    };
+    let state = if config.is_enabled(detector) {
+        "enabled"
+    } else {
+        "not enabled"
+    };
+    writeln!(
+        sink,
+        "`{}` is a rule of {}, which is {}",
+        id, detector, state
+    )?;
+    writeln!(sink, "  {}", description)?;
+    if config.disabled_rules.contains(&id) {
+        writeln!(sink, "  disabled by `disabled_rules`")?;
+    }
+    writeln!(
+        sink,
+        "  allowed inline by `<!-- spellcheck: allow({}) -->`",
+        id
+    )?;
+    writeln!(
+        sink,
+        "  configured by: {}",
+        keys(detector)
+            .iter()
+            .copied()
+            .chain(COMMON_KEYS.iter().copied())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )?;
+    Ok(())
+}
+
+/// Write the explanation for `subject`, a word, a phrase, a checker or a rule
+/// id, to `sink`.
+pub fn explain<W: Write>(subject: &str, config: &Config, sink: &mut W) -> Result<()> {
+    let subject = match Subject::parse(subject) {
+        Subject::Rule(detector, rule) => return explain_rule(detector, rule, config, sink),
+        Subject::Checker(detector) => return explain_checker(detector, config, sink),
+        Subject::Text(text) => text,
+    };
+
+    let findings = findings(subject, config)?;
+    if findings.is_empty() {
+        writeln!(sink, "`{}` is not flagged by any enabled checker", subject)?;
+        return Ok(());
+    }
+    for finding in findings {
+        writeln!(
+            sink,
+            "`{}` is flagged by {} as {} {}, rule `{}`",
+            finding.flagged, finding.detector, finding.category, finding.severity, finding.rule
+        )?;
+        if let Some(ref description) = finding.description {
+            writeln!(sink, "  {}", description)?;
+        }
+        if let Some(source) = rejected_by(&finding, config) {
+            writeln!(sink, "  rejected by {}", source)?;
+        }
+        if !finding.replacements.is_empty() {
+            writeln!(sink, "  replacements: {}", finding.replacements.join(", "))?;
+        }
+        if finding.ignored {
+            writeln!(
+                sink,
+                "  not reported, since it is listed in the ignore file"
+            )?;
+        }
+        writeln!(
+            sink,
+            "  configured by: {}",
+            keys(finding.detector)
+                .iter()
+                .copied()
+                .chain(COMMON_KEYS.iter().copied())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IgnoreConfig;
+
+    fn explained(subject: &str, config: &Config) -> String {
+        let mut sink = Vec::new();
+        explain(subject, config, &mut sink).unwrap();
+        String::from_utf8(sink).unwrap()
+    }
+
+    #[test]
+    fn terminology() {
+        let mut config = Config::parse(
+            r#"
+[terminology.terms]
+"web site" = "website"
+"#,
+        )
+        .unwrap();
+
+        let explanation = explained("web site", &config);
+        assert!(explanation.starts_with(
+            "`web site` is flagged by Terminology as style error, rule `terminology::disfavored-term`\n"
+        ));
+        assert!(explanation.contains("  rejected by the term `web site` = `website`\n"));
+        assert!(explanation.contains("  replacements: website\n"));
+        assert!(explanation.contains("  configured by: terminology, categories, disabled_rules, "));
+        assert!(!explanation.contains("ignore file\n"));
+
+        config.ignore = IgnoreConfig::parse(r#"words = ["Web Site"]"#).unwrap();
+        assert!(explained("web site", &config)
+            .contains("  not reported, since it is listed in the ignore file\n"));
+
+        assert_eq!(
+            explained("website", &config),
+            "`website` is not flagged by any enabled checker\n"
+        );
+    }
+
+    #[test]
+    fn checker() {
+        let config = Config::parse("[terminology.terms]\n\"web site\" = \"website\"").unwrap();
+        assert_eq!(
+            explained("terminology", &config),
+            "Terminology is enabled, configured by:\n  terminology\n"
+        );
+        assert!(explained("consistency", &config).starts_with("Consistency is not enabled"));
+    }
+
+    #[test]
+    fn rule() {
+        assert_eq!(
+            Subject::parse("hunspell::unknown-word"),
+            Subject::Rule(Detector::Hunspell, "unknown-word")
+        );
+        assert_eq!(
+            Subject::parse("LanguageTool::EN_A_VS_AN"),
+            Subject::Rule(Detector::LanguageTool, "EN_A_VS_AN")
+        );
+        assert_eq!(
+            Subject::parse("hunspell"),
+            Subject::Checker(Detector::Hunspell)
+        );
+        assert_eq!(Subject::parse("std::vec"), Subject::Text("std::vec"));
+        assert_eq!(
+            Subject::parse("hunspell:: word"),
+            Subject::Text("hunspell:: word")
+        );
+
+        let config = Config::parse(
+            r#"
+disabled_rules = ["terminology::disfavored-term"]
+[terminology.terms]
+"web site" = "website"
+"#,
+        )
+        .unwrap();
+        let explanation = explained("terminology::disfavored-term", &config);
+        assert!(explanation.starts_with(
+            "`terminology::disfavored-term` is a rule of Terminology, which is enabled\n"
+        ));
+        assert!(explanation.contains("  disabled by `disabled_rules`\n"));
+        assert!(explanation.contains(
+            "  allowed inline by `<!-- spellcheck: allow(terminology::disfavored-term) -->`\n"
+        ));
+        assert!(explanation.contains("  configured by: terminology, categories, "));
+        assert!(!explained("banned::banned-term", &config).contains("disabled by"));
+        assert_eq!(
+            explained("terminology::made-up", &config),
+            "`terminology::made-up` is not a rule of Terminology\n"
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! Import the accepted words of other spellcheckers.
+//!
+//! Supports the `words` of a `cspell.json`, codespell ignore word lists as
+//! well as the `ignore-words-list` of a `.codespellrc` or `setup.cfg`, and
+//! Vale `accept.txt` vocabularies. The words are converted into a hunspell
+//! dictionary, to be used as one of the `extra_dictionaries`.
+//!
+//! Words picked to be added to the dictionary during `fix` are appended to an
+//! existing dictionary the same way.
+
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// The formats of other spellcheckers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// A `cspell.json` with a list of `words`.
+    CSpell,
+    /// A `.codespellrc` or `setup.cfg` with an `ignore-words-list`.
+    CodespellConfig,
+    /// A Vale vocabulary `accept.txt`, which may contain regular expressions.
+    Vale,
+    /// One word per line, i.e. a codespell `--ignore-words` file.
+    WordList,
+}
+
+impl Source {
+    /// Derive the format from the file name.
+    pub fn detect(path: &Path) -> Self {
+        let file_name = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match file_name.as_str() {
+            "accept.txt" => Self::Vale,
+            ".codespellrc" | "setup.cfg" | "tox.ini" => Self::CodespellConfig,
+            name if name.ends_with(".json") => Self::CSpell,
+            _ => Self::WordList,
+        }
+    }
+
+    /// Extract all accepted words of `content`.
+    pub fn parse(self, content: &str) -> Result<Vec<String>> {
+        match self {
+            Self::CSpell => parse_cspell(content),
+            Self::CodespellConfig => Ok(parse_codespell_config(content)),
+            Self::Vale => Ok(parse_vale(content)),
+            Self::WordList => Ok(parse_word_list(content)),
+        }
+    }
+}
+
+/// `cspell.json` allows line comments.
+fn parse_cspell(content: &str) -> Result<Vec<String>> {
+    let content = content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Failed to parse cspell configuration").context(e))?;
+    let words = ["words", "ignoreWords"]
+        .iter()
+        .filter_map(|key| value.get(key).and_then(|words| words.as_array()))
+        .flatten()
+        .filter_map(|word| word.as_str())
+        .map(ToOwned::to_owned)
+        .collect();
+    Ok(words)
+}
+
+/// Only the `[codespell]` section applies.
+fn parse_codespell_config(content: &str) -> Vec<String> {
+    let mut in_section = false;
+    let mut words = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == "[codespell]";
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let mut split = line.splitn(2, '=');
+        let (key, value) = match (split.next(), split.next()) {
+            (Some(key), Some(value)) => (key.trim(), value),
+            _ => continue,
+        };
+        if key == "ignore-words-list" || key == "ignore_words_list" {
+            words.extend(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|word| !word.is_empty())
+                    .map(ToOwned::to_owned),
+            );
+        }
+    }
+    words
+}
+
+/// Entries of Vale vocabularies are regular expressions, only plain
+/// words can be imported.
+fn parse_vale(content: &str) -> Vec<String> {
+    parse_word_list(content)
+        .into_iter()
+        .filter(|word| {
+            let plain = word
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '\'' || c == '.');
+            if !plain {
+                warn!("Skipping regular expression {:?} of the vocabulary", word);
+            }
+            plain
+        })
+        .collect()
+}
+
+fn parse_word_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Read the accepted words of the file at `path`.
+pub fn read_words(path: &Path) -> Result<Vec<String>> {
+    let source = Source::detect(path);
+    debug!("Importing {} as {:?}", path.display(), source);
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+    source
+        .parse(&content)
+        .map_err(|e| e.context(anyhow!("Failed to import {}", path.display())))
+}
+
+/// Convert `words` into the content of a hunspell dictionary.
+///
+/// Phrases are split into their words, since dictionaries only contain single
+/// words, and words containing a `/` are skipped, since it separates affix
+/// flags.
+pub fn to_dictionary<I>(words: I) -> String
+where
+    I: IntoIterator<Item = String>,
+{
+    let words = words
+        .into_iter()
+        .flat_map(|phrase| {
+            dictionary_words(&phrase)
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .collect::<BTreeSet<_>>();
+    let mut dictionary = format!("{}\n", words.len());
+    for word in words {
+        dictionary.push_str(&word);
+        dictionary.push('\n');
+    }
+    dictionary
+}
+
+/// Split `phrase` into the words a dictionary can contain.
+fn dictionary_words(phrase: &str) -> impl Iterator<Item = &str> {
+    phrase.split_whitespace().filter(|word| {
+        let keep = !word.contains('/');
+        if !keep {
+            warn!("Skipping {:?}, `/` is not allowed in a dictionary", word);
+        }
+        keep
+    })
+}
+
+/// Add `words` to the hunspell dictionary `content`, which keeps its entries
+/// as they are, and return the new content and the number of added words.
+///
+/// Entries are compared without their affix flags, the count in the first
+/// line is updated.
+fn extend_dictionary(content: &str, words: &[String]) -> (String, usize) {
+    let mut lines = content.lines().peekable();
+    // the approximate number of entries, unless the file is empty
+    if matches!(lines.peek(), Some(first) if first.trim().parse::<usize>().is_ok()) {
+        let _ = lines.next();
+    }
+    let mut entries = lines
+        .filter(|line| !line.trim().is_empty())
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    let mut known = entries
+        .iter()
+        .map(|entry| {
+            entry
+                .split('/')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_owned()
+        })
+        .collect::<BTreeSet<_>>();
+    let mut added = 0;
+    for word in words.iter().flat_map(|phrase| dictionary_words(phrase)) {
+        if known.insert(word.to_owned()) {
+            entries.push(word.to_owned());
+            added += 1;
+        }
+    }
+    let mut dictionary = format!("{}\n", entries.len());
+    for entry in entries {
+        dictionary.push_str(&entry);
+        dictionary.push('\n');
+    }
+    (dictionary, added)
+}
+
+/// Add `words` to the hunspell dictionary at `path`, which is created if it
+/// does not exist yet, and return the number of added words.
+pub fn add_to_dictionary(path: &Path, words: &[String]) -> Result<usize> {
+    let content = if path.is_file() {
+        std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read dictionary {}", path.display()).context(e))?
+    } else {
+        String::new()
+    };
+    let (dictionary, added) = extend_dictionary(&content, words);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow!("Failed to create directory {}", dir.display()).context(e))?;
+    }
+    std::fs::write(path, dictionary)
+        .map_err(|e| anyhow!("Failed to write dictionary {}", path.display()).context(e))?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        assert_eq!(Source::detect(Path::new("cspell.json")), Source::CSpell);
+        assert_eq!(Source::detect(Path::new(".cspell.json")), Source::CSpell);
+        assert_eq!(
+            Source::detect(Path::new(".codespellrc")),
+            Source::CodespellConfig
+        );
+        assert_eq!(
+            Source::detect(Path::new("styles/Vocab/Project/accept.txt")),
+            Source::Vale
+        );
+        assert_eq!(
+            Source::detect(Path::new("codespell-ignore.txt")),
+            Source::WordList
+        );
+    }
+
+    #[test]
+    fn cspell() {
+        let content = r#"{
+    // the version of the configuration
+    "version": "0.2",
+    "words": ["rustc", "New York"],
+    "ignoreWords": ["hte"]
+}"#;
+        assert_eq!(
+            Source::CSpell.parse(content).unwrap(),
+            vec!["rustc", "New York", "hte"]
+        );
+        assert!(Source::CSpell.parse("{ words").is_err());
+    }
+
+    #[test]
+    fn codespell() {
+        let content = r#"
+[flake8]
+ignore-words-list = nope
+
+[codespell]
+skip = ./target
+ignore-words-list = crate, ser ,deque
+"#;
+        assert_eq!(
+            Source::CodespellConfig.parse(content).unwrap(),
+            vec!["crate", "ser", "deque"]
+        );
+        assert_eq!(
+            Source::WordList.parse("# comment\nser\n\ndeque\n").unwrap(),
+            vec!["ser", "deque"]
+        );
+    }
+
+    #[test]
+    fn vale() {
+        assert_eq!(
+            Source::Vale
+                .parse("Tokio\n[Cc]rates?\nrust-analyzer\n")
+                .unwrap(),
+            vec!["Tokio", "rust-analyzer"]
+        );
+    }
+
+    #[test]
+    fn dictionary() {
+        let words = vec![
+            "rustc".to_owned(),
+            "New York".to_owned(),
+            "a/b".to_owned(),
+            "rustc".to_owned(),
+        ];
+        assert_eq!(to_dictionary(words), "3\nNew\nYork\nrustc\n");
+    }
+
+    #[test]
+    fn extend() {
+        let words = vec!["rustc".to_owned(), "Tokio".to_owned(), "cargo".to_owned()];
+        assert_eq!(
+            extend_dictionary("", &words),
+            ("3\nrustc\nTokio\ncargo\n".to_owned(), 3)
+        );
+        assert_eq!(
+            extend_dictionary("2\ncargo/S\nserde\n", &words),
+            ("4\ncargo/S\nserde\nrustc\nTokio\n".to_owned(), 2)
+        );
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("dictionaries").join("project.dic");
+        assert_eq!(add_to_dictionary(&path, &words).unwrap(), 3);
+        assert_eq!(add_to_dictionary(&path, &words).unwrap(), 0);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "3\nrustc\nTokio\ncargo\n"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+#![deny(dead_code)]
+#![deny(missing_docs)]
+#![deny(unused_crate_dependencies)]
+#![warn(clippy::pedantic)]
+
+//! cargo-spellcheck
+//!
+//! A syntax tree based doc comment and common mark spell checker.
+//!
+//! Besides the `cargo spellcheck` binary, the extraction of doc comments and
+//! common mark is available to other documentation tools: a [`Documentation`]
+//! is loaded from a path or a string and consists of [`CheckableChunk`]s per
+//! [`ContentOrigin`]. Each chunk can be reduced to plain text by means of
+//! [`CheckableChunk::erase_cmark`] and every range of the plain text maps back
+//! to [`Span`]s of the source.
+//!
+//! ```
+//! use cargo_spellcheck::{ContentOrigin, Documentation};
+//!
+//! let source = "/// A *very* nice struct.\nstruct X;\n";
+//! let origin = ContentOrigin::RustSourceFile("src/lib.rs".into());
+//! let docs = Documentation::load_from_str(origin.clone(), source).unwrap();
+//! let chunk = &docs.get(&origin).unwrap()[0];
+//! let plain = chunk.erase_cmark();
+//! assert_eq!(plain.as_str(), "A very nice struct.");
+//!
+//! // `nice` in the plain text, mapped back to the source
+//! let spans = plain.find_spans(7..11);
+//! let (_range, span) = spans.iter().next().unwrap();
+//! assert_eq!((span.start.line, span.start.column), (1, 13));
+//! assert_eq!((span.end.line, span.end.column), (1, 16));
+//! ```
+
+mod action;
+mod checker;
+mod checksum;
+mod cli;
+mod commit_msg;
+mod completions;
+mod config;
+mod documentation;
+mod explain;
+mod import;
+mod man;
+mod output;
+mod reflow;
+mod sentence;
+mod span;
+mod suggestion;
+mod timings;
+mod traverse;
+mod util;
+
+pub use self::documentation::{CheckableChunk, ContentOrigin, Documentation, PlainOverlay, Range};
+pub use self::span::{LineColumn, Span};
+pub use self::suggestion::{Category, Detector, Severity, Suggestion, SuggestionSet};
+#[cfg(feature = "serde")]
+pub use self::suggestion::{SuggestionIn, SuggestionSetIn};
+
+pub(crate) use self::action::*;
+pub(crate) use self::config::Config;
+pub(crate) use self::documentation::*;
+pub(crate) use self::util::*;
+
+/// The entry point of the `cargo spellcheck` binary, not part of the public
+/// interface.
+#[doc(hidden)]
+pub use self::cli::main;
+
+use log::warn;
+
+#[cfg(target_os = "windows")]
+use signal_hook as _;
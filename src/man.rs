@@ -0,0 +1,138 @@
+//! A man page, rendered from the docopt usage string and the documented
+//! configuration of the readme.
+//!
+//! Neither is duplicated, so the man page generated at release time is always
+//! in line with the commandline and the configuration file which are accepted.
+
+use crate::completions::{options, Opt};
+
+use anyhow::Result;
+
+use std::io::Write;
+
+/// The readme, which documents all settings of the configuration file.
+const README: &str = include_str!("../README.md");
+
+/// Escape `s` for use in roff text.
+fn escape(s: &str) -> String {
+    let escaped = s.replace('\\', r"\e").replace('-', r"\-");
+    if escaped.starts_with(|c| c == '.' || c == '\'') {
+        format!(r"\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// The documented configuration, the first `toml` block of the
+/// `## Configuration` section of the readme.
+fn configuration(readme: &str) -> Option<&str> {
+    let section = &readme[readme.find("\n## Configuration")?..];
+    let block = &section[section.find("```toml\n")? + "```toml\n".len()..];
+    Some(&block[..block.find("```")?])
+}
+
+/// The option as it is written, i.e. `-c, --cfg=<cfg>`.
+fn synopsis(opt: &Opt) -> String {
+    let mut synopsis = String::new();
+    if let Some(short) = opt.short {
+        synopsis.push_str(&format!(r"\fB\-{}\fR, ", short));
+    }
+    synopsis.push_str(&format!(r"\fB\-\-{}\fR", escape(&opt.long)));
+    if let Some(ref value) = opt.value {
+        synopsis.push_str(&format!(r"=\fI{}\fR", escape(value)));
+    }
+    synopsis
+}
+
+/// Write the man page of section 1 derived from `usage`.
+pub fn generate<W: Write>(usage: &str, sink: &mut W) -> Result<()> {
+    let description = usage
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    writeln!(
+        sink,
+        r#".TH CARGO\-SPELLCHECK 1 "" "cargo\-spellcheck {}" "User Commands""#,
+        escape(env!("CARGO_PKG_VERSION"))
+    )?;
+    writeln!(sink, ".SH NAME")?;
+    writeln!(sink, r"cargo\-spellcheck \- {}", escape(description))?;
+
+    writeln!(sink, ".SH SYNOPSIS")?;
+    writeln!(sink, ".nf")?;
+    for line in usage
+        .lines()
+        .skip_while(|line| !line.starts_with("Usage:"))
+        .skip(1)
+        .take_while(|line| !line.trim().is_empty())
+    {
+        writeln!(sink, "{}", escape(line.trim()))?;
+    }
+    writeln!(sink, ".fi")?;
+
+    writeln!(sink, ".SH OPTIONS")?;
+    for opt in options(usage) {
+        writeln!(sink, ".TP")?;
+        writeln!(sink, "{}", synopsis(&opt))?;
+        writeln!(sink, "{}", escape(&opt.help))?;
+    }
+
+    if let Some(configuration) = configuration(README) {
+        writeln!(sink, ".SH CONFIGURATION")?;
+        writeln!(
+            sink,
+            "{}",
+            escape(
+                "All settings of the configuration file, with the default values where \
+                 applicable. `cargo spellcheck config --stdout` prints the defaults."
+            )
+        )?;
+        writeln!(sink, ".PP")?;
+        writeln!(sink, ".nf")?;
+        for line in configuration.lines() {
+            writeln!(sink, "{}", escape(line))?;
+        }
+        writeln!(sink, ".fi")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped() {
+        assert_eq!(escape("--cfg"), r"\-\-cfg");
+        assert_eq!(escape(r"C:\Users"), r"C:\eUsers");
+        assert_eq!(escape(".config"), r"\&.config");
+    }
+
+    #[test]
+    fn page() {
+        const USAGE: &str = r#"
+Spellcheck all your doc comments
+
+Usage:
+    cargo-spellcheck [(-v...|-q)] check [--cfg=<cfg>]
+    cargo-spellcheck --help
+
+Options:
+  -h --help                 Show this screen.
+  -c --cfg=<cfg>            Use a non default configuration file.
+"#;
+        let mut sink = Vec::new();
+        generate(USAGE, &mut sink).unwrap();
+        let page = String::from_utf8(sink).unwrap();
+        assert!(page.starts_with(".TH CARGO\\-SPELLCHECK 1 "));
+        assert!(page.contains(
+            ".SH NAME\ncargo\\-spellcheck \\- Spellcheck all your doc comments\n.SH SYNOPSIS\n.nf\ncargo\\-spellcheck [(\\-v...|\\-q)] check [\\-\\-cfg=<cfg>]\ncargo\\-spellcheck \\-\\-help\n.fi\n"
+        ));
+        assert!(page.contains(
+            ".TP\n\\fB\\-c\\fR, \\fB\\-\\-cfg\\fR=\\fI<cfg>\\fR\nUse a non default configuration file.\n"
+        ));
+        assert!(page.contains(".SH CONFIGURATION\n"));
+        assert!(page.contains("\nmax_line_length = 80\n"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Comma separated values, one row per suggestion, for spreadsheets.
+//!
+//! Lines are 1-indexed and columns 0-indexed, as in the `json` output, and
+//! both the column and the length of the flagged word are counted in units
+//! of the configured column encoding.
+
+use super::{absolute_span, ColumnEncoding, LineIndex};
+use crate::util::sub_chars;
+use crate::Suggestion;
+
+use anyhow::Result;
+
+use std::io::Write;
+
+/// The names of the columns, written as the first row.
+pub(super) const HEADER: &[&str] = &[
+    "path",
+    "line",
+    "column",
+    "length",
+    "word",
+    "category",
+    "rule",
+    "replacement",
+];
+
+/// Quote `field` if it contains a separator, a quote or a line break,
+/// doubling the contained quotes.
+fn escape(field: &str) -> String {
+    if field.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Write one row of `fields`.
+pub(super) fn write_row<W: Write, S: AsRef<str>>(sink: &mut W, fields: &[S]) -> Result<()> {
+    let row = fields
+        .iter()
+        .map(|field| escape(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(sink, "{}", row)?;
+    Ok(())
+}
+
+/// Write a single suggestion as one row.
+pub(super) fn write_suggestion<W: Write>(
+    sink: &mut W,
+    suggestion: &Suggestion<'_>,
+    index: Option<&LineIndex>,
+    encoding: ColumnEncoding,
+) -> Result<()> {
+    let span = absolute_span(suggestion);
+    let column = index
+        .and_then(|index| index.column(span.start.line, span.start.column, encoding))
+        .unwrap_or(span.start.column);
+    let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+    let length = encoding.count(&word);
+    write_row(
+        sink,
+        &[
+            suggestion.origin.as_path().display().to_string(),
+            span.start.line.to_string(),
+            column.to_string(),
+            length.to_string(),
+            word,
+            suggestion.category.to_string(),
+            suggestion.rule_id(),
+            suggestion.replacements.first().cloned().unwrap_or_default(),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation};
+
+    #[test]
+    fn escaped() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a, b"), "\"a, b\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn rows() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("🦀 literäls")));
+        let suggestions = dummy::check_all(&d);
+        let (_origin, suggestions) = suggestions.iter().next().unwrap();
+        let index = LineIndex::new(fluff_up!("🦀 literäls").to_owned());
+
+        let mut sink = Vec::new();
+        write_row(&mut sink, HEADER).unwrap();
+        write_suggestion(
+            &mut sink,
+            &suggestions[1],
+            Some(&index),
+            ColumnEncoding::Utf8,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(sink).unwrap(),
+            "path,line,column,length,word,category,rule,replacement\n\
+             /tmp/test/entity.rs,1,9,9,literäls,spelling,dummy::dummy,replacement_1\n"
+        );
+    }
+}
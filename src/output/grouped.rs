@@ -0,0 +1,163 @@
+//! Identical findings grouped into one entry, listing all their locations.
+//!
+//! A word that is unknown to the dictionary usually is reported at many
+//! places, with the very same replacements, so it is printed only once.
+
+use super::{absolute_span, truncation_notice};
+use crate::util::sub_chars;
+use crate::{Category, Severity, Suggestion, SuggestionSet};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use std::io::Write;
+
+/// At most this many replacements are listed.
+const MAX_REPLACEMENTS: usize = 5;
+
+/// What identical findings have in common.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key<'a> {
+    rule: String,
+    category: Category,
+    severity: Severity,
+    word: String,
+    replacements: &'a [String],
+    description: Option<&'a str>,
+}
+
+/// Group all suggestions by the flagged word and what is suggested for it,
+/// the most frequent first, and on a tie in order of their first occurrence.
+fn group<'a, 's>(suggestions: &'a SuggestionSet<'s>) -> Vec<(Key<'a>, Vec<&'a Suggestion<'s>>)> {
+    let mut groups = IndexMap::<Key<'a>, Vec<&'a Suggestion<'s>>>::new();
+    for suggestion in suggestions
+        .iter()
+        .flat_map(|(_origin, suggestions)| suggestions.iter())
+    {
+        let key = Key {
+            rule: suggestion.rule_id(),
+            category: suggestion.category,
+            severity: suggestion.severity,
+            word: sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+            replacements: suggestion.replacements.as_slice(),
+            description: suggestion.description.as_deref(),
+        };
+        groups.entry(key).or_default().push(suggestion);
+    }
+    let mut groups = groups.into_iter().collect::<Vec<_>>();
+    // stable, so the order of first occurrence is kept
+    groups.sort_by_key(|(_key, occurrences)| std::cmp::Reverse(occurrences.len()));
+    groups
+}
+
+/// Write every group of identical suggestions once, followed by all of
+/// their locations.
+pub(super) fn write_suggestions<W: Write>(
+    sink: &mut W,
+    suggestions: &SuggestionSet<'_>,
+) -> Result<()> {
+    let theme = super::theme::current();
+    let highlight = &theme.highlight;
+    let arrow_marker = &theme.arrow;
+    let fix = &theme.replacement;
+    let help = &theme.marker;
+
+    for (key, occurrences) in group(suggestions) {
+        let error = theme.severity(key.severity);
+        let times = match occurrences.len() {
+            1 => "once".to_owned(),
+            n => format!("{} times", n),
+        };
+        writeln!(
+            sink,
+            "{}{} `{}` {}",
+            error.apply_to(key.severity),
+            highlight.apply_to(format!(": spellcheck({})", key.rule)),
+            key.word,
+            times
+        )?;
+        if let Some(description) = key.description {
+            writeln!(sink, "   {} {}", help.apply_to("="), description)?;
+        }
+        if !key.replacements.is_empty() {
+            let mut replacements = key
+                .replacements
+                .iter()
+                .take(MAX_REPLACEMENTS)
+                .map(|replacement| format!("{}", fix.apply_to(replacement)))
+                .collect::<Vec<_>>();
+            if key.replacements.len() > MAX_REPLACEMENTS {
+                replacements.push(format!(
+                    "and {} others",
+                    key.replacements.len() - MAX_REPLACEMENTS
+                ));
+            }
+            writeln!(
+                sink,
+                "   {} replace with: {}",
+                help.apply_to("="),
+                replacements.join(", ")
+            )?;
+        }
+        for suggestion in occurrences {
+            let span = absolute_span(suggestion);
+            writeln!(
+                sink,
+                "  {} {}:{}:{}",
+                arrow_marker.apply_to("-->"),
+                suggestion.origin.as_path().display(),
+                span.start.line,
+                span.start.column + 1
+            )?;
+        }
+        writeln!(sink)?;
+    }
+    for (origin, _suggestions) in suggestions.iter() {
+        if let Some(notice) = truncation_notice(suggestions, origin) {
+            writeln!(sink, "note: {}", notice)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation};
+
+    #[test]
+    fn grouped() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!("two two", "one two"),
+        ));
+        // the dummy suggests distinct replacements for every word
+        let mut suggestions = SuggestionSet::new();
+        for (origin, found) in dummy::check_all(&d).iter() {
+            for suggestion in found {
+                let mut suggestion = suggestion.clone();
+                suggestion.replacements = vec!["three".to_owned()];
+                suggestions.add(origin.clone(), suggestion);
+            }
+        }
+
+        let mut sink = Vec::new();
+        write_suggestions(&mut sink, &suggestions).unwrap();
+        let rendered = String::from_utf8(sink).unwrap();
+        assert_eq!(
+            console::strip_ansi_codes(&rendered),
+            r#"error: spellcheck(dummy::dummy) `two` 3 times
+   = replace with: three
+  --> /tmp/test/entity.rs:1:5
+  --> /tmp/test/entity.rs:1:9
+  --> /tmp/test/entity.rs:2:9
+
+error: spellcheck(dummy::dummy) `one` once
+   = replace with: three
+  --> /tmp/test/entity.rs:2:5
+
+"#
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! A self-contained HTML report, i.e. to be published as CI artifact.
+//!
+//! The report has one section per file, the flagged word highlighted in its
+//! source line and the list of replacements of every finding. Styles are
+//! inlined, so the single file can be viewed without anything else.
+
+use super::{absolute_span, truncation_notice, LineIndex};
+use crate::util::sub_chars;
+use crate::{Suggestion, SuggestionSet};
+
+use anyhow::Result;
+
+use std::io::Write;
+
+/// Inline styles of the report.
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+h2 { font-size: 1.1em; margin-top: 2em; border-bottom: 1px solid #ccc; }
+code, pre { font-family: monospace; }
+pre { background: #f6f6f6; padding: 0.5em; overflow-x: auto; }
+mark { background: #ffd6d6; text-decoration: underline wavy #c00; }
+.finding { margin: 1em 0; }
+.location { color: #666; }
+.error { color: #c00; font-weight: bold; }
+.warning { color: #b60; font-weight: bold; }
+.info { color: #06a; font-weight: bold; }
+"#;
+
+/// Escape the characters with a special meaning in HTML text and attributes.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The source line of `suggestion` with the flagged part wrapped in `<mark>`,
+/// only the first line of findings spanning multiple lines.
+fn snippet(suggestion: &Suggestion<'_>, index: &LineIndex) -> Option<String> {
+    let span = absolute_span(suggestion);
+    let line = index.line(span.start.line)?;
+    let len = line.chars().count();
+    let start = span.start.column.min(len);
+    let end = if span.end.line == span.start.line {
+        (span.end.column + 1).min(len)
+    } else {
+        len
+    };
+    Some(format!(
+        "{}<mark>{}</mark>{}",
+        escape(&sub_chars(line, 0..start)),
+        escape(&sub_chars(line, start..end)),
+        escape(&sub_chars(line, end..len)),
+    ))
+}
+
+/// Write a single finding.
+fn write_suggestion<W: Write>(
+    sink: &mut W,
+    suggestion: &Suggestion<'_>,
+    index: Option<&LineIndex>,
+) -> Result<()> {
+    let span = absolute_span(suggestion);
+    let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+    writeln!(sink, r#"<div class="finding">"#)?;
+    writeln!(
+        sink,
+        r#"<p><span class="{severity}">{severity}</span> <code>{word}</code> <span class="location">{line}:{column}, {category} reported by {detector} as <code>{rule}</code></span></p>"#,
+        severity = suggestion.severity,
+        word = escape(&word),
+        line = span.start.line,
+        column = span.start.column + 1,
+        category = suggestion.category,
+        detector = suggestion.detector,
+        rule = escape(&suggestion.rule_id()),
+    )?;
+    if let Some(snippet) = index.and_then(|index| snippet(suggestion, index)) {
+        writeln!(sink, "<pre>{}</pre>", snippet)?;
+    }
+    if let Some(ref description) = suggestion.description {
+        writeln!(sink, "<p>{}</p>", escape(description))?;
+    }
+    if !suggestion.replacements.is_empty() {
+        writeln!(sink, "<ul>")?;
+        for replacement in suggestion.replacements.iter() {
+            writeln!(sink, "<li><code>{}</code></li>", escape(replacement))?;
+        }
+        writeln!(sink, "</ul>")?;
+    }
+    writeln!(sink, "</div>")?;
+    Ok(())
+}
+
+/// Write the full report of all `suggestions`.
+pub(super) fn write_report<W: Write>(sink: &mut W, suggestions: &SuggestionSet<'_>) -> Result<()> {
+    writeln!(sink, "<!DOCTYPE html>")?;
+    writeln!(sink, r#"<html lang="en">"#)?;
+    writeln!(sink, "<head>")?;
+    writeln!(sink, r#"<meta charset="utf-8">"#)?;
+    writeln!(sink, "<title>cargo spellcheck report</title>")?;
+    writeln!(sink, "<style>{}</style>", STYLE)?;
+    writeln!(sink, "</head>")?;
+    writeln!(sink, "<body>")?;
+    writeln!(sink, "<h1>cargo spellcheck report</h1>")?;
+    writeln!(
+        sink,
+        "<p>{} findings in {} files.</p>",
+        suggestions.total_count(),
+        suggestions.len()
+    )?;
+
+    // table of contents, linking to the sections
+    writeln!(sink, "<ul>")?;
+    for (idx, (origin, suggestions)) in suggestions.iter().enumerate() {
+        writeln!(
+            sink,
+            r##"<li><a href="#file-{}">{}</a> ({})</li>"##,
+            idx,
+            escape(&origin.as_path().display().to_string()),
+            suggestions.len()
+        )?;
+    }
+    writeln!(sink, "</ul>")?;
+
+    let notice = |origin| truncation_notice(suggestions, origin);
+    for (idx, (origin, suggestions)) in suggestions.iter().enumerate() {
+        let path = origin.as_path().display().to_string();
+        let index = LineIndex::load(origin)
+            .map_err(|e| {
+                log::warn!("Source snippets are not available: {:?}", e);
+            })
+            .ok();
+        writeln!(sink, r#"<section id="file-{}">"#, idx)?;
+        writeln!(sink, "<h2>{}</h2>", escape(&path))?;
+        for suggestion in suggestions {
+            write_suggestion(sink, suggestion, index.as_ref())?;
+        }
+        if let Some(notice) = notice(origin) {
+            writeln!(sink, "<p>{}</p>", escape(&notice))?;
+        }
+        writeln!(sink, "</section>")?;
+    }
+    writeln!(sink, "</body>")?;
+    writeln!(sink, "</html>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation};
+
+    #[test]
+    fn escaped() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn finding() {
+        let d = Documentation::from((
+            ContentOrigin::TestEntityRust,
+            fluff_up!("A <b>Vec<T></b> literal"),
+        ));
+        let suggestions = dummy::check_all(&d);
+        let (_origin, suggestions) = suggestions.iter().next().unwrap();
+        let last = suggestions.last().unwrap();
+        let index = LineIndex::new(fluff_up!("A <b>Vec<T></b> literal").to_owned());
+
+        assert_eq!(
+            snippet(last, &index).unwrap(),
+            "/// A &lt;b&gt;Vec&lt;T&gt;&lt;/b&gt; <mark>literal</mark>"
+        );
+
+        let mut sink = Vec::new();
+        write_suggestion(&mut sink, last, Some(&index)).unwrap();
+        let rendered = String::from_utf8(sink).unwrap();
+        assert!(rendered.contains("<code>literal</code>"));
+        assert!(rendered.contains("<li><code>replacement_"));
+        assert!(!rendered.contains("<T>"));
+    }
+}
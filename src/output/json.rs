@@ -0,0 +1,58 @@
+//! JSON Lines output, one object per suggestion.
+
+use super::{absolute_span, ColumnEncoding, LineIndex};
+use crate::suggestion::SuggestionSchema;
+use crate::{LineColumn, Span, Suggestion};
+
+use anyhow::Result;
+
+use std::io::Write;
+
+/// Convert the character based `column` with `convert`, falls back to
+/// characters if the file content is not available.
+fn encoded(
+    lc: LineColumn,
+    index: Option<&LineIndex>,
+    convert: impl Fn(&LineIndex, usize, usize) -> Option<usize>,
+) -> LineColumn {
+    let column = match index {
+        Some(index) => convert(index, lc.line, lc.column).unwrap_or_else(|| {
+            log::warn!(
+                "Column {} of line {} is not part of the file, reporting it in characters",
+                lc.column,
+                lc.line
+            );
+            lc.column
+        }),
+        None => lc.column,
+    };
+    LineColumn {
+        line: lc.line,
+        column,
+    }
+}
+
+/// Write a single suggestion as one line of JSON, the stable schema of
+/// suggestions with their location in the file.
+pub(super) fn write_suggestion<W: Write>(
+    sink: &mut W,
+    suggestion: &Suggestion<'_>,
+    index: Option<&LineIndex>,
+    encoding: ColumnEncoding,
+) -> Result<()> {
+    let span = absolute_span(suggestion);
+    let byte_range = index.and_then(|index| index.byte_range(&span));
+    // the end is inclusive and the last unit of the last character
+    let encoded = Span {
+        start: encoded(span.start, index, |index, line, column| {
+            index.column(line, column, encoding)
+        }),
+        end: encoded(span.end, index, |index, line, column| {
+            index.end_column(line, column, encoding)
+        }),
+    };
+    let item = SuggestionSchema::from(suggestion).located(encoded, byte_range);
+    serde_json::to_writer(&mut *sink, &item)?;
+    writeln!(sink)?;
+    Ok(())
+}
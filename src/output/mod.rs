@@ -0,0 +1,560 @@
+//! Rendering of the `check` results in different output formats.
+//!
+//! Next to the human readable default, there are formats
+//! intended for consumption by other tools.
+
+use crate::{ContentOrigin, Severity, Span, Suggestion, SuggestionSet};
+
+use anyhow::{anyhow, bail, Error, Result};
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+mod csv;
+mod grouped;
+mod html;
+mod json;
+mod order;
+mod rustc;
+mod tap;
+pub mod theme;
+
+pub use order::SortKey;
+pub use theme::ThemeConfig;
+
+/// Supported output formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Colored and annotated, for humans in front of a terminal.
+    Human,
+    /// One JSON object per line and suggestion.
+    Json,
+    /// Plain diagnostics as rendered by `rustc`.
+    Rustc,
+    /// Every distinct finding once, with all of its locations.
+    Grouped,
+    /// One row of comma separated values per suggestion, with a header.
+    Csv,
+    /// One Test Anything Protocol test point per checked file.
+    Tap,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        Self::Human
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "human" => Self::Human,
+            "json" => Self::Json,
+            "rustc" => Self::Rustc,
+            "grouped" => Self::Grouped,
+            "csv" => Self::Csv,
+            "tap" => Self::Tap,
+            other => bail!("Unknown output format `{}`", other),
+        })
+    }
+}
+
+/// What the columns of machine readable outputs count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ColumnEncoding {
+    /// Unicode scalar values, the same as in the human readable output.
+    #[serde(rename = "chars")]
+    Chars,
+    /// Bytes of the UTF-8 encoding.
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// UTF-16 code units, as used by the language server protocol.
+    #[serde(rename = "utf-16")]
+    Utf16,
+}
+
+impl Default for ColumnEncoding {
+    fn default() -> Self {
+        Self::Chars
+    }
+}
+
+impl FromStr for ColumnEncoding {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "chars" => Self::Chars,
+            "utf-8" | "utf8" => Self::Utf8,
+            "utf-16" | "utf16" => Self::Utf16,
+            other => bail!("Unknown column encoding `{}`", other),
+        })
+    }
+}
+
+impl ColumnEncoding {
+    /// Length of `s` in units of this encoding.
+    fn count(self, s: &str) -> usize {
+        match self {
+            Self::Chars => s.chars().count(),
+            Self::Utf8 => s.len(),
+            Self::Utf16 => s.encode_utf16().count(),
+        }
+    }
+}
+
+/// Kinds of reports written to a file, next to the regular output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportKind {
+    /// A self-contained HTML page.
+    Html,
+}
+
+/// A report of the `check` results, written to `path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub kind: ReportKind,
+    pub path: PathBuf,
+}
+
+impl FromStr for Report {
+    type Err = Error;
+    /// Parses `<kind>=<path>`, i.e. `html=out.html`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, path) = match s.split_once('=') {
+            Some((kind, path)) if !path.is_empty() => (kind, path),
+            _ => bail!(
+                "Expected a report as `<kind>=<path>`, i.e. `html=out.html`, not `{}`",
+                s
+            ),
+        };
+        let kind = match kind.to_lowercase().as_str() {
+            "html" => ReportKind::Html,
+            other => bail!("Unknown report kind `{}`", other),
+        };
+        Ok(Self {
+            kind,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Parameters of the output.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputConfig {
+    /// The format that is used for printing the `check` results.
+    #[serde(default)]
+    pub format: OutputFormat,
+    /// How columns are counted in machine readable formats.
+    #[serde(default)]
+    pub column_encoding: ColumnEncoding,
+    /// The order of the suggestions, within each group.
+    #[serde(default)]
+    pub sort_by: SortKey,
+    /// Cluster the suggestions with equal keys, each under a heading.
+    #[serde(default)]
+    pub group_by: Option<SortKey>,
+    /// Reports to write in addition, not part of the config file but derived
+    /// from `--report`.
+    #[serde(skip)]
+    pub reports: Vec<Report>,
+}
+
+impl OutputConfig {
+    /// If the suggestions are printed in a different order than file by file.
+    fn is_arranged(&self) -> bool {
+        self.sort_by != SortKey::File || self.group_by.is_some()
+    }
+
+    /// Check that the format supports the configured order.
+    pub fn validate(&self) -> Result<()> {
+        if self.is_arranged() && matches!(self.format, OutputFormat::Grouped | OutputFormat::Tap) {
+            bail!(
+                "Sorting and grouping is not supported by the `{}` format",
+                match self.format {
+                    OutputFormat::Grouped => "grouped",
+                    _ => "tap",
+                }
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Load the lines of `origin`, which only provide `what` and are optional.
+fn load_index(origin: &ContentOrigin, what: &str) -> Option<LineIndex> {
+    LineIndex::load(origin)
+        .map_err(|e| {
+            log::warn!("{} are not available: {:?}", what, e);
+        })
+        .ok()
+}
+
+/// Write a single suggestion in one of the formats which print suggestions
+/// one by one.
+fn write_suggestion<W: Write>(
+    sink: &mut W,
+    suggestion: &Suggestion<'_>,
+    index: Option<&LineIndex>,
+    config: &OutputConfig,
+) -> Result<()> {
+    match config.format {
+        OutputFormat::Human => writeln!(sink, "{}", suggestion)?,
+        OutputFormat::Rustc => rustc::write_suggestion(sink, suggestion, index)?,
+        OutputFormat::Csv => {
+            csv::write_suggestion(sink, suggestion, index, config.column_encoding)?
+        }
+        OutputFormat::Json => {
+            json::write_suggestion(sink, suggestion, index, config.column_encoding)?
+        }
+        OutputFormat::Grouped | OutputFormat::Tap => {
+            unreachable!("Not printed one by one. qed")
+        }
+    }
+    Ok(())
+}
+
+/// Write the notice of the suggestions of `origin` which were dropped, if any.
+fn write_truncation_notice<W: Write>(
+    sink: &mut W,
+    suggestions: &SuggestionSet<'_>,
+    origin: &ContentOrigin,
+    format: OutputFormat,
+) -> Result<()> {
+    let notice = match truncation_notice(suggestions, origin) {
+        Some(notice) => notice,
+        None => return Ok(()),
+    };
+    match format {
+        OutputFormat::Human | OutputFormat::Rustc => writeln!(sink, "note: {}\n", notice)?,
+        OutputFormat::Tap => writeln!(sink, "# {}", notice)?,
+        // would not be valid, so it goes to stderr
+        OutputFormat::Csv | OutputFormat::Json => log::warn!("{}", notice),
+        OutputFormat::Grouped => unreachable!("Grouped across all files. qed"),
+    }
+    Ok(())
+}
+
+/// Write all suggestions to `sink` in the configured format, where those at
+/// least as severe as `fail_on` are mistakes.
+///
+/// All `checked` files are listed by formats which report clean files as
+/// well, i.e. the test points of `tap`.
+pub fn write_suggestions<W: Write>(
+    suggestions: &SuggestionSet<'_>,
+    checked: &[ContentOrigin],
+    config: &OutputConfig,
+    fail_on: Severity,
+    mut sink: W,
+) -> Result<()> {
+    config.validate()?;
+    if config.format == OutputFormat::Grouped {
+        grouped::write_suggestions(&mut sink, suggestions)?;
+        sink.flush()?;
+        return Ok(());
+    }
+    if config.format == OutputFormat::Csv {
+        csv::write_row(&mut sink, csv::HEADER)?;
+    }
+    if config.is_arranged() {
+        write_arranged(&mut sink, suggestions, config)?;
+        sink.flush()?;
+        return Ok(());
+    }
+    if config.format == OutputFormat::Tap {
+        tap::write_test_points(&mut sink, suggestions, checked, fail_on)?;
+        sink.flush()?;
+        return Ok(());
+    }
+    for (origin, suggestions_of_file) in suggestions.iter() {
+        match config.format {
+            OutputFormat::Human => {
+                for suggestion in suggestions_of_file {
+                    write_suggestion(&mut sink, suggestion, None, config)?;
+                }
+            }
+            OutputFormat::Rustc | OutputFormat::Csv | OutputFormat::Json => {
+                let index = load_index(
+                    origin,
+                    match config.format {
+                        OutputFormat::Rustc => "Source snippets",
+                        OutputFormat::Csv => "Column encodings",
+                        _ => "Byte offsets",
+                    },
+                );
+                for suggestion in suggestions_of_file {
+                    write_suggestion(&mut sink, suggestion, index.as_ref(), config)?;
+                }
+            }
+            OutputFormat::Grouped | OutputFormat::Tap => {
+                unreachable!("Written across all files. qed")
+            }
+        }
+        write_truncation_notice(&mut sink, suggestions, origin, config.format)?;
+    }
+    sink.flush()?;
+    Ok(())
+}
+
+/// Write all suggestions sorted and grouped as configured, each group of the
+/// human readable formats under a heading. The dropped suggestions are noted
+/// at the end, since files are no longer printed one after another.
+fn write_arranged<W: Write>(
+    sink: &mut W,
+    suggestions: &SuggestionSet<'_>,
+    config: &OutputConfig,
+) -> Result<()> {
+    let headings = matches!(config.format, OutputFormat::Human | OutputFormat::Rustc);
+    let highlight = theme::current().highlight;
+    let mut indices = HashMap::<&ContentOrigin, Option<LineIndex>>::new();
+    for group in order::arrange(suggestions, config.sort_by, config.group_by) {
+        match (config.group_by, group.first()) {
+            (Some(group_by), Some(first)) if headings => {
+                let findings = match group.len() {
+                    1 => "1 finding".to_owned(),
+                    n => format!("{} findings", n),
+                };
+                writeln!(
+                    sink,
+                    "{}\n",
+                    highlight.apply_to(format!("== {}, {}", group_by.label(first), findings))
+                )?;
+            }
+            _ => {}
+        }
+        for entry in group {
+            let index = if config.format == OutputFormat::Human {
+                None
+            } else {
+                indices
+                    .entry(entry.origin)
+                    .or_insert_with(|| load_index(entry.origin, "Columns and source snippets"))
+                    .as_ref()
+            };
+            write_suggestion(sink, entry.suggestion, index, config)?;
+        }
+    }
+    for (origin, _suggestions) in suggestions.iter() {
+        write_truncation_notice(sink, suggestions, origin, config.format)?;
+    }
+    Ok(())
+}
+
+/// Write all configured reports of `suggestions`.
+pub fn write_reports(suggestions: &SuggestionSet<'_>, config: &OutputConfig) -> Result<()> {
+    for report in config.reports.iter() {
+        let file = std::fs::File::create(&report.path)
+            .map_err(|e| anyhow!("Failed to create report {}", report.path.display()).context(e))?;
+        let mut sink = std::io::BufWriter::new(file);
+        match report.kind {
+            ReportKind::Html => html::write_report(&mut sink, suggestions)?,
+        }
+        sink.flush()?;
+        log::info!("Wrote report to {}", report.path.display());
+    }
+    Ok(())
+}
+
+/// Notice of the suggestions of `origin` which were dropped or not even
+/// checked for, `None` if there are none.
+fn truncation_notice(suggestions: &SuggestionSet<'_>, origin: &ContentOrigin) -> Option<String> {
+    let path = origin.as_path().display();
+    let notice = match (suggestions.truncated(origin), suggestions.unchecked(origin)) {
+        (0, 0) => return None,
+        (truncated, 0) => format!("and {} more in {}", truncated, path),
+        (0, unchecked) => format!("{} chunks of {} were not checked", unchecked, path),
+        (truncated, unchecked) => format!(
+            "and {} more in {}, whose other {} chunks were not checked",
+            truncated, path, unchecked
+        ),
+    };
+    Some(format!("{}, limited by `max_suggestions_per_file`", notice))
+}
+
+/// Span relative to the file start, which only differs for doc tests.
+fn absolute_span(suggestion: &Suggestion<'_>) -> Span {
+    let mut span = suggestion.span;
+    if let ContentOrigin::RustDocTest(_, ref doctest) = suggestion.origin {
+        span.start.line += doctest.start.line;
+        span.end.line += doctest.start.line;
+    }
+    span
+}
+
+/// Byte offsets of all lines of a file, to convert between `LineColumn`s
+/// and byte offsets.
+pub(crate) struct LineIndex {
+    content: String,
+    /// Byte offset of the first character of each line.
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub(crate) fn new(content: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            content,
+            line_starts,
+        }
+    }
+
+    /// Read the content of the file `origin` refers to.
+    pub(crate) fn load(origin: &ContentOrigin) -> Result<Self> {
+        let path = origin.as_path();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+        Ok(Self::new(content))
+    }
+
+    /// The content of line `line`, 1-indexed, without the line ending.
+    fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map(|next| next - 1)
+            .unwrap_or_else(|| self.content.len());
+        self.content.get(start..end)
+    }
+
+    /// The byte offset of the character at `column` in line `line`
+    /// and the length of the character in bytes.
+    fn char_at(&self, line: usize, column: usize) -> Option<(usize, usize)> {
+        let line_start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let line_content = self.line(line)?;
+        line_content
+            .char_indices()
+            .nth(column)
+            .map(|(offset, c)| (line_start + offset, c.len_utf8()))
+            // allows pointing at the line ending
+            .or_else(|| {
+                Some((line_start + line_content.len(), 1)).filter(|_| {
+                    column == line_content.chars().count() && line < self.line_starts.len()
+                })
+            })
+    }
+
+    /// Convert the character based `column` in `line` to the given encoding,
+    /// the offset of the first unit of the character.
+    pub(crate) fn column(
+        &self,
+        line: usize,
+        column: usize,
+        encoding: ColumnEncoding,
+    ) -> Option<usize> {
+        let line = self.line(line)?;
+        let prefix = match line.char_indices().nth(column) {
+            Some((offset, _c)) => &line[..offset],
+            // allows pointing at the line ending
+            None if column == line.chars().count() => line,
+            None => return None,
+        };
+        Some(encoding.count(prefix))
+    }
+
+    /// Convert the character based, inclusive end `column` in `line` to the
+    /// given encoding, the offset of the last unit of the character.
+    ///
+    /// Differs from `column` for characters which take more than one unit,
+    /// i.e. `🦀` is two UTF-16 code units.
+    pub(crate) fn end_column(
+        &self,
+        line: usize,
+        column: usize,
+        encoding: ColumnEncoding,
+    ) -> Option<usize> {
+        let start = self.column(line, column, encoding)?;
+        let len = self
+            .line(line)?
+            .chars()
+            .nth(column)
+            .map_or(1, |c| encoding.count(c.encode_utf8(&mut [0; 4])));
+        Some(start + len - 1)
+    }
+
+    /// Convert an inclusive `Span` to an exclusive byte range.
+    pub(crate) fn byte_range(&self, span: &Span) -> Option<std::ops::Range<usize>> {
+        let (start, _) = self.char_at(span.start.line, span.start.column)?;
+        let (end, len) = self.char_at(span.end.line, span.end.column)?;
+        Some(start..(end + len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineColumn;
+
+    fn span(start: (usize, usize), end: (usize, usize)) -> Span {
+        Span {
+            start: LineColumn {
+                line: start.0,
+                column: start.1,
+            },
+            end: LineColumn {
+                line: end.0,
+                column: end.1,
+            },
+        }
+    }
+
+    #[test]
+    fn reports() {
+        assert_eq!(
+            "html=target/spellcheck.html".parse::<Report>().unwrap(),
+            Report {
+                kind: ReportKind::Html,
+                path: PathBuf::from("target/spellcheck.html"),
+            }
+        );
+        assert!("html".parse::<Report>().is_err());
+        assert!("html=".parse::<Report>().is_err());
+        assert!("pdf=out.pdf".parse::<Report>().is_err());
+    }
+
+    #[test]
+    fn byte_ranges() {
+        const CONTENT: &str = "/// Fïrst\n/// 🦀 secönd\n";
+        let index = LineIndex::new(CONTENT.to_owned());
+        let range = index.byte_range(&span((1, 4), (1, 8))).unwrap();
+        assert_eq!(&CONTENT[range], "Fïrst");
+        let range = index.byte_range(&span((2, 6), (2, 11))).unwrap();
+        assert_eq!(&CONTENT[range], "secönd");
+        let range = index.byte_range(&span((1, 4), (2, 4))).unwrap();
+        assert_eq!(&CONTENT[range], "Fïrst\n/// 🦀");
+        assert!(index.byte_range(&span((3, 4), (3, 5))).is_none());
+    }
+
+    #[test]
+    fn column_encodings() {
+        let index = LineIndex::new("/// 🦀 ïs\n".to_owned());
+        assert_eq!(index.column(1, 6, ColumnEncoding::Chars), Some(6));
+        assert_eq!(index.column(1, 6, ColumnEncoding::Utf8), Some(9));
+        assert_eq!(index.column(1, 6, ColumnEncoding::Utf16), Some(7));
+        assert_eq!(index.column(1, 7, ColumnEncoding::Utf16), Some(8));
+        assert_eq!(index.column(1, 8, ColumnEncoding::Utf16), Some(9));
+        assert_eq!(index.column(1, 9, ColumnEncoding::Utf16), None);
+        assert_eq!(index.column(3, 0, ColumnEncoding::Utf8), None);
+    }
+
+    #[test]
+    fn end_column_of_astral_characters() {
+        let index = LineIndex::new("/// 🦀 ïs 🦀\n".to_owned());
+        // the crab takes two UTF-16 code units and four bytes
+        assert_eq!(index.end_column(1, 4, ColumnEncoding::Chars), Some(4));
+        assert_eq!(index.end_column(1, 4, ColumnEncoding::Utf16), Some(5));
+        assert_eq!(index.end_column(1, 4, ColumnEncoding::Utf8), Some(7));
+        assert_eq!(index.end_column(1, 6, ColumnEncoding::Utf16), Some(7));
+        assert_eq!(index.end_column(1, 6, ColumnEncoding::Utf8), Some(10));
+        assert_eq!(index.column(1, 9, ColumnEncoding::Utf16), Some(10));
+        assert_eq!(index.end_column(1, 9, ColumnEncoding::Utf16), Some(11));
+        assert_eq!(index.end_column(3, 0, ColumnEncoding::Utf16), None);
+    }
+}
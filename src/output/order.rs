@@ -0,0 +1,214 @@
+//! Sorting and grouping of the suggestions across all files.
+//!
+//! By default suggestions are printed file by file, in the order they were
+//! found. For a per word review, i.e. to extend the dictionary, they can be
+//! sorted or grouped by the flagged word or by their severity instead.
+
+use crate::util::sub_chars;
+use crate::{ContentOrigin, Suggestion, SuggestionSet};
+
+use anyhow::{bail, Error, Result};
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// What suggestions are sorted or grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// The files in the order they were checked, suggestions by their position.
+    File,
+    /// The flagged word, ignoring case.
+    Word,
+    /// The most severe first.
+    Severity,
+}
+
+impl Default for SortKey {
+    fn default() -> Self {
+        Self::File
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "file" => Self::File,
+            "word" => Self::Word,
+            "severity" => Self::Severity,
+            other => bail!(
+                "Unknown sort key `{}`, expected `file`, `word` or `severity`",
+                other
+            ),
+        })
+    }
+}
+
+/// A suggestion with everything it is sorted by.
+#[derive(Debug, Clone)]
+pub(super) struct Entry<'a, 's> {
+    /// Position of the file in the suggestion set.
+    file: usize,
+    /// The flagged word, lowercased.
+    word: String,
+    pub(super) origin: &'a ContentOrigin,
+    pub(super) suggestion: &'a Suggestion<'s>,
+}
+
+impl SortKey {
+    fn compare(self, a: &Entry<'_, '_>, b: &Entry<'_, '_>) -> Ordering {
+        match self {
+            Self::File => a.file.cmp(&b.file),
+            Self::Word => a.word.cmp(&b.word),
+            Self::Severity => b.suggestion.severity.cmp(&a.suggestion.severity),
+        }
+    }
+
+    /// The heading of the group of `entry`.
+    pub(super) fn label(self, entry: &Entry<'_, '_>) -> String {
+        match self {
+            Self::File => format!("file {}", entry.origin.as_path().display()),
+            Self::Word => format!(
+                "word `{}`",
+                sub_chars(
+                    entry.suggestion.chunk.as_str(),
+                    entry.suggestion.range.clone()
+                )
+            ),
+            Self::Severity => format!("severity {}", entry.suggestion.severity),
+        }
+    }
+}
+
+/// Arrange all suggestions into groups of equal `group_by` keys, sorted by
+/// `sort_by` within each group. Without `group_by` there is a single group.
+///
+/// Ties keep the order in which the suggestions were found.
+pub(super) fn arrange<'a, 's>(
+    suggestions: &'a SuggestionSet<'s>,
+    sort_by: SortKey,
+    group_by: Option<SortKey>,
+) -> Vec<Vec<Entry<'a, 's>>> {
+    let files = suggestions
+        .iter()
+        .enumerate()
+        .map(|(file, (origin, _suggestions))| (origin, file))
+        .collect::<HashMap<_, _>>();
+    let mut entries = suggestions
+        .iter()
+        .flat_map(|(origin, suggestions)| {
+            suggestions
+                .iter()
+                .map(move |suggestion| (origin, suggestion))
+        })
+        .map(|(origin, suggestion)| Entry {
+            file: files[origin],
+            word: sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()).to_lowercase(),
+            origin,
+            suggestion,
+        })
+        .collect::<Vec<_>>();
+    // stable, so ties keep the order of the run
+    entries.sort_by(|a, b| {
+        group_by
+            .map_or(Ordering::Equal, |key| key.compare(a, b))
+            .then_with(|| sort_by.compare(a, b))
+    });
+
+    let mut groups: Vec<Vec<Entry<'a, 's>>> = Vec::new();
+    for entry in entries {
+        match groups.last_mut() {
+            Some(group) if group_by.map_or(true, |key| key.compare(&group[0], &entry).is_eq()) => {
+                group.push(entry);
+            }
+            _ => groups.push(vec![entry]),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, Documentation, Severity};
+
+    use std::path::PathBuf;
+
+    #[test]
+    fn sort_and_group() {
+        let d = Documentation::from((
+            ContentOrigin::RustSourceFile(PathBuf::from("a.rs")),
+            fluff_up!("Beta alpha"),
+        ));
+        let d2 = Documentation::from((
+            ContentOrigin::RustSourceFile(PathBuf::from("b.rs")),
+            fluff_up!("alpha gamma"),
+        ));
+        let mut suggestions = SuggestionSet::new();
+        for d in [&d, &d2].iter() {
+            for (origin, found) in dummy::check_all(d).iter() {
+                for suggestion in found {
+                    let mut suggestion = suggestion.clone();
+                    if suggestion.span.start.column > 5 {
+                        suggestion.severity = Severity::Warning;
+                    }
+                    suggestions.add(origin.clone(), suggestion);
+                }
+            }
+        }
+
+        let words = |sort_by, group_by| {
+            arrange(&suggestions, sort_by, group_by)
+                .into_iter()
+                .map(|group| {
+                    group
+                        .into_iter()
+                        .map(|entry| {
+                            format!(
+                                "{}:{}",
+                                entry.origin.as_path().display(),
+                                sub_chars(
+                                    entry.suggestion.chunk.as_str(),
+                                    entry.suggestion.range.clone()
+                                )
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            words(SortKey::File, None),
+            vec![vec!["a.rs:Beta", "a.rs:alpha", "b.rs:alpha", "b.rs:gamma"]]
+        );
+        assert_eq!(
+            words(SortKey::Word, None),
+            vec![vec!["a.rs:alpha", "b.rs:alpha", "a.rs:Beta", "b.rs:gamma"]]
+        );
+        assert_eq!(
+            words(SortKey::Severity, None),
+            vec![vec!["a.rs:Beta", "b.rs:alpha", "a.rs:alpha", "b.rs:gamma"]]
+        );
+        assert_eq!(
+            words(SortKey::Word, Some(SortKey::File)),
+            vec![
+                vec!["a.rs:alpha", "a.rs:Beta"],
+                vec!["b.rs:alpha", "b.rs:gamma"]
+            ]
+        );
+        assert_eq!(
+            words(SortKey::File, Some(SortKey::Word)),
+            vec![
+                vec!["a.rs:alpha", "b.rs:alpha"],
+                vec!["a.rs:Beta"],
+                vec!["b.rs:gamma"]
+            ]
+        );
+        assert!("severity".parse::<SortKey>().is_ok());
+        assert!("line".parse::<SortKey>().is_err());
+    }
+}
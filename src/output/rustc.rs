@@ -0,0 +1,138 @@
+//! Diagnostics formatted like the ones of `rustc`, for tools and habits built
+//! around compiler output.
+
+use super::{absolute_span, LineIndex};
+use crate::util::sub_chars;
+use crate::{Category, Suggestion};
+
+use anyhow::Result;
+
+use std::io::Write;
+
+/// At most this many replacements are listed.
+const MAX_REPLACEMENTS: usize = 5;
+
+/// Display width of `c` in the rendered source line.
+fn width(c: char) -> usize {
+    if c == '\t' {
+        4
+    } else {
+        1
+    }
+}
+
+/// Write one diagnostic for `suggestion`, with the source snippet from
+/// `index` if available.
+pub(super) fn write_suggestion<W: Write>(
+    sink: &mut W,
+    suggestion: &Suggestion<'_>,
+    index: Option<&LineIndex>,
+) -> Result<()> {
+    let span = absolute_span(suggestion);
+    let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+    let message = match suggestion.description {
+        Some(ref description) => description.clone(),
+        None if suggestion.category == Category::Spelling => {
+            format!("possibly misspelled word `{}`", word)
+        }
+        None => format!("possible {} mistake `{}`", suggestion.category, word),
+    };
+    let pad = " ".repeat(span.start.line.to_string().len());
+
+    writeln!(
+        sink,
+        "{}[{}]: {}",
+        suggestion.severity, suggestion.category, message
+    )?;
+    writeln!(
+        sink,
+        "{}--> {}:{}:{}",
+        pad,
+        suggestion.origin.as_path().display(),
+        span.start.line,
+        span.start.column + 1
+    )?;
+
+    if let Some(source) = index.and_then(|index| index.line(span.start.line)) {
+        let offset = source
+            .chars()
+            .take(span.start.column)
+            .map(width)
+            .sum::<usize>();
+        let carets = if span.end.line == span.start.line {
+            span.end.column + 1 - span.start.column
+        } else {
+            // only the first line is shown
+            source.chars().count().saturating_sub(span.start.column)
+        }
+        .max(1);
+        writeln!(sink, "{} |", pad)?;
+        writeln!(
+            sink,
+            "{} | {}",
+            span.start.line,
+            source.replace('\t', "    ")
+        )?;
+        writeln!(
+            sink,
+            "{} | {}{}",
+            pad,
+            " ".repeat(offset),
+            "^".repeat(carets)
+        )?;
+        writeln!(sink, "{} |", pad)?;
+    }
+
+    for replacement in suggestion.replacements.iter().take(MAX_REPLACEMENTS) {
+        writeln!(sink, "{} = help: replace with `{}`", pad, replacement)?;
+    }
+    if suggestion.replacements.len() > MAX_REPLACEMENTS {
+        writeln!(
+            sink,
+            "{} = help: and {} other replacements",
+            pad,
+            suggestion.replacements.len() - MAX_REPLACEMENTS
+        )?;
+    }
+    writeln!(
+        sink,
+        "{} = note: reported by {} as `{}`",
+        pad,
+        suggestion.detector,
+        suggestion.rule_id()
+    )?;
+    writeln!(sink)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, ContentOrigin, Documentation};
+
+    #[test]
+    fn diagnostic() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("two literals")));
+        let suggestions = dummy::check_all(&d);
+        let (_origin, suggestions) = suggestions.iter().next().unwrap();
+        let index = LineIndex::new(fluff_up!("two literals").to_owned());
+
+        let mut sink = Vec::new();
+        write_suggestion(&mut sink, &suggestions[1], Some(&index)).unwrap();
+        let rendered = String::from_utf8(sink).unwrap();
+        assert_eq!(
+            rendered,
+            r#"error[spelling]: possibly misspelled word `literals`
+ --> /tmp/test/entity.rs:1:9
+  |
+1 | /// two literals
+  |         ^^^^^^^^
+  |
+  = help: replace with `replacement_1`
+  = note: reported by Dummy as `dummy::dummy`
+
+"#
+        );
+    }
+}
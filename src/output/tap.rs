@@ -0,0 +1,186 @@
+//! Test Anything Protocol output, version 13.
+//!
+//! Every checked file is a test point, which fails if at least one mistake,
+//! by default an error, was found in it. All findings of a file are attached as YAML diagnostics, so
+//! warnings show up without failing the test point.
+
+use super::{absolute_span, write_truncation_notice, OutputFormat};
+use crate::util::sub_chars;
+use crate::{ContentOrigin, Severity, Suggestion, SuggestionSet};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use std::io::Write;
+
+/// A string as YAML scalar, JSON strings are a subset of YAML.
+fn quote(s: &str) -> Result<String> {
+    Ok(serde_json::to_string(s)?)
+}
+
+/// Write the version, the plan and one test point per file, which is every
+/// `checked` file and every other file with `suggestions`.
+pub(super) fn write_test_points<W: Write>(
+    sink: &mut W,
+    suggestions: &SuggestionSet<'_>,
+    checked: &[ContentOrigin],
+    fail_on: Severity,
+) -> Result<()> {
+    let mut points = checked
+        .iter()
+        .map(|origin| (origin, &[][..]))
+        .collect::<IndexMap<&ContentOrigin, &[Suggestion<'_>]>>();
+    for (origin, suggestions_of_file) in suggestions.iter() {
+        points.insert(origin, suggestions_of_file.as_slice());
+    }
+    write_header(sink, points.len())?;
+    for (number, (origin, suggestions_of_file)) in points.into_iter().enumerate() {
+        write_test_point(sink, number + 1, origin, suggestions_of_file, fail_on)?;
+        write_truncation_notice(sink, suggestions, origin, OutputFormat::Tap)?;
+    }
+    Ok(())
+}
+
+/// Write the version and the plan of `count` test points.
+fn write_header<W: Write>(sink: &mut W, count: usize) -> Result<()> {
+    writeln!(sink, "TAP version 13")?;
+    writeln!(sink, "1..{}", count)?;
+    Ok(())
+}
+
+/// Write the test point `number`, 1-indexed, for `origin` and its
+/// `suggestions`, failing on those at least as severe as `fail_on`.
+fn write_test_point<W: Write>(
+    sink: &mut W,
+    number: usize,
+    origin: &ContentOrigin,
+    suggestions: &[Suggestion<'_>],
+    fail_on: Severity,
+) -> Result<()> {
+    let ok = !suggestions
+        .iter()
+        .any(|suggestion| suggestion.severity.fails(fail_on));
+    writeln!(
+        sink,
+        "{} {} - {}",
+        if ok { "ok" } else { "not ok" },
+        number,
+        origin.as_path().display()
+    )?;
+    if suggestions.is_empty() {
+        return Ok(());
+    }
+    writeln!(sink, "  ---")?;
+    writeln!(sink, "  mistakes:")?;
+    for suggestion in suggestions {
+        let span = absolute_span(suggestion);
+        let word = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+        writeln!(sink, "    - word: {}", quote(&word)?)?;
+        writeln!(sink, "      line: {}", span.start.line)?;
+        writeln!(sink, "      column: {}", span.start.column + 1)?;
+        writeln!(sink, "      severity: {}", suggestion.severity)?;
+        writeln!(sink, "      category: {}", suggestion.category)?;
+        writeln!(sink, "      detector: {}", suggestion.detector)?;
+        writeln!(sink, "      rule: {}", suggestion.rule_id())?;
+        if let Some(ref description) = suggestion.description {
+            writeln!(sink, "      description: {}", quote(description)?)?;
+        }
+        let replacements = suggestion
+            .replacements
+            .iter()
+            .map(|replacement| quote(replacement))
+            .collect::<Result<Vec<_>>>()?;
+        writeln!(sink, "      replacements: [{}]", replacements.join(", "))?;
+    }
+    writeln!(sink, "  ...")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::dummy;
+    use crate::{fluff_up, Documentation, Severity};
+
+    #[test]
+    fn test_points() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("say \"hi\"")));
+        let suggestions = dummy::check_all(&d);
+        let (origin, suggestions) = suggestions.iter().next().unwrap();
+        let warnings = suggestions
+            .iter()
+            .cloned()
+            .map(|mut suggestion| {
+                suggestion.severity = Severity::Warning;
+                suggestion.description = Some("A \"quoted\" word.".to_owned());
+                suggestion
+            })
+            .collect::<Vec<_>>();
+
+        let mut sink = Vec::new();
+        write_header(&mut sink, 3).unwrap();
+        write_test_point(&mut sink, 1, origin, &[], Severity::Error).unwrap();
+        write_test_point(&mut sink, 2, origin, &warnings[..1], Severity::Error).unwrap();
+        write_test_point(&mut sink, 3, origin, &suggestions[1..], Severity::Error).unwrap();
+        assert_eq!(
+            String::from_utf8(sink).unwrap(),
+            r#"TAP version 13
+1..3
+ok 1 - /tmp/test/entity.rs
+ok 2 - /tmp/test/entity.rs
+  ---
+  mistakes:
+    - word: "say"
+      line: 1
+      column: 5
+      severity: warning
+      category: spelling
+      detector: Dummy
+      rule: dummy::dummy
+      description: "A \"quoted\" word."
+      replacements: ["replacement_0"]
+  ...
+not ok 3 - /tmp/test/entity.rs
+  ---
+  mistakes:
+    - word: "hi"
+      line: 1
+      column: 10
+      severity: error
+      category: spelling
+      detector: Dummy
+      rule: dummy::dummy
+      replacements: ["replacement_1"]
+  ...
+"#
+        );
+    }
+
+    #[test]
+    fn clean_files() {
+        let d = Documentation::from((ContentOrigin::TestEntityRust, fluff_up!("hi")));
+        let suggestions = dummy::check_all(&d);
+        let checked = vec![
+            ContentOrigin::TestEntityCommonMark,
+            ContentOrigin::TestEntityRust,
+        ];
+
+        let mut sink = Vec::new();
+        write_test_points(&mut sink, &SuggestionSet::new(), &checked, Severity::Error).unwrap();
+        assert_eq!(
+            String::from_utf8(sink).unwrap(),
+            "TAP version 13\n1..2\nok 1 - /tmp/test/entity.md\nok 2 - /tmp/test/entity.rs\n"
+        );
+
+        let mut sink = Vec::new();
+        write_test_points(&mut sink, &suggestions, &checked[..1], Severity::Error).unwrap();
+        let written = String::from_utf8(sink).unwrap();
+        assert!(written.starts_with(
+            "TAP version 13\n1..2\nok 1 - /tmp/test/entity.md\nnot ok 2 - /tmp/test/entity.rs\n"
+        ));
+
+        let mut sink = Vec::new();
+        write_test_points(&mut sink, &SuggestionSet::new(), &[], Severity::Error).unwrap();
+        assert_eq!(String::from_utf8(sink).unwrap(), "TAP version 13\n1..0\n");
+    }
+}
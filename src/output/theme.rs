@@ -0,0 +1,294 @@
+//! Colors and text attributes of the human readable output and the
+//! interactive picker, as configured in the `[theme]` section.
+//!
+//! A preset provides all styles, single ones can be overridden with the
+//! dotted notation of `console`, i.e. `"bold.red"` or `"black.on_yellow"`.
+
+use anyhow::{bail, Result};
+use console::Style;
+use serde::{Deserialize, Serialize};
+
+use std::sync::RwLock;
+
+/// The words understood in a dotted style.
+const STYLE_WORDS: &[&str] = &[
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright",
+    "on_black",
+    "on_red",
+    "on_green",
+    "on_yellow",
+    "on_blue",
+    "on_magenta",
+    "on_cyan",
+    "on_white",
+    "on_bright",
+    "bold",
+    "dim",
+    "underlined",
+    "blink",
+    "reverse",
+    "hidden",
+];
+
+/// The set of styles all others are derived from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    /// The regular colors, with bold highlights.
+    Default,
+    /// Without bold or dimmed text, with strong foreground and background
+    /// contrasts instead.
+    HighContrast,
+}
+
+impl Default for ThemePreset {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+/// The `[theme]` section, styles which are not set are taken from the preset.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// The styles of all entries which are not set.
+    #[serde(default)]
+    pub preset: ThemePreset,
+    /// The rule of a finding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+    /// The replacements of a finding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    /// The line numbers and margins around the flagged line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// The arrow pointing to the location of a finding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrow: Option<String>,
+    /// The marker underlining the flagged content and notes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub marker: Option<String>,
+    /// The severity of errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The severity of warnings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// The severity of infos.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<String>,
+    /// The questions of the interactive picker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question: Option<String>,
+    /// The selected replacement of the interactive picker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected: Option<String>,
+    /// The other replacements of the interactive picker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choice: Option<String>,
+    /// The manually entered replacement of the interactive picker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom: Option<String>,
+}
+
+/// Parse a dotted style, failing on unknown words instead of ignoring them.
+fn parse_style(key: &str, dotted: &str) -> Result<Style> {
+    if let Some(word) = dotted
+        .split('.')
+        .find(|word| !STYLE_WORDS.contains(&word.trim()))
+    {
+        bail!(
+            "Unknown style `{}` of theme entry `{}`, expected dot separated words of: {}",
+            word,
+            key,
+            STYLE_WORDS.join(", ")
+        );
+    }
+    Ok(Style::from_dotted_str(
+        &dotted
+            .split('.')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("."),
+    ))
+}
+
+impl ThemeConfig {
+    /// Resolve the preset and the overrides.
+    pub fn theme(&self) -> Result<Theme> {
+        let mut theme = Theme::preset(self.preset);
+        let mut overrides = [
+            ("highlight", &self.highlight, &mut theme.highlight),
+            ("replacement", &self.replacement, &mut theme.replacement),
+            ("context", &self.context, &mut theme.context),
+            ("arrow", &self.arrow, &mut theme.arrow),
+            ("marker", &self.marker, &mut theme.marker),
+            ("error", &self.error, &mut theme.error),
+            ("warning", &self.warning, &mut theme.warning),
+            ("info", &self.info, &mut theme.info),
+            ("question", &self.question, &mut theme.question),
+            ("selected", &self.selected, &mut theme.selected),
+            ("choice", &self.choice, &mut theme.choice),
+            ("custom", &self.custom, &mut theme.custom),
+        ];
+        for (key, dotted, style) in overrides.iter_mut() {
+            if let Some(dotted) = dotted {
+                **style = parse_style(key, dotted)?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// The resolved styles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// The rule of a finding.
+    pub highlight: Style,
+    /// The replacements of a finding.
+    pub replacement: Style,
+    /// The line numbers and margins.
+    pub context: Style,
+    /// The arrow pointing to the location.
+    pub arrow: Style,
+    /// The marker underlining the flagged content.
+    pub marker: Style,
+    /// The severity of errors.
+    pub error: Style,
+    /// The severity of warnings.
+    pub warning: Style,
+    /// The severity of infos.
+    pub info: Style,
+    /// The questions of the picker.
+    pub question: Style,
+    /// The selected replacement of the picker.
+    pub selected: Style,
+    /// The other replacements of the picker.
+    pub choice: Style,
+    /// The manually entered replacement.
+    pub custom: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset(ThemePreset::Default)
+    }
+}
+
+impl Theme {
+    /// All styles of `preset`.
+    pub fn preset(preset: ThemePreset) -> Self {
+        let style = |dotted: &str| Style::from_dotted_str(dotted);
+        match preset {
+            ThemePreset::Default => Self {
+                highlight: style("bold.white"),
+                replacement: style("green"),
+                context: style("bold.blue"),
+                arrow: style("blue"),
+                marker: style("bold.yellow"),
+                error: style("bold.red"),
+                warning: style("bold.yellow"),
+                info: style("bold.cyan"),
+                question: style("bold.blue"),
+                selected: style("bold.green.on_black"),
+                choice: style("blue.on_black"),
+                custom: style("yellow.on_black"),
+            },
+            ThemePreset::HighContrast => Self {
+                highlight: style("white.bright"),
+                replacement: style("black.on_green"),
+                context: style("white.bright"),
+                arrow: style("white.bright"),
+                marker: style("yellow.bright"),
+                error: style("white.bright.on_red"),
+                warning: style("black.on_yellow"),
+                info: style("black.on_cyan"),
+                question: style("white.bright.underlined"),
+                selected: style("black.on_white"),
+                choice: style("white.bright"),
+                custom: style("black.on_yellow"),
+            },
+        }
+    }
+
+    /// The style of the severity of a finding.
+    pub fn severity(&self, severity: crate::Severity) -> &Style {
+        match severity {
+            crate::Severity::Error => &self.error,
+            crate::Severity::Warning => &self.warning,
+            crate::Severity::Info => &self.info,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref THEME: RwLock<Theme> = RwLock::new(Theme::default());
+}
+
+/// Apply the configured theme for the remainder of the process.
+pub fn set(config: &ThemeConfig) -> Result<()> {
+    let theme = config.theme()?;
+    *THEME.write().expect("Theme lock is never poisoned. qed") = theme;
+    Ok(())
+}
+
+/// The theme of the process.
+pub fn current() -> Theme {
+    THEME
+        .read()
+        .expect("Theme lock is never poisoned. qed")
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides() {
+        let config: ThemeConfig = toml::from_str(
+            r#"
+preset = "high-contrast"
+error = "bold.red"
+"#,
+        )
+        .unwrap();
+        let theme = config.theme().unwrap();
+        assert_eq!(theme.error, Style::new().bold().red());
+        assert_eq!(theme.warning, Style::new().black().on_yellow());
+        // nothing of the preset is bold or dimmed
+        let theme = Theme::preset(ThemePreset::HighContrast);
+        for style in [
+            theme.highlight,
+            theme.replacement,
+            theme.context,
+            theme.arrow,
+            theme.marker,
+            theme.error,
+            theme.warning,
+            theme.info,
+            theme.question,
+            theme.selected,
+            theme.choice,
+            theme.custom,
+        ]
+        .iter()
+        {
+            let styled = style.apply_to("x").force_styling(true).to_string();
+            assert!(!styled.contains("\u{1b}[1m") && !styled.contains("\u{1b}[2m"));
+        }
+
+        let config: ThemeConfig = toml::from_str(r#"marker = "bold.purple""#).unwrap();
+        assert!(config.theme().is_err());
+        assert!(toml::from_str::<ThemeConfig>(r#"preset = "neon""#).is_err());
+    }
+}
@@ -1,6 +1,30 @@
 //! Reflow configuration.
+use crate::Severity;
+
 use serde::{Deserialize, Serialize};
 
+use std::path::PathBuf;
+
+/// What to do with a single word which exceeds the line width on its own,
+/// i.e. a long url or type path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LongWords {
+    /// Leave the word intact, the line stays too long.
+    Keep,
+    /// Break the word after punctuation, i.e. `/` or `.`, unless it is code.
+    Punctuation,
+    /// Break the word after punctuation and at the syllables of the
+    /// hyphenation dictionary, with a trailing `-`.
+    Hyphenate,
+}
+
+impl Default for LongWords {
+    fn default() -> Self {
+        Self::Keep
+    }
+}
+
 /// Parameters for wrapping doc comments
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReflowConfig {
@@ -8,12 +32,30 @@ pub struct ReflowConfig {
     #[serde(default)]
     #[serde(alias = "max_line_width")]
     pub(crate) max_line_length: usize,
+    /// Handling of words longer than the lines.
+    #[serde(default)]
+    pub(crate) long_words: LongWords,
+    /// Hyphenation patterns in the format of `hyph_*.dic` files, required
+    /// for `long_words = "hyphenate"`.
+    #[serde(default)]
+    pub(crate) hyphenation_dictionary: Option<PathBuf>,
+    /// Severity of all findings of this checker.
+    #[serde(default)]
+    pub(crate) severity: Severity,
+    /// Never end a line with one of these, taken from the top level
+    /// `abbreviations`.
+    #[serde(skip)]
+    pub(crate) abbreviations: Vec<String>,
 }
 
 impl Default for ReflowConfig {
     fn default() -> Self {
         Self {
             max_line_length: 80,
+            long_words: LongWords::default(),
+            hyphenation_dictionary: None,
+            severity: Severity::default(),
+            abbreviations: crate::sentence::default_abbreviations(),
         }
     }
 }
@@ -0,0 +1,144 @@
+//! Hyphenation of single words with the patterns of Liang's algorithm.
+//!
+//! Reads the `hyph_*.dic` files of the hyphen library as shipped by most
+//! distributions, i.e. `/usr/share/hyphen/hyph_en_US.dic`.
+
+use anyhow::{anyhow, Result};
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Hyphenation patterns of a single language.
+#[derive(Debug, Clone, Default)]
+pub struct Hyphenator {
+    /// The letters of a pattern, mapped to the levels between them.
+    patterns: HashMap<String, Vec<u32>>,
+    /// Length of the longest pattern in chars.
+    max_len: usize,
+    /// Minimal number of chars before the first hyphen.
+    left_min: usize,
+    /// Minimal number of chars after the last hyphen.
+    right_min: usize,
+}
+
+impl Hyphenator {
+    /// Load the patterns from a `hyph_*.dic` file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            anyhow!("Failed to read hyphenation patterns {}", path.display()).context(e)
+        })?;
+        let hyphenator = Self::parse(&content);
+        if hyphenator.patterns.is_empty() {
+            return Err(anyhow!(
+                "Hyphenation dictionary {} does not contain any patterns",
+                path.display()
+            ));
+        }
+        Ok(hyphenator)
+    }
+
+    /// Parse the patterns, the first line names the encoding and is skipped.
+    pub(crate) fn parse(content: &str) -> Self {
+        let mut hyphenator = Self {
+            left_min: 2,
+            right_min: 3,
+            ..Default::default()
+        };
+        for line in content.lines().skip(1) {
+            let line = line.trim();
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("LEFTHYPHENMIN"), Some(n)) => {
+                    hyphenator.left_min = n.parse().unwrap_or(hyphenator.left_min);
+                }
+                (Some("RIGHTHYPHENMIN"), Some(n)) => {
+                    hyphenator.right_min = n.parse().unwrap_or(hyphenator.right_min);
+                }
+                (Some(pattern), None) if !pattern.starts_with('%') && !pattern.contains('/') => {
+                    // keywords such as `NEXTLEVEL` or `COMPOUNDLEFTHYPHENMIN`
+                    if pattern.chars().all(|c| c.is_ascii_uppercase()) {
+                        continue;
+                    }
+                    hyphenator.insert(pattern);
+                }
+                _ => {}
+            }
+        }
+        hyphenator
+    }
+
+    /// Insert a pattern such as `hy3ph`, levels between letters are digits.
+    fn insert(&mut self, pattern: &str) {
+        let mut letters = String::with_capacity(pattern.len());
+        let mut levels = vec![0u32];
+        for c in pattern.chars() {
+            if let Some(level) = c.to_digit(10) {
+                *levels.last_mut().expect("Levels are never empty. qed") = level;
+            } else {
+                letters.extend(c.to_lowercase());
+                levels.push(0);
+            }
+        }
+        self.max_len = std::cmp::max(self.max_len, letters.chars().count());
+        self.patterns.insert(letters, levels);
+    }
+
+    /// The char indices of `word` a hyphen may be inserted before.
+    pub fn points(&self, word: &str) -> Vec<usize> {
+        let chars = std::iter::once('.')
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(std::iter::once('.'))
+            .collect::<Vec<char>>();
+        let n = chars.len() - 2;
+        // a lowercase letter with more than one char, keep the word intact
+        if n != word.chars().count() || n < self.left_min + self.right_min {
+            return Vec::new();
+        }
+        let mut levels = vec![0u32; chars.len() + 1];
+        for start in 0..chars.len() {
+            let end = std::cmp::min(chars.len(), start + self.max_len);
+            for stop in start + 1..=end {
+                let key = chars[start..stop].iter().collect::<String>();
+                if let Some(pattern) = self.patterns.get(&key) {
+                    for (idx, &level) in pattern.iter().enumerate() {
+                        let level_ref = &mut levels[start + idx];
+                        *level_ref = std::cmp::max(*level_ref, level);
+                    }
+                }
+            }
+        }
+        // the level in front of the word char `idx` is at `idx + 1`
+        (self.left_min..=n - self.right_min)
+            .filter(|&idx| levels[idx + 1] % 2 == 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A few of the patterns of Liang's thesis.
+    const PATTERNS: &str = r"UTF-8
+% excerpt
+LEFTHYPHENMIN 2
+RIGHTHYPHENMIN 3
+hy3ph
+he2n
+hena4
+hen5at
+1na
+n2at
+1tio
+2io
+o2n
+";
+
+    #[test]
+    fn liang() {
+        let hyphenator = Hyphenator::parse(PATTERNS);
+        assert_eq!(hyphenator.points("hyphenation"), vec![2, 6]);
+        assert_eq!(hyphenator.points("Hyphenation"), vec![2, 6]);
+        assert_eq!(hyphenator.points("hyp"), Vec::<usize>::new());
+    }
+}
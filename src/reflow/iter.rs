@@ -18,9 +18,6 @@ pub struct Tokeneer<'s> {
     /// Original source string of continuous lines which are to be wrapped.
     s: &'s str,
 
-    /// A set of matching spans to sub ranges.
-    spans: IndexMap<Range, Span>,
-
     /// If there would occur a line break, that falls within a range of this
     /// the break would only occur afterwards or the whole word gets moved to
     /// the next line.
@@ -43,7 +40,6 @@ impl<'s> Tokeneer<'s> {
         let inner = s.char_indices().enumerate().peekable();
         Self {
             s,
-            spans: Default::default(),
             unbreakable_ranges,
             unbreakable_idx: 0usize,
             inner,
@@ -57,6 +53,13 @@ impl<'s> Tokeneer<'s> {
         self.unbreakable_ranges.extend(unbreakable_ranges);
     }
 
+    /// If `range` overlaps with any of the unbreakable ranges.
+    fn is_unbreakable(&self, range: &Range) -> bool {
+        self.unbreakable_ranges
+            .iter()
+            .any(|unbreakable| unbreakable.start < range.end && range.start < unbreakable.end)
+    }
+
     pub fn craft_token(
         &mut self,
         char_idx: usize,
@@ -157,6 +160,27 @@ impl<'s> Iterator for Tokeneer<'s> {
     }
 }
 
+/// Characters after which a long word may be broken.
+const PUNCTUATION: &[char] = &[
+    '/', '\\', ':', '.', ',', ';', '-', '_', '?', '&', '=', '#', '+', '|',
+];
+
+/// If `word` looks like code, i.e. a type path or an identifier, which must
+/// not be broken, since the line break would render as a space within it.
+fn is_code_like(word: &str) -> bool {
+    if word.contains("::") || word.contains(|c| matches!(c, '(' | '<' | '`')) {
+        return true;
+    }
+    let identifier = word.trim_end_matches(|c: char| c.is_ascii_punctuation());
+    let camel_case = identifier
+        .chars()
+        .zip(identifier.chars().skip(1))
+        .any(|(c, next)| c.is_lowercase() && next.is_uppercase());
+    !identifier.is_empty()
+        && identifier.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && (identifier.contains('_') || camel_case)
+}
+
 /// Re-glue all tokenized items under the constrained of a maximum line width
 #[derive(Debug, Clone)]
 pub struct Gluon<'s> {
@@ -171,6 +195,17 @@ pub struct Gluon<'s> {
     /// if there are more lines than there are lines, the last
     /// values in this vector willl bre reused.
     indentations: &'s [Indentation<'s>],
+    /// Words which must not end a line, since they do not end a sentence.
+    abbreviations: &'s [String],
+    /// Handling of items which exceed the line width on their own.
+    long_words: LongWords,
+    /// Syllables for `LongWords::Hyphenate`.
+    hyphenator: Option<&'s Hyphenator>,
+    /// Pieces of a broken up long word, all but the last one end a line.
+    pending: VecDeque<(Range, std::borrow::Cow<'s, str>)>,
+    /// The next token, already taken from `inner` to check if it continues
+    /// a word broken at a hyphen.
+    lookahead: Option<(Range, Range, Cow<'s, str>)>,
     /// The inner iterator which first tokenizes the string into undividable items.
     inner: Tokeneer<'s>,
 }
@@ -185,6 +220,11 @@ impl<'s> Gluon<'s> {
             queue: VecDeque::new(),
             max_line_width,
             indentations,
+            abbreviations: &[],
+            long_words: LongWords::Keep,
+            hyphenator: None,
+            pending: VecDeque::new(),
+            lookahead: None,
             line_counter: 0usize,
             inner: Tokeneer::<'s>::new(s, vec![]),
         }
@@ -196,6 +236,185 @@ impl<'s> Gluon<'s> {
         self.inner.add_unbreakables(unbreakable_ranges);
     }
 
+    /// Never break a line right after one of `abbreviations`.
+    pub(crate) fn keep_abbreviations(&mut self, abbreviations: &'s [String]) {
+        self.abbreviations = abbreviations;
+    }
+
+    /// Break up words which do not fit into a line on their own.
+    pub(crate) fn break_long_words(
+        &mut self,
+        long_words: LongWords,
+        hyphenator: Option<&'s Hyphenator>,
+    ) {
+        self.long_words = long_words;
+        self.hyphenator = hyphenator;
+    }
+
+    /// Length of the queued items in chars, joined with one whitespace each.
+    fn queue_len(&self) -> usize {
+        let mut qiter = self.queue.iter().map(|(_, s)| s.chars().count());
+        if let Some(first) = qiter.next() {
+            qiter.fold(first, |acc, len| acc + 1 + len)
+        } else {
+            0usize
+        }
+    }
+
+    /// The char indices a `word` may be broken before, and if a hyphen has to
+    /// be appended to the part before.
+    fn split_points(&self, word: &str) -> Vec<(usize, bool)> {
+        if is_code_like(word) {
+            return Vec::new();
+        }
+        let chars = word.chars().collect::<Vec<char>>();
+        let mut points = (1..chars.len())
+            .filter(|&idx| {
+                PUNCTUATION.contains(&chars[idx - 1]) && !PUNCTUATION.contains(&chars[idx])
+            })
+            .map(|idx| (idx, false))
+            .collect::<Vec<_>>();
+        if let (LongWords::Hyphenate, Some(hyphenator)) = (self.long_words, self.hyphenator) {
+            let mut idx = 0;
+            while idx < chars.len() {
+                let len = chars[idx..]
+                    .iter()
+                    .take_while(|c| c.is_alphabetic())
+                    .count();
+                if len > 0 {
+                    let syllables = chars[idx..idx + len].iter().collect::<String>();
+                    points.extend(
+                        hyphenator
+                            .points(&syllables)
+                            .into_iter()
+                            .map(|point| (idx + point, true)),
+                    );
+                }
+                idx += len + 1;
+            }
+        }
+        points.sort_unstable();
+        points.dedup_by_key(|(idx, _)| *idx);
+        points
+    }
+
+    /// The next token, where a word broken at a hyphen at the end of a line
+    /// is joined with its continuation on the next line again, so reflowing
+    /// the result of a reflow does not change it.
+    ///
+    /// The hyphen is dropped if the word was broken at a syllable, and kept
+    /// otherwise, like for compounds broken after their `-`.
+    fn next_token(&mut self) -> Option<(Range, Range, Cow<'s, str>)> {
+        let mut token = self.lookahead.take().or_else(|| self.inner.next())?;
+        if self.long_words == LongWords::Keep {
+            return Some(token);
+        }
+        loop {
+            let (char_range, byte_range, s) = &token;
+            let head = match s.strip_suffix('-') {
+                Some(head) if head.ends_with(char::is_alphabetic) => head,
+                _ => break,
+            };
+            let next = match self.inner.next() {
+                Some(next) => next,
+                None => break,
+            };
+            let gap = &self.inner.s[byte_range.end..next.1.start];
+            if !gap.contains('\n')
+                || !next.2.starts_with(char::is_alphabetic)
+                || self.inner.is_unbreakable(char_range)
+                || self.inner.is_unbreakable(&next.0)
+            {
+                self.lookahead = Some(next);
+                break;
+            }
+            let syllable = match (self.long_words, self.hyphenator) {
+                (LongWords::Hyphenate, Some(hyphenator)) => {
+                    // the alphabetic run the word was broken within
+                    let before = head
+                        .chars()
+                        .rev()
+                        .take_while(|c| c.is_alphabetic())
+                        .collect::<Vec<_>>();
+                    let run = before
+                        .iter()
+                        .rev()
+                        .copied()
+                        .chain(next.2.chars().take_while(|c| c.is_alphabetic()))
+                        .collect::<String>();
+                    hyphenator.points(&run).contains(&before.len())
+                }
+                _ => false,
+            };
+            let joined = if syllable {
+                format!("{}{}", head, next.2)
+            } else {
+                format!("{}{}", s, next.2)
+            };
+            token = (
+                char_range.start..next.0.end,
+                byte_range.start..next.1.end,
+                Cow::Owned(joined),
+            );
+        }
+        Some(token)
+    }
+
+    /// Split `item` at `points`, the first piece is at most `first` chars long,
+    /// all others at most `width`, as long as there is a point to do so.
+    fn split_word(
+        item: &(Range, std::borrow::Cow<'s, str>),
+        points: &[(usize, bool)],
+        first: usize,
+        width: usize,
+    ) -> Vec<(Range, std::borrow::Cow<'s, str>)> {
+        let (range, s) = item;
+        let chars = s.chars().collect::<Vec<char>>();
+        let mut acc = Vec::with_capacity(4);
+        let mut start = 0;
+        let mut avail = first;
+        while chars.len() - start > avail {
+            let mut candidates = points.iter().filter(|(idx, _)| *idx > start);
+            let fitting = candidates
+                .clone()
+                .filter(|(idx, hyphen)| idx - start + usize::from(*hyphen) <= avail)
+                .next_back();
+            let (idx, hyphen) = match fitting.or_else(|| candidates.next()) {
+                Some(&point) => point,
+                None => break,
+            };
+            let mut piece = chars[start..idx].iter().collect::<String>();
+            if hyphen {
+                piece.push('-');
+            }
+            acc.push((range.start + start..range.start + idx, Cow::Owned(piece)));
+            start = idx;
+            avail = width;
+        }
+        acc.push((
+            range.start + start..range.end,
+            Cow::Owned(chars[start..].iter().collect()),
+        ));
+        acc
+    }
+
+    /// Create a new line due to the line width, moving a trailing
+    /// abbreviation over to the next line, unless it is the only item.
+    fn break_line(&mut self) -> (usize, String, Range) {
+        let carry = match self.queue.back() {
+            Some((_range, s))
+                if self.queue.len() > 1
+                    && crate::sentence::is_abbreviation(s, self.abbreviations) =>
+            {
+                self.queue.pop_back()
+            }
+            _ => None,
+        };
+        let line = self.craft_line();
+        self.queue.extend(carry);
+        line
+    }
+
     /// Create a new line based on the processing queue
     fn craft_line(&mut self) -> (usize, String, Range) {
         use itertools::Itertools;
@@ -229,33 +448,68 @@ impl<'s> Iterator for Gluon<'s> {
         let indentation = self
             .indentations
             .get(
-                // the next not yet crafted line
-                self.line_counter + 1,
+                // the next not yet crafted line, there are `line_counter` before
+                self.line_counter,
             )
             .map(|x| *x)
             .unwrap_or_else(|| self.indentations.last().map(|x| *x).unwrap_or_default());
 
-        while let Some((char_range, _byte_range, cow_str)) = self.inner.next() {
+        if let Some(piece) = self.pending.pop_front() {
+            self.queue.push_back(piece);
+            if !self.pending.is_empty() {
+                return Some(self.craft_line());
+            }
+        }
+
+        while let Some((char_range, _byte_range, cow_str)) = self.next_token() {
             // calculate the current characters that are already in that line
             // and assume one whitespace inbetween each of them
-            let mut qiter = self.queue.iter();
-            let acc_len = if let Some((first, _)) = qiter.next() {
-                qiter
-                    .map(|(r, _)| r.len())
-                    .fold(first.len(), |acc, len| acc + 1 + len)
-            } else {
-                0usize
-            };
+            let acc_len = self.queue_len();
 
             let offset = indentation.offset();
-            let item_len = char_range.len();
+            // a joined word is shorter than its range
+            let item_len = cow_str.chars().count();
             let item = (char_range.clone(), cow_str);
+
+            if offset + item_len > self.max_line_width
+                && self.long_words != LongWords::Keep
+                && !self.inner.is_unbreakable(&char_range)
+            {
+                let width = self.max_line_width.saturating_sub(offset);
+                let points = self.split_points(&item.1);
+                let first = if acc_len > 0 {
+                    width.saturating_sub(acc_len + 1)
+                } else {
+                    width
+                };
+                let pieces = Self::split_word(&item, &points, first, width);
+                if pieces.len() > 1 {
+                    if acc_len > 0 && pieces[0].1.chars().count() > first {
+                        // not even the first piece fits, start with a new line
+                        let line = self.break_line();
+                        let carried = self.queue_len();
+                        let first = if carried > 0 {
+                            width.saturating_sub(carried + 1)
+                        } else {
+                            width
+                        };
+                        self.pending
+                            .extend(Self::split_word(&item, &points, first, width));
+                        return Some(line);
+                    }
+                    let mut pieces = pieces.into_iter();
+                    self.queue.extend(pieces.next());
+                    self.pending.extend(pieces);
+                    return Some(self.craft_line());
+                }
+            }
+
             let ret = if offset + acc_len <= self.max_line_width {
                 // calculate the sum if we would add the word
                 let sum = offset + acc_len + 1 + item_len;
-                if sum > self.max_line_width {
-                    // if the line length would be exceeded
-                    let ret = self.craft_line();
+                if acc_len > 0 && sum > self.max_line_width {
+                    // if the line length would be exceeded, an empty line would not help
+                    let ret = self.break_line();
                     self.queue.push_back(item);
                     ret
                 } else {
@@ -272,7 +526,7 @@ impl<'s> Iterator for Gluon<'s> {
                     // craft a line before inserting
                     // to avoid exceeding too much
                     // so use what is in there now and leave the overly long item for the next `next()` call
-                    let line = self.craft_line();
+                    let line = self.break_line();
                     self.queue.push_back(item);
                     line
                 } else {
@@ -283,7 +537,7 @@ impl<'s> Iterator for Gluon<'s> {
                 // the queue len is already larger than what the max line length allows
                 // since we do this iteratively, it should only happen for an unbreakable statement
                 // that exceeds the max line length or very large offsets
-                let ret = self.craft_line();
+                let ret = self.break_line();
                 self.queue.push_back(item);
                 ret
             };
@@ -420,6 +674,21 @@ Xong for a singlX line"#;
             verify_reflow(CONTENT, EXPECTED, 20usize, vec![], vec![0]);
         }
 
+        #[test]
+        fn keep_abbreviations() {
+            const CONTENT: &'static str = "something kinda like e.g. twelve lines";
+            let indentations = vec![Indentation::<'static>::new(0)];
+            let abbreviations = crate::sentence::default_abbreviations();
+
+            let mut gluon = Gluon::new(CONTENT, 25usize, &indentations);
+            let lines = gluon.clone().map(|(_, line, _)| line).collect::<Vec<_>>();
+            assert_eq!(lines, vec!["something kinda like e.g.", "twelve lines"]);
+
+            gluon.keep_abbreviations(&abbreviations);
+            let lines = gluon.map(|(_, line, _)| line).collect::<Vec<_>>();
+            assert_eq!(lines, vec!["something kinda like", "e.g. twelve lines"]);
+        }
+
         #[test]
         fn deep_indentation_too_long() {
             const CONTENT: &'static str = r#"deep indentation"#;
@@ -442,5 +711,100 @@ indentation"#;
             // 22 > 21 = 4 + 1 + 11 + 5
             verify_reflow(CONTENT, EXPECTED, 22usize, vec![], vec![5, 5]);
         }
+
+        #[test]
+        fn long_words() {
+            const CONTENT: &'static str =
+                "see https://docs.rs/cargo-spellcheck/latest/cargo_spellcheck for the hyphenation";
+            let indentations = vec![Indentation::<'static>::new(0)];
+            let hyphenator =
+                Hyphenator::parse("UTF-8\nhy3ph\nhe2n\nhena4\nhen5at\n1na\nn2at\n1tio\n2io\no2n");
+
+            let gluon = Gluon::new(CONTENT, 20usize, &indentations);
+            let lines = gluon.map(|(_, line, _)| line).collect::<Vec<_>>();
+            assert_eq!(
+                lines,
+                vec![
+                    "see",
+                    "https://docs.rs/cargo-spellcheck/latest/cargo_spellcheck",
+                    "for the hyphenation",
+                ]
+            );
+
+            let mut gluon = Gluon::new(CONTENT, 20usize, &indentations);
+            gluon.break_long_words(LongWords::Punctuation, None);
+            let lines = gluon.map(|(_, line, _)| line).collect::<Vec<_>>();
+            assert_eq!(
+                lines,
+                vec![
+                    "see https://docs.rs/",
+                    "cargo-spellcheck/",
+                    "latest/cargo_",
+                    "spellcheck for the",
+                    "hyphenation",
+                ]
+            );
+
+            const WORD: &'static str = "unbreakable hyphenation";
+            let mut gluon = Gluon::new(WORD, 8usize, &indentations);
+            gluon.break_long_words(LongWords::Hyphenate, Some(&hyphenator));
+            let lines = gluon.map(|(_, line, _)| line).collect::<Vec<_>>();
+            assert_eq!(lines, vec!["unbreakable", "hyphen-", "ation"]);
+
+            // code is never broken
+            const CODE: &'static str = "a std::collections::HashMap or a long_identifier_name";
+            let mut gluon = Gluon::new(CODE, 12usize, &indentations);
+            gluon.break_long_words(LongWords::Hyphenate, Some(&hyphenator));
+            let lines = gluon.map(|(_, line, _)| line).collect::<Vec<_>>();
+            assert_eq!(
+                lines,
+                vec![
+                    "a",
+                    "std::collections::HashMap",
+                    "or a",
+                    "long_identifier_name"
+                ]
+            );
+        }
+
+        #[test]
+        fn long_words_round_trip() {
+            const CONTENT: &'static str =
+                "see https://docs.rs/cargo-spellcheck/latest/cargo_spellcheck for the hyphenation";
+            let indentations = vec![Indentation::<'static>::new(0)];
+            let hyphenator =
+                Hyphenator::parse("UTF-8\nhy3ph\nhe2n\nhena4\nhen5at\n1na\nn2at\n1tio\n2io\no2n");
+            let reflow = |content: &str, width: usize, long_words: LongWords| {
+                let mut gluon = Gluon::new(content, width, &indentations);
+                gluon.break_long_words(long_words, Some(&hyphenator));
+                gluon
+                    .map(|(_, line, _)| line)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            // reflowing again changes nothing
+            for &long_words in [LongWords::Punctuation, LongWords::Hyphenate].iter() {
+                for &width in [7usize, 8, 12, 20].iter() {
+                    let once = reflow(CONTENT, width, long_words);
+                    assert_eq!(reflow(&once, width, long_words), once);
+                }
+            }
+            // words broken at syllables are joined again for a wider width
+            const PROSE: &'static str = "the hyphenation of hyphenation";
+            for &width in [7usize, 8, 10].iter() {
+                let once = reflow(PROSE, width, LongWords::Hyphenate);
+                assert!(once.contains("-\n"));
+                assert_eq!(reflow(&once, 100, LongWords::Hyphenate), PROSE);
+            }
+            assert_eq!(
+                reflow(
+                    "a hyphen-\nation and a\ncargo-\nspellcheck",
+                    100,
+                    LongWords::Hyphenate
+                ),
+                "a hyphenation and a cargo-spellcheck"
+            );
+        }
     }
 }
@@ -5,54 +5,55 @@
 
 use anyhow::{anyhow, Result};
 
-use crate::checker::Checker;
-use crate::documentation::{CheckableChunk, Documentation};
+use crate::checker::{Batch, Checker};
+use crate::documentation::CheckableChunk;
 use crate::util::{
     byte_range_to_char_range, byte_range_to_char_range_many, load_span_from, sub_char_range,
 };
-use crate::{CommentVariant, ContentOrigin, Detector, Range, Span, Suggestion, SuggestionSet};
-
-use indexmap::IndexMap;
+use crate::{
+    Category, CommentVariant, ContentOrigin, Detector, Range, Span, Suggestion, SuggestionSet,
+};
 
 use pulldown_cmark::{Event, Options, Parser, Tag};
 
 mod config;
-pub use config::ReflowConfig;
+pub use config::{LongWords, ReflowConfig};
 
-mod iter;
-pub use iter::{Gluon, Tokeneer};
+mod hyphenation;
+pub use hyphenation::Hyphenator;
 
-use rayon::prelude::*;
+mod iter;
+pub use iter::Gluon;
 
 #[derive(Debug)]
 pub struct Reflow;
 
 impl Checker for Reflow {
     type Config = ReflowConfig;
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
-    where
-        'a: 's,
-    {
-        let suggestions = docu
-            .par_iter()
-            .try_fold::<SuggestionSet, Result<SuggestionSet>, _, _>(
-                || SuggestionSet::new(),
-                |mut acc, (origin, chunks)| {
-                    for chunk in chunks {
-                        let suggestions = reflow(origin, chunk, config)?;
-                        acc.extend(origin.clone(), suggestions);
-                    }
-                    Ok(acc)
-                },
-            )
-            .try_reduce(
-                || SuggestionSet::new(),
-                |mut a, b| {
-                    a.join(b);
-                    Ok(a)
-                },
-            )?;
-        Ok(suggestions)
+    type Engine = Option<Hyphenator>;
+    const DETECTOR: Detector = Detector::Reflow;
+
+    fn init(config: &Self::Config) -> Result<Self::Engine> {
+        match (config.long_words, &config.hyphenation_dictionary) {
+            (LongWords::Hyphenate, Some(path)) => Ok(Some(Hyphenator::load(path)?)),
+            (LongWords::Hyphenate, None) => Err(anyhow!(
+                "Hyphenating long words requires a `hyphenation_dictionary`"
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    fn check_batch<'s>(
+        engine: &Self::Engine,
+        config: &Self::Config,
+        batch: &Batch<'s>,
+    ) -> Result<SuggestionSet<'s>> {
+        let mut acc = SuggestionSet::new();
+        for &(origin, chunk) in batch {
+            let suggestions = reflow(origin, chunk, config, engine.as_ref())?;
+            acc.extend(origin.clone(), suggestions);
+        }
+        Ok(acc)
     }
 }
 
@@ -134,13 +135,15 @@ fn extract_delimiter<'s>(s: &'s str) -> Option<&'static str> {
 /// `range` denotes the range of the paragraph of interest in the top-level `CheckableChunk`.
 /// `unbreakable_ranges` contains all ranges of words/sequences which must not be split during
 /// the reflow. They are relative to the top-level `CheckableChunk` similar to `range`. The indentation
-/// vec contains the indentation for each line in `s`.
+/// vec contains the indentation for each line in `s`. Words longer than a line
+/// are broken up as configured in `cfg`, with the syllables of `hyphenator`.
 fn reflow_inner<'s>(
     s: &'s str,
     range: Range,
     unbreakable_ranges: &[Range],
     indentations: &[Indentation<'s>],
-    max_line_width: usize,
+    cfg: &ReflowConfig,
+    hyphenator: Option<&Hyphenator>,
     variant: &CommentVariant,
 ) -> Result<Option<String>> {
     // Get type of newline from current chunk, either plain \n or \r\n
@@ -165,12 +168,64 @@ fn reflow_inner<'s>(
         .iter()
         .map(|r| (r.start.saturating_sub(range.start))..(r.end.saturating_sub(range.start)));
 
-    let mut gluon = Gluon::new(s_absolute, max_line_width, &indentations);
+    // avoid stray spaces after newlines due to a truely required indentation
+    // of 3 for `///` but practically, it's `/// ` (added space), which should be accounted for,
+    // since that is used for accounting for the skip covered by `///`,
+    // which is being removed by the transformation `s` to `s_absolute`
+    // that removes the leading space.
+    let (indentation_skip_n, extra_space) = match variant {
+        CommentVariant::TripleSlash | CommentVariant::DoubleSlashEM => {
+            let n = variant.prefix_len();
+            (n + 1, " ")
+        }
+        _ => (variant.prefix_len(), ""),
+    };
+
+    // The markdown nesting of the paragraph between the comment prefix and
+    // the first word, i.e. of list items or block quotes. Repeated on every
+    // line, with list markers replaced by whitespace.
+    let nesting = sub_char_range(s, 0..range.start)
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .skip(extra_space.len())
+        .map(|c| {
+            if c == '>' || c.is_whitespace() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>();
+
+    // The quote markers of the following lines are part of the paragraph,
+    // blank them so they are not glued to the text.
+    let mut line_start = false;
+    let s_blanked = s_absolute
+        .chars()
+        .map(|c| match c {
+            '\n' => {
+                line_start = true;
+                c
+            }
+            '>' if line_start => ' ',
+            c => {
+                line_start &= c.is_whitespace();
+                c
+            }
+        })
+        .collect::<String>();
+
+    let mut gluon = Gluon::new(&s_blanked, cfg.max_line_length, &indentations);
     gluon.add_unbreakables(unbreakables);
+    gluon.keep_abbreviations(&cfg.abbreviations);
+    gluon.break_long_words(cfg.long_words, hyphenator);
 
     let mut reflow_applied = false;
     let mut lines = s_absolute.lines();
-    let mut indents_iter = indentations.iter();
+    // the first line keeps what precedes the paragraph
+    let mut indents_iter = indentations.iter().skip(1);
     let last_indent = indentations
         .last()
         .copied()
@@ -197,30 +252,19 @@ fn reflow_inner<'s>(
             reflow_applied = true;
         }
 
-        // avoid stray spaces after newlines due to a truely required indentation
-        // of 3 for `///` but practically, it's `/// ` (added space), which should be accounted for,
-        // since that is used for accounting for the skip covered by `///`,
-        // which is being removed by the transformation `s` to `s_absolute`
-        // that removes the leading space.
-        let (indentation_skip_n, extra_space) = match variant {
-            CommentVariant::TripleSlash | CommentVariant::DoubleSlashEM => {
-                let n = variant.prefix_len();
-                (n + 1, " ")
-            }
-            _ => (variant.prefix_len(), ""),
-        };
         let pre = if let Some(indentation) = indents_iter.next() {
             indentation
         } else {
             &last_indent
         }
-        .to_string_but_skip_n(indentation_skip_n);
+        .to_string_but_skip_n(indentation_skip_n + nesting.chars().count());
 
-        log::trace!(target: "glue", "glue[shift={}]: acc = {:?} + {:?} + {:?} + {:?} + {:?} + {:?}",
+        log::trace!(target: "glue", "glue[shift={}]: acc = {:?} + {:?} + {:?} + {:?} + {:?} + {:?} + {:?}",
                 indentation_skip_n,
                 &pre,
                 &variant.prefix_string(),
                 extra_space,
+                &nesting,
                 &content,
                 &variant.suffix_string(),
                 line_delimiter
@@ -228,6 +272,7 @@ fn reflow_inner<'s>(
         acc.push_str(&pre);
         acc.push_str(&variant.prefix_string());
         acc.push_str(extra_space);
+        acc.push_str(&nesting);
         acc.push_str(&content);
         acc.push_str(&variant.suffix_string());
         acc.push_str(line_delimiter);
@@ -322,7 +367,8 @@ fn store_suggestion<'s>(
     bytes_paragraph: usize,
     bytes_end: usize,
     bytes_unbreakable_ranges: &[Range],
-    max_line_width: usize,
+    cfg: &ReflowConfig,
+    hyphenator: Option<&Hyphenator>,
 ) -> Result<(usize, Option<Suggestion<'s>>)> {
     let bytes_range = Range {
         start: bytes_paragraph,
@@ -345,14 +391,6 @@ fn store_suggestion<'s>(
         &s[bytes_range.clone()],
     );
 
-    // with markdown, the initial paragraph for `/// `
-    // might be shifted, so the start in those cases must be shifted back
-    // to right after `///`, which is done by substracting one.
-    let adjustment = match chunk.variant() {
-        CommentVariant::DoubleSlashEM | CommentVariant::TripleSlash => 1usize,
-        _ => 0usize,
-    };
-
     debug_assert_eq!(&s[bytes_range], sub_char_range(s, range.clone()));
 
     let range2span = chunk.find_spans(range.clone());
@@ -383,6 +421,14 @@ fn store_suggestion<'s>(
         load_span_from(sb, span).unwrap()
     );
 
+    // The width of the markdown nesting in front of the paragraph, every line
+    // of the paragraph is indented by it.
+    let nesting = sub_char_range(s, 0..range.start)
+        .rsplit('\n')
+        .next()
+        .map(|prefix| prefix.chars().count())
+        .unwrap_or_default();
+
     // Get indentation for each span, if a span covers multiple
     // lines, use same indentation for all lines.
     let mut first = true;
@@ -394,16 +440,16 @@ fn store_suggestion<'s>(
             // TODO use `sub_char_range(s, range.clone())`
             // TODO and `Indent::with_str(..)`
 
-            // Adjust the column by adding the adjustment to every line
-            // but the first. Necessary, since cmark swallows leading whitespace
-            // but the following leading whitespaces of literals in the same
-            // chunk are still present, yet they are not part of the prefix
-            // as defined by the `CommentVariant` for `///` and `//!`.
-            let col = span
-                .start
-                .column
-                .saturating_sub(adjustment * (first as usize))
-                + adjustment;
+            // The span of the first line starts with the paragraph, all others
+            // at the start of the literal, right after the prefix as defined
+            // by the `CommentVariant`. So the nesting, including the leading
+            // whitespace of `///` and `//!` literals which cmark swallows, is
+            // added to every line but the first.
+            let col = if first {
+                span.start.column
+            } else {
+                span.start.column + nesting
+            };
             let indentation = Indentation::new(col);
             first = false;
             vec![indentation; span.end.line.saturating_sub(span.start.line) + 1]
@@ -417,18 +463,23 @@ fn store_suggestion<'s>(
             range.clone(),
             unbreakable_ranges,
             &indentations,
-            max_line_width,
+            cfg,
+            hyphenator,
             &chunk.variant(),
         )?
         .map(|replacement| {
             let suggestion = Suggestion {
                 chunk,
                 detector: Detector::Reflow,
+                severity: cfg.severity,
+                category: Category::Style,
+                rule: "line-length".to_owned(),
                 origin: origin.clone(),
                 description: None,
                 range,
                 replacements: vec![replacement],
                 span,
+                confidence: None,
             };
             suggestion
         }),
@@ -440,6 +491,7 @@ fn reflow<'s>(
     origin: &ContentOrigin,
     chunk: &'s CheckableChunk,
     cfg: &ReflowConfig,
+    hyphenator: Option<&Hyphenator>,
 ) -> Result<Vec<Suggestion<'s>>> {
     log::debug!("Reflowing {:?}", origin);
     let parser = Parser::new_ext(chunk.as_str(), Options::all());
@@ -451,6 +503,18 @@ fn reflow<'s>(
                                                                     // to be used for reflowing
     let mut unbreakables = Vec::with_capacity(256);
 
+    // the text of list items in tight lists is not wrapped in a paragraph,
+    // so it is tracked while the innermost block is an item
+    let mut blocks: Vec<bool> = Vec::with_capacity(16);
+    let mut tight: Option<Range> = None;
+    let mut tight_items: Vec<Range> = Vec::with_capacity(16);
+    let extend_tight = |blocks: &[bool], tight: &mut Option<Range>, cover: &Range| {
+        if blocks.last() == Some(&true) {
+            let range = tight.get_or_insert_with(|| cover.clone());
+            range.end = std::cmp::max(range.end, cover.end);
+        }
+    };
+
     let mut acc = Vec::with_capacity(128);
 
     for (event, cover) in parser.into_offset_iter() {
@@ -471,12 +535,16 @@ fn reflow<'s>(
                     | Tag::Strong
                     | Tag::Emphasis
                     | Tag::Strikethrough => {
+                        extend_tight(&blocks, &mut tight, &cover);
                         unbreakable_stack.push(cover);
                     }
                     Tag::Paragraph => {
+                        blocks.push(false);
                         paragraph = cover.start;
                     }
                     _ => {
+                        tight_items.extend(tight.take());
+                        blocks.push(tag == Tag::Item);
                         // all of these break a reflow-able chunk
                         let (p, suggestion) = store_suggestion(
                             chunk,
@@ -484,7 +552,8 @@ fn reflow<'s>(
                             paragraph,
                             paragraph,
                             unbreakables.as_slice(),
-                            cfg.max_line_length,
+                            cfg,
+                            hyphenator,
                         )?;
                         paragraph = p;
                         if let Some(suggestion) = suggestion {
@@ -512,6 +581,7 @@ fn reflow<'s>(
                         let _ = unbreakable_stack.pop();
                     }
                     Tag::Paragraph => {
+                        let _ = blocks.pop();
                         // regular end of paragraph
                         let (p, suggestion) = store_suggestion(
                             chunk,
@@ -519,7 +589,8 @@ fn reflow<'s>(
                             paragraph,
                             cover.end,
                             unbreakables.as_slice(),
-                            cfg.max_line_length,
+                            cfg,
+                            hyphenator,
                         )?;
                         paragraph = p;
                         if let Some(suggestion) = suggestion {
@@ -528,16 +599,22 @@ fn reflow<'s>(
                         unbreakable_stack.clear();
                     }
                     _ => {
+                        tight_items.extend(tight.take());
+                        let _ = blocks.pop();
                         paragraph = cover.end;
                     }
                 }
             }
-            Event::Text(_s) => {}
+            Event::Text(_s) => {
+                extend_tight(&blocks, &mut tight, &cover);
+            }
             Event::Code(_s) => {
+                extend_tight(&blocks, &mut tight, &cover);
                 // always make code unbreakable
                 unbreakables.push(cover);
             }
             Event::Html(_s) => {
+                extend_tight(&blocks, &mut tight, &cover);
                 unbreakables.push(cover);
                 // TODO verify this does not interfere with paragraphs
             }
@@ -547,6 +624,9 @@ fn reflow<'s>(
             Event::SoftBreak => {
                 // ignored
             }
+            Event::HardBreak if blocks.last() == Some(&true) => {
+                tight_items.extend(tight.take());
+            }
             Event::HardBreak => {
                 let (p, suggestion) = store_suggestion(
                     chunk,
@@ -554,7 +634,8 @@ fn reflow<'s>(
                     paragraph,
                     cover.end,
                     unbreakables.as_slice(),
-                    cfg.max_line_length,
+                    cfg,
+                    hyphenator,
                 )?;
                 paragraph = p;
                 if let Some(suggestion) = suggestion {
@@ -570,6 +651,28 @@ fn reflow<'s>(
             }
         }
     }
+    tight_items.extend(tight.take());
+
+    for mut item in tight_items {
+        // like paragraphs, include the line delimiter
+        let rest = &chunk.as_str()[item.end..];
+        if rest.starts_with("\r\n") {
+            item.end += 2;
+        } else if rest.starts_with('\n') {
+            item.end += 1;
+        }
+        let (_, suggestion) = store_suggestion(
+            chunk,
+            origin,
+            item.start,
+            item.end,
+            unbreakables.as_slice(),
+            cfg,
+            hyphenator,
+        )?;
+        acc.extend(suggestion);
+    }
+    acc.sort_by_key(|suggestion| suggestion.range.start);
 
     Ok(acc)
 }
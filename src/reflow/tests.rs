@@ -1,6 +1,6 @@
 use super::*;
 use crate::{chyrp_up, fluff_up};
-use crate::{LineColumn, Span};
+use crate::{Documentation, LineColumn, Severity, Span};
 
 macro_rules! verify_reflow_inner {
     ($n:literal break [ $( $line:literal ),+ $(,)?] => $expected:literal) => {
@@ -23,12 +23,18 @@ macro_rules! verify_reflow_inner {
             Indentation::<'static>::new(n)
         }).collect::<Vec<_>>();
         let unbreakables = Vec::new();
+        let cfg = ReflowConfig {
+            max_line_length: $n,
+            abbreviations: Vec::new(),
+            ..Default::default()
+        };
         let replacement = reflow_inner(
             chunk.as_str(),
             range,
             &unbreakables,
             &indentation,
-            $n,
+            &cfg,
+            None,
             &chunk.variant()
         );
 
@@ -78,6 +84,10 @@ macro_rules! reflow_content {
     ($max_line_width:literal break $content_type:expr, $content:expr => applied $expected:literal) => {
         const CFG: ReflowConfig = ReflowConfig {
             max_line_length: $max_line_width,
+            long_words: LongWords::Keep,
+            hyphenation_dictionary: None,
+            severity: Severity::Error,
+            abbreviations: Vec::new(),
         };
 
         let _ = env_logger::Builder::new()
@@ -92,7 +102,7 @@ macro_rules! reflow_content {
         assert_eq!(dbg!(chunks).len(), 1);
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &CFG, None).expect("Reflow is working. qed");
 
         let patches = suggestions
             .into_iter()
@@ -121,6 +131,10 @@ macro_rules! reflow_content {
     ($max_line_width:literal break $content_type:expr, $content:expr => ok) => {
         const CFG: ReflowConfig = ReflowConfig {
             max_line_length: $max_line_width,
+            long_words: LongWords::Keep,
+            hyphenation_dictionary: None,
+            severity: Severity::Error,
+            abbreviations: Vec::new(),
         };
 
         let _ = env_logger::Builder::new()
@@ -135,7 +149,7 @@ macro_rules! reflow_content {
         assert_eq!(dbg!(chunks).len(), 1);
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &CFG, None).expect("Reflow is working. qed");
 
         assert_eq!(
             dbg!(suggestions).len(),
@@ -146,6 +160,10 @@ macro_rules! reflow_content {
     ($max_line_width:literal break $content_type:expr, $content:expr => patches [ $( $expected:literal ),+ $(,)?]) => {
         const CFG: ReflowConfig = ReflowConfig {
             max_line_length: $max_line_width,
+            long_words: LongWords::Keep,
+            hyphenation_dictionary: None,
+            severity: Severity::Error,
+            abbreviations: Vec::new(),
         };
 
         let _ = env_logger::Builder::new()
@@ -161,7 +179,7 @@ macro_rules! reflow_content {
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
         println!("reflow content:\n {:?}", $content);
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &CFG, None).expect("Reflow is working. qed");
         let patches = suggestions
             .into_iter()
             .filter_map(|suggestion| {
@@ -284,6 +302,10 @@ fn reflow_indentations() {
 
     const CONFIG: ReflowConfig = ReflowConfig {
         max_line_length: 10,
+        long_words: LongWords::Keep,
+        hyphenation_dictionary: None,
+        severity: Severity::Error,
+        abbreviations: Vec::new(),
     };
 
     let docs = Documentation::from((ContentOrigin::TestEntityRust, CONTENT));
@@ -294,8 +316,8 @@ fn reflow_indentations() {
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = &chunks[0];
 
-    let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, chunk, &CONFIG).expect("Reflow is wokring. qed");
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, chunk, &CONFIG, None)
+        .expect("Reflow is wokring. qed");
 
     let suggestion = suggestion_set
         .iter()
@@ -334,9 +356,13 @@ fn reflow_doc_indentations() {
 
     let cfg = ReflowConfig {
         max_line_length: 45,
+        long_words: LongWords::Keep,
+        hyphenation_dictionary: None,
+        severity: Severity::Error,
+        abbreviations: Vec::new(),
     };
     let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, chunk, &cfg).expect("Reflow is working. qed");
+        reflow(&ContentOrigin::TestEntityRust, chunk, &cfg, None).expect("Reflow is working. qed");
 
     let suggestions = suggestion_set
         .iter()
@@ -407,10 +433,14 @@ fn reflow_markdown_two_paragraphs() {
 
     let cfg = ReflowConfig {
         max_line_length: 60,
+        long_words: LongWords::Keep,
+        hyphenation_dictionary: None,
+        severity: Severity::Error,
+        abbreviations: Vec::new(),
     };
 
     let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, &chunk, &cfg).expect("Reflow is working. qed");
+        reflow(&ContentOrigin::TestEntityRust, &chunk, &cfg, None).expect("Reflow is working. qed");
 
     for (sug, expected) in suggestion_set.iter().zip(expected) {
         assert_eq!(sug.replacements.len(), 1);
@@ -447,11 +477,15 @@ With a second part that is fine"#
 
     let cfg = ReflowConfig {
         max_line_length: 45,
+        long_words: LongWords::Keep,
+        hyphenation_dictionary: None,
+        severity: Severity::Error,
+        abbreviations: Vec::new(),
     };
 
     for (chunk, expect) in chunks.iter().zip(expected) {
-        let suggestion_set =
-            reflow(&ContentOrigin::TestEntityRust, chunk, &cfg).expect("Reflow is working. qed");
+        let suggestion_set = reflow(&ContentOrigin::TestEntityRust, chunk, &cfg, None)
+            .expect("Reflow is working. qed");
         let sug = suggestion_set
             .iter()
             .next()
@@ -489,6 +523,10 @@ fn reflow_doc_long() {
 fn reflow_sole_markdown() {
     const CONFIG: ReflowConfig = ReflowConfig {
         max_line_length: 60,
+        long_words: LongWords::Keep,
+        hyphenation_dictionary: None,
+        severity: Severity::Error,
+        abbreviations: Vec::new(),
     };
 
     const CONTENT: &'static str =
@@ -546,7 +584,7 @@ multiline. Fullstop."#,
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = chunks.first().unwrap();
 
-    let suggestion_set = reflow(&ContentOrigin::TestEntityCommonMark, &chunk, &CONFIG)
+    let suggestion_set = reflow(&ContentOrigin::TestEntityCommonMark, &chunk, &CONFIG, None)
         .expect("Reflow is working. qed");
     assert_eq!(suggestion_set.len(), 2);
 
@@ -592,6 +630,10 @@ fn reflow_line_delimiters() {
 fn reflow_check_span() {
     const CONFIG: ReflowConfig = ReflowConfig {
         max_line_length: 27,
+        long_words: LongWords::Keep,
+        hyphenation_dictionary: None,
+        severity: Severity::Error,
+        abbreviations: Vec::new(),
     };
 
     const CONTENT: &'static str = "/// A comment as we have many here and we will always
@@ -615,8 +657,8 @@ struct Fff;
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = chunks.first().unwrap();
 
-    let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, &chunk, &CONFIG).expect("Reflow is working. qed");
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, &chunk, &CONFIG, None)
+        .expect("Reflow is working. qed");
     assert_eq!(suggestion_set.len(), 1);
     let suggestion = suggestion_set
         .first()
@@ -687,6 +729,9 @@ configured with `-m <code>` to
 return a non-zero return code
 if mistakes are found instead
 of `0`.
+"##,
+r##"Parse doc comments from
+      arbitrary files
 "##
     ]);
 }
@@ -713,9 +758,161 @@ of `0`.
 
 ## Implemented Features + Roadmap
 
-* [x] Parse doc comments from arbitrary files
+* [x] Parse doc comments from
+      arbitrary files
 * [x] Decent error printing
 
 "###
     );
 }
+
+/// Reflow `$content` with all patches applied, none of the lines of
+/// `$expected` may exceed the width.
+macro_rules! reflow_nested {
+    ($max_line_width:literal break $content_type:expr, $content:expr => $expected:literal) => {
+        for line in $expected.lines() {
+            assert!(
+                line.chars().count() <= $max_line_width,
+                "Line {:?} exceeds {}",
+                line,
+                $max_line_width
+            );
+        }
+        reflow_content!($max_line_width break $content_type, $content => applied $expected);
+    };
+}
+
+#[test]
+fn reflow_nested_impl() {
+    const CONTENT: &'static str = r#"mod outer {
+    mod inner {
+        impl Fluffy {
+            /// A method documentation which is far too long for a single line and must be wrapped.
+            fn fluff() {}
+        }
+    }
+}
+"#;
+    reflow_nested!(40 break ContentOrigin::TestEntityRust, CONTENT => r#"mod outer {
+    mod inner {
+        impl Fluffy {
+            /// A method documentation
+            /// which is far too long
+            /// for a single line and
+            /// must be wrapped.
+            fn fluff() {}
+        }
+    }
+}
+"#);
+}
+
+#[test]
+fn reflow_nested_inner_module_doc() {
+    const CONTENT: &'static str = r#"mod outer {
+    mod inner {
+        //! The documentation of a module which is far too long for a single line.
+    }
+}
+"#;
+    reflow_nested!(40 break ContentOrigin::TestEntityRust, CONTENT => r#"mod outer {
+    mod inner {
+        //! The documentation of a
+        //! module which is far too long
+        //! for a single line.
+    }
+}
+"#);
+}
+
+#[test]
+fn reflow_nested_tight_list() {
+    const CONTENT: &'static str = r#"impl Fluffy {
+    /// Options:
+    ///
+    /// * an item which is far too long for a single line and must be wrapped somehow
+    ///   * a nested item which is far too long for a single line as well
+    /// * short
+    fn fluff() {}
+}
+"#;
+    reflow_nested!(40 break ContentOrigin::TestEntityRust, CONTENT => r#"impl Fluffy {
+    /// Options:
+    ///
+    /// * an item which is far too long
+    ///   for a single line and must be
+    ///   wrapped somehow
+    ///   * a nested item which is far
+    ///     too long for a single line
+    ///     as well
+    /// * short
+    fn fluff() {}
+}
+"#);
+}
+
+#[test]
+fn reflow_nested_loose_list() {
+    const CONTENT: &'static str = r#"impl Fluffy {
+    /// 1. an item which is far too long for a single line and must be wrapped
+    ///
+    ///    a second paragraph of the item which is far too long as well
+    fn fluff() {}
+}
+"#;
+    reflow_nested!(40 break ContentOrigin::TestEntityRust, CONTENT => r#"impl Fluffy {
+    /// 1. an item which is far too long
+    ///    for a single line and must be
+    ///    wrapped
+    ///
+    ///    a second paragraph of the
+    ///    item which is far too long as
+    ///    well
+    fn fluff() {}
+}
+"#);
+}
+
+#[test]
+fn reflow_nested_quote() {
+    const CONTENT: &'static str = r#"impl Fluffy {
+    /// > a quote which is far too long for a single line and
+    /// > must be wrapped somehow.
+    ///
+    /// > * a list in a quote which is far too long for a single line
+    fn fluff() {}
+}
+"#;
+    reflow_nested!(40 break ContentOrigin::TestEntityRust, CONTENT => r#"impl Fluffy {
+    /// > a quote which is far too long
+    /// > for a single line and must be
+    /// > wrapped somehow.
+    ///
+    /// > * a list in a quote which is
+    /// >   far too long for a single
+    /// >   line
+    fn fluff() {}
+}
+"#);
+}
+
+#[test]
+fn reflow_nested_markdown_list() {
+    const CONTENT: &'static str = r#"A list:
+
+* an item which is far too long for a single line and must be wrapped somehow.
+  1. a nested item which is far too long for a single line and must be wrapped.
+     * and one more level which is far too long for a single line.
+"#;
+    reflow_nested!(40 break ContentOrigin::TestEntityCommonMark, CONTENT => r#"A list:
+
+* an item which is far too long for a
+  single line and must be wrapped
+  somehow.
+  1. a nested item which is far too long
+     for a single line and must be
+     wrapped.
+     * and one more level which is far
+       too long for a single line.
+"#);
+}
@@ -0,0 +1,79 @@
+//! Sentence boundaries, aware of abbreviations.
+//!
+//! A period usually ends a sentence, unless it belongs to an abbreviation
+//! like `e.g.` or `Dr.`. Neither should the following word be treated as the
+//! start of a new sentence, nor should a line be wrapped right after one.
+
+/// Abbreviations known without any configuration.
+pub const DEFAULT_ABBREVIATIONS: &[&str] = &[
+    "approx.", "cf.", "Dr.", "e.g.", "etc.", "fig.", "i.e.", "incl.", "Mr.", "Mrs.", "Ms.", "no.",
+    "Prof.", "resp.", "vs.",
+];
+
+/// The default for the `abbreviations` of the configuration.
+pub fn default_abbreviations() -> Vec<String> {
+    DEFAULT_ABBREVIATIONS
+        .iter()
+        .map(|&abbreviation| abbreviation.to_owned())
+        .collect()
+}
+
+/// If `word` is one of `abbreviations`, ignoring case as well as opening
+/// brackets and quotes in front of it.
+pub fn is_abbreviation(word: &str, abbreviations: &[String]) -> bool {
+    let word = word.trim_start_matches(|c| matches!(c, '(' | '[' | '"' | '\'' | '`'));
+    word.ends_with('.')
+        && abbreviations
+            .iter()
+            .any(|abbreviation| abbreviation.eq_ignore_ascii_case(word))
+}
+
+/// If the word preceding the character at `char_idx` of `s` is an
+/// abbreviation, so the character does not start a new sentence.
+pub fn follows_abbreviation(s: &str, char_idx: usize, abbreviations: &[String]) -> bool {
+    let preceding = s.chars().take(char_idx).collect::<Vec<_>>();
+    let end = preceding
+        .iter()
+        .rposition(|c| !c.is_whitespace())
+        .map(|idx| idx + 1)
+        .unwrap_or_default();
+    // there must be some whitespace in between
+    if end == 0 || end == preceding.len() {
+        return false;
+    }
+    let start = preceding[..end]
+        .iter()
+        .rposition(|c| c.is_whitespace())
+        .map(|idx| idx + 1)
+        .unwrap_or_default();
+    let word = preceding[start..end].iter().collect::<String>();
+    is_abbreviation(&word, abbreviations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abbreviation() {
+        let abbreviations = default_abbreviations();
+        assert!(is_abbreviation("e.g.", &abbreviations));
+        assert!(is_abbreviation("(E.g.", &abbreviations));
+        assert!(is_abbreviation("dr.", &abbreviations));
+        assert!(!is_abbreviation("e.g.,", &abbreviations));
+        assert!(!is_abbreviation("end.", &abbreviations));
+        assert!(!is_abbreviation("ie", &abbreviations));
+    }
+
+    #[test]
+    fn follows() {
+        let abbreviations = default_abbreviations();
+        let s = "Ask Dr. who. Or ask\n(i.e.  the doctor";
+        assert!(follows_abbreviation(s, 8, &abbreviations));
+        assert!(!follows_abbreviation(s, 13, &abbreviations));
+        assert!(follows_abbreviation(s, 27, &abbreviations));
+        // within the abbreviation itself
+        assert!(!follows_abbreviation(s, 6, &abbreviations));
+        assert!(!follows_abbreviation(s, 0, &abbreviations));
+    }
+}
@@ -17,6 +17,7 @@ use std::cmp;
 use std::convert::TryFrom;
 
 use enumflags2::BitFlags;
+use serde::{Deserialize, Serialize};
 
 use crate::{Range, Span};
 
@@ -33,6 +34,60 @@ pub enum Detector {
     /// Detection of nothing, a test helper.
     #[cfg(test)]
     Dummy = 0b1000,
+    /// Words spelled in different variants across the checked files.
+    Consistency = 0b1_0000,
+    /// Disfavored terms of a style guide.
+    Terminology = 0b10_0000,
+    /// Terms disallowed by policy.
+    Banned = 0b100_0000,
+}
+
+/// How severe a suggestion is, configurable per checker.
+///
+/// By default only `Error`s are accounted for in the exit code, `Warning`s
+/// and `Info`s are reported but do not fail a run, see `fail_on`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Informal only, likely a matter of taste.
+    Info,
+    /// Should be looked at, but does not fail the run by default.
+    Warning,
+    /// A mistake, accounted for in the exit code.
+    Error,
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+impl Severity {
+    /// If findings of this severity are mistakes that influence the exit
+    /// code, given the least severe ones that do.
+    pub fn fails(&self, fail_on: Severity) -> bool {
+        *self >= fail_on
+    }
+}
+
+/// The kind of mistake a suggestion is about.
+///
+/// Allows enabling, disabling and re-assigning the severity of findings
+/// across checkers.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    /// A word that is not in the dictionary.
+    Spelling,
+    /// Grammatical mistakes, the default for anything not more specific.
+    Grammar,
+    /// Wording, punctuation, typography and line length.
+    Style,
+    /// Repeated words or phrases.
+    Repetition,
+    /// Capitalization of words.
+    Casing,
 }
 
 /// Terminal size in characters.
@@ -67,18 +122,58 @@ pub fn get_terminal_size() -> usize {
 
 use std::fmt;
 
+impl fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::Spelling => "spelling",
+            Self::Grammar => "grammar",
+            Self::Style => "style",
+            Self::Repetition => "repetition",
+            Self::Casing => "casing",
+        })
+    }
+}
+
 impl fmt::Display for Detector {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         formatter.write_str(match self {
             Self::LanguageTool => "LanguageTool",
             Self::Hunspell => "Hunspell",
             Self::Reflow => "Reflow",
+            Self::Consistency => "Consistency",
+            Self::Terminology => "Terminology",
+            Self::Banned => "Banned",
             #[cfg(test)]
             Self::Dummy => "Dummy",
         })
     }
 }
 
+impl std::str::FromStr for Detector {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "hunspell" => Self::Hunspell,
+            "languagetool" => Self::LanguageTool,
+            "reflow" => Self::Reflow,
+            "consistency" => Self::Consistency,
+            "terminology" => Self::Terminology,
+            "banned" => Self::Banned,
+            other => anyhow::bail!("Unknown checker `{}`", other),
+        })
+    }
+}
+
 /// For long lines, literal will be trimmed to display in one terminal line.
 /// Misspelled words that are too long shall also be ellipsized.
 pub fn condition_display_content(
@@ -286,6 +381,13 @@ pub fn condition_display_content(
 pub struct Suggestion<'s> {
     /// Which checker suggested the change.
     pub detector: Detector,
+    /// How severe the mistake is, as configured for the `detector`.
+    pub severity: Severity,
+    /// The kind of mistake.
+    pub category: Category,
+    /// The rule within the checker which was violated, i.e. `unknown-word`,
+    /// see `rule_id`.
+    pub rule: String,
     /// Reference to the file location the `span` and `literal` relate to.
     pub origin: ContentOrigin,
     /// The suggestion is relative to a specific chunk.
@@ -299,25 +401,45 @@ pub struct Suggestion<'s> {
     pub replacements: Vec<String>,
     /// Descriptive reason for the suggestion.
     pub description: Option<String>,
+    /// How certain the checker is that this is a mistake, in percent, `None`
+    /// if it is certain or can not tell.
+    pub confidence: Option<u8>,
+}
+
+impl<'s> Suggestion<'s> {
+    /// If the checker is at least `min` percent certain of the mistake,
+    /// suggestions without a confidence always are.
+    pub fn is_confident(&self, min: u8) -> bool {
+        self.confidence.map_or(true, |confidence| confidence >= min)
+    }
+
+    /// The stable identifier of the violated rule, the lowercase checker and
+    /// the rule, i.e. `hunspell::unknown-word` or `languagetool::EN_A_VS_AN`.
+    pub fn rule_id(&self) -> String {
+        format!(
+            "{}::{}",
+            self.detector.to_string().to_lowercase(),
+            self.rule
+        )
+    }
 }
 
 impl<'s> fmt::Display for Suggestion<'s> {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use console::Style;
-
-        let highlight = Style::new().bold().white();
-        let error = Style::new().bold().red();
-        let arrow_marker = Style::new().blue();
-        let context_marker = Style::new().bold().blue();
-        let fix = Style::new().green();
-        let help = Style::new().yellow().bold();
+        let theme = crate::output::theme::current();
+        let highlight = &theme.highlight;
+        let error = theme.severity(self.severity);
+        let arrow_marker = &theme.arrow;
+        let context_marker = &theme.context;
+        let fix = &theme.replacement;
+        let help = &theme.marker;
 
         let line_number_digit_count = self.span.start.line.to_string().len();
         let indent = 3 + line_number_digit_count;
 
-        error.apply_to("error").fmt(formatter)?;
+        error.apply_to(self.severity).fmt(formatter)?;
         highlight
-            .apply_to(format!(": spellcheck({})", &self.detector))
+            .apply_to(format!(": spellcheck({})", self.rule_id()))
             .fmt(formatter)?;
         formatter.write_str("\n")?;
 
@@ -505,6 +627,11 @@ impl<'s> fmt::Debug for Suggestion<'s> {
 #[derive(Debug, Clone)]
 pub struct SuggestionSet<'s> {
     per_file: indexmap::IndexMap<ContentOrigin, Vec<Suggestion<'s>>>,
+    /// The number of suggestions dropped per file by [`Self::truncate`].
+    truncated: indexmap::IndexMap<ContentOrigin, usize>,
+    /// The number of chunks per file which were not checked, since the file
+    /// reached the `max_suggestions_per_file` before.
+    unchecked: indexmap::IndexMap<ContentOrigin, usize>,
 }
 
 impl<'s> SuggestionSet<'s> {
@@ -512,6 +639,8 @@ impl<'s> SuggestionSet<'s> {
     pub fn new() -> Self {
         Self {
             per_file: indexmap::IndexMap::with_capacity(64),
+            truncated: indexmap::IndexMap::new(),
+            unchecked: indexmap::IndexMap::new(),
         }
     }
 
@@ -555,7 +684,7 @@ impl<'s> SuggestionSet<'s> {
     pub fn entry(
         &mut self,
         origin: ContentOrigin,
-    ) -> indexmap::map::Entry<ContentOrigin, Vec<Suggestion<'s>>> {
+    ) -> indexmap::map::Entry<'_, ContentOrigin, Vec<Suggestion<'s>>> {
         self.per_file.entry(origin)
     }
 
@@ -603,16 +732,78 @@ impl<'s> SuggestionSet<'s> {
         })
     }
 
+    /// Merge another suggestion set into self, including the number of
+    /// dropped suggestions and unchecked chunks.
+    pub fn merge(&mut self, other: SuggestionSet<'s>) {
+        for (origin, count) in other.truncated.iter() {
+            *self.truncated.entry(origin.clone()).or_default() += count;
+        }
+        for (origin, count) in other.unchecked.iter() {
+            *self.unchecked.entry(origin.clone()).or_default() += count;
+        }
+        self.join(other);
+    }
+
     /// Obtain the number of items in the set
     #[inline]
     pub fn len(&self) -> usize {
         self.per_file.len()
     }
 
+    /// Keep only the suggestions for which `f` returns `true`, allowing them to be
+    /// modified on the way.
+    ///
+    /// Files without any remaining suggestions are dropped.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&ContentOrigin, &mut Suggestion<'s>) -> bool,
+    {
+        self.per_file.retain(|origin, suggestions| {
+            suggestions.retain_mut(|suggestion| f(origin, suggestion));
+            !suggestions.is_empty()
+        });
+    }
+
+    /// Sort the suggestions of each file by their position in the file.
+    pub fn sort(&mut self) {
+        for suggestions in self.per_file.values_mut() {
+            suggestions.sort_by_key(|suggestion| {
+                (suggestion.span.start.line, suggestion.span.start.column)
+            });
+        }
+    }
+
     /// Count the number of suggestions across all files in total
     pub fn total_count(&self) -> usize {
         self.per_file.iter().map(|(_origin, vec)| vec.len()).sum()
     }
+
+    /// Keep at most `max` suggestions per file, the first ones, and remember
+    /// how many were dropped.
+    pub fn truncate(&mut self, max: usize) {
+        for (origin, suggestions) in self.per_file.iter_mut() {
+            if suggestions.len() > max {
+                *self.truncated.entry(origin.clone()).or_default() += suggestions.len() - max;
+                suggestions.truncate(max);
+            }
+        }
+    }
+
+    /// The number of suggestions of `origin` dropped by [`Self::truncate`].
+    pub fn truncated(&self, origin: &ContentOrigin) -> usize {
+        self.truncated.get(origin).copied().unwrap_or_default()
+    }
+
+    /// Remember that `chunks` of `origin` were not checked, since it already
+    /// reached the maximum number of suggestions.
+    pub fn not_checked(&mut self, origin: ContentOrigin, chunks: usize) {
+        *self.unchecked.entry(origin).or_default() += chunks;
+    }
+
+    /// The number of chunks of `origin` which were not checked.
+    pub fn unchecked(&self, origin: &ContentOrigin) -> usize {
+        self.unchecked.get(origin).copied().unwrap_or_default()
+    }
 }
 
 impl<'s> IntoIterator for SuggestionSet<'s> {
@@ -631,6 +822,377 @@ impl<'s> IntoIterator for &'s SuggestionSet<'s> {
     }
 }
 
+pub(crate) use self::schema::SuggestionSchema;
+#[cfg(feature = "serde")]
+pub use self::schema::{SuggestionIn, SuggestionSetIn};
+
+/// A stable serialization of suggestions, which the `json` output uses, and
+/// the `serde` feature exposes to library consumers.
+///
+/// A suggestion refers to the chunk it was found in, which is serialized by
+/// its content only. Deserializing requires the documentation the chunks are
+/// part of, see [`SuggestionIn`] and [`SuggestionSetIn`], and fails for
+/// suggestions of chunks which are not part of it anymore.
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+mod schema {
+    use super::*;
+    use crate::{Documentation, LineColumn};
+
+    use serde::de::{DeserializeSeed, Deserializer, Error as _};
+    #[cfg(feature = "serde")]
+    use serde::ser::Serializer;
+
+    use std::borrow::Cow;
+    use std::path::Path;
+
+    #[derive(Serialize, Deserialize)]
+    struct Position {
+        line: usize,
+        column: usize,
+    }
+
+    /// The span in characters, where `end` is inclusive.
+    #[derive(Serialize, Deserialize)]
+    struct SpanSchema {
+        start: Position,
+        end: Position,
+    }
+
+    impl From<Span> for SpanSchema {
+        fn from(span: Span) -> Self {
+            Self {
+                start: Position {
+                    line: span.start.line,
+                    column: span.start.column,
+                },
+                end: Position {
+                    line: span.end.line,
+                    column: span.end.column,
+                },
+            }
+        }
+    }
+
+    impl From<SpanSchema> for Span {
+        fn from(span: SpanSchema) -> Self {
+            Self {
+                start: LineColumn {
+                    line: span.start.line,
+                    column: span.start.column,
+                },
+                end: LineColumn {
+                    line: span.end.line,
+                    column: span.end.column,
+                },
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "kind", rename_all = "kebab-case")]
+    enum OriginSchema<'a> {
+        CommonMarkFile {
+            path: Cow<'a, Path>,
+        },
+        RustDocTest {
+            path: Cow<'a, Path>,
+            span: SpanSchema,
+        },
+        RustSourceFile {
+            path: Cow<'a, Path>,
+        },
+    }
+
+    impl<'a> From<&'a ContentOrigin> for OriginSchema<'a> {
+        fn from(origin: &'a ContentOrigin) -> Self {
+            match origin {
+                ContentOrigin::CommonMarkFile(path) => Self::CommonMarkFile {
+                    path: Cow::Borrowed(path.as_path()),
+                },
+                ContentOrigin::RustDocTest(path, span) => Self::RustDocTest {
+                    path: Cow::Borrowed(path.as_path()),
+                    span: SpanSchema::from(*span),
+                },
+                ContentOrigin::RustSourceFile(path) => Self::RustSourceFile {
+                    path: Cow::Borrowed(path.as_path()),
+                },
+                #[cfg(test)]
+                ContentOrigin::TestEntityRust => Self::RustSourceFile {
+                    path: Cow::Borrowed(origin.as_path()),
+                },
+                #[cfg(test)]
+                ContentOrigin::TestEntityCommonMark => Self::CommonMarkFile {
+                    path: Cow::Borrowed(origin.as_path()),
+                },
+            }
+        }
+    }
+
+    impl<'a> From<OriginSchema<'a>> for ContentOrigin {
+        fn from(origin: OriginSchema<'a>) -> Self {
+            match origin {
+                OriginSchema::CommonMarkFile { path } => Self::CommonMarkFile(path.into_owned()),
+                OriginSchema::RustDocTest { path, span } => {
+                    Self::RustDocTest(path.into_owned(), Span::from(span))
+                }
+                OriginSchema::RustSourceFile { path } => Self::RustSourceFile(path.into_owned()),
+            }
+        }
+    }
+
+    /// Where a suggestion is located in its file, as printed by the `json`
+    /// output.
+    #[derive(Serialize, Deserialize)]
+    struct LocationSchema {
+        /// The span within the file, in units of the column encoding.
+        span: SpanSchema,
+        /// Byte offsets into the file, where `end` is exclusive, if the file
+        /// is available.
+        byte_range: Option<Range>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub(crate) struct SuggestionSchema<'a> {
+        /// The checker, as named on the commandline, i.e. `hunspell`.
+        detector: Cow<'a, str>,
+        severity: Severity,
+        category: Category,
+        rule: Cow<'a, str>,
+        origin: OriginSchema<'a>,
+        /// The content of the chunk.
+        chunk: Cow<'a, str>,
+        span: SpanSchema,
+        range: Range,
+        replacements: Cow<'a, [String]>,
+        description: Option<Cow<'a, str>>,
+        confidence: Option<u8>,
+        /// Only part of the `json` output, unlike `span` in the units of the
+        /// column encoding and within the file for doc tests as well.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        location: Option<LocationSchema>,
+    }
+
+    impl<'a> SuggestionSchema<'a> {
+        /// Add the location within the file, with the columns of `span` in
+        /// the units of the column encoding.
+        pub(crate) fn located(mut self, span: Span, byte_range: Option<Range>) -> Self {
+            self.location = Some(LocationSchema {
+                span: SpanSchema::from(span),
+                byte_range,
+            });
+            self
+        }
+    }
+
+    impl<'a, 's> From<&'a Suggestion<'s>> for SuggestionSchema<'a> {
+        fn from(suggestion: &'a Suggestion<'s>) -> Self {
+            Self {
+                detector: Cow::Owned(suggestion.detector.to_string().to_lowercase()),
+                severity: suggestion.severity,
+                category: suggestion.category,
+                rule: Cow::Borrowed(suggestion.rule.as_str()),
+                origin: OriginSchema::from(&suggestion.origin),
+                chunk: Cow::Borrowed(suggestion.chunk.as_str()),
+                span: SpanSchema::from(suggestion.span),
+                range: suggestion.range.clone(),
+                replacements: Cow::Borrowed(suggestion.replacements.as_slice()),
+                description: suggestion.description.as_deref().map(Cow::Borrowed),
+                confidence: suggestion.confidence,
+                location: None,
+            }
+        }
+    }
+
+    /// The suggestions of one file.
+    #[derive(Serialize, Deserialize)]
+    struct FileSchema<'a> {
+        origin: OriginSchema<'a>,
+        suggestions: Vec<SuggestionSchema<'a>>,
+        /// The number of suggestions dropped by `truncate`.
+        #[serde(default)]
+        truncated: usize,
+        /// The number of chunks which were not checked.
+        #[serde(default)]
+        unchecked: usize,
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'s> Serialize for Suggestion<'s> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            SuggestionSchema::from(self).serialize(serializer)
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'s> Serialize for SuggestionSet<'s> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(
+                self.per_file
+                    .iter()
+                    .map(|(origin, suggestions)| FileSchema {
+                        origin: OriginSchema::from(origin),
+                        suggestions: suggestions.iter().map(SuggestionSchema::from).collect(),
+                        truncated: self.truncated(origin),
+                        unchecked: self.unchecked(origin),
+                    }),
+            )
+        }
+    }
+
+    /// Find the chunk of `documentation` the suggestion was found in.
+    fn resolve<'s>(
+        documentation: &'s Documentation,
+        schema: SuggestionSchema<'_>,
+    ) -> Result<Suggestion<'s>, String> {
+        let origin = ContentOrigin::from(schema.origin);
+        let span = Span::from(schema.span);
+        let range = schema.range;
+        let content = schema.chunk;
+        let candidates = documentation
+            .get(&origin)
+            .unwrap_or_default()
+            .iter()
+            .filter(|chunk| chunk.as_str() == content)
+            .collect::<Vec<_>>();
+        // identical chunks of one file are told apart by their location
+        let chunk = candidates
+            .iter()
+            .find(|chunk| {
+                chunk
+                    .find_spans(range.clone())
+                    .values()
+                    .any(|found| *found == span)
+            })
+            .or_else(|| candidates.first())
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "The chunk of the suggestion at {}:{} is not part of the documentation",
+                    origin.as_path().display(),
+                    span.start.line
+                )
+            })?;
+        let detector = schema
+            .detector
+            .parse::<Detector>()
+            .map_err(|e| e.to_string())?;
+        Ok(Suggestion {
+            detector,
+            severity: schema.severity,
+            category: schema.category,
+            rule: schema.rule.into_owned(),
+            origin,
+            chunk,
+            span,
+            range,
+            replacements: schema.replacements.into_owned(),
+            description: schema.description.map(Cow::into_owned),
+            confidence: schema.confidence,
+        })
+    }
+
+    /// Deserialize a single suggestion of a chunk of the documentation.
+    #[derive(Debug, Clone, Copy)]
+    pub struct SuggestionIn<'s>(pub &'s Documentation);
+
+    impl<'de, 's> DeserializeSeed<'de> for SuggestionIn<'s> {
+        type Value = Suggestion<'s>;
+        fn deserialize<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            let schema = SuggestionSchema::deserialize(deserializer)?;
+            resolve(self.0, schema).map_err(D::Error::custom)
+        }
+    }
+
+    /// Deserialize suggestions of the chunks of the documentation.
+    #[derive(Debug, Clone, Copy)]
+    pub struct SuggestionSetIn<'s>(pub &'s Documentation);
+
+    impl<'de, 's> DeserializeSeed<'de> for SuggestionSetIn<'s> {
+        type Value = SuggestionSet<'s>;
+        fn deserialize<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            let files = Vec::<FileSchema<'_>>::deserialize(deserializer)?;
+            let mut set = SuggestionSet::new();
+            for file in files {
+                let origin = ContentOrigin::from(file.origin);
+                let suggestions = file
+                    .suggestions
+                    .into_iter()
+                    .map(|schema| resolve(self.0, schema))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(D::Error::custom)?;
+                if file.truncated > 0 {
+                    set.truncated.insert(origin.clone(), file.truncated);
+                }
+                if file.unchecked > 0 {
+                    set.unchecked.insert(origin.clone(), file.unchecked);
+                }
+                set.extend(origin, suggestions);
+            }
+            Ok(set)
+        }
+    }
+
+    #[cfg(all(test, feature = "serde"))]
+    mod tests {
+        use super::*;
+        use std::path::PathBuf;
+
+        #[test]
+        fn roundtrip() {
+            let origin = ContentOrigin::RustSourceFile(PathBuf::from("/tmp/roundtrip.rs"));
+            let documentation = Documentation::load_from_str(
+                origin.clone(),
+                "/// A wrod.\nstruct A;\n\n/// A wrod.\nstruct B;\n",
+            )
+            .unwrap();
+            let chunks = documentation.get(&origin).unwrap();
+            let mut set = SuggestionSet::new();
+            for chunk in chunks {
+                let range = 3..7;
+                let span = *chunk.find_spans(range.clone()).values().next().unwrap();
+                set.add(
+                    origin.clone(),
+                    Suggestion {
+                        detector: Detector::Hunspell,
+                        severity: Severity::Error,
+                        category: Category::Spelling,
+                        rule: "unknown-word".to_owned(),
+                        origin: origin.clone(),
+                        chunk,
+                        span,
+                        range,
+                        replacements: vec!["word".to_owned()],
+                        description: None,
+                        confidence: Some(90),
+                    },
+                );
+            }
+
+            let json = serde_json::to_string(&set).unwrap();
+            let mut deserializer = serde_json::Deserializer::from_str(&json);
+            let deserialized = SuggestionSetIn(&documentation)
+                .deserialize(&mut deserializer)
+                .unwrap();
+            let suggestions = deserialized.iter().next().unwrap().1;
+            assert_eq!(suggestions, set.iter().next().unwrap().1);
+            // identical chunks are kept apart
+            assert!(std::ptr::eq(suggestions[1].chunk, &chunks[1]));
+
+            let other = Documentation::load_from_str(origin, "/// Other.\nstruct A;\n").unwrap();
+            let mut deserializer = serde_json::Deserializer::from_str(&json);
+            assert!(SuggestionSetIn(&other)
+                .deserialize(&mut deserializer)
+                .is_err());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -638,6 +1200,52 @@ mod tests {
     use console;
     use std::fmt;
 
+    #[test]
+    fn truncate() {
+        let chunk = CheckableChunk::from_str(
+            "laziest",
+            indexmap::indexmap! { 0..7 => Span {
+                start: LineColumn { line: 1, column: 4 },
+                end: LineColumn { line: 1, column: 10 },
+            }},
+            CommentVariant::TripleSlash,
+        );
+        let suggestion = Suggestion {
+            detector: Detector::Dummy,
+            severity: Severity::Error,
+            category: Category::Spelling,
+            rule: "dummy".to_owned(),
+            origin: ContentOrigin::TestEntityRust,
+            chunk: &chunk,
+            span: Span {
+                start: LineColumn { line: 1, column: 4 },
+                end: LineColumn {
+                    line: 1,
+                    column: 10,
+                },
+            },
+            range: 0..7,
+            replacements: vec![],
+            description: None,
+            confidence: None,
+        };
+        let mut set = SuggestionSet::new();
+        set.extend(ContentOrigin::TestEntityRust, vec![suggestion.clone(); 5]);
+        set.extend(ContentOrigin::TestEntityCommonMark, vec![suggestion; 2]);
+        set.truncate(2);
+        assert_eq!(set.total_count(), 4);
+        assert_eq!(set.truncated(&ContentOrigin::TestEntityRust), 3);
+        assert_eq!(set.truncated(&ContentOrigin::TestEntityCommonMark), 0);
+
+        let mut merged = SuggestionSet::new();
+        set.not_checked(ContentOrigin::TestEntityRust, 7);
+        merged.merge(set.clone());
+        merged.merge(set);
+        assert_eq!(merged.total_count(), 8);
+        assert_eq!(merged.truncated(&ContentOrigin::TestEntityRust), 6);
+        assert_eq!(merged.unchecked(&ContentOrigin::TestEntityRust), 14);
+    }
+
     /// A test helper comparing the output against an expected output.
     ///
     /// Strips all colour codes from both the expected string as well as the
@@ -673,6 +1281,9 @@ mod tests {
 
         let suggestion = Suggestion {
             detector: Detector::Dummy,
+            severity: Severity::Error,
+            category: Category::Spelling,
+            rule: "dummy".to_owned(),
             origin: ContentOrigin::TestEntityRust,
             chunk: &chunk,
             range: 7..12,
@@ -689,9 +1300,10 @@ mod tests {
                 "replacement_2".to_owned(),
             ],
             description: Some("Possible spelling mistake found.".to_owned()),
+            confidence: None,
         };
 
-        const EXPECTED: &'static str = r#"error: spellcheck(Dummy)
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy::dummy)
   --> /tmp/test/entity.rs:1
    |
  1 |  Is it dyrck again?
@@ -724,6 +1336,9 @@ mod tests {
 
         let suggestion = Suggestion {
             detector: Detector::Dummy,
+            severity: Severity::Error,
+            category: Category::Spelling,
+            rule: "dummy".to_owned(),
             origin: ContentOrigin::TestEntityRust,
             chunk: &chunk,
             range: 7..12,
@@ -736,9 +1351,10 @@ mod tests {
             },
             replacements: vec![],
             description: Some("Possible spelling mistake found.".to_owned()),
+            confidence: None,
         };
 
-        const EXPECTED: &'static str = r#"error: spellcheck(Dummy)
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy::dummy)
   --> /tmp/test/entity.rs:1
    |
  1 |  Is it dyrck again?
@@ -793,6 +1409,9 @@ mod tests {
 
         let suggestion = Suggestion {
             detector: Detector::Dummy,
+            severity: Severity::Error,
+            category: Category::Spelling,
+            rule: "dummy".to_owned(),
             origin: ContentOrigin::TestEntityRust,
             chunk: &chunk,
             range: 6..12,
@@ -812,9 +1431,10 @@ mod tests {
                 "replacement_2".to_owned(),
             ],
             description: Some("Possible spelling mistake found.".to_owned()),
+            confidence: None,
         };
 
-        const EXPECTED: &'static str = r#"error: spellcheck(Dummy)
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy::dummy)
   --> /tmp/test/entity.rs:1
    |
  1 |  Line mitake 1
@@ -862,6 +1482,9 @@ mod tests {
 
         let suggestion = Suggestion {
             detector: Detector::Dummy,
+            severity: Severity::Error,
+            category: Category::Spelling,
+            rule: "dummy".to_owned(),
             origin: ContentOrigin::TestEntityRust,
             chunk: &chunk,
             range: 66..94,
@@ -878,9 +1501,10 @@ mod tests {
                 "replacement_2".to_owned(),
             ],
             description: Some("Possible spelling mistake found.".to_owned()),
+            confidence: None,
         };
 
-        const EXPECTED: &'static str = r#"error: spellcheck(Dummy)
+        const EXPECTED: &'static str = r#"error: spellcheck(dummy::dummy)
   --> /tmp/test/entity.rs:2
    |
  2 | ..uuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuper duuu...uper too long
@@ -920,6 +1544,9 @@ mod tests {
 
         let suggestion = Suggestion {
             detector: Detector::Dummy,
+            severity: Severity::Error,
+            category: Category::Spelling,
+            rule: "dummy".to_owned(),
             origin: ContentOrigin::TestEntityRust,
             chunk: &chunk,
             span: Span {
@@ -935,6 +1562,7 @@ mod tests {
             range: 2..6,
             replacements: vec!["whocares".to_owned()],
             description: None,
+            confidence: None,
         };
 
         let suggestion = dbg!(suggestion);
@@ -0,0 +1,171 @@
+//! Wall time spent in the individual stages of a run, per origin.
+//!
+//! Only recorded if enabled with `--timings`, and reported once the run
+//! completed.
+
+use crate::Detector;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A stage of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Discovery of files, manifests and module declarations.
+    Traversal,
+    /// Extraction of the documentation chunks from a file.
+    Extraction,
+    /// Checking the chunks with a particular checker.
+    Check(Detector),
+    /// Applying fixes to a file.
+    Patching,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Traversal => formatter.write_str("traversal"),
+            Self::Extraction => formatter.write_str("extraction"),
+            Self::Check(detector) => write!(formatter, "check({})", detector),
+            Self::Patching => formatter.write_str("patching"),
+        }
+    }
+}
+
+/// Accumulated durations per stage and origin.
+#[derive(Debug, Default)]
+pub struct Timings {
+    per_origin: Mutex<IndexMap<(Stage, PathBuf), Duration>>,
+}
+
+impl Timings {
+    /// Add `duration` to the time spent in `stage` for `path`.
+    pub fn record(&self, stage: Stage, path: &Path, duration: Duration) {
+        let mut per_origin = self.per_origin.lock().expect("Lock is never poisoned. qed");
+        *per_origin
+            .entry((stage, path.to_owned()))
+            .or_insert_with(Duration::default) += duration;
+    }
+
+    /// Write the durations per origin, followed by the durations per stage.
+    pub fn report<W: Write>(&self, mut sink: W) -> Result<()> {
+        let per_origin = self.per_origin.lock().expect("Lock is never poisoned. qed");
+        let mut per_stage = IndexMap::<Stage, Duration>::new();
+        writeln!(sink, "Timings per origin:")?;
+        for ((stage, path), duration) in per_origin.iter() {
+            writeln!(
+                sink,
+                "  {:<24} {:>12} {}",
+                stage.to_string(),
+                format!("{:.3?}", duration),
+                path.display()
+            )?;
+            *per_stage.entry(*stage).or_insert_with(Duration::default) += *duration;
+        }
+        writeln!(sink, "Timings per stage:")?;
+        for (stage, duration) in per_stage.iter() {
+            writeln!(
+                sink,
+                "  {:<24} {:>12}",
+                stage.to_string(),
+                format!("{:.3?}", duration)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static::lazy_static! {
+    static ref TIMINGS: Timings = Timings::default();
+}
+
+/// Start recording for the remainder of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// If recording was enabled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Record `duration` for `stage` and `path`, if enabled.
+pub fn record(stage: Stage, path: &Path, duration: Duration) {
+    if is_enabled() {
+        TIMINGS.record(stage, path, duration);
+    }
+}
+
+/// Report all recorded timings.
+pub fn report<W: Write>(sink: W) -> Result<()> {
+    TIMINGS.report(sink)
+}
+
+/// Records the time until dropped.
+pub struct Timer {
+    stage: Stage,
+    path: PathBuf,
+    start: Instant,
+}
+
+impl Timer {
+    /// Start measuring `stage` for `path`, `None` if recording is disabled.
+    pub fn start(stage: Stage, path: &Path) -> Option<Self> {
+        if is_enabled() {
+            Some(Self {
+                stage,
+                path: path.to_owned(),
+                start: Instant::now(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        TIMINGS.record(self.stage, &self.path, self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let timings = Timings::default();
+        let lib = Path::new("src/lib.rs");
+        let main = Path::new("src/main.rs");
+        timings.record(Stage::Extraction, lib, Duration::from_millis(2));
+        timings.record(Stage::Check(Detector::Dummy), lib, Duration::from_millis(5));
+        timings.record(Stage::Check(Detector::Dummy), lib, Duration::from_millis(5));
+        timings.record(
+            Stage::Check(Detector::Dummy),
+            main,
+            Duration::from_millis(1),
+        );
+
+        let mut sink = Vec::new();
+        timings.report(&mut sink).unwrap();
+        let report = String::from_utf8(sink).unwrap();
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "Timings per origin:");
+        assert!(lines[2].starts_with("  check(Dummy) "));
+        assert!(lines[2].contains("10.000ms"));
+        assert!(lines[2].ends_with("src/lib.rs"));
+        assert_eq!(lines[4], "Timings per stage:");
+        assert!(lines[6].contains("11.000ms"));
+    }
+}
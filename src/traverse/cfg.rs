@@ -0,0 +1,125 @@
+//! Evaluate `#[cfg(..)]` attributes of module declarations, to allow
+//! skipping modules which are only compiled for i.e. a particular platform.
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+
+/// Outcome of an evaluation, where only excluded predicates are known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tristate {
+    False,
+    Unknown,
+    True,
+}
+
+impl std::ops::Not for Tristate {
+    type Output = Self;
+    fn not(self) -> Self {
+        match self {
+            Self::False => Self::True,
+            Self::Unknown => Self::Unknown,
+            Self::True => Self::False,
+        }
+    }
+}
+
+/// Normalize a predicate, i.e. `feature = "x"` and `feature="x"` are the same.
+pub(crate) fn normalize(predicate: &str) -> String {
+    predicate.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Split the content of `all(..)` or `any(..)` at the commas.
+fn split_args(stream: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut args = vec![Vec::new()];
+    for tree in stream {
+        match tree {
+            TokenTree::Punct(ref punct) if punct.as_char() == ',' => args.push(Vec::new()),
+            tree => args
+                .last_mut()
+                .expect("Always has one item. qed")
+                .push(tree),
+        }
+    }
+    args.retain(|arg| !arg.is_empty());
+    args
+}
+
+fn evaluate(predicate: &[TokenTree], excluded: &[String]) -> Tristate {
+    match predicate {
+        [TokenTree::Ident(ident), TokenTree::Group(group)]
+            if group.delimiter() == Delimiter::Parenthesis =>
+        {
+            let args = split_args(group.stream());
+            let mut results = args.iter().map(|arg| evaluate(arg, excluded));
+            match ident.to_string().as_str() {
+                "all" => results.fold(Tristate::True, |acc, x| match (acc, x) {
+                    (Tristate::False, _) | (_, Tristate::False) => Tristate::False,
+                    (Tristate::Unknown, _) | (_, Tristate::Unknown) => Tristate::Unknown,
+                    _ => Tristate::True,
+                }),
+                "any" => results.fold(Tristate::False, |acc, x| match (acc, x) {
+                    (Tristate::True, _) | (_, Tristate::True) => Tristate::True,
+                    (Tristate::Unknown, _) | (_, Tristate::Unknown) => Tristate::Unknown,
+                    _ => Tristate::False,
+                }),
+                "not" if args.len() == 1 => !results.next().unwrap_or(Tristate::Unknown),
+                _ => Tristate::Unknown,
+            }
+        }
+        atom => {
+            let atom = normalize(&atom.iter().cloned().collect::<TokenStream>().to_string());
+            if excluded.contains(&atom) {
+                Tristate::False
+            } else {
+                Tristate::Unknown
+            }
+        }
+    }
+}
+
+/// Check if the content of a `#[cfg(..)]` attribute, the part within the
+/// parenthesis, can never be true when all `excluded` predicates are false.
+///
+/// `excluded` must be normalized.
+pub(crate) fn is_excluded(cfg: TokenStream, excluded: &[String]) -> bool {
+    if excluded.is_empty() {
+        return false;
+    }
+    let predicate = cfg.into_iter().collect::<Vec<_>>();
+    evaluate(&predicate, excluded) == Tristate::False
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excluded(cfg: &str, excluded: &[&str]) -> bool {
+        let excluded = excluded
+            .iter()
+            .map(|predicate| normalize(predicate))
+            .collect::<Vec<_>>();
+        is_excluded(syn::parse_str(cfg).unwrap(), &excluded)
+    }
+
+    #[test]
+    fn evaluation() {
+        assert!(excluded(
+            r#"target_os = "windows""#,
+            &[r#"target_os="windows""#]
+        ));
+        assert!(excluded(
+            r#"feature = "nightly""#,
+            &[r#"feature = "nightly""#]
+        ));
+        assert!(!excluded(
+            r#"feature = "stable""#,
+            &[r#"feature = "nightly""#]
+        ));
+        assert!(excluded("windows", &["windows"]));
+        assert!(!excluded("not(windows)", &["windows"]));
+        assert!(!excluded("any(unix, windows)", &["windows"]));
+        assert!(excluded("any(macos, windows)", &["windows", "macos"]));
+        assert!(excluded("all(unix, windows)", &["windows"]));
+        assert!(excluded("not(not(windows))", &["windows"]));
+        assert!(!excluded("windows", &[]));
+    }
+}
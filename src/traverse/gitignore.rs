@@ -0,0 +1,104 @@
+//! Honor `.gitignore` and `.ignore` files of the checked paths.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+
+use log::trace;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File names of ignore files, in order of precedence within one directory.
+const IGNORE_FILE_NAMES: &[&str] = &[".ignore", ".gitignore"];
+
+/// Lazily loads and caches the ignore files of all parent directories
+/// up to the root of the repository.
+#[derive(Debug, Default)]
+pub struct IgnoreFiles {
+    per_dir: Mutex<HashMap<PathBuf, Vec<Gitignore>>>,
+}
+
+impl IgnoreFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all ignore files in `dir`, which also covers `.git/info/exclude`
+    /// for the root of a repository.
+    fn load(dir: &Path) -> Vec<Gitignore> {
+        let mut candidates = IGNORE_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .collect::<Vec<_>>();
+        candidates.push(dir.join(".git").join("info").join("exclude"));
+        candidates
+            .into_iter()
+            .filter(|path| path.is_file())
+            .filter_map(|path| {
+                // patterns are always relative to `dir`, which is the repository
+                // root in case of `.git/info/exclude`
+                let mut builder = GitignoreBuilder::new(dir);
+                if let Some(err) = builder.add(&path) {
+                    log::warn!("Failed to parse ignore file {}: {}", path.display(), err);
+                }
+                builder.build().ok()
+            })
+            .collect()
+    }
+
+    /// Check if `path` is ignored by any of the ignore files in its parent
+    /// directories, where the closest one takes precedence.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        let mut per_dir = self.per_dir.lock().expect("Lock is never poisoned. qed");
+        for dir in path.ancestors().skip(1) {
+            let matchers = per_dir
+                .entry(dir.to_owned())
+                .or_insert_with(|| Self::load(dir));
+            for matcher in matchers.iter() {
+                match matcher.matched_path_or_any_parents(path, is_dir) {
+                    Match::Ignore(glob) => {
+                        trace!(
+                            "Ignored {} due to {:?} in {}",
+                            path.display(),
+                            glob.original(),
+                            dir.display()
+                        );
+                        return true;
+                    }
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            // ignore files outside of the repository do not apply
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_precedence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let nested = root.join("nested");
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.generated.rs\n").unwrap();
+        std::fs::write(nested.join(".ignore"), "!keep.generated.rs\n").unwrap();
+
+        let ignore_files = IgnoreFiles::new();
+        assert!(ignore_files.is_ignored(&root.join("target").join("debug").join("out.rs")));
+        assert!(ignore_files.is_ignored(&root.join("bindings.generated.rs")));
+        assert!(ignore_files.is_ignored(&nested.join("other.generated.rs")));
+        assert!(!ignore_files.is_ignored(&nested.join("keep.generated.rs")));
+        assert!(!ignore_files.is_ignored(&root.join("lib.rs")));
+    }
+}
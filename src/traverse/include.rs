@@ -0,0 +1,263 @@
+//! Resolve the path of `include!(..)` invocations, to check the doc comments
+//! of the included files as well.
+//!
+//! Besides a plain string literal, `concat!(..)` of string literals and
+//! `env!(..)` is understood. Since `OUT_DIR` is only set while building,
+//! `env!("OUT_DIR")` is looked up in the build directories of the package
+//! within the target directory, the most recently built one wins.
+
+use super::{load_manifest, owning_manifest};
+
+use log::{debug, trace, warn};
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+
+use std::path::{Path, PathBuf};
+
+/// A single argument of `concat!(..)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    /// A string literal.
+    Literal(String),
+    /// The value of the environment variable, `env!(..)`.
+    Env(String),
+}
+
+fn string_literal(tree: &TokenTree) -> Option<String> {
+    syn::parse2::<syn::LitStr>(TokenStream::from(tree.clone()))
+        .ok()
+        .map(|literal| literal.value())
+}
+
+/// The argument of a macro invocation `name!(..)`.
+fn macro_args<'a>(trees: &'a [TokenTree], name: &str) -> Option<&'a proc_macro2::Group> {
+    match trees {
+        [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Group(group)]
+            if ident == name
+                && punct.as_char() == '!'
+                && group.delimiter() == Delimiter::Parenthesis =>
+        {
+            Some(group)
+        }
+        _ => None,
+    }
+}
+
+/// A single part, a string literal or `env!("VAR")`.
+fn part(trees: &[TokenTree]) -> Option<Part> {
+    if let [literal] = trees {
+        return string_literal(literal).map(Part::Literal);
+    }
+    let args = macro_args(trees, "env")?
+        .stream()
+        .into_iter()
+        .collect::<Vec<_>>();
+    match args.as_slice() {
+        [var] => string_literal(var).map(Part::Env),
+        _ => None,
+    }
+}
+
+/// The parts of the argument of `include!(..)`, `None` if anything but
+/// string literals, `concat!(..)` and `env!(..)` is used.
+fn parts(args: TokenStream) -> Option<Vec<Part>> {
+    let args = args.into_iter().collect::<Vec<_>>();
+    if let Some(concat) = macro_args(args.as_slice(), "concat") {
+        let items = concat.stream().into_iter().collect::<Vec<_>>();
+        items
+            .split(|tree| matches!(tree, TokenTree::Punct(punct) if punct.as_char() == ','))
+            .filter(|item| !item.is_empty())
+            .map(part)
+            .collect()
+    } else {
+        part(args.as_slice()).map(|part| vec![part])
+    }
+}
+
+/// The target directory the package of `manifest_dir` is built in.
+fn target_dir(manifest_dir: &Path) -> Option<PathBuf> {
+    if let Some(target_dir) = std::env::var_os("CARGO_TARGET_DIR") {
+        return Some(PathBuf::from(target_dir));
+    }
+    // the target directory of a workspace member is the one of the workspace
+    manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("target"))
+        .find(|dir| dir.is_dir())
+}
+
+/// If `dir_name` is the name of a build directory of `package`, which is the
+/// package name and a hash of 16 hex digits, i.e. `foo-0123456789abcdef`.
+///
+/// Build directories of packages with the same prefix, like `foo-bar`, never
+/// match.
+fn is_build_dir_of(dir_name: &str, package: &str) -> bool {
+    match dir_name
+        .strip_prefix(package)
+        .and_then(|rest| rest.strip_prefix('-'))
+    {
+        Some(hash) => hash.len() == 16 && hash.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')),
+        None => false,
+    }
+}
+
+/// All `OUT_DIR`s of the package which owns `source`, one per profile and
+/// target triple it was built for.
+fn out_dirs(source: &Path) -> Vec<PathBuf> {
+    if let Some(out_dir) = std::env::var_os("OUT_DIR") {
+        return vec![PathBuf::from(out_dir)];
+    }
+    let manifest_dir = match owning_manifest(source).as_deref().and_then(Path::parent) {
+        Some(manifest_dir) => manifest_dir.to_owned(),
+        None => return Vec::new(),
+    };
+    let name = match load_manifest(&manifest_dir)
+        .ok()
+        .and_then(|manifest| manifest.package)
+    {
+        Some(package) => package.name,
+        None => return Vec::new(),
+    };
+    let target_dir = match target_dir(&manifest_dir) {
+        Some(target_dir) => target_dir,
+        None => return Vec::new(),
+    };
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    // `target/<profile>/build` and `target/<triple>/<profile>/build`
+    let profiles = subdirs(&target_dir)
+        .into_iter()
+        .flat_map(|dir| std::iter::once(dir.clone()).chain(subdirs(&dir)))
+        .collect::<Vec<_>>();
+    profiles
+        .iter()
+        .flat_map(|profile| subdirs(&profile.join("build")))
+        .filter(|build| {
+            build
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |dir_name| is_build_dir_of(dir_name, &name))
+        })
+        .map(|build| build.join("out"))
+        .filter(|out| out.is_dir())
+        .collect()
+}
+
+/// Join `parts` with all environment variables replaced, `OUT_DIR` by
+/// `out_dir`.
+fn evaluate(parts: &[Part], source: &Path, out_dir: Option<&Path>) -> Option<String> {
+    let mut evaluated = String::new();
+    for part in parts {
+        match part {
+            Part::Literal(literal) => evaluated.push_str(literal),
+            Part::Env(var) if var == "OUT_DIR" => {
+                evaluated.push_str(out_dir?.to_str()?);
+            }
+            Part::Env(var) if var == "CARGO_MANIFEST_DIR" => {
+                let manifest = owning_manifest(source)?;
+                evaluated.push_str(manifest.parent()?.to_str()?);
+            }
+            Part::Env(var) => match std::env::var(var) {
+                Ok(value) => evaluated.push_str(value.as_str()),
+                Err(_) => {
+                    debug!("Environment variable {} of `include!` is not set", var);
+                    return None;
+                }
+            },
+        }
+    }
+    Some(evaluated)
+}
+
+/// The file included by `include!(args)` within `source`, where relative
+/// paths are relative to `base`.
+///
+/// Warns if the included file does not exist.
+pub(crate) fn resolve(args: TokenStream, base: &Path, source: &Path) -> Option<PathBuf> {
+    let parts = match parts(args) {
+        Some(parts) => parts,
+        None => {
+            trace!("Ignoring `include!` which is not made of literals and `env!`");
+            return None;
+        }
+    };
+    let out_dir = Part::Env("OUT_DIR".to_owned());
+    if !parts.contains(&out_dir) {
+        let included = base.join(evaluate(parts.as_slice(), source, None)?);
+        if included.is_file() {
+            return Some(included);
+        }
+        warn!("Included file {} does not exist", included.display());
+        return None;
+    }
+    let newest = out_dirs(source)
+        .into_iter()
+        .filter_map(|dir| evaluate(parts.as_slice(), source, Some(dir.as_path())))
+        .map(|included| base.join(included))
+        .filter(|included| included.is_file())
+        .max_by_key(|included| {
+            included
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        });
+    if newest.is_none() {
+        let suffix = parts
+            .iter()
+            .skip_while(|part| **part != out_dir)
+            .filter_map(|part| match part {
+                Part::Literal(literal) => Some(literal.as_str()),
+                Part::Env(_) => None,
+            })
+            .collect::<String>();
+        warn!(
+            "Included file $OUT_DIR{} of {} does not exist, build the package first",
+            suffix,
+            source.display()
+        );
+    }
+    newest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(args: &str) -> Option<Vec<Part>> {
+        parts(syn::parse_str::<TokenStream>(args).unwrap())
+    }
+
+    #[test]
+    fn arguments() {
+        assert_eq!(
+            parsed(r#""generated.rs""#),
+            Some(vec![Part::Literal("generated.rs".to_owned())])
+        );
+        assert_eq!(
+            parsed(r#"concat!(env!("OUT_DIR"), "/bindings.rs")"#),
+            Some(vec![
+                Part::Env("OUT_DIR".to_owned()),
+                Part::Literal("/bindings.rs".to_owned())
+            ])
+        );
+        assert_eq!(parsed(r#"concat!(env!("OUT_DIR"), file!())"#), None);
+        assert_eq!(parsed("PATH"), None);
+    }
+
+    #[test]
+    fn build_dirs() {
+        assert!(is_build_dir_of("foo-0123456789abcdef", "foo"));
+        assert!(is_build_dir_of("foo-bar-0123456789abcdef", "foo-bar"));
+        assert!(!is_build_dir_of("foo-bar-0123456789abcdef", "foo"));
+        assert!(!is_build_dir_of("foo-0123456789ABCDEF", "foo"));
+        assert!(!is_build_dir_of("foo-0123456789abcde", "foo"));
+        assert!(!is_build_dir_of("foo", "foo"));
+    }
+}
@@ -1,9 +1,8 @@
 use super::*;
-use crate::Documentation;
 
-use std::fs;
+use std::collections::HashSet;
 
-use log::{trace, warn};
+use log::{debug, trace, warn};
 
 use std::path::{Path, PathBuf};
 
@@ -16,6 +15,16 @@ pub struct TraverseModulesIter {
     queue: VecDeque<(PathBuf, usize)>,
     /// zero limits to the provided path, if it is a directory, all children are collected
     max_depth: usize,
+    /// follow symlinked files and directories, otherwise they are skipped
+    follow_symlinks: bool,
+    /// canonical paths of all yielded files, to break cycles introduced by symlinks
+    visited: HashSet<PathBuf>,
+    /// normalized cfg predicates, modules which require any of them are not followed
+    exclude_cfg: Vec<String>,
+    /// only follow modules declared `pub`
+    only_public: bool,
+    /// find the modules by scanning the lines of the files instead of parsing them
+    fast: bool,
 }
 
 impl Default for TraverseModulesIter {
@@ -23,18 +32,36 @@ impl Default for TraverseModulesIter {
         Self {
             max_depth: usize::MAX,
             queue: VecDeque::with_capacity(128),
+            follow_symlinks: false,
+            visited: HashSet::with_capacity(128),
+            exclude_cfg: Vec::new(),
+            only_public: false,
+            fast: false,
         }
     }
 }
 
+/// Check if resolving `path` involves a symlink, either the file itself
+/// or any of the directories leading up to it.
+///
+/// Module paths are joined onto canonical directories, so only the
+/// components added by the module declarations can be symlinks.
+fn is_symlinked(path: &Path) -> bool {
+    path.ancestors().any(|ancestor| {
+        ancestor
+            .symlink_metadata()
+            .map(|meta| meta.file_type().is_symlink())
+            .unwrap_or(false)
+    })
+}
+
 impl TraverseModulesIter {
     fn add_initial_path<P>(&mut self, path: P, level: usize) -> Result<()>
     where
         P: AsRef<Path>,
     {
         let path = path.as_ref();
-        let path = path
-            .canonicalize()
+        let path = crate::util::canonicalize(path)
             .map_err(|e| anyhow!("Failed to canonicalize path {}", path.display()).context(e))?;
         let meta = path.metadata().map_err(|e| {
             anyhow!("Failed to obtain meta data for path {}", path.display()).context(e)
@@ -45,6 +72,7 @@ impl TraverseModulesIter {
             walkdir::WalkDir::new(path)
                 .max_depth(1)
                 .same_file_system(true)
+                .follow_links(self.follow_symlinks)
                 .into_iter()
                 .filter_map(|entry| {
                     entry
@@ -80,9 +108,17 @@ impl TraverseModulesIter {
         Ok(me)
     }
 
-    pub fn with_depth_limit<P: AsRef<Path>>(path: P, max_depth: usize) -> Result<Self> {
+    /// Create a new iterator, which only follows symlinks if `follow_symlinks` is set.
+    ///
+    /// Explicitly provided paths are always resolved.
+    pub fn with_options<P: AsRef<Path>>(
+        path: P,
+        max_depth: usize,
+        follow_symlinks: bool,
+    ) -> Result<Self> {
         let mut me = Self {
             max_depth,
+            follow_symlinks,
             ..Default::default()
         };
         me.add_initial_path(path, 0)?;
@@ -92,17 +128,47 @@ impl TraverseModulesIter {
     /// Create a new path with (almost) infinite depth bounds
     #[allow(unused)]
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
-        Self::with_depth_limit(path, usize::MAX)
+        Self::with_options(path, usize::MAX, true)
+    }
+
+    /// Do not follow modules which are only compiled if any of the given
+    /// cfg predicates, i.e. `target_os = "windows"`, is true.
+    pub fn exclude_cfg<I, S>(mut self, predicates: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.exclude_cfg = predicates
+            .into_iter()
+            .map(|predicate| cfg::normalize(predicate.as_ref()))
+            .collect();
+        self
+    }
+
+    /// Only follow modules which are declared `pub`, so are part of the
+    /// public api if their parent is.
+    pub fn only_public(mut self, only_public: bool) -> Self {
+        self.only_public = only_public;
+        self
+    }
+
+    /// Find the modules by scanning the lines of each file instead of parsing
+    /// it, which ignores cfg attributes and visibility.
+    pub fn fast(mut self, fast: bool) -> Self {
+        self.fast = fast;
+        self
     }
 
     pub fn collect_modules(&mut self, path: &Path, level: usize) -> Result<()> {
         if path.is_file() {
             trace!("collecting mods declared in file {}", path.display());
-            self.queue.extend(
-                extract_modules_from_file(path)?
-                    .into_iter()
-                    .map(|item| (item, level)),
-            );
+            let modules = if self.fast {
+                scan_modules_from_file(path)?
+            } else {
+                extract_modules_from_file(path, &self.exclude_cfg, self.only_public)?
+            };
+            self.queue
+                .extend(modules.into_iter().map(|item| (item, level)));
         } else {
             warn!("Only dealing with files, dropping {}", path.display());
         }
@@ -113,38 +179,46 @@ impl TraverseModulesIter {
 impl Iterator for TraverseModulesIter {
     type Item = PathBuf;
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some((path, level)) = self.queue.pop_front() {
+        while let Some((path, level)) = self.queue.pop_front() {
+            // initial paths are canonical, so only module paths can be symlinked
+            if level > 0 && !self.follow_symlinks && is_symlinked(path.as_path()) {
+                debug!("Not following symlinked module {}", path.display());
+                continue;
+            }
+            let canonical = crate::util::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            if !self.visited.insert(canonical) {
+                debug!("Already visited {}, skipping", path.display());
+                continue;
+            }
             if level < self.max_depth {
                 // ignore the error here, there is nothing we can do really
                 // TODO potentially consider returning a result covering this
                 let _ = self.collect_modules(path.as_path(), level + 1);
             }
-            Some(path)
-        } else {
-            None
+            return Some(path);
         }
+        None
     }
 }
 
-/// traverse path with a depth limit, if the path is a directory all its children will be collected
+/// traverse path, if the path is a directory all its children will be collected
 /// instead
-pub(crate) fn traverse(path: &Path) -> Result<impl Iterator<Item = Documentation>> {
-    traverse_with_depth_limit(path, usize::MAX)
-}
-
-/// traverse path with a depth limit, if the path is a directory all its children will be collected
-/// as depth 0 instead
-pub(crate) fn traverse_with_depth_limit(
+///
+/// Only files for which `filter` returns `true` are yielded, modules declared within the others
+/// are still followed.
+pub(crate) fn traverse<F>(
     path: &Path,
-    max_depth: usize,
-) -> Result<impl Iterator<Item = Documentation>> {
-    let it = TraverseModulesIter::with_depth_limit(path, max_depth)?
-        .filter_map(|path: PathBuf| -> Option<Documentation> {
-            fs::read_to_string(&path).ok().map(|content| {
-                Documentation::from((ContentOrigin::RustSourceFile(path), content.as_str()))
-            })
-        })
-        .filter(|documentation| !documentation.is_empty());
+    config: &Config,
+    filter: F,
+) -> Result<impl Iterator<Item = PathBuf>>
+where
+    F: Fn(&Path) -> bool,
+{
+    let it = TraverseModulesIter::with_options(path, usize::MAX, config.follow_symlinks)?
+        .exclude_cfg(config.exclude_cfg.iter())
+        .only_public(config.only_public)
+        .fast(config.fast_extraction)
+        .filter(move |path: &PathBuf| filter(path.as_path()));
     Ok(it)
 }
 
@@ -194,4 +268,35 @@ mod tests {
 
         assert_eq!(found.len(), expect.len());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("real")).unwrap();
+        fs::write(root.join("lib.rs"), "mod a;\nmod b;\n").unwrap();
+        fs::write(root.join("real").join("a.rs"), "//! A\n").unwrap();
+        symlink(root.join("real").join("a.rs"), root.join("a.rs")).unwrap();
+        // cycle back to the entry file
+        symlink(root.join("lib.rs"), root.join("b.rs")).unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let found = TraverseModulesIter::with_options(root.join("lib.rs"), usize::MAX, true)
+            .expect("Must succeed to traverse file tree.")
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(found, vec![root.join("lib.rs"), root.join("a.rs")]);
+
+        let found = TraverseModulesIter::with_options(root.join("lib.rs"), usize::MAX, false)
+            .expect("Must succeed to traverse file tree.")
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(found, vec![root.join("lib.rs")]);
+
+        // neither relative components nor the canonical prefix are symlinks
+        assert!(!is_symlinked(&root.join(".").join("lib.rs")));
+        assert!(!is_symlinked(Path::new("./Cargo.toml")));
+        assert!(is_symlinked(&root.join("a.rs")));
+    }
 }
@@ -0,0 +1,190 @@
+//! Discover the packages of a workspace and their targets with `cargo metadata`.
+//!
+//! Cargo resolves the member globs, the `exclude` list, virtual workspaces
+//! and the inferred or renamed target paths exactly as it does when building.
+//! If cargo is not available or fails, i.e. for a manifest with errors, the
+//! manifest is parsed instead, see `handle_manifest`.
+
+use super::{CheckEntity, PackageSelection};
+use crate::config::TargetsConfig;
+
+use anyhow::{anyhow, bail, Result};
+use log::{debug, trace, warn};
+use serde::Deserialize;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The output of `cargo metadata --no-deps`, only the workspace members.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Metadata {
+    packages: Vec<Package>,
+    workspace_root: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    name: String,
+    manifest_path: PathBuf,
+    #[serde(default)]
+    readme: Option<PathBuf>,
+    #[serde(default)]
+    description: Option<String>,
+    targets: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Target {
+    kind: Vec<String>,
+    src_path: PathBuf,
+    #[serde(default)]
+    doctest: bool,
+}
+
+impl Target {
+    fn is_checked(&self, targets: &TargetsConfig) -> bool {
+        let is = |kind: &str| self.kind.iter().any(|x| x == kind);
+        if is("bin") {
+            true
+        } else if is("test") {
+            targets.tests
+        } else if is("example") {
+            targets.examples
+        } else if is("bench") {
+            targets.benches
+        } else if is("custom-build") {
+            targets.build
+        } else if is("lib") || is("rlib") || is("proc-macro") {
+            self.doctest
+        } else {
+            // cargo never doctests the other library kinds, i.e. `staticlib`
+            // or `cdylib`, so `doctest = false` does not tell anything
+            true
+        }
+    }
+}
+
+impl Package {
+    fn dir(&self) -> &Path {
+        self.manifest_path.parent().unwrap_or(&self.manifest_path)
+    }
+
+    /// The root files of the targets, tests, examples, benches and the build
+    /// script only if enabled in `targets`.
+    fn products(&self, targets: &TargetsConfig) -> Vec<CheckEntity> {
+        self.targets
+            .iter()
+            .filter(|target| target.is_checked(targets))
+            .filter(|target| target.src_path.is_file())
+            .map(|target| CheckEntity::Source(target.src_path.clone(), true))
+            .collect()
+    }
+
+    fn readme(&self) -> Vec<CheckEntity> {
+        let mut acc = Vec::with_capacity(2);
+        if let Some(ref readme) = self.readme {
+            let readme = self.dir().join(readme);
+            if readme.is_file() {
+                acc.push(CheckEntity::Markdown(readme));
+            } else {
+                warn!(
+                    "README.md defined in Cargo.toml {} is not a file",
+                    readme.display()
+                );
+            }
+        }
+        if let Some(ref description) = self.description {
+            acc.push(CheckEntity::ManifestDescription(description.clone()));
+        }
+        acc
+    }
+}
+
+/// Run `cargo metadata` for the manifest in `manifest_dir`.
+pub(crate) fn load(manifest_dir: &Path) -> Result<Metadata> {
+    let manifest = manifest_dir.join("Cargo.toml");
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .args(&[
+            "metadata",
+            "--format-version",
+            "1",
+            "--no-deps",
+            "--offline",
+        ])
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .output()
+        .map_err(|e| anyhow!("Failed to run cargo metadata").context(e))?;
+    if !output.status.success() {
+        bail!(
+            "cargo metadata failed for {}: {}",
+            manifest.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| anyhow!("Failed to parse the metadata of {}", manifest.display()).context(e))
+}
+
+/// All files of the packages of `metadata` which are selected.
+///
+/// The members of the workspace are only considered if `manifest_dir` is the
+/// root of the workspace, like a manifest without a `[workspace]` section.
+pub(crate) fn handle_metadata(
+    metadata: Metadata,
+    manifest_dir: &Path,
+    skip_readme: bool,
+    selection: &PackageSelection,
+    targets: &TargetsConfig,
+) -> Result<Vec<CheckEntity>> {
+    let same_dir = |dir: &Path| {
+        crate::util::canonicalize(dir)
+            .map(|dir| crate::util::same_path(&dir, manifest_dir))
+            .unwrap_or(false)
+    };
+    let is_workspace_root = same_dir(&metadata.workspace_root);
+
+    let mut found = Vec::with_capacity(8);
+    let mut acc = Vec::with_capacity(32);
+    for package in metadata.packages.iter() {
+        let with_readme = if same_dir(package.dir()) {
+            if !selection.selects(Some(package.name.as_str())) {
+                continue;
+            }
+            true
+        } else if is_workspace_root {
+            match selection.selects_member(Some(package.name.as_str())) {
+                Some(with_readme) => with_readme,
+                None => {
+                    trace!("Workspace member {} is not selected", package.name);
+                    continue;
+                }
+            }
+        } else {
+            continue;
+        };
+        debug!(
+            "Handling package {} of {}",
+            package.name,
+            package.manifest_path.display()
+        );
+        found.push(package.name.clone());
+        acc.extend(package.products(targets));
+        if with_readme && !skip_readme {
+            acc.extend(package.readme());
+        }
+    }
+
+    if let PackageSelection::Packages(names) = selection {
+        if let Some(name) = names.iter().find(|name| !found.contains(name)) {
+            bail!(
+                "Package `{}` is not part of the manifest {}",
+                name,
+                manifest_dir.display()
+            );
+        }
+    }
+    trace!("manifest products {:?}", &acc);
+    Ok(acc)
+}
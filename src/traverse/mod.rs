@@ -3,15 +3,18 @@
 //! Essentially collects all `Chunk`s used for parsing with an associated `Origin`.
 
 use super::*;
+use crate::config::TargetsConfig;
+use crate::timings::{Stage, Timer};
 use crate::Documentation;
 
 use anyhow::{anyhow, bail, Error, Result};
 use log::{debug, trace, warn};
 
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-pub(crate) fn cwd() -> Result<PathBuf> {
+pub fn cwd() -> Result<PathBuf> {
     std::env::current_dir().map_err(|_e| anyhow::anyhow!("Missing cwd!"))
 }
 
@@ -25,11 +28,30 @@ use std::collections::VecDeque;
 mod iter;
 pub use iter::*;
 
-use proc_macro2::Spacing;
+mod gitignore;
+pub use gitignore::*;
+
+mod staged;
+pub use staged::*;
+
+mod cfg;
+mod include;
+mod metadata;
+
 use proc_macro2::TokenStream;
 use proc_macro2::TokenTree;
+use proc_macro2::{Delimiter, Spacing};
+
+/// The approximate size in bytes of the chunks of a large common mark file.
+const LARGE_FILE_CHUNK_SIZE: usize = 64 << 10;
 
-fn extract_modules_inner<P: AsRef<Path>>(path: P, stream: TokenStream) -> Result<Vec<PathBuf>> {
+fn extract_modules_inner<P: AsRef<Path>>(
+    path: P,
+    source: &Path,
+    stream: TokenStream,
+    exclude_cfg: &[String],
+    only_public: bool,
+) -> Result<Vec<PathBuf>> {
     let path: &Path = path.as_ref();
 
     // Ident {
@@ -58,83 +80,366 @@ fn extract_modules_inner<P: AsRef<Path>>(path: P, stream: TokenStream) -> Result
         ModulKeyword,
         ModulName,
         ModulFin(String),
+        /// Content of an attribute `#[..]` or `#![..]`.
+        AttributeContent,
+        IncludeBang,
+        IncludeArgs,
+    }
+
+    /// Extract the content of `cfg(..)`.
+    fn cfg_attribute(stream: TokenStream) -> Option<TokenStream> {
+        let mut iter = stream.into_iter();
+        match (iter.next(), iter.next(), iter.next()) {
+            (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(group)), None)
+                if ident == "cfg" && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                Some(group.stream())
+            }
+            _ => None,
+        }
+    }
+
+    /// Extract the literal from `path = "other/file.rs"`.
+    fn path_attribute(stream: TokenStream) -> Option<PathBuf> {
+        let mut iter = stream.into_iter();
+        match (iter.next(), iter.next(), iter.next(), iter.next()) {
+            (Some(TokenTree::Ident(ident)), Some(TokenTree::Punct(punct)), Some(literal), None)
+                if ident == "path" && punct.as_char() == '=' =>
+            {
+                string_literal(literal)
+            }
+            _ => None,
+        }
+    }
+
+    /// Only plain string literals are supported.
+    fn string_literal(tree: TokenTree) -> Option<PathBuf> {
+        syn::parse2::<syn::LitStr>(TokenStream::from(tree))
+            .ok()
+            .map(|literal| PathBuf::from(literal.value()))
     }
 
     let mut acc = Vec::with_capacity(16);
     let mut state = SeekingFor::ModulKeyword;
+    // a `#[path = ".."]` attribute applies to the next `mod` declaration
+    let mut path_attr: Option<PathBuf> = None;
+    // as do all `#[cfg(..)]` attributes
+    let mut cfg_attrs: Vec<TokenStream> = Vec::new();
+    // the upcoming declaration is `pub`, but not `pub(crate)` or alike
+    let mut public = false;
     for tree in stream {
-        match tree {
-            TokenTree::Ident(ident) => match state {
-                SeekingFor::ModulKeyword => {
-                    if ident == "mod" {
-                        state = SeekingFor::ModulName;
-                    }
+        state = match (state, tree) {
+            (SeekingFor::ModulKeyword, TokenTree::Ident(ident)) if ident == "mod" => {
+                SeekingFor::ModulName
+            }
+            (SeekingFor::ModulKeyword, TokenTree::Ident(ident)) if ident == "pub" => {
+                public = true;
+                SeekingFor::ModulKeyword
+            }
+            (SeekingFor::ModulKeyword, TokenTree::Group(group))
+                if public && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                public = false;
+                SeekingFor::ModulKeyword
+            }
+            (SeekingFor::ModulKeyword, TokenTree::Ident(ident)) if ident == "include" => {
+                SeekingFor::IncludeBang
+            }
+            (SeekingFor::ModulKeyword, TokenTree::Ident(_ident)) => {
+                public = false;
+                SeekingFor::ModulKeyword
+            }
+            (SeekingFor::ModulName, TokenTree::Ident(ident)) => {
+                SeekingFor::ModulFin(ident.to_string())
+            }
+            (SeekingFor::ModulFin(mod_name), TokenTree::Punct(punct))
+                if punct.as_char() == ';' && punct.spacing() == Spacing::Alone =>
+            {
+                let excluded = cfg_attrs
+                    .drain(..)
+                    .any(|cfg| cfg::is_excluded(cfg, exclude_cfg));
+                let private = only_public && !std::mem::take(&mut public);
+                if excluded {
+                    debug!("Module {} is excluded by its cfg attribute", mod_name);
+                    path_attr = None;
+                    SeekingFor::ModulKeyword
+                } else if private {
+                    debug!("Module {} is not part of the public api", mod_name);
+                    path_attr = None;
+                    SeekingFor::ModulKeyword
+                } else {
+                    acc.extend(module_file(&base, path, &mod_name, path_attr.take())?);
+                    SeekingFor::ModulKeyword
                 }
-                SeekingFor::ModulName => {
-                    state = SeekingFor::ModulFin(ident.to_string());
+            }
+            (SeekingFor::ModulKeyword, TokenTree::Punct(punct)) if punct.as_char() == '#' => {
+                SeekingFor::AttributeContent
+            }
+            (SeekingFor::AttributeContent, TokenTree::Punct(punct)) if punct.as_char() == '!' => {
+                SeekingFor::AttributeContent
+            }
+            (SeekingFor::AttributeContent, TokenTree::Group(group))
+                if group.delimiter() == Delimiter::Bracket =>
+            {
+                if let Some(path) = path_attribute(group.stream()) {
+                    path_attr = Some(path);
+                } else if let Some(cfg) = cfg_attribute(group.stream()) {
+                    cfg_attrs.push(cfg);
                 }
-                _ => {}
-            },
-            TokenTree::Punct(punct) => {
-                if let SeekingFor::ModulFin(mod_name) = state {
-                    if punct.as_char() == ';' && punct.spacing() == Spacing::Alone {
-                        let path1 = base.join(&mod_name).join("mod.rs");
-                        let path2 = base.join(&mod_name).with_extension("rs");
-                        let path3 = base
-                            .join(
-                                path.file_stem()
-                                    .expect("If parent exists, should work (TM)"),
-                            )
-                            .join(mod_name)
-                            .with_extension("rs");
-                        match (path1.is_file(), path2.is_file(), path3.is_file()) {
-                            (true, false, false) => acc.push(path1),
-                            (false, true, false) => acc.push(path2),
-                            (false, false, true) => acc.push(path3),
-                            (true, true, _) | (true, _, true) | (_, true, true) => {
-                                return Err(anyhow::anyhow!(
-                                    "Detected both module entry files: {} and {} and {}",
-                                    path1.display(),
-                                    path2.display(),
-                                    path3.display()
-                                ))
-                            }
-                            _ => trace!(
-                                "Neither file nor dir with mod.rs {} / {} / {}",
-                                path1.display(),
-                                path2.display(),
-                                path2.display()
-                            ),
-                        };
-                    } else {
-                        trace!("Either not alone or not a semi colon {:?}", punct);
-                    }
+                SeekingFor::ModulKeyword
+            }
+            (SeekingFor::IncludeBang, TokenTree::Punct(punct)) if punct.as_char() == '!' => {
+                SeekingFor::IncludeArgs
+            }
+            (SeekingFor::IncludeArgs, TokenTree::Group(group))
+                if group.delimiter() == Delimiter::Parenthesis =>
+            {
+                // relative to the file, even within inline modules
+                let dir = source.parent().unwrap_or(&base);
+                acc.extend(include::resolve(group.stream(), dir, source));
+                SeekingFor::ModulKeyword
+            }
+            (SeekingFor::ModulFin(mod_name), TokenTree::Group(group))
+                if group.delimiter() == Delimiter::Brace =>
+            {
+                // an inline module, the declared modules within are relative to its name
+                path_attr = None;
+                let excluded = cfg_attrs
+                    .drain(..)
+                    .any(|cfg| cfg::is_excluded(cfg, exclude_cfg));
+                let private = only_public && !std::mem::take(&mut public);
+                if excluded {
+                    debug!(
+                        "Inline module {} is excluded by its cfg attribute",
+                        mod_name
+                    );
+                } else if private {
+                    debug!("Inline module {} is not part of the public api", mod_name);
+                } else {
+                    let virtual_path = base.join(&mod_name).join("mod.rs");
+                    acc.extend(extract_modules_inner(
+                        virtual_path,
+                        source,
+                        group.stream(),
+                        exclude_cfg,
+                        only_public,
+                    )?);
                 }
-                state = SeekingFor::ModulKeyword;
+                SeekingFor::ModulKeyword
             }
-            _ => {
-                state = SeekingFor::ModulKeyword;
+            (_, TokenTree::Punct(punct)) if punct.as_char() == ';' => {
+                path_attr = None;
+                cfg_attrs.clear();
+                public = false;
+                SeekingFor::ModulKeyword
+            }
+            (_, TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                // any other item body
+                path_attr = None;
+                cfg_attrs.clear();
+                public = false;
+                SeekingFor::ModulKeyword
+            }
+            (_, tree) => {
+                trace!("Either not alone or not a semi colon {:?}", tree);
+                SeekingFor::ModulKeyword
             }
         };
     }
     Ok(acc)
 }
 
+/// Resolve the file of the module `mod_name` declared in the file at `path`,
+/// none if there is no such file.
+///
+/// A `#[path = ".."]` attribute is relative to `base`, the directory of the
+/// declaring file.
+fn module_file(
+    base: &Path,
+    path: &Path,
+    mod_name: &str,
+    path_attr: Option<PathBuf>,
+) -> Result<Option<PathBuf>> {
+    if let Some(path_attr) = path_attr {
+        let path = base.join(path_attr);
+        if path.is_file() {
+            return Ok(Some(path));
+        }
+        warn!(
+            "Path attribute of module {} points to missing file {}",
+            mod_name,
+            path.display()
+        );
+        return Ok(None);
+    }
+    let path1 = base.join(mod_name).join("mod.rs");
+    let path2 = base.join(mod_name).with_extension("rs");
+    let path3 = base
+        .join(
+            path.file_stem()
+                .expect("If parent exists, should work (TM)"),
+        )
+        .join(mod_name)
+        .with_extension("rs");
+    Ok(match (path1.is_file(), path2.is_file(), path3.is_file()) {
+        (true, false, false) => Some(path1),
+        (false, true, false) => Some(path2),
+        (false, false, true) => Some(path3),
+        (true, true, _) | (true, _, true) | (_, true, true) => {
+            return Err(anyhow::anyhow!(
+                "Detected both module entry files: {} and {} and {}",
+                path1.display(),
+                path2.display(),
+                path3.display()
+            ))
+        }
+        _ => {
+            trace!(
+                "Neither file nor dir with mod.rs {} / {} / {}",
+                path1.display(),
+                path2.display(),
+                path2.display()
+            );
+            None
+        }
+    })
+}
+
+/// Find the `mod x;` declarations of the rust source `content` of the file at
+/// `path` by scanning its lines.
+///
+/// Only declarations at the start of a line are found, `include!`s are not
+/// followed, and cfg attributes and visibility are not considered. Inline
+/// modules are tracked by the braces of each line.
+fn scan_modules(path: &Path, content: &str) -> Result<Vec<PathBuf>> {
+    /// Extract the literal from `#[path = "other/file.rs"]`, only plain string
+    /// literals are supported.
+    fn path_attribute(code: &str) -> Option<PathBuf> {
+        let literal = code
+            .strip_prefix("#[")?
+            .strip_suffix(']')?
+            .trim()
+            .strip_prefix("path")?
+            .trim_start()
+            .strip_prefix('=')?
+            .trim()
+            .strip_prefix('"')?
+            .strip_suffix('"')?;
+        Some(literal)
+            .filter(|literal| !literal.contains(|c| c == '"' || c == '\\'))
+            .map(PathBuf::from)
+    }
+
+    /// Strip `pub`, `pub(crate)` and alike off a declaration.
+    fn strip_visibility(code: &str) -> &str {
+        match code.strip_prefix("pub") {
+            Some(rest) if rest.trim_start().starts_with('(') => rest
+                .find(')')
+                .map_or(rest, |end| &rest[end + 1..])
+                .trim_start(),
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+            _ => code,
+        }
+    }
+
+    let mut acc = Vec::with_capacity(16);
+    // the (virtual) file of each enclosing module and the depth of its braces
+    let mut scopes = vec![(path.to_owned(), 0_isize)];
+    let mut depth = 0_isize;
+    // a `#[path = ".."]` attribute applies to the next `mod` declaration
+    let mut path_attr: Option<PathBuf> = None;
+    for line in content.lines() {
+        let code = line.split("//").next().unwrap_or_default().trim();
+        let (file, _) = scopes.last().expect("The file itself is never left. qed");
+        let base = file
+            .parent()
+            .ok_or_else(|| anyhow!("Must have a valid parent directory: {}", file.display()))?
+            .to_owned();
+        if code.starts_with("#[") {
+            if let Some(path) = path_attribute(code) {
+                path_attr = Some(path);
+            }
+        } else if let Some(rest) = strip_visibility(code).strip_prefix("mod ") {
+            let rest = rest.trim_start();
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let (mod_name, rest) = rest.split_at(end);
+            let rest = rest.trim_start();
+            if rest.starts_with(';') {
+                acc.extend(module_file(&base, file, mod_name, path_attr.take())?);
+            } else if rest.starts_with('{') {
+                // an inline module, the declared modules within are relative to its name
+                path_attr = None;
+                scopes.push((base.join(mod_name).join("mod.rs"), depth + 1));
+            }
+        } else if !code.is_empty() {
+            path_attr = None;
+        }
+        depth += crate::util::brace_delta(line);
+        while scopes.len() > 1 && matches!(scopes.last(), Some((_, scope)) if depth < *scope) {
+            scopes.pop();
+        }
+    }
+    Ok(acc)
+}
+
+/// Load the source file at `path` to collect its modules, none if it is not
+/// valid UTF-8.
+fn load_modules_source(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(s) => Ok(Some(s)),
+        // reported once the content is extracted
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+            debug!(
+                "Not collecting modules of {}, it is not valid UTF-8",
+                path.display()
+            );
+            Ok(None)
+        }
+        Err(e) => {
+            Err(Error::from(e)
+                .context(anyhow!("Failed to read file content of {}", path.display())))
+        }
+    }
+}
+
 /// Read all `mod x;` declarations from a source file.
-pub(crate) fn extract_modules_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
+///
+/// Modules with a `#[cfg(..)]` attribute that can never be true if the predicates
+/// in `exclude_cfg` are false, are skipped. As are modules which are not `pub`,
+/// if `only_public` is set.
+pub(crate) fn extract_modules_from_file<P: AsRef<Path>>(
+    path: P,
+    exclude_cfg: &[String],
+    only_public: bool,
+) -> Result<Vec<PathBuf>> {
     let path: &Path = path.as_ref();
+    let _timer = Timer::start(Stage::Traversal, path);
     if let Some(path_str) = path.to_str() {
-        let s = std::fs::read_to_string(path_str).map_err(|e| {
-            Error::from(e).context(anyhow!("Failed to read file content of {}", path_str))
-        })?;
+        let s = match load_modules_source(path)? {
+            Some(s) => s,
+            None => return Ok(Vec::new()),
+        };
         let stream = syn::parse_str::<proc_macro2::TokenStream>(s.as_str())
             .map_err(|e| Error::from(e).context(anyhow!("File {} has syntax errors", path_str)))?;
-        extract_modules_inner(path.to_owned(), stream)
+        extract_modules_inner(path.to_owned(), path, stream, exclude_cfg, only_public)
     } else {
         Err(anyhow::anyhow!("path must have a string representation"))
     }
 }
 
+/// Read all `mod x;` declarations from a source file by scanning its lines
+/// instead of parsing it, see `--fast`.
+pub(crate) fn scan_modules_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
+    let path: &Path = path.as_ref();
+    let _timer = Timer::start(Stage::Traversal, path);
+    match load_modules_source(path)? {
+        Some(s) => scan_modules(path, &s),
+        None => Ok(Vec::new()),
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CheckEntity {
     Markdown(PathBuf),
@@ -167,12 +472,11 @@ fn load_manifest<P: AsRef<Path>>(manifest_dir: P) -> Result<cargo_toml::Manifest
 /// can convert manifest with or without Cargo.toml into the dir that contains the manifest
 fn to_manifest_dir<P: AsRef<Path>>(manifest_dir: P) -> Result<PathBuf> {
     let manifest_dir: &Path = manifest_dir.as_ref();
-    if manifest_dir.ends_with("Cargo.toml") {
+    crate::util::canonicalize(if manifest_dir.ends_with("Cargo.toml") {
         manifest_dir.parent().unwrap()
     } else {
         manifest_dir
-    }
-    .canonicalize()
+    })
     .map_err(|e| {
         Error::from(e).context(anyhow!(
             "Failed to canonicalize path {}",
@@ -182,9 +486,13 @@ fn to_manifest_dir<P: AsRef<Path>>(manifest_dir: P) -> Result<PathBuf> {
 }
 
 /// Extract all cargo manifest products / build targets.
+///
+/// Tests, examples, benches and the build script are only extracted if
+/// enabled in `targets`.
 fn extract_products(
     manifest: &cargo_toml::Manifest,
     manifest_dir: &Path,
+    targets: &TargetsConfig,
 ) -> Result<Vec<CheckEntity>> {
     let iter = manifest
         .bin
@@ -192,9 +500,48 @@ fn extract_products(
         .cloned()
         .chain(manifest.lib.iter().cloned().map(|x| x));
 
-    let items = iter
+    let mut paths = iter
         .filter(|product| product.doctest)
         .filter_map(|product| product.path)
+        .collect::<Vec<String>>();
+
+    // explicitly declared targets may omit the path, cargo then infers it
+    let inferred = |products: &[cargo_toml::Product], dir: &str| -> Vec<String> {
+        products
+            .iter()
+            .filter_map(|product| {
+                product.path.clone().or_else(|| {
+                    product
+                        .name
+                        .as_ref()
+                        .map(|name| format!("{}/{}.rs", dir, name))
+                })
+            })
+            .collect()
+    };
+    if targets.tests {
+        paths.extend(inferred(&manifest.test, "tests"));
+    }
+    if targets.examples {
+        paths.extend(inferred(&manifest.example, "examples"));
+    }
+    if targets.benches {
+        paths.extend(inferred(&manifest.bench, "benches"));
+    }
+    if targets.build {
+        let build = manifest
+            .package
+            .as_ref()
+            .and_then(|package| package.build.as_ref());
+        match build {
+            Some(cargo_toml::Value::String(path)) => paths.push(path.clone()),
+            Some(cargo_toml::Value::Boolean(false)) => {}
+            _ => paths.push("build.rs".to_owned()),
+        }
+    }
+
+    let items = paths
+        .into_iter()
         // cargo_toml's complete is not very truthfull
         .filter(|path_str| manifest_dir.join(path_str).is_file())
         .map(|path_str| CheckEntity::Source(manifest_dir.join(path_str), true))
@@ -211,9 +558,9 @@ fn extract_readme(
     let mut acc = Vec::with_capacity(2);
     if let Some(package) = manifest.package.clone() {
         if let Some(readme) = package.readme {
-            let readme = PathBuf::from(readme);
+            let readme = manifest_dir.join(readme);
             if readme.is_file() {
-                acc.push(CheckEntity::Markdown(manifest_dir.join(readme)));
+                acc.push(CheckEntity::Markdown(readme));
             } else {
                 warn!(
                     "README.md defined in Cargo.toml {} is not a file",
@@ -228,11 +575,81 @@ fn extract_readme(
     Ok(acc)
 }
 
-fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result<Vec<CheckEntity>> {
+/// Which packages of a workspace to check, akin to `cargo`'s
+/// `--workspace` and `-p/--package` flags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSelection {
+    /// The root package and the products of all workspace members.
+    Default,
+    /// All packages of the workspace, including their readme and description.
+    Workspace,
+    /// Only the packages with the given names.
+    Packages(Vec<String>),
+}
+
+impl Default for PackageSelection {
+    fn default() -> Self {
+        Self::Default
+    }
+}
+
+impl PackageSelection {
+    fn package_name(manifest: &cargo_toml::Manifest) -> Option<&str> {
+        manifest
+            .package
+            .as_ref()
+            .map(|package| package.name.as_str())
+    }
+
+    /// If the package with `name` is checked, in case it is the root.
+    fn selects(&self, name: Option<&str>) -> bool {
+        match self {
+            Self::Default | Self::Workspace => true,
+            Self::Packages(names) => name
+                .map(|name| names.iter().any(|x| x == name))
+                .unwrap_or(false),
+        }
+    }
+
+    /// If the workspace member with `name` is checked and if so, only its
+    /// products or the readme and description as well.
+    fn selects_member(&self, name: Option<&str>) -> Option<bool> {
+        match self {
+            Self::Default => Some(false),
+            Self::Workspace => Some(true),
+            Self::Packages(_) if self.selects(name) => Some(true),
+            Self::Packages(_) => None,
+        }
+    }
+}
+
+fn handle_manifest<P: AsRef<Path>>(
+    manifest_dir: P,
+    skip_readme: bool,
+    selection: &PackageSelection,
+    targets: &TargetsConfig,
+) -> Result<Vec<CheckEntity>> {
     let manifest_dir = to_manifest_dir(manifest_dir)?;
     trace!("Handle manifest in dir: {}", manifest_dir.display());
 
     let manifest_dir = manifest_dir.as_path();
+    match metadata::load(manifest_dir) {
+        Ok(metadata) => {
+            return metadata::handle_metadata(
+                metadata,
+                manifest_dir,
+                skip_readme,
+                selection,
+                targets,
+            )
+        }
+        Err(e) => debug!(
+            "Parsing the manifest in {} instead: {:?}",
+            manifest_dir.display(),
+            e
+        ),
+    }
+
     let manifest = load_manifest(manifest_dir).map_err(|e| {
         anyhow!(
             "Failed to load manifest from dir {}",
@@ -241,23 +658,31 @@ fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result
         .context(e)
     })?;
 
-    let mut acc = extract_products(&manifest, &manifest_dir).map_err(|e| {
-        anyhow!(
-            "Failed to extract products from manifest {}",
-            manifest_dir.display()
-        )
-        .context(e)
-    })?;
+    let mut found = Vec::with_capacity(8);
+    let mut acc = Vec::with_capacity(32);
 
-    if !skip_readme {
-        let v = extract_readme(&manifest, &manifest_dir).map_err(|e| {
-            anyhow!(
-                "Failed to extract readme / description from manifest {}",
-                manifest_dir.display()
-            )
-            .context(e)
-        })?;
-        acc.extend(v);
+    if selection.selects(PackageSelection::package_name(&manifest)) {
+        found.extend(PackageSelection::package_name(&manifest).map(ToOwned::to_owned));
+        acc.extend(
+            extract_products(&manifest, &manifest_dir, targets).map_err(|e| {
+                anyhow!(
+                    "Failed to extract products from manifest {}",
+                    manifest_dir.display()
+                )
+                .context(e)
+            })?,
+        );
+
+        if !skip_readme {
+            let v = extract_readme(&manifest, &manifest_dir).map_err(|e| {
+                anyhow!(
+                    "Failed to extract readme / description from manifest {}",
+                    manifest_dir.display()
+                )
+                .context(e)
+            })?;
+            acc.extend(v);
+        }
     }
 
     if let Some(workspace) = manifest.workspace {
@@ -279,27 +704,120 @@ fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result
                     )
                     .context(e)
                 }) {
-                    if let Ok(member) = extract_products(&member_manifest, &member_dir) {
+                    let with_readme = if let Some(with_readme) =
+                        selection.selects_member(PackageSelection::package_name(&member_manifest))
+                    {
+                        with_readme
+                    } else {
+                        trace!("Workspace member {} is not selected", item);
+                        return Ok(());
+                    };
+                    found.extend(
+                        PackageSelection::package_name(&member_manifest).map(ToOwned::to_owned),
+                    );
+                    if let Ok(member) = extract_products(&member_manifest, &member_dir, targets) {
                         acc.extend(member.into_iter());
                     } else {
                         warn!("Workspace member {} product extraction failed", item);
                     }
+                    if with_readme && !skip_readme {
+                        if let Ok(member) = extract_readme(&member_manifest, &member_dir) {
+                            acc.extend(member.into_iter());
+                        } else {
+                            warn!("Workspace member {} readme extraction failed", item);
+                        }
+                    }
                 } else {
                     warn!("Opening manifest from member failed {}", item);
                 }
                 Ok(())
             })?;
     }
+
+    if let PackageSelection::Packages(names) = selection {
+        if let Some(name) = names.iter().find(|name| !found.contains(name)) {
+            bail!(
+                "Package `{}` is not part of the manifest {}",
+                name,
+                manifest_dir.display()
+            );
+        }
+    }
     Ok(acc)
 }
 
-/// Extract all chunks from
-pub(crate) fn extract(
+/// The kind of content of a file to check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// Rust source, its doc comments are checked.
+    Rust,
+    /// A common mark file, checked as a whole.
+    CommonMark,
+}
+
+/// Why a file is checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// Passed on the commandline.
+    Argument,
+    /// A build target of the manifest.
+    Target(PathBuf),
+    /// The readme of the manifest.
+    Readme(PathBuf),
+    /// A module declared within the crate, or the module tree, of this file.
+    Module(PathBuf),
+    /// Within one of the `extra_src_dirs`.
+    ExtraSrcDir(PathBuf),
+}
+
+impl fmt::Display for FileKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rust => write!(f, "rust source"),
+            Self::CommonMark => write!(f, "common mark"),
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Argument => write!(f, "passed as argument"),
+            Self::Target(manifest) => write!(f, "target of {}", manifest.display()),
+            Self::Readme(manifest) => write!(f, "readme of {}", manifest.display()),
+            Self::Module(root) => write!(f, "module below {}", root.display()),
+            Self::ExtraSrcDir(dir) => write!(f, "in extra source dir {}", dir.display()),
+        }
+    }
+}
+
+/// A file which is checked, found during the traversal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discovered {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// What the file contains.
+    pub kind: FileKind,
+    /// Why the file is checked.
+    pub reason: Reason,
+}
+
+/// The manifest closest to `path`, the one of the package it belongs to.
+fn owning_manifest(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|manifest| manifest.is_file())
+}
+
+/// Find all files to check, without extracting anything from them.
+pub fn discover(
     mut paths: Vec<PathBuf>,
     mut recurse: bool,
     skip_readme: bool,
-    _config: &Config,
-) -> Result<Documentation> {
+    selection: &PackageSelection,
+    config: &Config,
+) -> Result<Vec<Discovered>> {
     let cwd = cwd()?;
     // if there are no arguments, pretend to be told to check the whole project
     if paths.is_empty() {
@@ -309,6 +827,19 @@ pub(crate) fn extract(
 
     debug!("Running on inputs {:?} / recursive={}", &paths, recurse);
 
+    let ignore_files = IgnoreFiles::new();
+    let is_included = |path: &Path| -> bool {
+        if !config.is_included(path) {
+            debug!("Excluded by filters {}", path.display());
+            return false;
+        }
+        if config.respect_ignore_files && ignore_files.is_ignored(path) {
+            debug!("Excluded by ignore files {}", path.display());
+            return false;
+        }
+        true
+    };
+
     #[derive(Debug, Clone)]
     enum Extraction {
         Manifest(PathBuf),
@@ -320,13 +851,9 @@ pub(crate) fn extract(
     // stage 1 - obtain canonical paths
     let mut flow = VecDeque::<PathBuf>::with_capacity(32);
     flow.extend(paths.into_iter().filter_map(|path_in| {
-        let path = if path_in.is_absolute() {
-            path_in.to_owned()
-        } else {
-            cwd.join(&path_in)
-        };
+        let path = crate::util::absolute(&path_in, &cwd);
         debug!("Processing {} -> {}", path_in.display(), path.display());
-        path.canonicalize().ok()
+        crate::util::canonicalize(&path).ok()
     }));
 
     debug!("Running on absolute dirs {:?} ", &flow);
@@ -355,7 +882,12 @@ pub(crate) fn extract(
                     // we know it's a directory, and we limit the entries to 0 levels,
                     // will cause to yield all "^.*\.rs$" files in that dir
                     // which is what we want in this case
-                    flow.extend(TraverseModulesIter::with_depth_limit(&path, 0)?);
+                    let _timer = Timer::start(Stage::Traversal, &path);
+                    flow.extend(TraverseModulesIter::with_options(
+                        &path,
+                        0,
+                        config.follow_symlinks,
+                    )?);
                     continue;
                 }
             } else {
@@ -375,70 +907,235 @@ pub(crate) fn extract(
         .try_fold::<Vec<_>, _, Result<_>>(Vec::with_capacity(64), |mut acc, tagged_path| {
             match tagged_path {
                 Extraction::Manifest(ref cargo_toml_path) => {
-                    let manifest_list = handle_manifest(cargo_toml_path, skip_readme)?;
-                    acc.extend(manifest_list);
+                    let _timer = Timer::start(Stage::Traversal, cargo_toml_path);
+                    let manifest_list =
+                        handle_manifest(cargo_toml_path, skip_readme, selection, &config.targets)?;
+                    acc.extend(manifest_list.into_iter().map(|entity| {
+                        // workspace members have a manifest of their own
+                        let manifest = |path: &Path| {
+                            owning_manifest(path).unwrap_or_else(|| cargo_toml_path.to_owned())
+                        };
+                        let reason = match entity {
+                            CheckEntity::Markdown(ref path) => Reason::Readme(manifest(path)),
+                            CheckEntity::Source(ref path, _) => Reason::Target(manifest(path)),
+                            CheckEntity::ManifestDescription(_) => {
+                                Reason::Readme(cargo_toml_path.to_owned())
+                            }
+                        };
+                        (entity, reason)
+                    }));
                 }
                 Extraction::Missing(ref missing_path) => warn!(
                     "File passed as argument or listed in Cargo.toml manifest does not exist: {}",
                     missing_path.display()
                 ),
-                Extraction::Source(path) => acc.push(CheckEntity::Source(path, recurse)),
-                Extraction::Markdown(path) => acc.push(CheckEntity::Markdown(path)),
+                Extraction::Source(path) => {
+                    acc.push((CheckEntity::Source(path, recurse), Reason::Argument))
+                }
+                Extraction::Markdown(path) => {
+                    acc.push((CheckEntity::Markdown(path), Reason::Argument))
+                }
             }
             Ok(acc)
         })?;
 
     // stage 4 - expand from the passed source files, if recursive, recurse down the module train
-    let combined: Documentation = files_to_check
-        .into_iter()
-        .try_fold::<Documentation, _, Result<Documentation>>(
-            Documentation::new(),
-            |mut docs, item| {
-                match item {
-                    CheckEntity::Source(path, recurse) => {
-                        if recurse {
-                            let iter = traverse(path.as_path())?;
-                            docs.extend(iter);
-                        } else {
-                            let content: String = fs::read_to_string(&path).map_err(|e| {
-                                anyhow!("Failed to read {}", path.display()).context(e)
-                            })?;
-                            docs.add_rust(
-                                ContentOrigin::RustSourceFile(path.to_owned()),
-                                content.as_str(),
-                            )
-                            .unwrap_or_else(|_e| {
-                                log::error!("BUG: Failed to create cluster for {}", path.display())
-                            });
-                        }
-                    }
-                    CheckEntity::Markdown(path) => {
-                        let content = std::fs::read_to_string(&path).map_err(|e| {
-                            anyhow!("Common mark / markdown file does not exist").context(e)
-                        })?;
-                        if content.len() < 1 {
-                            bail!("Common mark / markdown file is empty")
-                        }
-                        docs.add_commonmark(
-                            ContentOrigin::CommonMarkFile(path.to_owned()),
-                            content.as_str(),
-                        )?;
-                    }
-                    other => {
-                        warn!("Did not impl handling of {:?} type files", other);
-                        // TODO generate Documentation structs from non-file sources
-                    }
+    let mut discovered = Vec::with_capacity(files_to_check.len());
+    for (entity, reason) in files_to_check {
+        match entity {
+            CheckEntity::Source(path, true) => {
+                let root = crate::util::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                for module in traverse(path.as_path(), config, &is_included)? {
+                    let reason = if module == root {
+                        reason.clone()
+                    } else {
+                        Reason::Module(path.clone())
+                    };
+                    discovered.push(Discovered {
+                        path: module,
+                        kind: FileKind::Rust,
+                        reason,
+                    });
                 }
-                Ok(docs)
-            },
-        )?;
+            }
+            CheckEntity::Source(path, false) if is_included(&path) => discovered.push(Discovered {
+                path,
+                kind: FileKind::Rust,
+                reason,
+            }),
+            CheckEntity::Markdown(path) if is_included(&path) => discovered.push(Discovered {
+                path,
+                kind: FileKind::CommonMark,
+                reason,
+            }),
+            CheckEntity::Source(..) | CheckEntity::Markdown(_) => {}
+            other => {
+                warn!("Did not impl handling of {:?} type files", other);
+                // TODO generate Documentation structs from non-file sources
+            }
+        }
+    }
+
+    // stage 5 - generated sources, which are found neither via manifest nor module declarations
+    for dir in config.extra_src_dirs.iter() {
+        discovered.extend(
+            discover_extra_src_dir(dir, config.follow_symlinks)?
+                .into_iter()
+                .map(|path| Discovered {
+                    path,
+                    kind: FileKind::Rust,
+                    reason: Reason::ExtraSrcDir(dir.to_owned()),
+                }),
+        );
+    }
+    Ok(discovered)
+}
 
+/// Extract all chunks from
+pub fn extract(
+    paths: Vec<PathBuf>,
+    recurse: bool,
+    skip_readme: bool,
+    selection: &PackageSelection,
+    config: &Config,
+) -> Result<Documentation> {
+    let mut combined = Documentation::new();
+    for Discovered { path, kind, reason } in
+        discover(paths, recurse, skip_readme, selection, config)?
+    {
+        let content = if config.from_index {
+            staged::load(&path)
+        } else {
+            fs::read_to_string(&path)
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                warn!("Skipping {}, it is not valid UTF-8", path.display());
+                continue;
+            }
+            Err(e) if matches!(reason, Reason::Module(_)) => {
+                debug!("Failed to read module {}: {}", path.display(), e);
+                continue;
+            }
+            Err(e) if kind == FileKind::CommonMark => {
+                return Err(anyhow!("Common mark / markdown file does not exist").context(e))
+            }
+            Err(e) => return Err(anyhow!("Failed to read {}", path.display()).context(e)),
+        };
+        trace!("Extracting {} ({})", path.display(), reason);
+        add_file(&mut combined, path, kind, content.as_str(), config)?;
+    }
+    retain_configured(&mut combined, config);
     Ok(combined)
 }
 
+/// Extract the chunks of the single file of `origin` once more, i.e. after it
+/// was edited.
+pub(crate) fn extract_again(origin: &ContentOrigin, config: &Config) -> Result<Documentation> {
+    let path = origin.as_path().to_owned();
+    let kind = match origin {
+        ContentOrigin::CommonMarkFile(_) => FileKind::CommonMark,
+        _ => FileKind::Rust,
+    };
+    let content = fs::read_to_string(&path)
+        .map_err(|e| anyhow!("Failed to read {}", path.display()).context(e))?;
+    let mut documentation = Documentation::new();
+    add_file(&mut documentation, path, kind, content.as_str(), config)?;
+    retain_configured(&mut documentation, config);
+    Ok(documentation)
+}
+
+/// Add the chunks of the file at `path` with `content` to `combined`.
+fn add_file(
+    combined: &mut Documentation,
+    path: PathBuf,
+    kind: FileKind,
+    content: &str,
+    config: &Config,
+) -> Result<()> {
+    match kind {
+        FileKind::Rust => {
+            combined.join(rust_documentation(path, content, config.fast_extraction));
+        }
+        FileKind::CommonMark => {
+            if content.len() < 1 {
+                bail!("Common mark / markdown file is empty")
+            }
+            if content.len() > config.large_file_size {
+                debug!(
+                    "Checking {} in chunks, it exceeds {} bytes",
+                    path.display(),
+                    config.large_file_size
+                );
+                combined.add_commonmark_chunked(
+                    ContentOrigin::CommonMarkFile(path),
+                    content,
+                    LARGE_FILE_CHUNK_SIZE,
+                )?;
+            } else {
+                combined.add_commonmark(ContentOrigin::CommonMarkFile(path), content)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Drop the chunks which are configured not to be checked.
+fn retain_configured(documentation: &mut Documentation, config: &Config) {
+    if !config.check_doc_hidden {
+        documentation.retain_chunks(|chunk| !chunk.is_hidden());
+    }
+    if config.only_public {
+        documentation.retain_chunks(|chunk| !chunk.is_private());
+    }
+}
+
+/// The documentation of a rust source file, scanned by lines if `fast`.
+pub(crate) fn rust_documentation(path: PathBuf, content: &str, fast: bool) -> Documentation {
+    let origin = ContentOrigin::RustSourceFile(path);
+    if fast {
+        let mut docs = Documentation::new();
+        docs.add_rust_scanned(origin, content);
+        docs
+    } else {
+        Documentation::from((origin, content))
+    }
+}
+
+/// All `.rs` files within `dir` and its subdirectories, i.e. generated code in
+/// `OUT_DIR`.
+///
+/// Include, exclude and ignore files are not applied, since generated code
+/// commonly resides in the ignored `target` directory.
+fn discover_extra_src_dir(dir: &Path, follow_symlinks: bool) -> Result<Vec<PathBuf>> {
+    let dir = crate::util::canonicalize(dir).map_err(|e| {
+        anyhow!("Failed to canonicalize extra source dir {}", dir.display()).context(e)
+    })?;
+    if !dir.is_dir() {
+        bail!("Extra source dir {} is not a directory", dir.display());
+    }
+    let paths = walkdir::WalkDir::new(&dir)
+        .follow_links(follow_symlinks)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .filter(|ext| *ext == "rs")
+                .is_some()
+        })
+        .map(|entry| entry.path().to_owned())
+        .collect();
+    Ok(paths)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::PathPattern;
 
     const TEST_FILE_FRAGMENTS: &str = "src/nested/fragments.rs";
     const TEST_FILE_SIMPLE: &str = "src/nested/fragments/simple.rs";
@@ -447,7 +1144,7 @@ mod tests {
         let _ = env_logger::try_init();
 
         assert_eq!(
-            extract_modules_from_file(demo_dir().join(TEST_FILE_FRAGMENTS))
+            extract_modules_from_file(demo_dir().join(TEST_FILE_FRAGMENTS), &[], false)
                 .expect("fragments.rs must exist"),
             vec![
                 demo_dir()
@@ -460,11 +1157,291 @@ mod tests {
         );
     }
 
+    #[test]
+    fn path_attributes_and_includes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("platform")).unwrap();
+        fs::write(root.join("platform").join("unix.rs"), "//! Unix\n").unwrap();
+        fs::write(root.join("generated.rs"), "/// Generated\n").unwrap();
+        fs::write(root.join("regular.rs"), "//! Regular\n").unwrap();
+        fs::write(root.join("ffi.rs"), "/// Foreign\n").unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"generated\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        let out_dir = root.join("target/debug/build/generated-0123456789abcdef/out");
+        fs::create_dir_all(&out_dir).unwrap();
+        fs::write(out_dir.join("bindings.rs"), "/// Bindings\n").unwrap();
+        let lib = root.join("lib.rs");
+        fs::write(
+            &lib,
+            r#"
+#[cfg(unix)]
+#[path = "platform/unix.rs"]
+pub(crate) mod sys;
+
+mod regular;
+
+include!("generated.rs");
+include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+include!(concat!(env!("OUT_DIR"), "/missing.rs"));
+
+mod ffi {
+    include!(concat!(env!("CARGO_MANIFEST_DIR"), "/ffi.rs"));
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            extract_modules_from_file(&lib, &[], false).expect("lib.rs must exist"),
+            vec![
+                root.join("platform").join("unix.rs"),
+                root.join("regular.rs"),
+                root.join("generated.rs"),
+                out_dir.join("bindings.rs"),
+                root.join("ffi.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn scanned_modules() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("platform")).unwrap();
+        fs::create_dir_all(root.join("api")).unwrap();
+        for file in &[
+            "platform/unix.rs",
+            "regular.rs",
+            "api/nested.rs",
+            "after.rs",
+        ] {
+            fs::write(root.join(file), "//! Module\n").unwrap();
+        }
+        let lib = root.join("lib.rs");
+        fs::write(
+            &lib,
+            r#"
+#[cfg(unix)]
+#[path = "platform/unix.rs"]
+pub(crate) mod sys;
+
+mod regular; // trailing comment
+// mod commented;
+
+pub mod api {
+    pub mod nested;
+    fn body() {}
+}
+mod after;
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            scan_modules_from_file(&lib).expect("lib.rs must exist"),
+            vec![
+                root.join("platform").join("unix.rs"),
+                root.join("regular.rs"),
+                root.join("api").join("nested.rs"),
+                root.join("after.rs"),
+            ]
+        );
+        let fragments = demo_dir().join(TEST_FILE_FRAGMENTS);
+        assert_eq!(
+            scan_modules_from_file(&fragments).unwrap(),
+            extract_modules_from_file(&fragments, &[], false).unwrap()
+        );
+    }
+
+    #[test]
+    fn cfg_gated_modules() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("sys")).unwrap();
+        for file in &["windows.rs", "unix.rs", "sys/common.rs"] {
+            fs::write(root.join(file), "//! Platform\n").unwrap();
+        }
+        let lib = root.join("lib.rs");
+        fs::write(
+            &lib,
+            r#"
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(all(unix, feature = "std"))]
+mod unix;
+
+#[cfg(not(target_os = "windows"))]
+mod sys {
+    mod common;
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            extract_modules_from_file(&lib, &[], false).expect("lib.rs must exist"),
+            vec![
+                root.join("windows.rs"),
+                root.join("unix.rs"),
+                root.join("sys").join("common.rs"),
+            ]
+        );
+        assert_eq!(
+            extract_modules_from_file(&lib, &[r#"target_os="windows""#.to_owned()], false)
+                .expect("lib.rs must exist"),
+            vec![root.join("unix.rs"), root.join("sys").join("common.rs")]
+        );
+    }
+
+    #[test]
+    fn public_modules() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("api")).unwrap();
+        for file in &["api/nested.rs", "api/internal.rs", "private.rs"] {
+            fs::write(root.join(file), "//! Module\n").unwrap();
+        }
+        let lib = root.join("lib.rs");
+        fs::write(
+            &lib,
+            r#"
+pub mod api {
+    pub mod nested;
+    pub(crate) mod internal;
+}
+mod private;
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            extract_modules_from_file(&lib, &[], false).expect("lib.rs must exist"),
+            vec![
+                root.join("api").join("nested.rs"),
+                root.join("api").join("internal.rs"),
+                root.join("private.rs"),
+            ]
+        );
+        assert_eq!(
+            extract_modules_from_file(&lib, &[], true).expect("lib.rs must exist"),
+            vec![root.join("api").join("nested.rs")]
+        );
+    }
+
+    #[test]
+    fn discover_reasons() {
+        let manifest = demo_dir().join("Cargo.toml");
+        let discovered = discover(
+            vec![manifest.clone(), demo_dir().join("src/nested/justone.rs")],
+            false,
+            false,
+            &PackageSelection::Default,
+            &Config::default(),
+        )
+        .unwrap();
+        let reasons_of = |sub: &str| {
+            discovered
+                .iter()
+                .filter(|discovered| discovered.path == demo_dir().join(sub))
+                .map(|discovered| (discovered.kind, discovered.reason.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            reasons_of("README.md"),
+            vec![(FileKind::CommonMark, Reason::Readme(manifest.clone()))]
+        );
+        assert_eq!(
+            reasons_of("src/main.rs"),
+            vec![(FileKind::Rust, Reason::Target(manifest.clone()))]
+        );
+        assert_eq!(
+            reasons_of("src/nested/justone.rs"),
+            vec![
+                (
+                    FileKind::Rust,
+                    Reason::Module(demo_dir().join("src/main.rs"))
+                ),
+                (FileKind::Rust, Reason::Argument),
+            ]
+        );
+    }
+
+    #[test]
+    fn extra_src_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("nested")).unwrap();
+        fs::write(
+            root.join("bindings.rs"),
+            "/// Generated binding\nstruct X;\n",
+        )
+        .unwrap();
+        fs::write(root.join("nested").join("more.rs"), "//! More\n").unwrap();
+        fs::write(root.join("nested").join("notes.txt"), "not rust").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let config = Config {
+            extra_src_dirs: vec![root.clone()],
+            ..Config::default()
+        };
+        let docs = extract(
+            vec![demo_dir().join("src/lib.rs")],
+            false,
+            false,
+            &PackageSelection::default(),
+            &config,
+        )
+        .expect("Must be able to extract demo dir and extra source dir");
+        let origins = docs
+            .iter()
+            .map(|(origin, _)| origin.as_path().to_owned())
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            origins,
+            vec![
+                demo_dir().join("src/lib.rs"),
+                root.join("bindings.rs"),
+                root.join("nested").join("more.rs"),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_skipped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("lib.rs"), "//! Entry\nmod latin1;\n").unwrap();
+        fs::write(root.join("latin1.rs"), b"//! Caf\xe9\nmod nested;\n").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        let extracted = |path: &str| {
+            extract(
+                vec![root.join(path)],
+                true,
+                false,
+                &PackageSelection::default(),
+                &Config::default(),
+            )
+            .expect("Files which are not UTF-8 are skipped")
+            .iter()
+            .map(|(origin, _)| origin.as_path().to_owned())
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(extracted("lib.rs"), vec![root.join("lib.rs")]);
+        assert!(extracted("latin1.rs").is_empty());
+    }
+
     #[test]
     fn manifest_entries() {
         let (manifest, dir) = demo_dir_manifest();
         assert_eq!(
-            extract_products(&manifest, &dir).expect("Must succeed"),
+            extract_products(&manifest, &dir, &TargetsConfig::default()).expect("Must succeed"),
             vec![
                 CheckEntity::Source(demo_dir().join("src/main.rs"), true),
                 CheckEntity::Source(demo_dir().join("src/lib.rs"), true),
@@ -485,6 +1462,179 @@ mod tests {
         manifest_dir().join("demo")
     }
 
+    #[test]
+    fn package_selection() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"alpha\", \"beta\"]\n",
+        )
+        .unwrap();
+        for member in &["alpha", "beta"] {
+            fs::create_dir_all(root.join(member).join("src")).unwrap();
+            fs::write(
+                root.join(member).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nreadme = \"README.md\"\n",
+                    member
+                ),
+            )
+            .unwrap();
+            fs::write(root.join(member).join("src").join("lib.rs"), "//! Docs\n").unwrap();
+            fs::write(root.join(member).join("README.md"), "# Title\n").unwrap();
+        }
+        let root = root.canonicalize().unwrap();
+
+        let targets = TargetsConfig::default();
+        let source = |member: &str| CheckEntity::Source(root.join(member).join("src/lib.rs"), true);
+        let readme = |member: &str| CheckEntity::Markdown(root.join(member).join("README.md"));
+
+        assert_eq!(
+            handle_manifest(&root, false, &PackageSelection::Default, &targets).unwrap(),
+            vec![source("alpha"), source("beta")]
+        );
+        assert_eq!(
+            handle_manifest(&root, false, &PackageSelection::Workspace, &targets).unwrap(),
+            vec![
+                source("alpha"),
+                readme("alpha"),
+                source("beta"),
+                readme("beta")
+            ]
+        );
+        assert_eq!(
+            handle_manifest(&root, true, &PackageSelection::Workspace, &targets).unwrap(),
+            vec![source("alpha"), source("beta")]
+        );
+        assert_eq!(
+            handle_manifest(
+                &root,
+                false,
+                &PackageSelection::Packages(vec!["beta".to_owned()]),
+                &targets
+            )
+            .unwrap(),
+            vec![source("beta"), readme("beta")]
+        );
+        assert!(handle_manifest(
+            &root,
+            false,
+            &PackageSelection::Packages(vec!["gamma".to_owned()]),
+            &targets
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn target_kinds() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for dir in &["src", "tests", "examples", "benches"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"targets\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        for file in &[
+            "src/lib.rs",
+            "tests/integration.rs",
+            "examples/demo.rs",
+            "benches/speed.rs",
+            "build.rs",
+        ] {
+            fs::write(root.join(file), "//! Docs\n").unwrap();
+        }
+        let root = root.canonicalize().unwrap();
+        let manifest = load_manifest(&root).unwrap();
+        let source = |file: &str| CheckEntity::Source(root.join(file), true);
+
+        assert_eq!(
+            extract_products(&manifest, &root, &TargetsConfig::default()).unwrap(),
+            vec![source("src/lib.rs")]
+        );
+        assert_eq!(
+            extract_products(&manifest, &root, &"tests,build".parse().unwrap()).unwrap(),
+            vec![
+                source("src/lib.rs"),
+                source("tests/integration.rs"),
+                source("build.rs")
+            ]
+        );
+        assert_eq!(
+            extract_products(&manifest, &root, &"all".parse().unwrap()).unwrap(),
+            vec![
+                source("src/lib.rs"),
+                source("tests/integration.rs"),
+                source("examples/demo.rs"),
+                source("benches/speed.rs"),
+                source("build.rs")
+            ]
+        );
+        assert!("tests,docs".parse::<TargetsConfig>().is_err());
+    }
+
+    #[test]
+    fn virtual_workspace_metadata() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/skipped\"]\n",
+        )
+        .unwrap();
+        for member in &["renamed", "skipped"] {
+            fs::create_dir_all(root.join("crates").join(member).join("source")).unwrap();
+            fs::write(
+                root.join("crates").join(member).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\n\n[lib]\npath = \"source/entry.rs\"\n",
+                    member
+                ),
+            )
+            .unwrap();
+            fs::write(
+                root.join("crates").join(member).join("source/entry.rs"),
+                "//! Docs\n",
+            )
+            .unwrap();
+        }
+        let root = root.canonicalize().unwrap();
+        let source = CheckEntity::Source(root.join("crates/renamed/source/entry.rs"), true);
+
+        assert_eq!(
+            handle_manifest(
+                &root,
+                false,
+                &PackageSelection::Workspace,
+                &TargetsConfig::default()
+            )
+            .unwrap(),
+            vec![source.clone()]
+        );
+        // excluded packages are not part of the workspace
+        assert!(handle_manifest(
+            &root,
+            false,
+            &PackageSelection::Packages(vec!["skipped".to_owned()]),
+            &TargetsConfig::default()
+        )
+        .is_err());
+        // a member on its own is its only package
+        assert_eq!(
+            handle_manifest(
+                root.join("crates/renamed"),
+                true,
+                &PackageSelection::Default,
+                &TargetsConfig::default()
+            )
+            .unwrap(),
+            vec![source]
+        );
+    }
+
     fn demo_dir_manifest() -> (cargo_toml::Manifest, PathBuf) {
         (
             load_manifest(demo_dir()).expect("Demo dir manifest must exist"),
@@ -527,6 +1677,18 @@ mod tests {
         };
 
         ([ $( $path:literal ),* $(,)?] + $recurse: expr => [ $( $file:literal ),* $(,)?] ) => {
+            extract_test!([ $( $path ),* ] + $recurse, Config::default() => [ $( $file ),* ]);
+        };
+
+        ($name:ident, [ $( $path:literal ),* $(,)?] + $recurse: expr, $config: expr => [ $( $file:literal ),* $(,)?] ) => {
+
+            #[test]
+            fn $name() {
+                extract_test!([ $( $path ),* ] + $recurse, $config => [ $( $file ),* ]);
+            }
+        };
+
+        ([ $( $path:literal ),* $(,)?] + $recurse: expr, $config: expr => [ $( $file:literal ),* $(,)?] ) => {
                     let _ = env_logger::builder()
             .is_test(true)
             .filter(None, log::LevelFilter::Trace)
@@ -540,7 +1702,8 @@ mod tests {
                 ],
                 $recurse,
                 false,
-                &Config::default(),
+                &PackageSelection::default(),
+                &$config,
             )
             .expect("Must be able to extract demo dir");
             assert_eq!(
@@ -620,6 +1783,45 @@ mod tests {
         "src/nested/mod.rs",
     ]);
 
+    fn filtered(include: &[&str], exclude: &[&str]) -> Config {
+        let patterns = |globs: &[&str]| -> Vec<PathPattern> {
+            globs
+                .iter()
+                .map(|glob| {
+                    demo_dir()
+                        .join(glob)
+                        .to_str()
+                        .unwrap()
+                        .parse::<PathPattern>()
+                        .unwrap()
+                })
+                .collect()
+        };
+        Config {
+            include: patterns(include),
+            exclude: patterns(exclude),
+            ..Config::default()
+        }
+    }
+
+    extract_test!(traverse_manifest_rec_exclude, ["Cargo.toml"] + true, filtered(&[], &["src/nested/fragments*", "README.md"]) => [
+        "src/lib.rs",
+        "src/main.rs",
+        "src/nested/again/mod.rs",
+        "src/nested/justone.rs",
+        "src/nested/justtwo.rs",
+        "src/nested/mod.rs",
+    ]);
+
+    extract_test!(traverse_manifest_rec_include, ["Cargo.toml"] + true, filtered(&["src/nested/**"], &["src/nested/again/**"]) => [
+        "src/nested/fragments/enumerate.rs",
+        "src/nested/fragments/simple.rs",
+        "src/nested/fragments.rs",
+        "src/nested/justone.rs",
+        "src/nested/justtwo.rs",
+        "src/nested/mod.rs",
+    ]);
+
     extract_test!(traverse_nested_mod_rs_1, ["src/nested/mod.rs"] + false => [
         "src/nested/mod.rs"
     ]);
@@ -0,0 +1,101 @@
+//! Obtain the files staged for the next commit, for use in pre-commit hooks.
+
+use anyhow::{anyhow, bail, Result};
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Run `git` with the given arguments in `dir` and return stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| anyhow!("Failed to execute `git {}`", args.join(" ")).context(e))?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Absolute paths of all files that are added, copied, modified or renamed in
+/// the index of the repository containing `dir`.
+pub fn staged_files(dir: &Path) -> Result<HashSet<PathBuf>> {
+    let toplevel = git(dir, &["rev-parse", "--show-toplevel"])?;
+    let toplevel = PathBuf::from(String::from_utf8_lossy(&toplevel).trim());
+
+    let names = git(
+        dir,
+        &[
+            "diff",
+            "--cached",
+            "--name-only",
+            "--diff-filter=ACMR",
+            "-z",
+        ],
+    )?;
+    let staged = names
+        .split(|c| *c == b'\0')
+        .filter(|name| !name.is_empty())
+        .map(|name| toplevel.join(String::from_utf8_lossy(name).as_ref()))
+        .filter_map(|path| crate::util::canonicalize(&path).ok())
+        .collect::<HashSet<PathBuf>>();
+    log::debug!("Found {} staged files", staged.len());
+    Ok(staged)
+}
+
+/// Load the staged content of the file at `path`, which is what will be
+/// committed, rather than the one of the working tree.
+///
+/// Like `read_to_string`, fails with `InvalidData` if it is not UTF-8.
+pub(crate) fn load(path: &Path) -> io::Result<String> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Path has no file name"))?;
+    // `:./<name>` is the index entry relative to `dir`
+    let blob = git(dir, &["show", &format!(":./{}", name)])
+        .map_err(|e| io::Error::new(io::ErrorKind::NotFound, format!("{:?}", e)))?;
+    String::from_utf8(blob).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staged_in_fresh_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        if git(&root, &["init", "-q"]).is_err() {
+            // no `git` available, nothing to test
+            return;
+        }
+        std::fs::write(root.join("src").join("lib.rs"), "//! Staged\n").unwrap();
+        std::fs::write(root.join("README.md"), "# Not staged\n").unwrap();
+        git(&root, &["add", "src/lib.rs"]).unwrap();
+
+        let staged = staged_files(&root.join("src")).unwrap();
+        let root = root.canonicalize().unwrap();
+        assert_eq!(
+            staged,
+            vec![root.join("src").join("lib.rs")].into_iter().collect()
+        );
+
+        // the index content is checked, not the one of the working tree
+        std::fs::write(root.join("src").join("lib.rs"), "//! Modified\n").unwrap();
+        assert_eq!(
+            load(&root.join("src").join("lib.rs")).unwrap(),
+            "//! Staged\n"
+        );
+        assert!(load(&root.join("README.md")).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{LineColumn, Range, Span};
 use anyhow::{anyhow, bail, Result};
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Iterate over a str and annotate with line and column.
 ///
@@ -14,7 +14,7 @@ pub fn iter_with_line_column_from<'a>(
     struct State {
         cursor: LineColumn,
         previous_char_was_newline: bool,
-    };
+    }
     let initial = State {
         cursor: start_point,
         previous_char_was_newline: false,
@@ -85,11 +85,9 @@ where
 /// Extract span from a file as `String`.
 ///
 /// Helpful to validate bandaids against what's actually in the file.
-#[allow(unused)]
 pub(crate) fn load_span_from_file(path: impl AsRef<Path>, span: Span) -> Result<String> {
     let path = path.as_ref();
-    let path = path
-        .canonicalize()
+    let path = canonicalize(path)
         .map_err(|e| anyhow!("Failed to canonicalize {}", path.display()).context(e))?;
 
     let ro = std::fs::OpenOptions::new()
@@ -97,11 +95,26 @@ pub(crate) fn load_span_from_file(path: impl AsRef<Path>, span: Span) -> Result<
         .open(&path)
         .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
 
-    let mut reader = std::io::BufReader::new(ro);
+    let reader = std::io::BufReader::new(ro);
 
     load_span_from(reader, span)
 }
 
+/// The number of braces a line of rust source opens, less the ones it closes.
+///
+/// Braces in trailing `//` comments are not counted, the ones in string and
+/// character literals are, which is sufficient for scanning by lines.
+pub(crate) fn brace_delta(line: &str) -> isize {
+    let code = line.find("//").map_or(line, |idx| &line[..idx]);
+    code.chars()
+        .map(|c| match c {
+            '{' => 1,
+            '}' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
 /// Extract a subset of chars by iterating.
 /// Range must be in characters.
 pub fn sub_chars(s: &str, range: Range) -> String {
@@ -111,6 +124,35 @@ pub fn sub_chars(s: &str, range: Range) -> String {
         .collect::<String>()
 }
 
+/// Transfer the capitalization of `word` onto `correction`.
+pub(crate) fn match_case(word: &str, correction: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) if first.is_uppercase() => {
+            if word.chars().count() > 1 && chars.all(char::is_uppercase) {
+                correction.to_uppercase()
+            } else {
+                let mut correction = correction.chars();
+                correction
+                    .next()
+                    .map(|first| first.to_uppercase().chain(correction).collect())
+                    .unwrap_or_default()
+            }
+        }
+        _ => correction.to_owned(),
+    }
+}
+
+/// Like `match_case`, but a `correction` with capitals of its own, i.e. a
+/// proper noun, an acronym or one which is already cased, is kept as is.
+pub(crate) fn preserve_case(word: &str, correction: &str) -> String {
+    if correction.chars().any(char::is_uppercase) {
+        correction.to_owned()
+    } else {
+        match_case(word, correction)
+    }
+}
+
 use core::ops::{Bound, RangeBounds};
 
 /// Convert a given byte range of a string, that is known to be
@@ -260,6 +302,146 @@ where
     &s[byte_range]
 }
 
+/// The longest path windows accepts without the extended-length prefix.
+const MAX_PATH: usize = 260;
+
+/// Strip the extended-length prefix of a verbatim windows path, `\\?\C:\x`
+/// becomes `C:\x` and `\\?\UNC\server\share\x` becomes `\\server\share\x`.
+///
+/// `None` if the path is not verbatim, or requires the prefix since it is too
+/// long or has components the regular form would interpret differently.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn strip_verbatim(path: &str) -> Option<String> {
+    let rest = path.strip_prefix(r"\\?\")?;
+    let stripped = if let Some(unc) = rest.strip_prefix(r"UNC\") {
+        format!(r"\\{}", unc)
+    } else {
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(drive), Some(':'), Some('\\')) if drive.is_ascii_alphabetic() => rest.to_owned(),
+            _ => return None,
+        }
+    };
+    let representable = stripped.len() < MAX_PATH
+        && !stripped.contains('/')
+        && stripped.split('\\').skip(1).all(|component| {
+            component != "." && component != ".." && !component.ends_with(|c| c == '.' || c == ' ')
+        });
+    if representable {
+        Some(stripped)
+    } else {
+        None
+    }
+}
+
+/// Canonicalize `path`, without the extended-length prefix `\\?\` windows
+/// adds, unless the path requires it.
+///
+/// The prefixed form never equals the paths as passed by the user or returned
+/// by `current_dir`, and makes messages hard to read.
+pub fn canonicalize(path: &Path) -> std::io::Result<PathBuf> {
+    let canonical = path.canonicalize()?;
+    #[cfg(windows)]
+    {
+        if let Some(stripped) = canonical.to_str().and_then(strip_verbatim) {
+            return Ok(PathBuf::from(stripped));
+        }
+    }
+    Ok(canonical)
+}
+
+/// Resolve a drive relative windows path, `C:foo`, which is relative to the
+/// current directory of the drive `C:`.
+///
+/// That is `cwd` if it is on the same drive, otherwise the one tracked in the
+/// `=C:` environment variable, or the root of the drive if there is none.
+/// `None` if `path` is not drive relative.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn resolve_drive_relative(
+    path: &str,
+    cwd: &str,
+    drive_cwd: impl FnOnce(&str) -> Option<String>,
+) -> Option<String> {
+    let mut chars = path.chars();
+    let drive = match (chars.next(), chars.next(), chars.next()) {
+        (Some(drive), Some(':'), next)
+            if drive.is_ascii_alphabetic() && !matches!(next, Some('\\') | Some('/')) =>
+        {
+            drive.to_ascii_uppercase()
+        }
+        _ => return None,
+    };
+    let drive = format!("{}:", drive);
+    let rest = &path[2..];
+    let same_drive = cwd
+        .get(..2)
+        .map_or(false, |prefix| prefix.eq_ignore_ascii_case(&drive));
+    let base = if same_drive {
+        cwd.to_owned()
+    } else {
+        drive_cwd(&drive).unwrap_or_else(|| format!(r"{}\", drive))
+    };
+    Some(if rest.is_empty() {
+        base
+    } else {
+        format!(r"{}\{}", base.trim_end_matches('\\'), rest)
+    })
+}
+
+/// Resolve `path` relative to `cwd`, unless it is absolute already.
+///
+/// On windows, drive relative paths like `C:foo` are resolved against the
+/// current directory of their drive, `Path::join` would replace `cwd` as a
+/// whole instead.
+pub fn absolute(path: &Path, cwd: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let resolved = path.to_str().zip(cwd.to_str()).and_then(|(path, cwd)| {
+            resolve_drive_relative(path, cwd, |drive| std::env::var(format!("={}", drive)).ok())
+        });
+        if let Some(resolved) = resolved {
+            return PathBuf::from(resolved);
+        }
+    }
+    if path.is_absolute() {
+        path.to_owned()
+    } else {
+        cwd.join(path)
+    }
+}
+
+fn strip_path_prefix_with<'a>(
+    path: &'a Path,
+    prefix: &Path,
+    ignore_case: bool,
+) -> Option<&'a Path> {
+    let mut components = path.components();
+    for expected in prefix.components() {
+        let component = components.next()?;
+        let same = if ignore_case {
+            component.as_os_str().to_string_lossy().to_lowercase()
+                == expected.as_os_str().to_string_lossy().to_lowercase()
+        } else {
+            component == expected
+        };
+        if !same {
+            return None;
+        }
+    }
+    Some(components.as_path())
+}
+
+/// Strip `prefix` from `path`, comparing the components as the file system
+/// does, ignoring the case on windows.
+pub fn strip_path_prefix<'a>(path: &'a Path, prefix: &Path) -> Option<&'a Path> {
+    strip_path_prefix_with(path, prefix, cfg!(windows))
+}
+
+/// If `a` and `b` are the same path, compared as the file system does.
+pub fn same_path(a: &Path, b: &Path) -> bool {
+    a.components().count() == b.components().count() && strip_path_prefix(a, b).is_some()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -409,4 +591,88 @@ Schlupfwespe,
             vec![0..0, 1..3]
         );
     }
+
+    #[test]
+    fn case() {
+        assert_eq!(match_case("teh", "the"), "the");
+        assert_eq!(match_case("Teh", "the"), "The");
+        assert_eq!(match_case("TEH", "the"), "THE");
+        assert_eq!(match_case("A", "a lot"), "A lot");
+
+        assert_eq!(preserve_case("Alotted", "allotted"), "Allotted");
+        assert_eq!(preserve_case("ALOTTED", "allotted"), "ALLOTTED");
+        assert_eq!(preserve_case("alotted", "allotted"), "allotted");
+        assert_eq!(preserve_case("GITHUB", "GitHub"), "GitHub");
+        assert_eq!(preserve_case("english", "English"), "English");
+    }
+
+    #[test]
+    fn verbatim_paths() {
+        assert_eq!(
+            strip_verbatim(r"\\?\C:\Users\alice\lib.rs").as_deref(),
+            Some(r"C:\Users\alice\lib.rs")
+        );
+        assert_eq!(
+            strip_verbatim(r"\\?\UNC\server\share\lib.rs").as_deref(),
+            Some(r"\\server\share\lib.rs")
+        );
+        assert_eq!(strip_verbatim(r"C:\Users\alice\lib.rs"), None);
+        assert_eq!(strip_verbatim(r"\\?\C:\Users\alice\lib.rs."), None);
+        assert_eq!(strip_verbatim(r"\\?\C:\Users\..\lib.rs"), None);
+        assert_eq!(strip_verbatim(r"\\?\Volume{b75e2c83}\lib.rs"), None);
+        let long = format!(r"\\?\C:\{}\lib.rs", "x".repeat(MAX_PATH));
+        assert_eq!(strip_verbatim(&long), None);
+    }
+
+    #[test]
+    fn drive_relative_paths() {
+        let none = |_drive: &str| None;
+        assert_eq!(
+            resolve_drive_relative(r"C:src\lib.rs", r"C:\Users\alice", none).as_deref(),
+            Some(r"C:\Users\alice\src\lib.rs")
+        );
+        assert_eq!(
+            resolve_drive_relative("c:", r"C:\Users\alice", none).as_deref(),
+            Some(r"C:\Users\alice")
+        );
+        assert_eq!(
+            resolve_drive_relative("D:lib.rs", r"C:\Users\alice", none).as_deref(),
+            Some(r"D:\lib.rs")
+        );
+        assert_eq!(
+            resolve_drive_relative("D:lib.rs", r"C:\Users\alice", |_drive| Some(
+                r"D:\work\".to_owned()
+            ))
+            .as_deref(),
+            Some(r"D:\work\lib.rs")
+        );
+        assert_eq!(
+            resolve_drive_relative(r"C:\lib.rs", r"C:\Users", none),
+            None
+        );
+        assert_eq!(resolve_drive_relative("C:/lib.rs", r"C:\Users", none), None);
+        assert_eq!(
+            resolve_drive_relative("src/lib.rs", r"C:\Users", none),
+            None
+        );
+    }
+
+    #[test]
+    fn path_prefix() {
+        let path = Path::new("/Home/Alice/src/lib.rs");
+        assert_eq!(
+            strip_path_prefix_with(path, Path::new("/home/alice"), true),
+            Some(Path::new("src/lib.rs"))
+        );
+        assert_eq!(
+            strip_path_prefix_with(path, Path::new("/home/alice"), false),
+            None
+        );
+        assert_eq!(
+            strip_path_prefix_with(path, Path::new("/Home/Alice/src/lib.rs/more"), true),
+            None
+        );
+        assert!(same_path(path, Path::new("/Home/Alice/src/lib.rs")));
+        assert!(!same_path(path, Path::new("/Home/Alice/src")));
+    }
 }